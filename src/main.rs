@@ -1,35 +1,255 @@
-use std::{env, io, path::PathBuf};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::{env, io, process};
 
 mod models;
 mod services;
+mod utils;
 
+use models::config::Config;
+use services::accessible_service;
 use services::config_service;
-use services::ui_manager;
+use services::headless_service::{self, PlaybackSource};
+use services::render_service;
+use services::ui_manager::{self, StartOptions};
+
+/// Opciones de la línea de comandos
+#[derive(Default)]
+struct Args {
+    /// Directorio de música a guardar en la configuración
+    music_path: Option<PathBuf>,
+    /// Modo sin interfaz que imprime el estado como JSON
+    stdout_status: bool,
+    /// Interfaz de líneas para lectores de pantalla, sin modo raw
+    accessible: bool,
+    /// Qué reproducir en modo sin interfaz
+    source: Option<PlaybackSource>,
+    /// Biblioteca de Rhythmbox o iTunes de la que importar playlists al iniciar
+    import_from: Option<PathBuf>,
+    /// Playlist a generar como un solo WAV, y el archivo de salida
+    render: Option<(String, PathBuf)>,
+    /// Imprimir las estadísticas de la biblioteca como JSON y salir
+    export_stats: bool,
+    /// Carpeta en la que abrir la interfaz, relativa al directorio de música o absoluta
+    dir: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args::default();
+    // Las rutas se toman tal cual: un nombre que no es UTF-8 válido no se pierde
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.to_str() else {
+            parsed.music_path = Some(PathBuf::from(arg));
+            continue;
+        };
+        match flag {
+            "--stdout-status" => parsed.stdout_status = true,
+            "--accessible" => parsed.accessible = true,
+            "--play" => {
+                let path = args.next().ok_or("--play needs a file or directory")?;
+                parsed.source = Some(PlaybackSource::Path(PathBuf::from(path)));
+            }
+            "--playlist" => {
+                let name = args.next().ok_or("--playlist needs a playlist name")?;
+                parsed.source = Some(PlaybackSource::Playlist(
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+            "--dir" => {
+                let path = args.next().ok_or("--dir needs a folder")?;
+                parsed.dir = Some(PathBuf::from(path));
+            }
+            "--shuffle-all" => parsed.source = Some(PlaybackSource::ShuffleAll),
+            "--genres" => {
+                let list = args
+                    .next()
+                    .ok_or("--genres needs a list like Jazz,Blues or Jazz+Blues")?;
+                parsed.source = Some(PlaybackSource::genres(&list.to_string_lossy())?);
+            }
+            "--import-from" => {
+                let path = args
+                    .next()
+                    .ok_or("--import-from needs a Rhythmbox or iTunes XML file")?;
+                parsed.import_from = Some(PathBuf::from(path));
+            }
+            "--render-playlist" => {
+                let name = args
+                    .next()
+                    .ok_or("--render-playlist needs a playlist name and an output file")?
+                    .to_string_lossy()
+                    .into_owned();
+                let output = args
+                    .next()
+                    .ok_or("--render-playlist needs an output file")?;
+                parsed.render = Some((name, PathBuf::from(output)));
+            }
+            "--export-stats" => {
+                let format = args.next().ok_or("--export-stats needs a format (json)")?;
+                if format != "json" {
+                    return Err(format!(
+                        "Unsupported stats format '{}', use json",
+                        format.to_string_lossy()
+                    ));
+                }
+                parsed.export_stats = true;
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option '{}'", flag)),
+            _ => parsed.music_path = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(parsed)
+}
 
 fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-
-    // Si se da un argumento se establece como path
-    if args.len() > 1 {
-        let music_path = PathBuf::from(&args[1]);
-        if music_path.is_dir() {
-            let config = models::config::Config { music_path };
-            config_service::save_config(&config)?;
-            println!(
-                "Music path set to '{}' and saved.",
-                config.music_path.display()
+    let args = parse_args().unwrap_or_else(|message| {
+        eprintln!("Error: {}", message);
+        process::exit(2);
+    });
+
+    if args.dir.is_some()
+        && (args.render.is_some() || args.export_stats || args.accessible || args.stdout_status)
+    {
+        eprintln!("Error: --dir only applies to the interface.");
+        process::exit(2);
+    }
+
+    if let Some((name, output)) = &args.render {
+        if args.stdout_status
+            || args.accessible
+            || args.source.is_some()
+            || args.import_from.is_some()
+        {
+            eprintln!("Error: --render-playlist can't be combined with other options.");
+            process::exit(2);
+        }
+        let config = config_service::load_config();
+        if render_service::run_render_playlist(&config, name, output).is_err() {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.export_stats {
+        let mut config = config_service::load_config();
+        if let Some(music_path) = args.music_path {
+            config.music_path = music_path;
+        }
+        if let Err(e) = headless_service::run_export_stats(&config) {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.accessible {
+        if args.stdout_status || args.import_from.is_some() {
+            eprintln!(
+                "Error: --accessible can't be combined with --stdout-status or --import-from."
             );
-            ui_manager::run(&config)?;
-        } else {
-            println!("Error: '{}' is not a valid directory.", args[1]);
-            println!("Press Enter to exit...");
-            let mut _input = String::new();
-            io::stdin().read_line(&mut _input)?;
+            process::exit(2);
+        }
+        let mut config = config_service::load_config();
+        if let Some(music_path) = args.music_path {
+            config.music_path = music_path;
+        }
+        if accessible_service::run_accessible(&config, args.source).is_err() {
+            process::exit(1);
         }
         return Ok(());
     }
 
-    // si no, se carga desde cfg el path establecido
+    if args.import_from.is_some() && args.stdout_status {
+        eprintln!("Error: --import-from can't be combined with --stdout-status.");
+        process::exit(2);
+    }
+
+    // Con --dir, --play también vale en la interfaz
+    let plays_in_interface =
+        args.dir.is_some() && matches!(args.source, Some(PlaybackSource::Path(_)));
+    if args.source.is_some() && !args.stdout_status && !plays_in_interface {
+        eprintln!(
+            "Error: --play, --playlist, --shuffle-all and --genres require --stdout-status or --accessible (--play also works with --dir)."
+        );
+        process::exit(2);
+    }
+
+    // La interfaz necesita una terminal: con stdout redirigido solo se admite el modo JSON
+    if !args.stdout_status && !io::stdout().is_terminal() {
+        eprintln!("Error: stdout is not a terminal, so the interface cannot start.");
+        eprintln!(
+            "Use --stdout-status with --play <path>, --playlist <name>, --shuffle-all or --genres <list> to print status as JSON lines."
+        );
+        process::exit(1);
+    }
+
+    if args.stdout_status {
+        let Some(source) = args.source else {
+            eprintln!(
+                "Error: --stdout-status needs --play, --playlist, --shuffle-all or --genres."
+            );
+            process::exit(2);
+        };
+        let mut config = config_service::load_config();
+        if let Some(music_path) = args.music_path {
+            config.music_path = music_path;
+        }
+        if headless_service::run_stdout_status(&config, source).is_err() {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = match &args.music_path {
+        Some(music_arg) => set_library_path(music_arg)?,
+        None => load_library_path()?,
+    };
+    let Some(config) = config else {
+        return Ok(());
+    };
+
+    let play = match args.source {
+        Some(PlaybackSource::Path(path)) => Some(path),
+        _ => None,
+    };
+    let options = StartOptions {
+        import_from: args.import_from.as_deref(),
+        // Solo se ofrece restaurar la sesión al abrir sin argumentos
+        offer_restore: env::args_os().len() == 1,
+        start_dir: args.dir.as_deref(),
+        play,
+    };
+    ui_manager::run(&config, options)?;
+
+    Ok(())
+}
+
+/// Guarda el directorio de música dado como argumento en la configuración
+///
+/// Retorna `None` si no es un directorio válido, tras avisarlo.
+fn set_library_path(music_arg: &Path) -> io::Result<Option<Config>> {
+    let music_path = music_arg.to_path_buf();
+    if !music_path.is_dir() {
+        println!("Error: '{}' is not a valid directory.", music_arg.display());
+        println!("Press Enter to exit...");
+        let mut _input = String::new();
+        io::stdin().read_line(&mut _input)?;
+        return Ok(None);
+    }
+    let mut config = config_service::load_config();
+    config.music_path = music_path;
+    config_service::save_config(&config)?;
+    println!(
+        "Music path set to '{}' and saved.",
+        config.music_path.display()
+    );
+    Ok(Some(config))
+}
+
+/// Carga la configuración y comprueba el directorio de música guardado
+///
+/// Retorna `None` si no está establecido o no es válido, tras avisarlo.
+fn load_library_path() -> io::Result<Option<Config>> {
     let config = config_service::load_config();
 
     if config.music_path.as_os_str().is_empty() {
@@ -42,7 +262,7 @@ fn main() -> io::Result<()> {
         println!("Press Enter to exit...");
         let mut _input = String::new();
         io::stdin().read_line(&mut _input)?;
-        return Ok(());
+        return Ok(None);
     }
 
     if !config.music_path.is_dir() {
@@ -54,10 +274,8 @@ fn main() -> io::Result<()> {
         println!("Press Enter to exit...");
         let mut _input = String::new();
         io::stdin().read_line(&mut _input)?;
-        return Ok(());
+        return Ok(None);
     }
 
-    ui_manager::run(&config)?;
-
-    Ok(())
+    Ok(Some(config))
 }
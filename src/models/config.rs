@@ -1,9 +1,277 @@
+use crate::models::symbols::SymbolSet;
+use crate::models::theme::ThemePreset;
+use crate::models::track::TrackFilter;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Cómo se confirma una acción peligrosa
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmStyle {
+    /// Ventana que pide confirmar (borrar playlist); salir y vaciar la cola actúan de inmediato
+    #[default]
+    Popup,
+    /// Pulsar la misma tecla dos veces en menos de un segundo
+    Chord,
+}
+
+/// Destino de los avisos de escritorio
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationBackend {
+    /// Sin avisos
+    #[default]
+    Off,
+    /// Notificaciones del sistema operativo
+    Os,
+    /// Una línea por aviso en `notifications.log`, en el directorio de caché
+    Log,
+}
+
+/// Cómo se reparte el volumen entre los pasos de `z`/`x`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeCurve {
+    /// El porcentaje es la amplitud: los pasos bajos suenan grandes y los altos casi no se notan
+    #[default]
+    Linear,
+    /// El porcentaje sigue una escala en dB: cada paso suena como el mismo cambio
+    Logarithmic,
+}
+
+/// Con qué se ordenan los tops de géneros y artistas en Estadísticas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsRanking {
+    /// Cantidad de pistas
+    #[default]
+    Tracks,
+    /// Suma de la duración de las pistas
+    Duration,
+    /// Tiempo escuchado según el registro de `session_log_path`
+    Listening,
+}
+
+impl StatsRanking {
+    /// Nombre para mostrar
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tracks => "track count",
+            Self::Duration => "total duration",
+            Self::Listening => "listening time",
+        }
+    }
+
+    /// El modo siguiente, volviendo al primero después del último
+    pub fn next(self) -> Self {
+        match self {
+            Self::Tracks => Self::Duration,
+            Self::Duration => Self::Listening,
+            Self::Listening => Self::Tracks,
+        }
+    }
+}
 
 /// Estructura de configuración del reproductor de música
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     /// Ruta al directorio principal de música
     pub music_path: PathBuf,
+    /// Comando a ejecutar cuando empieza una nueva pista
+    ///
+    /// Admite los marcadores `{artist}`, `{title}`, `{album}` y `{path}`.
+    pub on_track_change: Option<String>,
+    /// Comando a ejecutar cuando termina (o se salta) una pista
+    pub on_track_finished: Option<String>,
+    /// Aviso de escritorio al empezar cada pista: "off", "os" o "log" (stderr)
+    pub track_notifications: NotificationBackend,
+    /// Comando a ejecutar cuando se detiene la reproducción
+    pub on_playback_stopped: Option<String>,
+    /// Agrupa variantes del nombre de artista ("Beatles, The", "feat.", mayúsculas)
+    pub fold_artist_names: bool,
+    /// Orden de preferencia entre etiquetas cuando un archivo tiene varias
+    ///
+    /// Valores: "id3v2.4", "id3v2.3", "id3v2.2", "ape", "id3v1", "vorbis", "mp4", "riff", "aiff".
+    pub tag_priority: Vec<String>,
+    /// Filas que se mantienen visibles por encima y por debajo de la selección en las listas
+    pub scrolloff: usize,
+    /// Mantiene la selección centrada verticalmente salvo cerca de los extremos
+    pub centered_cursor: bool,
+    /// Reabre la salida de audio a la frecuencia de muestreo de cada pista si el dispositivo la admite
+    pub prefer_native_sample_rate: bool,
+    /// Tras una suspensión del equipo, sigue sonando donde estaba (`false`: queda en pausa)
+    pub resume_after_sleep: bool,
+    /// Acelera el volumen al mantener pulsada la tecla (pasos de 2% y luego de 5%)
+    pub key_acceleration: bool,
+    /// Milisegundos que hay que mantener la tecla antes de usar el paso grande
+    pub key_acceleration_delay_ms: u64,
+    /// Curva del volumen: "linear" o "logarithmic" (pasos parejos al oído)
+    pub volume_curve: VolumeCurve,
+    /// Plantilla de la línea de estado
+    ///
+    /// Admite los marcadores `{state}`, `{source}`, `{artist}`, `{title}`, `{elapsed}`,
+    /// `{duration}`, `{shuffle}` y `{volume}`.
+    pub status_format: String,
+    /// Segundos que se cuentan para una pista sin duración conocida al calcular lo que falta de la cola
+    pub unknown_duration_estimate_secs: u64,
+    /// Prefijo de las rutas en bibliotecas importadas que se reemplaza por `music_path`
+    ///
+    /// Por ejemplo `/home/ana/Música` si la biblioteca viene de otro equipo.
+    pub import_path_prefix: Option<String>,
+    /// Tema de colores: "default", "nord", "solarized", "dracula", "gruvbox" o "monochrome"
+    pub theme_preset: ThemePreset,
+    /// Símbolos de la interfaz: "unicode" o "ascii" (para terminales sin Unicode)
+    pub symbol_set: SymbolSet,
+    /// Trata "Álbum (Disc 1)" y "Álbum (Disc 2)" como un solo álbum
+    pub merge_disc_albums: bool,
+    /// Milisegundos de silencio entre pistas al generar un archivo con `--render-playlist`
+    pub render_gap_ms: u64,
+    /// Milisegundos en que cada pista se funde con la siguiente al generar un
+    /// archivo con `--render-playlist`; no se combina con `render_gap_ms`
+    pub render_crossfade_ms: u64,
+    /// Horas durante las que una pista ya escuchada queda fuera de las colas aleatorias (0 desactiva)
+    pub shuffle_exclusion_hours: u64,
+    /// Al entrar en una carpeta que es un álbum, reproducirla en vez de solo ofrecerlo
+    pub auto_play_album: bool,
+    /// Archivo donde se agrega una línea JSON por cada pista escuchada
+    pub session_log_path: Option<PathBuf>,
+    /// Orden de los tops en Estadísticas: "tracks", "duration" o "listening"
+    pub stats_ranking: StatsRanking,
+    /// Cómo se confirman las acciones peligrosas: "popup" o "chord"
+    pub confirm_style: ConfirmStyle,
+    /// Estilo por acción que reemplaza al general
+    ///
+    /// Acciones: "delete_playlist", "quit" y "clear_queue".
+    pub confirm_style_overrides: HashMap<String, ConfirmStyle>,
+    /// Carpeta donde se importan y exportan playlists
+    ///
+    /// Si no se define se usa `Playlists` dentro de `music_path`, cuando existe.
+    pub default_playlist_dir: Option<PathBuf>,
+    /// Modo para lectores de pantalla: sin reloj ni barra de progreso que cambien
+    /// a cada momento, y cada cambio de estado se anuncia en una línea al final
+    pub screen_reader_mode: bool,
+    /// En modo lector de pantalla, escribe también cada anuncio en stderr
+    pub announce_to_stderr: bool,
+    /// Guarda el orden propio de una carpeta en un `.rusted-order.json` dentro de
+    /// ella, para que viaje con la música, en lugar del directorio de configuración
+    pub portable_folder_order: bool,
+    /// Último patrón usado para adivinar etiquetas desde los nombres de archivo
+    pub last_tag_pattern: Option<String>,
+    /// Salta al escanear las carpetas que tienen un archivo `.nomedia` (tonos,
+    /// grabaciones y otros audios que no son música)
+    pub respect_nomedia: bool,
+    /// Muestra, escanea y reproduce los archivos y carpetas ocultos (nombre con
+    /// `.` al inicio); los AppleDouble `._*` y los archivos vacíos se saltan igual
+    pub show_hidden_files: bool,
+    /// Ordena cada tipo de entrada de las carpetas por fecha de modificación,
+    /// las más recientes primero, en vez de por nombre
+    pub sort_folders_by_date: bool,
+    /// Captura el ratón para el clic derecho; sin ella la terminal conserva la
+    /// selección de texto y el desplazamiento propios
+    pub mouse_capture: bool,
+    /// Filtro inicial de las mezclas aleatorias (años, duración, géneros)
+    ///
+    /// Se puede cambiar durante la sesión con `Ctrl+F`.
+    pub shuffle_filter: TrackFilter,
+}
+
+impl Config {
+    /// Carpeta de playlists configurada, o `music_path/Playlists` si existe
+    pub fn playlist_dir(&self) -> Option<PathBuf> {
+        self.default_playlist_dir.clone().or_else(|| {
+            let dir = self.music_path.join("Playlists");
+            dir.is_dir().then_some(dir)
+        })
+    }
+
+    /// Estilo de confirmación de una acción, teniendo en cuenta los reemplazos
+    pub fn confirm_style_for(&self, action: &str) -> ConfirmStyle {
+        self.confirm_style_overrides
+            .get(action)
+            .copied()
+            .unwrap_or(self.confirm_style)
+    }
+
+    /// Ventana en la que una pista escuchada queda fuera de las colas aleatorias
+    ///
+    /// Un valor enorme en `shuffle_exclusion_hours` satura en vez de desbordar.
+    pub fn shuffle_exclusion_window(&self) -> Duration {
+        Duration::from_secs(self.shuffle_exclusion_hours.saturating_mul(3600))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            music_path: PathBuf::new(),
+            on_track_change: None,
+            track_notifications: NotificationBackend::Off,
+            on_track_finished: None,
+            on_playback_stopped: None,
+            fold_artist_names: true,
+            tag_priority: ["id3v2.4", "id3v2.3", "id3v2.2", "ape", "id3v1"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            scrolloff: 3,
+            centered_cursor: false,
+            prefer_native_sample_rate: false,
+            resume_after_sleep: true,
+            key_acceleration: true,
+            key_acceleration_delay_ms: 500,
+            volume_curve: VolumeCurve::Linear,
+            status_format: "{state} {source}{shuffle}{queue}".to_string(),
+            unknown_duration_estimate_secs: 240,
+            import_path_prefix: None,
+            theme_preset: ThemePreset::Default,
+            symbol_set: SymbolSet::Unicode,
+            merge_disc_albums: true,
+            render_gap_ms: 0,
+            render_crossfade_ms: 0,
+            shuffle_exclusion_hours: 4,
+            auto_play_album: false,
+            session_log_path: None,
+            stats_ranking: StatsRanking::Tracks,
+            confirm_style: ConfirmStyle::Popup,
+            confirm_style_overrides: HashMap::new(),
+            default_playlist_dir: None,
+            screen_reader_mode: false,
+            announce_to_stderr: false,
+            portable_folder_order: false,
+            last_tag_pattern: None,
+            respect_nomedia: true,
+            show_hidden_files: false,
+            sort_folders_by_date: false,
+            mouse_capture: true,
+            shuffle_filter: TrackFilter::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_exclusion_window_saturates() {
+        let config = Config {
+            shuffle_exclusion_hours: u64::MAX,
+            ..Config::default()
+        };
+        assert_eq!(
+            config.shuffle_exclusion_window(),
+            Duration::from_secs(u64::MAX)
+        );
+        let config = Config {
+            shuffle_exclusion_hours: 4,
+            ..Config::default()
+        };
+        assert_eq!(
+            config.shuffle_exclusion_window(),
+            Duration::from_secs(4 * 3600)
+        );
+    }
 }
@@ -0,0 +1,48 @@
+/// Errores posibles al leer la biblioteca y sus etiquetas
+#[derive(Debug)]
+pub enum RustedPlayerError {
+    Io(std::io::Error),
+    Lofty(lofty::error::LoftyError),
+    Walk(walkdir::Error),
+    TagWrite(String),
+}
+
+impl std::fmt::Display for RustedPlayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Error de E/S: {}", e),
+            Self::Lofty(e) => write!(f, "Error al leer etiquetas: {}", e),
+            Self::Walk(e) => write!(f, "Error al recorrer el directorio: {}", e),
+            Self::TagWrite(s) => write!(f, "Error al escribir etiquetas: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for RustedPlayerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Lofty(e) => Some(e),
+            Self::Walk(e) => Some(e),
+            Self::TagWrite(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RustedPlayerError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<lofty::error::LoftyError> for RustedPlayerError {
+    fn from(e: lofty::error::LoftyError) -> Self {
+        Self::Lofty(e)
+    }
+}
+
+impl From<walkdir::Error> for RustedPlayerError {
+    fn from(e: walkdir::Error) -> Self {
+        Self::Walk(e)
+    }
+}
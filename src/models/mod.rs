@@ -1 +1,6 @@
 pub mod config;
+pub mod error;
+pub mod stored_path;
+pub mod symbols;
+pub mod theme;
+pub mod track;
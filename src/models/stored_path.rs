@@ -0,0 +1,110 @@
+//! Rutas en los archivos JSON sin perder nombres que no son Unicode válido
+//!
+//! Se usa con `#[serde(with = "stored_path")]` (o `stored_path::vec` para listas).
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::{Path, PathBuf};
+
+/// Ruta guardada en JSON: texto si es Unicode válido, si no el nombre crudo
+///
+/// En Unix un nombre de archivo puede tener bytes que no son UTF-8 y en Windows
+/// mitades de pares sustitutos; pasarlos a texto los cambiaría por `�` y la
+/// pista ya no se encontraría al recargar la playlist.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum StoredPath {
+    Text(String),
+    UnixBytes { unix_bytes: Vec<u8> },
+    WindowsWide { windows_wide: Vec<u16> },
+}
+
+impl StoredPath {
+    fn from_path(path: &Path) -> Self {
+        if let Some(text) = path.to_str() {
+            return Self::Text(text.to_string());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Self::UnixBytes {
+                unix_bytes: path.as_os_str().as_bytes().to_vec(),
+            }
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStrExt;
+            Self::WindowsWide {
+                windows_wide: path.as_os_str().encode_wide().collect(),
+            }
+        }
+        #[cfg(not(any(unix, windows)))]
+        Self::Text(path.to_string_lossy().into_owned())
+    }
+
+    /// Un nombre crudo de otro sistema se interpreta con reemplazo
+    fn into_path(self) -> PathBuf {
+        match self {
+            Self::Text(text) => PathBuf::from(text),
+            Self::UnixBytes { unix_bytes } => path_from_unix_bytes(unix_bytes),
+            Self::WindowsWide { windows_wide } => path_from_windows_wide(&windows_wide),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn path_from_unix_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_unix_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(windows)]
+fn path_from_windows_wide(wide: &[u16]) -> PathBuf {
+    use std::os::windows::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_wide(wide))
+}
+
+#[cfg(not(windows))]
+fn path_from_windows_wide(wide: &[u16]) -> PathBuf {
+    PathBuf::from(String::from_utf16_lossy(wide))
+}
+
+/// Serializa una ruta como texto, o como su nombre crudo si no es Unicode válido
+pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    StoredPath::from_path(path).serialize(serializer)
+}
+
+/// Lee una ruta escrita por `serialize` (o una ruta de texto común)
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+    StoredPath::deserialize(deserializer).map(StoredPath::into_path)
+}
+
+/// Lo mismo para una lista de rutas
+pub mod vec {
+    use super::StoredPath;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::PathBuf;
+
+    /// Serializa cada ruta como `stored_path::serialize`
+    pub fn serialize<S: Serializer>(paths: &[PathBuf], serializer: S) -> Result<S::Ok, S::Error> {
+        let stored: Vec<StoredPath> = paths
+            .iter()
+            .map(|path| StoredPath::from_path(path))
+            .collect();
+        stored.serialize(serializer)
+    }
+
+    /// Lee una lista escrita por `serialize`
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<PathBuf>, D::Error> {
+        Ok(Vec::<StoredPath>::deserialize(deserializer)?
+            .into_iter()
+            .map(StoredPath::into_path)
+            .collect())
+    }
+}
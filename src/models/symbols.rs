@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Juego de símbolos con el que se dibuja la interfaz
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolSet {
+    /// Símbolos Unicode (`♪`, `⏸`, `♥`, ...)
+    #[default]
+    Unicode,
+    /// Solo ASCII, para terminales o conexiones SSH sin Unicode
+    Ascii,
+}
+
+/// Símbolos Unicode de la interfaz y su reemplazo ASCII
+///
+/// Los nombres de archivo y las etiquetas no pasan por aquí: son datos del
+/// usuario, no símbolos de la interfaz. `[DIR]` y `[PL]` ya son ASCII.
+const ASCII_FALLBACKS: &[(&str, &str)] = &[
+    ("♪", ">"),
+    ("⏸", "||"),
+    ("♥", "<3"),
+    ("★", "*"),
+    ("⚠", "!"),
+    ("▶", ">"),
+    ("✂", "cut"),
+    ("▸", ">"),
+    ("→", "->"),
+    ("←", "<-"),
+    ("↑", "^"),
+    ("↓", "v"),
+    ("…", "..."),
+    ("—", "-"),
+    ("–", "-"),
+    ("≥", ">="),
+    ("≤", "<="),
+    ("≈", "~"),
+    ("━", "="),
+    ("╸", ">"),
+    ("─", "-"),
+];
+
+/// Reemplazo ASCII de un símbolo; el mismo símbolo si no está en la tabla
+fn ascii_fallback(symbol: &'static str) -> &'static str {
+    ASCII_FALLBACKS
+        .iter()
+        .find(|(unicode, _)| *unicode == symbol)
+        .map_or(symbol, |(_, ascii)| ascii)
+}
+
+/// Símbolos que usan los `draw_*` de la interfaz
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symbols {
+    set: SymbolSet,
+    /// Reproduciendo, en la barra de estado y el mini reproductor
+    pub playing: &'static str,
+    /// En pausa
+    pub paused: &'static str,
+    /// Pista favorita
+    pub favorite: &'static str,
+    /// Pista que falló al reproducirse
+    pub warning: &'static str,
+    /// Fila de la pista que está sonando
+    pub now_playing: &'static str,
+    /// Pista con recortes
+    pub trim: &'static str,
+    /// Conversión de frecuencia de muestreo
+    pub arrow: &'static str,
+    /// Parte ya reproducida de la barra de progreso
+    pub bar_done: &'static str,
+    /// Posición actual en la barra de progreso
+    pub bar_head: &'static str,
+    /// Parte pendiente de la barra de progreso
+    pub bar_todo: &'static str,
+}
+
+impl Symbols {
+    pub fn new(set: SymbolSet) -> Self {
+        let pick = |symbol: &'static str| match set {
+            SymbolSet::Unicode => symbol,
+            SymbolSet::Ascii => ascii_fallback(symbol),
+        };
+        Self {
+            set,
+            playing: pick("♪"),
+            paused: pick("⏸"),
+            favorite: pick("♥"),
+            warning: pick("⚠"),
+            now_playing: pick("▶"),
+            trim: pick("✂"),
+            arrow: pick("→"),
+            bar_done: pick("━"),
+            bar_head: pick("╸"),
+            bar_todo: pick("─"),
+        }
+    }
+
+    /// Un símbolo de la tabla en el juego actual, para armar textos que
+    /// mezclan símbolos con datos del usuario
+    pub fn get(&self, symbol: &'static str) -> &'static str {
+        match self.set {
+            SymbolSet::Unicode => symbol,
+            SymbolSet::Ascii => ascii_fallback(symbol),
+        }
+    }
+
+    /// Adapta un texto escrito entero por la interfaz (ayudas, migas) al juego de símbolos
+    ///
+    /// En ASCII reemplaza cada símbolo de la tabla; el resto del texto queda igual.
+    /// No sirve para textos con títulos o nombres de archivo: también los cambiaría.
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.set == SymbolSet::Unicode || text.is_ascii() {
+            return Cow::Borrowed(text);
+        }
+        let mut result = String::with_capacity(text.len());
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            let symbol: &str = c.encode_utf8(&mut buffer);
+            match ASCII_FALLBACKS
+                .iter()
+                .find(|(unicode, _)| *unicode == symbol)
+            {
+                Some((_, ascii)) => result.push_str(ascii),
+                None => result.push(c),
+            }
+        }
+        Cow::Owned(result)
+    }
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Self::new(SymbolSet::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_set_replaces_every_table_symbol() {
+        let symbols = Symbols::new(SymbolSet::Ascii);
+        for (unicode, ascii) in ASCII_FALLBACKS {
+            assert_eq!(symbols.get(unicode), *ascii);
+            assert!(ascii.is_ascii());
+        }
+        assert_eq!(symbols.text("≈2h — 3 ♥"), "~2h - 3 <3");
+    }
+
+    #[test]
+    fn unicode_set_keeps_symbols_and_other_text() {
+        let symbols = Symbols::new(SymbolSet::Unicode);
+        assert_eq!(symbols.get("≈"), "≈");
+        assert_eq!(symbols.text("♪ Café"), "♪ Café");
+        assert_eq!(Symbols::new(SymbolSet::Ascii).text("Café"), "Café");
+    }
+}
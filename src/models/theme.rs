@@ -0,0 +1,178 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// Temas de colores incluidos
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    Nord,
+    Solarized,
+    Dracula,
+    Gruvbox,
+    Monochrome,
+}
+
+impl ThemePreset {
+    /// Todos los temas, en el orden en que se recorren
+    pub const ALL: [ThemePreset; 6] = [
+        Self::Default,
+        Self::Nord,
+        Self::Solarized,
+        Self::Dracula,
+        Self::Gruvbox,
+        Self::Monochrome,
+    ];
+
+    /// Nombre para mostrar
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::Nord => "Nord",
+            Self::Solarized => "Solarized",
+            Self::Dracula => "Dracula",
+            Self::Gruvbox => "Gruvbox",
+            Self::Monochrome => "Monochrome",
+        }
+    }
+
+    /// El tema siguiente, volviendo al primero después del último
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Colores usados por la interfaz
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Texto normal
+    pub text: Color,
+    /// Selección, pestaña activa y bordes con foco
+    pub accent: Color,
+    /// Fondo de la fila resaltada
+    pub highlight_bg: Color,
+    /// Información secundaria
+    pub dim: Color,
+    /// Directorios en el navegador
+    pub directory: Color,
+    /// Pistas marcadas
+    pub marked: Color,
+    /// Favoritos y pistas faltantes
+    pub alert: Color,
+    /// Mini reproductor
+    pub player: Color,
+    /// Barras de los gráficos
+    pub chart: Color,
+    /// Números dentro de las barras
+    pub chart_value: Color,
+    /// Pista recién movida dentro de una playlist
+    pub moved: Color,
+    /// Al marcar, pistas que ya están en alguna playlist
+    pub in_playlist: Color,
+    /// Pista que está sonando, distinta de la selección
+    pub playing: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        theme_for_preset(ThemePreset::Default)
+    }
+}
+
+/// Colores de cada tema incluido
+pub fn theme_for_preset(preset: ThemePreset) -> Theme {
+    match preset {
+        ThemePreset::Default => Theme {
+            text: Color::White,
+            accent: Color::Yellow,
+            highlight_bg: Color::DarkGray,
+            dim: Color::DarkGray,
+            directory: Color::Blue,
+            marked: Color::Green,
+            alert: Color::Red,
+            player: Color::Cyan,
+            chart: Color::Green,
+            chart_value: Color::Black,
+            moved: Color::Cyan,
+            in_playlist: Color::Cyan,
+            playing: Color::Magenta,
+        },
+        ThemePreset::Nord => Theme {
+            text: Color::Rgb(216, 222, 233),
+            accent: Color::Rgb(136, 192, 208),
+            highlight_bg: Color::Rgb(67, 76, 94),
+            dim: Color::Rgb(97, 110, 136),
+            directory: Color::Rgb(129, 161, 193),
+            marked: Color::Rgb(163, 190, 140),
+            alert: Color::Rgb(191, 97, 106),
+            player: Color::Rgb(143, 188, 187),
+            chart: Color::Rgb(94, 129, 172),
+            chart_value: Color::Rgb(46, 52, 64),
+            moved: Color::Rgb(180, 142, 173),
+            in_playlist: Color::Rgb(143, 188, 187),
+            playing: Color::Rgb(235, 203, 139),
+        },
+        ThemePreset::Solarized => Theme {
+            text: Color::Rgb(147, 161, 161),
+            accent: Color::Rgb(181, 137, 0),
+            highlight_bg: Color::Rgb(7, 54, 66),
+            dim: Color::Rgb(88, 110, 117),
+            directory: Color::Rgb(38, 139, 210),
+            marked: Color::Rgb(133, 153, 0),
+            alert: Color::Rgb(220, 50, 47),
+            player: Color::Rgb(42, 161, 152),
+            chart: Color::Rgb(108, 113, 196),
+            chart_value: Color::Rgb(0, 43, 54),
+            moved: Color::Rgb(211, 54, 130),
+            in_playlist: Color::Rgb(42, 161, 152),
+            playing: Color::Rgb(203, 75, 22),
+        },
+        ThemePreset::Dracula => Theme {
+            text: Color::Rgb(248, 248, 242),
+            accent: Color::Rgb(255, 121, 198),
+            highlight_bg: Color::Rgb(68, 71, 90),
+            dim: Color::Rgb(98, 114, 164),
+            directory: Color::Rgb(189, 147, 249),
+            marked: Color::Rgb(80, 250, 123),
+            alert: Color::Rgb(255, 85, 85),
+            player: Color::Rgb(139, 233, 253),
+            chart: Color::Rgb(189, 147, 249),
+            chart_value: Color::Rgb(40, 42, 54),
+            moved: Color::Rgb(241, 250, 140),
+            in_playlist: Color::Rgb(139, 233, 253),
+            playing: Color::Rgb(255, 184, 108),
+        },
+        ThemePreset::Gruvbox => Theme {
+            text: Color::Rgb(235, 219, 178),
+            accent: Color::Rgb(250, 189, 47),
+            highlight_bg: Color::Rgb(80, 73, 69),
+            dim: Color::Rgb(146, 131, 116),
+            directory: Color::Rgb(131, 165, 152),
+            marked: Color::Rgb(184, 187, 38),
+            alert: Color::Rgb(251, 73, 52),
+            player: Color::Rgb(142, 192, 124),
+            chart: Color::Rgb(254, 128, 25),
+            chart_value: Color::Rgb(40, 40, 40),
+            moved: Color::Rgb(211, 134, 155),
+            in_playlist: Color::Rgb(142, 192, 124),
+            playing: Color::Rgb(254, 128, 25),
+        },
+        ThemePreset::Monochrome => Theme {
+            text: Color::White,
+            accent: Color::White,
+            highlight_bg: Color::DarkGray,
+            dim: Color::Gray,
+            directory: Color::White,
+            marked: Color::Gray,
+            alert: Color::White,
+            player: Color::White,
+            chart: Color::Gray,
+            chart_value: Color::Black,
+            moved: Color::Gray,
+            in_playlist: Color::DarkGray,
+            playing: Color::White,
+        },
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::models::stored_path;
+use crate::models::symbols::Symbols;
+use crate::utils::format_duration_clock;
+
+/// Entrada de una playlist: la ruta de la pista y datos ya conocidos de ella
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "TrackRefRepr")]
+pub struct TrackRef {
+    /// Ruta al archivo de audio
+    #[serde(serialize_with = "stored_path::serialize")]
+    pub path: PathBuf,
+    /// Título conocido sin leer el archivo (p. ej. de una línea `#EXTINF`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_title: Option<String>,
+    /// Duración conocida sin leer el archivo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_duration: Option<Duration>,
+}
+
+/// Formas aceptadas al leer: la ruta sola (archivos antiguos) o el objeto completo
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TrackRefRepr {
+    Path(#[serde(deserialize_with = "stored_path::deserialize")] PathBuf),
+    Full {
+        #[serde(deserialize_with = "stored_path::deserialize")]
+        path: PathBuf,
+        #[serde(default)]
+        cached_title: Option<String>,
+        #[serde(default)]
+        cached_duration: Option<Duration>,
+    },
+}
+
+impl From<TrackRefRepr> for TrackRef {
+    fn from(repr: TrackRefRepr) -> Self {
+        match repr {
+            TrackRefRepr::Path(path) => Self::from(path),
+            TrackRefRepr::Full {
+                path,
+                cached_title,
+                cached_duration,
+            } => Self {
+                path,
+                cached_title,
+                cached_duration,
+            },
+        }
+    }
+}
+
+impl From<PathBuf> for TrackRef {
+    fn from(path: PathBuf) -> Self {
+        Self {
+            path,
+            ..Default::default()
+        }
+    }
+}
+
+impl AsRef<Path> for TrackRef {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Condiciones que tiene que cumplir una pista para entrar en una mezcla
+///
+/// Los campos vacíos no filtran. Una pista a la que le falta el dato de un
+/// filtro activo (sin año, sin duración, sin género) entra salvo en modo estricto.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrackFilter {
+    /// Año mínimo, inclusive
+    pub year_from: Option<u32>,
+    /// Año máximo, inclusive
+    pub year_to: Option<u32>,
+    /// Duración mínima en segundos
+    pub min_seconds: Option<u64>,
+    /// Duración máxima en segundos
+    pub max_seconds: Option<u64>,
+    /// Si no está vacío, solo entran estos géneros
+    pub genres: Vec<String>,
+    /// Géneros que quedan afuera
+    pub exclude_genres: Vec<String>,
+    /// Deja afuera las pistas a las que les falta el dato filtrado
+    pub strict: bool,
+}
+
+impl TrackFilter {
+    /// No hay ninguna condición activa
+    pub fn is_empty(&self) -> bool {
+        self.year_from.is_none()
+            && self.year_to.is_none()
+            && self.min_seconds.is_none()
+            && self.max_seconds.is_none()
+            && self.genres.is_empty()
+            && self.exclude_genres.is_empty()
+    }
+
+    /// Resumen corto de las condiciones activas, p. ej. `1990–1999, ≥2:00, -jazz`
+    ///
+    /// Los símbolos siguen a `symbols`; los géneros se muestran tal como se escribieron.
+    pub fn summary(&self, symbols: &Symbols) -> String {
+        let clock = |seconds: u64| format_duration_clock(Duration::from_secs(seconds));
+        let (range, at_least, at_most) = (symbols.get("–"), symbols.get("≥"), symbols.get("≤"));
+        let mut parts = Vec::new();
+        match (self.year_from, self.year_to) {
+            (Some(from), Some(to)) => parts.push(format!("{}{}{}", from, range, to)),
+            (Some(from), None) => parts.push(format!("{}{}", at_least, from)),
+            (None, Some(to)) => parts.push(format!("{}{}", at_most, to)),
+            (None, None) => {}
+        }
+        if let Some(min) = self.min_seconds {
+            parts.push(format!("{}{}", at_least, clock(min)));
+        }
+        if let Some(max) = self.max_seconds {
+            parts.push(format!("{}{}", at_most, clock(max)));
+        }
+        if !self.genres.is_empty() {
+            parts.push(self.genres.join("/"));
+        }
+        parts.extend(
+            self.exclude_genres
+                .iter()
+                .map(|genre| format!("-{}", genre)),
+        );
+        if self.strict && !parts.is_empty() {
+            parts.push("strict".to_string());
+        }
+        parts.join(", ")
+    }
+}
@@ -0,0 +1,297 @@
+use crate::models::config::{Config, VolumeCurve};
+use crate::services::headless_service::{self, PlaybackSource};
+use crate::services::player_service::{PlayerCommand, PlayerService, PlayerStatus};
+use crate::services::playlist_storage_service;
+use crate::services::shuffle_service::ShuffleHistory;
+use crate::services::trim_service::TrimStore;
+use crate::services::volume_service;
+use crate::utils::format_duration_clock;
+use lofty::{Accessor, TaggedFileExt};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// Cada cuánto se revisan los comandos escritos mientras se espera al reproductor
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Paso de volumen de los comandos `+` y `-`
+const VOLUME_STEP: f32 = 0.1;
+
+const MENU: &str = "MENU: [1] play [2] next [3] pause [+] louder [-] quieter [s] status \
+                    [o <path>] open [l <name>] playlist [h] help [q] quit";
+
+/// Comandos que se pueden escribir en modo accesible
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Play,
+    Next,
+    Pause,
+    VolumeUp,
+    VolumeDown,
+    Status,
+    Open(PathBuf),
+    Playlist(String),
+    Help,
+    Quit,
+}
+
+/// Interpreta una línea escrita; acepta el número del menú o el nombre del comando
+fn parse_command(line: &str) -> Option<Command> {
+    let line = line.trim();
+    let (word, rest) = match line.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (line, ""),
+    };
+    match (word.to_lowercase().as_str(), rest.is_empty()) {
+        ("1" | "play", true) => Some(Command::Play),
+        ("2" | "next", true) => Some(Command::Next),
+        ("3" | "pause", true) => Some(Command::Pause),
+        ("+" | "louder", true) => Some(Command::VolumeUp),
+        ("-" | "quieter", true) => Some(Command::VolumeDown),
+        ("s" | "status", true) => Some(Command::Status),
+        ("o" | "open", false) => Some(Command::Open(PathBuf::from(rest))),
+        ("l" | "playlist", false) => Some(Command::Playlist(rest.to_string())),
+        ("h" | "help", true) => Some(Command::Help),
+        ("q" | "quit", true) => Some(Command::Quit),
+        _ => None,
+    }
+}
+
+/// "Artista - Título" según las etiquetas, o el nombre del archivo si no hay
+fn track_label(path: &Path) -> String {
+    let tags = lofty::read_from_path(path).ok().and_then(|file| {
+        file.primary_tag().map(|tag| {
+            (
+                tag.artist().map(|a| a.into_owned()),
+                tag.title().map(|t| t.into_owned()),
+            )
+        })
+    });
+    let file_name = || {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    match tags {
+        Some((Some(artist), Some(title))) => format!("{} - {}", artist, title),
+        Some((None, Some(title))) => title,
+        _ => file_name(),
+    }
+}
+
+/// Imprime una línea y la envía de inmediato, aunque stdout no sea una terminal
+fn say(line: &str) {
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// Estado de la reproducción visto desde el modo accesible
+struct Session {
+    player: PlayerService,
+    queue: Vec<PathBuf>,
+    current: Option<PathBuf>,
+    position: Duration,
+    /// Nivel que ve el usuario (1.0 = 100%), no la amplitud del reproductor
+    volume: f32,
+    volume_curve: VolumeCurve,
+    paused: bool,
+    /// Pistas que sonaron, para que el aleatorio de la interfaz no las repita
+    history: ShuffleHistory,
+}
+
+impl Session {
+    fn playing(&self) -> bool {
+        self.current.is_some()
+    }
+
+    fn start(&mut self, tracks: Vec<PathBuf>) {
+        self.queue = tracks;
+        self.paused = false;
+        let _ = self
+            .player
+            .send(PlayerCommand::PlayAlbum(self.queue.clone()));
+    }
+
+    fn set_volume(&mut self, level: f32) {
+        let amplitude = volume_service::level_to_amplitude(self.volume_curve, level);
+        let _ = self.player.send(PlayerCommand::SetVolume(amplitude));
+    }
+
+    fn status_line(&self) -> String {
+        match &self.current {
+            Some(path) => format!(
+                "{}: {} at {}, volume {}%",
+                if self.paused { "PAUSED" } else { "PLAYING" },
+                track_label(path),
+                format_duration_clock(self.position),
+                (self.volume * 100.0).round()
+            ),
+            None => format!("STOPPED: {} tracks queued", self.queue.len()),
+        }
+    }
+
+    /// Ejecuta un comando; retorna `false` si hay que salir
+    fn execute(&mut self, config: &Config, command: Command) -> bool {
+        match command {
+            Command::Play if self.paused => {
+                self.paused = false;
+                let _ = self.player.send(PlayerCommand::TogglePause);
+                say("RESUMED");
+            }
+            Command::Play if self.playing() => say(&self.status_line()),
+            Command::Play if self.queue.is_empty() => {
+                say("ERROR: nothing to play, use o <path> or l <playlist name>")
+            }
+            Command::Play => self.start(self.queue.clone()),
+            Command::Next if self.playing() => {
+                let _ = self.player.send(PlayerCommand::SkipNext);
+            }
+            Command::Pause if self.playing() => {
+                self.paused = !self.paused;
+                let _ = self.player.send(PlayerCommand::TogglePause);
+                say(if self.paused { "PAUSED" } else { "RESUMED" });
+            }
+            Command::Next | Command::Pause => say("STOPPED"),
+            Command::VolumeUp => self.set_volume(self.volume + VOLUME_STEP),
+            Command::VolumeDown => self.set_volume(self.volume - VOLUME_STEP),
+            Command::Status => say(&self.status_line()),
+            Command::Open(path) => self.load(config, PlaybackSource::Path(path)),
+            Command::Playlist(name) => self.load(config, PlaybackSource::Playlist(name)),
+            Command::Help => say(MENU),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    fn load(&mut self, config: &Config, source: PlaybackSource) {
+        match headless_service::playable_tracks(config, &source) {
+            Ok(tracks) => {
+                say(&format!("QUEUED: {} tracks", tracks.len()));
+                self.start(tracks);
+            }
+            Err(message) => say(&format!("ERROR: {}", message)),
+        }
+    }
+
+    fn handle_status(&mut self, status: PlayerStatus) {
+        match status {
+            PlayerStatus::TrackChanged(path) => {
+                say(&format!("PLAYING: {}", track_label(&path)));
+                self.history
+                    .record(&path, playlist_storage_service::unix_now());
+                self.current = Some(path);
+                self.position = Duration::ZERO;
+            }
+            PlayerStatus::Position(position) => self.position = position,
+            PlayerStatus::Volume(amplitude) => {
+                let volume = volume_service::amplitude_to_level(self.volume_curve, amplitude);
+                // El reproductor informa el volumen al iniciar; solo se anuncian cambios
+                if (volume - self.volume).abs() > f32::EPSILON {
+                    say(&format!("VOLUME: {}%", (volume * 100.0).round()));
+                }
+                self.volume = volume;
+            }
+            PlayerStatus::Stopped => {
+                self.current = None;
+                self.paused = false;
+                self.position = Duration::ZERO;
+                say("STOPPED");
+            }
+            PlayerStatus::Error(message) => say(&format!("ERROR: {}", message)),
+            PlayerStatus::TrackError(path, problem) => say(&format!(
+                "ERROR: skipped {}: {}",
+                track_label(&path),
+                problem
+            )),
+            PlayerStatus::ResumedAfterSleep { paused } => {
+                self.paused = paused;
+                say(if paused {
+                    "PAUSED after sleep"
+                } else {
+                    "RESUMED after sleep"
+                });
+            }
+            PlayerStatus::AudioFormat { .. } | PlayerStatus::QueueSnapshot { .. } => {}
+        }
+    }
+}
+
+/// Interfaz de líneas para lectores de pantalla y líneas braille
+///
+/// No usa modo raw ni secuencias de escape: anuncia cada cambio con una línea
+/// (`PLAYING: Artista - Título`, `PAUSED`, `STOPPED`, `ERROR: ...`) y lee
+/// comandos completos terminados con Enter. Si se da `source` empieza a
+/// reproducirlo de inmediato.
+///
+/// # Errores
+/// - Retorna `Other` si el reproductor termina inesperadamente (sin dispositivo de audio)
+pub fn run_accessible(config: &Config, source: Option<PlaybackSource>) -> io::Result<()> {
+    let mut session = Session {
+        player: PlayerService::with_audio(),
+        queue: Vec::new(),
+        current: None,
+        position: Duration::ZERO,
+        volume: 1.0,
+        volume_curve: config.volume_curve,
+        paused: false,
+        history: ShuffleHistory::load(),
+    };
+    let _ = session.player.send(PlayerCommand::SetPreferNativeRate(
+        config.prefer_native_sample_rate,
+    ));
+    let _ = session.player.send(PlayerCommand::SetResumeAfterSleep(
+        config.resume_after_sleep,
+    ));
+    let _ = session
+        .player
+        .send(PlayerCommand::SetTrims(TrimStore::load().all()));
+
+    // stdin se lee en otro hilo para no dejar de atender al reproductor
+    let (line_sender, lines) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if line_sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    say("rusted-player accessible mode");
+    match source {
+        Some(source) => session.load(config, source),
+        None => say("Nothing queued. Type o <path> or l <playlist name> to start."),
+    }
+    say(MENU);
+
+    let result = loop {
+        match session.player.receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(status) => session.handle_status(status),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                break Err(io::Error::other("El reproductor terminó inesperadamente"));
+            }
+        }
+
+        match lines.try_recv() {
+            Ok(line) if line.trim().is_empty() => {}
+            Ok(line) => match parse_command(&line) {
+                Some(command) => {
+                    if !session.execute(config, command) {
+                        break Ok(());
+                    }
+                }
+                None => say(&format!("UNKNOWN: {}. {}", line.trim(), MENU)),
+            },
+            Err(mpsc::TryRecvError::Empty) => {}
+            // Fin de la entrada (Ctrl+D o tubería cerrada)
+            Err(mpsc::TryRecvError::Disconnected) => break Ok(()),
+        }
+    };
+
+    let _ = session.player.send(PlayerCommand::Quit);
+    let _ = session.history.save();
+    say("BYE");
+    result
+}
@@ -0,0 +1,202 @@
+use crate::services::status_format_service::truncate;
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+/// Separador entre segmentos
+pub const SEPARATOR: &str = " ▸ ";
+
+/// Marca que reemplaza los segmentos del medio que no entran
+pub const COLLAPSED: &str = "…";
+
+/// Carpeta de la ruta de migas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Nombre que se muestra
+    pub label: String,
+    pub path: PathBuf,
+}
+
+/// Parte visible de la ruta de migas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Crumb {
+    /// Segmento `index` de la ruta, con el texto a mostrar (recortado si hizo falta)
+    Segment { index: usize, label: String },
+    /// Segmentos del medio que no entran
+    Collapsed,
+}
+
+/// Segmentos desde la raíz de la biblioteca hasta `dir`
+///
+/// Si `dir` está fuera de la biblioteca la ruta empieza en la raíz del sistema.
+pub fn segments(root: &Path, dir: &Path) -> Vec<Segment> {
+    let label = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    };
+    let start = if dir.starts_with(root) {
+        root
+    } else {
+        dir.ancestors().last().unwrap_or(dir)
+    };
+    let mut path = start.to_path_buf();
+    let mut segments = vec![Segment {
+        label: label(start),
+        path: path.clone(),
+    }];
+    for component in dir
+        .strip_prefix(start)
+        .unwrap_or(Path::new(""))
+        .components()
+    {
+        path.push(component);
+        segments.push(Segment {
+            label: component.as_os_str().to_string_lossy().into_owned(),
+            path: path.clone(),
+        });
+    }
+    segments
+}
+
+/// Elige qué segmentos mostrar en `width` columnas
+///
+/// Si no entran todos se conservan el primero y el último, y se agregan los
+/// más cercanos al último que quepan; los demás se reemplazan por `…`. Si ni
+/// así entra, se descarta el primero y se recorta el último; sin lugar para
+/// `… ▸ ` queda solo el último recortado.
+pub fn collapse(labels: &[&str], width: usize) -> Vec<Crumb> {
+    let Some(last) = labels.len().checked_sub(1) else {
+        return Vec::new();
+    };
+    let crumb = |index: usize| Crumb::Segment {
+        index,
+        label: labels[index].to_string(),
+    };
+
+    let total = labels.iter().map(|label| label.width()).sum::<usize>() + SEPARATOR.width() * last;
+    if total <= width {
+        return (0..=last).map(crumb).collect();
+    }
+
+    let gap = SEPARATOR.width();
+    let mut used = labels[0].width() + gap + COLLAPSED.width() + gap + labels[last].width();
+    if last > 0 && used <= width {
+        let mut tail = vec![crumb(last)];
+        for index in (1..last).rev() {
+            let extra = labels[index].width() + gap;
+            if used + extra > width {
+                break;
+            }
+            used += extra;
+            tail.push(crumb(index));
+        }
+        tail.reverse();
+        let mut crumbs = vec![crumb(0), Crumb::Collapsed];
+        crumbs.extend(tail);
+        return crumbs;
+    }
+
+    let room = width.saturating_sub(COLLAPSED.width() + gap);
+    if last == 0 || room == 0 {
+        return vec![Crumb::Segment {
+            index: last,
+            label: truncate(labels[last], width),
+        }];
+    }
+    vec![
+        Crumb::Collapsed,
+        Crumb::Segment {
+            index: last,
+            label: truncate(labels[last], room),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// La ruta de migas tal como se dibuja
+    fn render(crumbs: &[Crumb]) -> String {
+        let parts: Vec<&str> = crumbs
+            .iter()
+            .map(|crumb| match crumb {
+                Crumb::Segment { label, .. } => label.as_str(),
+                Crumb::Collapsed => COLLAPSED,
+            })
+            .collect();
+        parts.join(SEPARATOR)
+    }
+
+    #[test]
+    fn collapse_table() {
+        let deep = ["Music", "Rock", "Pink Floyd", "1971", "Meddle"];
+        let table: [(&[&str], usize, &str); 10] = [
+            (&deep, 60, "Music ▸ Rock ▸ Pink Floyd ▸ 1971 ▸ Meddle"),
+            // Se conservan los más cercanos al último
+            (&deep, 30, "Music ▸ … ▸ 1971 ▸ Meddle"),
+            (&deep, 22, "Music ▸ … ▸ Meddle"),
+            // Sin lugar para el primero queda solo el último, recortado
+            (&deep, 12, "… ▸ Meddle"),
+            (&deep, 8, "… ▸ Med…"),
+            (&["Music"], 3, "Mu…"),
+            // Sin lugar para "… ▸ " tampoco va la marca
+            (&deep, 4, "Med…"),
+            (&deep, 1, "…"),
+            // Anchos dobles: se recorta por columnas sin partir caracteres
+            (&["音楽", "日本", "坂本龍一"], 30, "音楽 ▸ 日本 ▸ 坂本龍一"),
+            (&["音楽", "日本", "坂本龍一"], 9, "… ▸ 坂本…"),
+        ];
+        for (labels, width, expected) in table {
+            assert_eq!(
+                render(&collapse(labels, width)),
+                expected,
+                "{labels:?} at {width}"
+            );
+        }
+        assert_eq!(collapse(&[], 10), []);
+    }
+
+    #[test]
+    fn collapsed_crumbs_never_exceed_the_width() {
+        let paths: [&[&str]; 3] = [
+            &["Music", "Rock", "Pink Floyd", "1971", "Meddle"],
+            &[
+                "音楽",
+                "日本のアーティスト",
+                "坂本龍一",
+                "戦場のメリークリスマス",
+            ],
+            &["/", "a"],
+        ];
+        for labels in paths {
+            for width in 0..60 {
+                let rendered = render(&collapse(labels, width));
+                assert!(
+                    rendered.width() <= width,
+                    "{rendered:?} is wider than {width}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn segments_start_at_the_library_or_the_filesystem_root() {
+        let labels = |root: &str, dir: &str| -> Vec<String> {
+            segments(Path::new(root), Path::new(dir))
+                .into_iter()
+                .map(|segment| segment.label)
+                .collect()
+        };
+        assert_eq!(
+            labels("/home/me/Music", "/home/me/Music/Rock/Meddle"),
+            ["Music", "Rock", "Meddle"]
+        );
+        assert_eq!(labels("/home/me/Music", "/home/me/Music"), ["Music"]);
+        assert_eq!(labels("/home/me/Music", "/mnt/usb"), ["/", "mnt", "usb"]);
+        let last = segments(Path::new("/m"), Path::new("/m/a/b"))
+            .pop()
+            .unwrap();
+        assert_eq!(last.path, Path::new("/m/a/b"));
+    }
+}
@@ -0,0 +1,34 @@
+use std::process::{Command, Stdio};
+
+/// Comandos para leer el portapapeles, en orden de preferencia según la plataforma
+#[cfg(target_os = "macos")]
+const PASTE_COMMANDS: &[&[&str]] = &[&["pbpaste"]];
+
+#[cfg(windows)]
+const PASTE_COMMANDS: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+
+#[cfg(not(any(target_os = "macos", windows)))]
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+/// Lee texto del portapapeles del sistema
+///
+/// Se usa como alternativa para terminales sin bracketed paste. Prueba las
+/// herramientas de la plataforma en orden y retorna `None` si ninguna funciona.
+pub fn read_clipboard() -> Option<String> {
+    PASTE_COMMANDS.iter().find_map(|command| {
+        let output = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    })
+}
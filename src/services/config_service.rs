@@ -52,12 +52,48 @@ fn get_config_dir() -> Result<PathBuf, std::io::Error> {
         return Ok(dir);
     }
 
-    Err(std::io::Error::new(
-        std::io::ErrorKind::Other,
+    Err(std::io::Error::other(
         "No se pudo determinar el directorio de configuración del usuario",
     ))
 }
 
+/// Obtiene el directorio de caché del usuario, para datos que se pueden regenerar
+///
+/// Soporta:
+/// - Linux/Unix: XDG_CACHE_HOME o ~/.cache/
+/// - macOS: ~/Library/Caches/
+/// - Windows: %LOCALAPPDATA%\rusted-player\
+pub fn get_cache_dir() -> Result<PathBuf, std::io::Error> {
+    if let Ok(xdg_cache) = env::var("XDG_CACHE_HOME") {
+        let dir = PathBuf::from(xdg_cache).join(CONFIG_DIR_NAME);
+        ensure_dir_exists(&dir)?;
+        return Ok(dir);
+    }
+
+    if let Ok(local_appdata) = env::var("LOCALAPPDATA") {
+        let dir = PathBuf::from(local_appdata).join(CONFIG_DIR_NAME);
+        ensure_dir_exists(&dir)?;
+        return Ok(dir);
+    }
+
+    if let Ok(home) = env::var("HOME") {
+        #[cfg(target_os = "macos")]
+        let dir = PathBuf::from(&home)
+            .join("Library")
+            .join("Caches")
+            .join(CONFIG_DIR_NAME);
+        #[cfg(not(target_os = "macos"))]
+        let dir = PathBuf::from(&home).join(".cache").join(CONFIG_DIR_NAME);
+
+        ensure_dir_exists(&dir)?;
+        return Ok(dir);
+    }
+
+    Err(std::io::Error::other(
+        "No se pudo determinar el directorio de caché del usuario",
+    ))
+}
+
 /// Helper para crear directorio si no existe
 fn ensure_dir_exists(dir: &PathBuf) -> Result<(), std::io::Error> {
     if !dir.exists() {
@@ -71,6 +107,20 @@ fn get_config_path() -> Result<PathBuf, std::io::Error> {
     Ok(get_config_dir()?.join(CONFIG_FILE_NAME))
 }
 
+/// Obtiene la ruta para un archivo exportado dentro del directorio de configuración
+pub fn get_export_path(file_name: &str) -> Result<PathBuf, std::io::Error> {
+    Ok(get_config_dir()?.join(file_name))
+}
+
+/// Guarda un reporte de texto en el directorio de configuración
+///
+/// Retorna la ruta del archivo escrito.
+pub fn save_report(file_name: &str, contents: &str) -> Result<PathBuf, std::io::Error> {
+    let report_path = get_export_path(file_name)?;
+    fs::write(&report_path, contents)?;
+    Ok(report_path)
+}
+
 /// Expande variables de entorno en la ruta
 ///
 /// Soporta:
@@ -80,38 +130,38 @@ fn expand_env_vars(path: &str) -> PathBuf {
     let mut result = path.to_string();
 
     // Windows variables
-    if result.contains("%USERNAME%") {
-        if let Ok(username) = env::var("USERNAME") {
-            result = result.replace("%USERNAME%", &username);
-        }
+    if result.contains("%USERNAME%")
+        && let Ok(username) = env::var("USERNAME")
+    {
+        result = result.replace("%USERNAME%", &username);
     }
-    if result.contains("%USERPROFILE%") {
-        if let Ok(userprofile) = env::var("USERPROFILE") {
-            result = result.replace("%USERPROFILE%", &userprofile);
-        }
+    if result.contains("%USERPROFILE%")
+        && let Ok(userprofile) = env::var("USERPROFILE")
+    {
+        result = result.replace("%USERPROFILE%", &userprofile);
     }
-    if result.contains("%APPDATA%") {
-        if let Ok(appdata) = env::var("APPDATA") {
-            result = result.replace("%APPDATA%", &appdata);
-        }
+    if result.contains("%APPDATA%")
+        && let Ok(appdata) = env::var("APPDATA")
+    {
+        result = result.replace("%APPDATA%", &appdata);
     }
-    if result.contains("%LOCALAPPDATA%") {
-        if let Ok(localappdata) = env::var("LOCALAPPDATA") {
-            result = result.replace("%LOCALAPPDATA%", &localappdata);
-        }
+    if result.contains("%LOCALAPPDATA%")
+        && let Ok(localappdata) = env::var("LOCALAPPDATA")
+    {
+        result = result.replace("%LOCALAPPDATA%", &localappdata);
     }
 
     // Unix/Linux/macOS variables
-    if result.contains("$USER") {
-        if let Ok(user) = env::var("USER") {
-            result = result.replace("$USER", &user);
-        }
+    if result.contains("$USER")
+        && let Ok(user) = env::var("USER")
+    {
+        result = result.replace("$USER", &user);
     }
-    if result.contains("$HOME") || result.contains("~") {
-        if let Ok(home) = env::var("HOME") {
-            result = result.replace("$HOME", &home);
-            result = result.replace("~", &home);
-        }
+    if (result.contains("$HOME") || result.contains("~"))
+        && let Ok(home) = env::var("HOME")
+    {
+        result = result.replace("$HOME", &home);
+        result = result.replace("~", &home);
     }
 
     PathBuf::from(result)
@@ -133,12 +183,9 @@ pub fn load_config() -> Config {
         }
     };
 
-    let config = match fs::read_to_string(&config_path) {
+    match fs::read_to_string(&config_path) {
         Ok(config_str) => {
-            let mut config: Config = match serde_json::from_str(&config_str) {
-                Ok(cfg) => cfg,
-                Err(_e) => Config::default(),
-            };
+            let mut config: Config = serde_json::from_str(&config_str).unwrap_or_default();
 
             // Expande variables de entorno en la ruta
             if let Some(path_str) = config.music_path.to_str() {
@@ -150,9 +197,7 @@ pub fn load_config() -> Config {
             // Archivo no existe o error de lectura - usar configuración por defecto
             Config::default()
         }
-    };
-
-    config
+    }
 }
 
 /// Guarda la configuración en el archivo config.json
@@ -161,8 +206,7 @@ pub fn load_config() -> Config {
 /// Crea el directorio si no existe.
 pub fn save_config(config: &Config) -> Result<(), std::io::Error> {
     let config_path = get_config_path()?;
-    let config_str = serde_json::to_string_pretty(config)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    let config_str = serde_json::to_string_pretty(config).map_err(std::io::Error::other)?;
     fs::write(config_path, config_str)?;
     Ok(())
 }
@@ -0,0 +1,217 @@
+use crate::services::config_service;
+use crate::services::metadata_service::read_embedded_art;
+use crate::utils::fnv1a;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Carpeta de las portadas dentro del directorio de caché
+const COVERS_DIR_NAME: &str = "covers";
+
+/// Extensión de cada portada guardada; los bytes son la imagen tal como venía
+/// en la etiqueta (JPEG o PNG)
+const COVER_EXTENSION: &str = "cover";
+
+/// Tamaño máximo de la caché; al pasarlo se borran las portadas usadas hace más tiempo
+const MAX_CACHE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Portadas embebidas ya extraídas, guardadas en el directorio de caché del sistema
+///
+/// Se guarda una portada por álbum, no por pista, y la carpeta no pasa de
+/// `MAX_CACHE_BYTES`. Una portada más vieja que la última modificación de la
+/// pista se vuelve a extraer: si se editan las etiquetas, se reemplaza.
+#[derive(Debug, Clone)]
+pub struct CoverCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl CoverCache {
+    /// Caché en el directorio de caché del usuario
+    ///
+    /// La carpeta de portadas recién se crea al guardar la primera.
+    ///
+    /// # Errors
+    /// Retorna error si no se puede determinar el directorio de caché
+    pub fn open() -> Result<Self, std::io::Error> {
+        Ok(Self::in_dir(
+            config_service::get_cache_dir()?.join(COVERS_DIR_NAME),
+            MAX_CACHE_BYTES,
+        ))
+    }
+
+    fn in_dir(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// Archivo de la portada de un álbum, o de la pista si no tiene álbum
+    fn entry_path(&self, track_path: &Path, album: Option<&str>) -> PathBuf {
+        let key = match album {
+            Some(album) => fnv1a(album.as_bytes()),
+            None => fnv1a(track_path.as_os_str().as_encoded_bytes()),
+        };
+        self.dir
+            .join(format!("{:016x}", key))
+            .with_extension(COVER_EXTENSION)
+    }
+
+    /// Portada guardada, si no es anterior a la última modificación de la pista
+    ///
+    /// Leerla la marca como recién usada, para que la poda la conserve.
+    fn cached(entry: &Path, track_path: &Path) -> Option<Vec<u8>> {
+        let stored = fs::metadata(entry).and_then(|m| m.modified()).ok()?;
+        let track = fs::metadata(track_path).and_then(|m| m.modified()).ok()?;
+        if stored < track {
+            return None;
+        }
+        let cover = fs::read(entry).ok()?;
+        let _ = fs::File::options()
+            .write(true)
+            .open(entry)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some(cover)
+    }
+
+    /// Guarda la portada escribiendo primero un temporal, para no dejar una
+    /// imagen a medias si el programa se corta
+    fn store(&self, entry: &Path, cover: &[u8]) -> Result<(), std::io::Error> {
+        fs::create_dir_all(&self.dir)?;
+        let partial = entry.with_extension("partial");
+        fs::write(&partial, cover)?;
+        fs::rename(&partial, entry)?;
+        self.prune();
+        Ok(())
+    }
+
+    /// Borra las portadas usadas hace más tiempo hasta entrar en `max_bytes`
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut covers: Vec<(SystemTime, u64, PathBuf)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect();
+        let mut total: u64 = covers.iter().map(|(_, size, _)| size).sum();
+        covers.sort_by_key(|(modified, _, _)| *modified);
+        for (_, size, path) in covers {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+/// Portada embebida de la pista, leída de la caché o extraída con lofty
+///
+/// `album` es la clave del álbum de la pista: todas sus pistas comparten una
+/// entrada. Lo extraído se guarda para la próxima vez; si no se puede guardar,
+/// la portada se devuelve igual. Una pista sin portada embebida no deja entrada.
+pub fn get_or_extract_cover(
+    track_path: &Path,
+    album: Option<&str>,
+    cache: &CoverCache,
+) -> Option<Vec<u8>> {
+    let entry = cache.entry_path(track_path, album);
+    if let Some(cover) = CoverCache::cached(&entry, track_path) {
+        return Some(cover);
+    }
+    let cover = read_embedded_art(track_path)?;
+    let _ = cache.store(&entry, &cover);
+    Some(cover)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn set_modified(path: &Path, secs: u64) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn tracks_of_an_album_share_one_entry() {
+        let cache = CoverCache::in_dir(PathBuf::from("/cache"), MAX_CACHE_BYTES);
+        let one = Path::new("/m/Album/01.mp3");
+        let two = Path::new("/m/Album/02.mp3");
+        assert_eq!(
+            cache.entry_path(one, Some("album:album:artist")),
+            cache.entry_path(two, Some("album:album:artist"))
+        );
+        assert_ne!(cache.entry_path(one, None), cache.entry_path(two, None));
+    }
+
+    #[test]
+    fn the_folder_is_created_only_when_a_cover_is_stored() {
+        let root = temp_dir("covers-lazy");
+        let cache = CoverCache::in_dir(root.join(COVERS_DIR_NAME), MAX_CACHE_BYTES);
+        let track = root.join("01.mp3");
+        fs::write(&track, b"audio").unwrap();
+        let entry = cache.entry_path(&track, Some("album"));
+        assert_eq!(CoverCache::cached(&entry, &track), None);
+        assert!(!cache.dir.exists());
+
+        cache.store(&entry, b"jpeg").unwrap();
+        assert_eq!(CoverCache::cached(&entry, &track), Some(b"jpeg".to_vec()));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn a_cover_older_than_the_track_is_stale() {
+        let root = temp_dir("covers-stale");
+        let cache = CoverCache::in_dir(root.join(COVERS_DIR_NAME), MAX_CACHE_BYTES);
+        let track = root.join("01.mp3");
+        fs::write(&track, b"audio").unwrap();
+        let entry = cache.entry_path(&track, None);
+        cache.store(&entry, b"jpeg").unwrap();
+        set_modified(&entry, 1_000);
+        set_modified(&track, 2_000);
+        assert_eq!(CoverCache::cached(&entry, &track), None);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn pruning_drops_the_least_recently_used_covers() {
+        let root = temp_dir("covers-prune");
+        let cache = CoverCache::in_dir(root.clone(), 25);
+        for i in 0..5u64 {
+            let path = root.join(format!("{i}.{COVER_EXTENSION}"));
+            fs::write(&path, [0u8; 10]).unwrap();
+            set_modified(&path, 1_000 + i);
+        }
+        // La más vieja se acaba de leer
+        set_modified(&root.join(format!("0.{COVER_EXTENSION}")), 5_000);
+        cache.prune();
+
+        let mut left: Vec<String> = fs::read_dir(&root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["0.cover", "4.cover"]);
+        let _ = fs::remove_dir_all(root);
+    }
+}
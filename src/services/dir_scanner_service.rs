@@ -0,0 +1,634 @@
+use crate::services::file_walker_service::is_junk_file;
+use crate::services::metadata_service::split_disc_suffix;
+use crate::services::playlist_storage_service;
+use crate::utils::is_audio_file;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Tipo de entrada de un directorio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EntryKind {
+    /// Subdirectorio (incluye `..`)
+    Dir,
+    /// Lista `.m3u`/`.m3u8`
+    Playlist,
+    /// Archivo de audio
+    Track,
+}
+
+/// Entrada visible de un directorio
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Nombre del archivo o directorio tal como se muestra
+    ///
+    /// Un nombre que no es UTF-8 válido queda con caracteres de reemplazo; para
+    /// llegar al archivo se usa `file_name`.
+    pub name: String,
+    /// Nombre exacto en el sistema de archivos
+    pub file_name: OsString,
+    /// Tipo de entrada
+    pub kind: EntryKind,
+    /// Fecha de modificación, si el sistema la informa
+    pub modified: Option<SystemTime>,
+    /// Carpetas de disco que agrupa esta entrada, en orden; vacío en una carpeta normal
+    pub discs: Vec<OsString>,
+    /// Es un enlace simbólico (la interfaz lo marca con `@`)
+    pub symlink: bool,
+}
+
+impl DirEntry {
+    /// Ruta de la entrada dentro de `dir`, sin pasar por el nombre mostrado
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.file_name)
+    }
+
+    /// Texto con el que se muestra en la lista (`[DIR] `, `[PL] ` o el nombre)
+    pub fn label(&self) -> String {
+        match self.kind {
+            EntryKind::Dir if !self.discs.is_empty() => {
+                format!("[DIR] {} ({} discs)", self.name, self.discs.len())
+            }
+            EntryKind::Dir => format!("[DIR] {}", self.name),
+            EntryKind::Playlist => format!("[PL] {}", self.name),
+            EntryKind::Track => self.name.clone(),
+        }
+    }
+}
+
+/// Orden de las entradas dentro de cada tipo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Alfabético sin distinguir mayúsculas
+    #[default]
+    Name,
+    /// Más recientes primero
+    Modified,
+}
+
+/// Lee un directorio y decide qué entradas mostrar, sin tocar el estado de la interfaz
+#[derive(Debug, Clone)]
+pub struct DirScanner {
+    /// Muestra archivos y carpetas que empiezan con `.`
+    ///
+    /// Los AppleDouble (`._*`) y los archivos vacíos no se muestran nunca.
+    pub show_hidden: bool,
+    /// Si se indica, solo se muestran pistas con esa extensión
+    pub format_filter: Option<String>,
+    /// Orden de las entradas
+    pub sort_mode: SortMode,
+    /// Muestra las carpetas "X (Disc 1)", "X (Disc 2)" como una sola entrada
+    pub merge_discs: bool,
+}
+
+impl DirScanner {
+    /// Crea un escáner que muestra todo, ordenado por nombre
+    pub fn new() -> Self {
+        Self {
+            show_hidden: true,
+            format_filter: None,
+            sort_mode: SortMode::default(),
+            merge_discs: true,
+        }
+    }
+
+    /// Lista las entradas de un directorio
+    ///
+    /// Los directorios van primero (con `..` al inicio si hay padre), luego las
+    /// listas M3U y al final las pistas. Retorna una lista vacía si no se puede leer.
+    pub fn scan(&self, dir: &Path) -> Vec<DirEntry> {
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut entries: Vec<DirEntry> = read_dir
+            .filter_map(|res| res.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let name = file_name.to_string_lossy().into_owned();
+                if !self.show_hidden && name.starts_with('.') {
+                    return None;
+                }
+                let path = entry.path();
+                let kind = if path.is_dir() {
+                    EntryKind::Dir
+                } else if playlist_storage_service::is_m3u_file(&path) {
+                    EntryKind::Playlist
+                } else if is_audio_file(&path)
+                    && self.matches_format(&path)
+                    && !is_junk_file(&path, self.show_hidden)
+                {
+                    EntryKind::Track
+                } else {
+                    return None;
+                };
+                let modified = entry.metadata().and_then(|m| m.modified()).ok();
+                let symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+                Some(DirEntry {
+                    name,
+                    file_name,
+                    kind,
+                    modified,
+                    discs: Vec::new(),
+                    symlink,
+                })
+            })
+            .collect();
+
+        if self.merge_discs {
+            entries = merge_disc_dirs(entries);
+        }
+
+        match self.sort_mode {
+            SortMode::Name => entries.sort_by_cached_key(|e| (e.kind, e.name.to_lowercase())),
+            SortMode::Modified => {
+                entries.sort_by_key(|e| (e.kind, std::cmp::Reverse(e.modified)));
+            }
+        }
+
+        if dir.parent().is_some() {
+            entries.insert(
+                0,
+                DirEntry {
+                    name: "..".to_string(),
+                    file_name: OsString::from(".."),
+                    kind: EntryKind::Dir,
+                    modified: None,
+                    discs: Vec::new(),
+                    symlink: false,
+                },
+            );
+        }
+        entries
+    }
+
+    fn matches_format(&self, path: &Path) -> bool {
+        match &self.format_filter {
+            Some(format) => path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(format)),
+            None => true,
+        }
+    }
+}
+
+/// Máximo de pistas para tratar una carpeta como un álbum
+pub const LEAF_ALBUM_MAX_TRACKS: usize = 100;
+
+/// Qué hacer al entrar en una carpeta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafAlbumAction {
+    /// Carpeta común: solo se muestra el contenido
+    Browse,
+    /// Álbum: se ofrece reproducirlo
+    Prompt,
+    /// Álbum: se reproduce tras un momento si no se toca ninguna tecla
+    AutoPlay,
+}
+
+/// Decide qué hacer al entrar en `dir`, ya escaneado en `entries`
+///
+/// Una carpeta es un álbum si solo tiene pistas (sin subcarpetas ni listas)
+/// y no más de `LEAF_ALBUM_MAX_TRACKS`. La raíz de la biblioteca nunca cuenta.
+pub fn leaf_album_action(
+    dir: &Path,
+    library_root: &Path,
+    entries: &[DirEntry],
+    auto_play: bool,
+) -> LeafAlbumAction {
+    let contents = entries.iter().filter(|entry| entry.name != "..");
+    let is_leaf = contents.clone().all(|entry| entry.kind == EntryKind::Track);
+    let tracks = contents.count();
+    if dir == library_root || !is_leaf || tracks == 0 || tracks > LEAF_ALBUM_MAX_TRACKS {
+        LeafAlbumAction::Browse
+    } else if auto_play {
+        LeafAlbumAction::AutoPlay
+    } else {
+        LeafAlbumAction::Prompt
+    }
+}
+
+/// Agrega debajo de cada álbum fusionado abierto una fila por cada disco
+///
+/// `open` tiene los `file_name` de los álbumes abiertos. Las filas de disco son
+/// carpetas comunes que apuntan a la carpeta real de ese disco, en orden.
+pub fn expand_disc_groups(entries: Vec<DirEntry>, open: &HashSet<OsString>) -> Vec<DirEntry> {
+    let mut expanded = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let discs: Vec<DirEntry> = if open.contains(&entry.file_name) {
+            entry
+                .discs
+                .iter()
+                .map(|disc| DirEntry {
+                    name: disc.to_string_lossy().into_owned(),
+                    file_name: disc.clone(),
+                    kind: EntryKind::Dir,
+                    modified: None,
+                    discs: Vec::new(),
+                    symlink: false,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        expanded.push(entry);
+        expanded.extend(discs);
+    }
+    expanded
+}
+
+/// Junta las carpetas hermanas que son discos de un mismo álbum en una sola entrada
+///
+/// Solo se fusionan grupos de dos o más carpetas; los discos quedan ordenados por número.
+fn merge_disc_dirs(entries: Vec<DirEntry>) -> Vec<DirEntry> {
+    let mut groups: HashMap<String, Vec<(u32, DirEntry)>> = HashMap::new();
+    let mut merged = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match split_disc_suffix(&entry.name).filter(|_| entry.kind == EntryKind::Dir) {
+            Some((base, disc)) => groups.entry(base).or_default().push((disc, entry)),
+            None => merged.push(entry),
+        }
+    }
+
+    for (base, mut discs) in groups {
+        if discs.len() < 2 {
+            merged.extend(discs.into_iter().map(|(_, entry)| entry));
+            continue;
+        }
+        discs.sort_by(|a, b| (a.0, &a.1.name).cmp(&(b.0, &b.1.name)));
+        merged.push(DirEntry {
+            file_name: OsString::from(&base),
+            name: base,
+            kind: EntryKind::Dir,
+            modified: discs.iter().filter_map(|(_, entry)| entry.modified).max(),
+            symlink: discs.iter().all(|(_, entry)| entry.symlink),
+            discs: discs
+                .into_iter()
+                .map(|(_, entry)| entry.file_name)
+                .collect(),
+        });
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, kind: EntryKind) -> DirEntry {
+        DirEntry {
+            name: name.to_string(),
+            file_name: OsString::from(name),
+            kind,
+            modified: None,
+            discs: Vec::new(),
+            symlink: false,
+        }
+    }
+
+    fn dirs(names: &[&str]) -> Vec<DirEntry> {
+        names
+            .iter()
+            .map(|name| entry(name, EntryKind::Dir))
+            .collect()
+    }
+
+    /// Etiquetas y discos de cada entrada, ordenadas como en la lista
+    fn summary(mut entries: Vec<DirEntry>) -> Vec<(String, Vec<String>)> {
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+            .into_iter()
+            .map(|e| {
+                let discs = e
+                    .discs
+                    .iter()
+                    .map(|d| d.to_string_lossy().into_owned())
+                    .collect();
+                (e.label(), discs)
+            })
+            .collect()
+    }
+
+    /// Etiqueta de una fila y las carpetas de disco que agrupa
+    type Row<'a> = (&'a str, &'a [&'a str]);
+
+    /// Carpeta temporal con los archivos indicados (los que terminan en `/` son carpetas)
+    fn temp_tree(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            match file.strip_suffix('/') {
+                Some(sub) => std::fs::create_dir_all(dir.join(sub)).unwrap(),
+                None => std::fs::write(dir.join(file), b"audio").unwrap(),
+            }
+        }
+        dir
+    }
+
+    fn labels(entries: &[DirEntry]) -> Vec<String> {
+        entries.iter().map(DirEntry::label).collect()
+    }
+
+    #[test]
+    fn scan_lists_dirs_then_playlists_then_tracks() {
+        let dir = temp_tree(
+            "scan-order",
+            &[
+                "b.mp3",
+                "A.flac",
+                "mix.m3u",
+                "Zeta/",
+                "alpha/",
+                "cover.jpg",
+                "notes.txt",
+            ],
+        );
+        assert_eq!(
+            labels(&DirScanner::new().scan(&dir)),
+            [
+                "[DIR] ..",
+                "[DIR] alpha",
+                "[DIR] Zeta",
+                "[PL] mix.m3u",
+                "A.flac",
+                "b.mp3"
+            ]
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn scan_filters_hidden_entries_and_formats() {
+        let dir = temp_tree(
+            "scan-filters",
+            &[".hidden.mp3", ".cache/", "a.mp3", "b.flac"],
+        );
+        let scanner = DirScanner {
+            show_hidden: false,
+            ..DirScanner::new()
+        };
+        assert_eq!(labels(&scanner.scan(&dir)), ["[DIR] ..", "a.mp3", "b.flac"]);
+
+        let scanner = DirScanner {
+            format_filter: Some("FLAC".to_string()),
+            ..DirScanner::new()
+        };
+        assert_eq!(
+            labels(&scanner.scan(&dir)),
+            ["[DIR] ..", "[DIR] .cache", "b.flac"]
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn scan_by_date_puts_the_newest_first() {
+        let dir = temp_tree("scan-date", &["old.mp3", "new.mp3", "mid.mp3"]);
+        for (name, secs) in [("old.mp3", 1_000), ("mid.mp3", 2_000), ("new.mp3", 3_000)] {
+            std::fs::File::options()
+                .write(true)
+                .open(dir.join(name))
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                .unwrap();
+        }
+        let scanner = DirScanner {
+            sort_mode: SortMode::Modified,
+            ..DirScanner::new()
+        };
+        assert_eq!(
+            labels(&scanner.scan(&dir)),
+            ["[DIR] ..", "new.mp3", "mid.mp3", "old.mp3"]
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn scan_of_a_missing_dir_is_empty() {
+        let dir = std::env::temp_dir().join("rusted-player-test-does-not-exist");
+        assert!(DirScanner::new().scan(&dir).is_empty());
+    }
+
+    #[test]
+    fn merge_disc_dirs_table() {
+        let cases: &[(&str, &[&str], &[Row])] = &[
+            (
+                "two discs become one row, in disc order",
+                &["Album (Disc 2)", "Album (Disc 1)"],
+                &[(
+                    "[DIR] Album (2 discs)",
+                    &["Album (Disc 1)", "Album (Disc 2)"],
+                )],
+            ),
+            (
+                "a lone disc stays as it is",
+                &["Album (Disc 1)", "Other"],
+                &[("[DIR] Album (Disc 1)", &[]), ("[DIR] Other", &[])],
+            ),
+            (
+                "different albums are not mixed",
+                &["A - CD1", "A - CD2", "B [disc 1]", "B [disc 2]"],
+                &[
+                    ("[DIR] A (2 discs)", &["A - CD1", "A - CD2"]),
+                    ("[DIR] B (2 discs)", &["B [disc 1]", "B [disc 2]"]),
+                ],
+            ),
+            (
+                "numbers sort numerically",
+                &["X (Disc 10)", "X (Disc 2)", "X (Disc 1)"],
+                &[(
+                    "[DIR] X (3 discs)",
+                    &["X (Disc 1)", "X (Disc 2)", "X (Disc 10)"],
+                )],
+            ),
+        ];
+        for (description, input, expected) in cases {
+            let expected: Vec<(String, Vec<String>)> = expected
+                .iter()
+                .map(|(label, discs)| {
+                    (
+                        label.to_string(),
+                        discs.iter().map(|d| d.to_string()).collect(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                summary(merge_disc_dirs(dirs(input))),
+                expected,
+                "{}",
+                description
+            );
+        }
+    }
+
+    #[test]
+    fn tracks_named_like_discs_are_not_merged() {
+        let entries = vec![
+            entry("Song (CD 1).mp3", EntryKind::Track),
+            entry("Song (CD 2).mp3", EntryKind::Track),
+        ];
+        assert!(merge_disc_dirs(entries).iter().all(|e| e.discs.is_empty()));
+    }
+
+    #[test]
+    fn expand_disc_groups_table() {
+        let mut merged = merge_disc_dirs(dirs(&["Album (Disc 1)", "Album (Disc 2)", "Zeta"]));
+        merged.sort_by(|a, b| a.name.cmp(&b.name));
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            (
+                "closed albums show one row",
+                &[],
+                &["[DIR] Album (2 discs)", "[DIR] Zeta"],
+            ),
+            (
+                "an open album lists each disc right under it",
+                &["Album"],
+                &[
+                    "[DIR] Album (2 discs)",
+                    "[DIR] Album (Disc 1)",
+                    "[DIR] Album (Disc 2)",
+                    "[DIR] Zeta",
+                ],
+            ),
+            (
+                "plain folders ignore the open set",
+                &["Zeta"],
+                &["[DIR] Album (2 discs)", "[DIR] Zeta"],
+            ),
+        ];
+        for (description, open, expected) in cases {
+            let open: HashSet<OsString> = open.iter().map(OsString::from).collect();
+            let labels: Vec<String> = expand_disc_groups(merged.clone(), &open)
+                .iter()
+                .map(DirEntry::label)
+                .collect();
+            assert_eq!(labels, *expected, "{}", description);
+        }
+    }
+
+    #[test]
+    fn disc_rows_lead_to_the_real_folder() {
+        let merged = merge_disc_dirs(dirs(&["Album (Disc 1)", "Album (Disc 2)"]));
+        let open = HashSet::from([OsString::from("Album")]);
+        let expanded = expand_disc_groups(merged, &open);
+        let dir = Path::new("/music");
+        assert_eq!(expanded[2].path(dir), dir.join("Album (Disc 2)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exact_names_survive_playlists_and_sessions() {
+        use crate::services::playlist_storage_service::Playlist;
+        use crate::services::session_service::PartialAppState;
+        use std::os::unix::ffi::OsStringExt;
+
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-exact-names-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // Los dos últimos no son UTF-8 válido y se muestran igual: "caf\u{fffd}.mp3"
+        let names = [
+            OsString::from("東京.mp3"),
+            OsString::from("🎵.mp3"),
+            OsString::from("Cafe\u{301}.mp3"),
+            OsString::from_vec(b"caf\xe9.mp3".to_vec()),
+            OsString::from_vec(b"caf\xe8.mp3".to_vec()),
+        ];
+        for name in &names {
+            std::fs::write(dir.join(name), b"audio").unwrap();
+        }
+
+        let tracks: Vec<PathBuf> = DirScanner::new()
+            .scan(&dir)
+            .iter()
+            .filter(|entry| entry.kind == EntryKind::Track)
+            .map(|entry| entry.path(&dir))
+            .collect();
+        assert_eq!(tracks.len(), names.len());
+        let labels: HashSet<String> = tracks
+            .iter()
+            .map(|track| track.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(labels.len(), names.len() - 1, "two names share a label");
+
+        let playlist = Playlist::new("Names".to_string(), tracks.clone());
+        let playlist: Playlist =
+            serde_json::from_str(&serde_json::to_string(&playlist).unwrap()).unwrap();
+        let session = PartialAppState {
+            marked_tracks: tracks.clone(),
+            ..Default::default()
+        };
+        let session: PartialAppState =
+            serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+        let from_playlist: Vec<PathBuf> = playlist.tracks.into_iter().map(|t| t.path).collect();
+        assert_eq!(from_playlist, tracks);
+        assert_eq!(session.marked_tracks, tracks);
+        assert!(tracks.iter().all(|track| track.exists()));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn leaf_album_action_table() {
+        let root = Path::new("/music");
+        let album = Path::new("/music/Artist/Album");
+        let parent = entry("..", EntryKind::Dir);
+        let track = |i: usize| entry(&format!("{i:03}.mp3"), EntryKind::Track);
+        let tracks = |n: usize| -> Vec<DirEntry> {
+            std::iter::once(parent.clone())
+                .chain((0..n).map(track))
+                .collect()
+        };
+        let with = |extra: DirEntry| {
+            let mut entries = tracks(3);
+            entries.insert(1, extra);
+            entries
+        };
+
+        // (carpeta, entradas, reproducir solo, acción)
+        let table = [
+            (album, tracks(3), false, LeafAlbumAction::Prompt),
+            (album, tracks(3), true, LeafAlbumAction::AutoPlay),
+            (
+                album,
+                tracks(LEAF_ALBUM_MAX_TRACKS),
+                false,
+                LeafAlbumAction::Prompt,
+            ),
+            (
+                album,
+                tracks(LEAF_ALBUM_MAX_TRACKS + 1),
+                true,
+                LeafAlbumAction::Browse,
+            ),
+            (album, tracks(0), true, LeafAlbumAction::Browse),
+            (root, tracks(3), true, LeafAlbumAction::Browse),
+            (
+                album,
+                with(entry("Disc 1", EntryKind::Dir)),
+                true,
+                LeafAlbumAction::Browse,
+            ),
+            (
+                album,
+                with(entry("Mix.m3u", EntryKind::Playlist)),
+                true,
+                LeafAlbumAction::Browse,
+            ),
+        ];
+        for (i, (dir, entries, auto_play, expected)) in table.into_iter().enumerate() {
+            assert_eq!(
+                leaf_album_action(dir, root, &entries, auto_play),
+                expected,
+                "row {i}"
+            );
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use crate::utils;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use walkdir::WalkDir;
+
+/// Archivo que marca una carpeta para que no se indexe (convención de Android)
+pub const NOMEDIA_FILE: &str = ".nomedia";
+
+/// Archivos que los sistemas operativos dejan junto a la música
+const JUNK_FILE_NAMES: &[&str] = &[".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// Prefijo de los archivos AppleDouble que macOS escribe en discos ajenos
+///
+/// `._01 Track.mp3` tiene la extensión de una pista pero solo guarda atributos:
+/// no se puede decodificar.
+const APPLE_DOUBLE_PREFIX: &str = "._";
+
+/// Pistas de la primera tanda del recolector: pocas, para empezar a sonar enseguida
+const FIRST_BATCH: usize = 200;
+/// Pistas de cada tanda siguiente
+const BATCH: usize = 1000;
+
+/// Verifica si una ruta es un archivo de audio existente
+fn is_audio_file(path: &Path) -> bool {
+    path.is_file() && utils::is_audio_file(path)
+}
+
+/// Nombre oculto en Unix y macOS: empieza con `.`
+pub fn is_hidden_name(name: &OsStr) -> bool {
+    name.as_encoded_bytes().starts_with(b".")
+}
+
+/// Decide si un archivo es basura que no se lista, no se escanea ni se reproduce
+///
+/// Siempre lo son los AppleDouble (`._*`), `.DS_Store`, `Thumbs.db`, `desktop.ini`
+/// y los archivos vacíos; los demás ocultos solo si `show_hidden` es falso.
+pub fn is_junk_file(path: &Path, show_hidden: bool) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let junk_name = name
+        .as_encoded_bytes()
+        .starts_with(APPLE_DOUBLE_PREFIX.as_bytes())
+        || JUNK_FILE_NAMES
+            .iter()
+            .any(|junk| name.eq_ignore_ascii_case(junk))
+        || (!show_hidden && is_hidden_name(name));
+    junk_name || std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == 0)
+}
+
+/// Archivo encontrado por `walk_unique_files`
+#[derive(Debug)]
+pub struct WalkedFile {
+    /// Ruta por la que se llegó al archivo, quizá a través de enlaces
+    pub path: PathBuf,
+    /// Ruta real del archivo, sin enlaces simbólicos
+    pub real_path: PathBuf,
+}
+
+/// Archivos de un árbol, siguiendo enlaces simbólicos y una vez por archivo real
+///
+/// Los directorios se recorren por nombre, así que de varios caminos al mismo
+/// archivo se conserva el primero en ese orden. Los enlaces que vuelven a un
+/// directorio ya abierto se saltan en vez de recorrerse sin fin. Con
+/// `respect_nomedia` no se entra en las subcarpetas que tienen un `.nomedia`.
+/// Los archivos basura (ver `is_junk_file`) se saltan y, sin `show_hidden`,
+/// tampoco se entra en las carpetas ocultas.
+///
+/// # Errores
+/// - Solo la raíz ilegible (o inexistente) se entrega como `Err`; las entradas
+///   ilegibles de dentro (enlaces rotos, carpetas sin permiso) se saltan
+pub fn walk_unique_files(
+    root: &Path,
+    respect_nomedia: bool,
+    show_hidden: bool,
+) -> impl Iterator<Item = walkdir::Result<WalkedFile>> {
+    let mut seen = HashSet::new();
+    WalkDir::new(root)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |entry| {
+            // La raíz se recorre siempre: quien la eligió quiere su contenido
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_dir() {
+                !(respect_nomedia && entry.path().join(NOMEDIA_FILE).exists())
+                    && (show_hidden || !is_hidden_name(entry.file_name()))
+            } else {
+                !is_junk_file(entry.path(), show_hidden)
+            }
+        })
+        .filter(|entry| !entry.as_ref().is_err_and(|e| e.loop_ancestor().is_some()))
+        .filter_map(move |entry| match entry {
+            Ok(entry) if !entry.file_type().is_file() => None,
+            Ok(entry) => {
+                let real_path = entry
+                    .path()
+                    .canonicalize()
+                    .unwrap_or_else(|_| entry.path().to_path_buf());
+                seen.insert(real_path.clone()).then(|| {
+                    Ok(WalkedFile {
+                        path: entry.into_path(),
+                        real_path,
+                    })
+                })
+            }
+            // Un enlace roto no debe impedir leer el resto del árbol
+            Err(e) if e.depth() > 0 => None,
+            Err(e) => Some(Err(e)),
+        })
+}
+
+/// Archivos de audio de un directorio y todos sus subdirectorios, ordenados por ruta
+///
+/// Sigue enlaces simbólicos; un archivo enlazado desde varias carpetas aparece una vez.
+/// Con `respect_nomedia` se saltan las carpetas con `.nomedia`; la basura y los
+/// ocultos se filtran como en `walk_unique_files`.
+pub fn walk_audio_files(root: &Path, respect_nomedia: bool, show_hidden: bool) -> Vec<PathBuf> {
+    let mut tracks: Vec<PathBuf> = walk_unique_files(root, respect_nomedia, show_hidden)
+        .filter_map(|e| e.ok())
+        .map(|file| file.path)
+        .filter(|p| is_audio_file(p))
+        .collect();
+    tracks.sort();
+    tracks
+}
+
+/// Archivos de audio de un directorio, sin entrar en subdirectorios, ordenados por nombre
+///
+/// Sin los archivos basura (ver `is_junk_file`). Retorna una lista vacía si el
+/// directorio no se puede leer.
+pub fn walk_audio_files_shallow(dir: &Path, show_hidden: bool) -> Vec<PathBuf> {
+    let mut tracks: Vec<PathBuf> = WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| is_audio_file(p) && !is_junk_file(p, show_hidden))
+        .collect();
+    tracks.sort();
+    tracks
+}
+
+/// Lo que entrega el recolector de pistas
+#[derive(Debug)]
+pub enum CollectorMessage {
+    /// Pistas nuevas, en orden de recorrido
+    Batch(Vec<PathBuf>),
+    /// Terminó el recorrido
+    Done,
+}
+
+/// Recorre un árbol en otro hilo y entrega sus archivos de audio por tandas
+///
+/// La primera tanda es corta para poder empezar a reproducir enseguida. Usa el
+/// mismo recorrido que `walk_audio_files` (una vez por archivo real, respetando
+/// `.nomedia` y sin basura). El recorrido se detiene al cancelar o al soltar el recolector.
+pub struct TrackCollector {
+    receiver: Receiver<CollectorMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TrackCollector {
+    /// Empieza a recorrer `root` en un hilo aparte
+    pub fn spawn(root: PathBuf, respect_nomedia: bool, show_hidden: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancelled);
+        std::thread::spawn(move || {
+            let mut batch = Vec::with_capacity(FIRST_BATCH);
+            let mut batch_size = FIRST_BATCH;
+            let tracks = walk_unique_files(&root, respect_nomedia, show_hidden)
+                .filter_map(|e| e.ok())
+                .map(|file| file.path)
+                .filter(|p| is_audio_file(p));
+            for track in tracks {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                batch.push(track);
+                if batch.len() >= batch_size {
+                    let full = std::mem::replace(&mut batch, Vec::with_capacity(BATCH));
+                    if sender.send(CollectorMessage::Batch(full)).is_err() {
+                        return;
+                    }
+                    batch_size = BATCH;
+                }
+            }
+            if !batch.is_empty() {
+                let _ = sender.send(CollectorMessage::Batch(batch));
+            }
+            let _ = sender.send(CollectorMessage::Done);
+        });
+        Self {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Mensajes que llegaron desde la última consulta, sin esperar
+    pub fn drain(&self) -> Vec<CollectorMessage> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Detiene el recorrido; las tandas ya enviadas se descartan al soltarlo
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for TrackCollector {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    /// Carpeta temporal que se borra al soltarla
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "rusted-player-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, relative: &str) -> PathBuf {
+            let path = self.0.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"not really audio").unwrap();
+            path
+        }
+
+        /// Crea un enlace simbólico; falso si el sistema no lo permite
+        #[cfg(unix)]
+        fn link(&self, target: &Path, relative: &str) -> bool {
+            std::os::unix::fs::symlink(target, self.0.join(relative)).is_ok()
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn collect_all(collector: &TrackCollector) -> Vec<Vec<PathBuf>> {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let mut batches = Vec::new();
+        loop {
+            for message in collector.drain() {
+                match message {
+                    CollectorMessage::Batch(batch) => batches.push(batch),
+                    CollectorMessage::Done => return batches,
+                }
+            }
+            assert!(Instant::now() < deadline, "the collector never finished");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn collector_batches_a_large_tree() {
+        let tree = TempTree::new("collector");
+        let total = 2500;
+        for i in 0..total {
+            tree.file(&format!("artist{:02}/album/{:04}.mp3", i / 100, i));
+        }
+        tree.file("artist00/album/cover.jpg");
+        tree.file("artist00/album/._0000.mp3");
+        tree.file("skipped/.nomedia");
+        tree.file("skipped/hidden.mp3");
+
+        let collector = TrackCollector::spawn(tree.0.clone(), true, false);
+        let batches = collect_all(&collector);
+
+        assert_eq!(batches[0].len(), FIRST_BATCH);
+        assert!(
+            batches[1..]
+                .iter()
+                .all(|b| !b.is_empty() && b.len() <= BATCH)
+        );
+        let collected: Vec<PathBuf> = batches.into_iter().flatten().collect();
+        assert_eq!(collected.len(), total);
+        let unique: HashSet<&PathBuf> = collected.iter().collect();
+        assert_eq!(unique.len(), total);
+        // Misma selección que el recorrido de una sola vez
+        let mut sorted = collected.clone();
+        sorted.sort();
+        assert_eq!(sorted, walk_audio_files(&tree.0, true, false));
+    }
+
+    #[test]
+    fn collector_of_a_small_tree_sends_one_batch() {
+        let tree = TempTree::new("collector-small");
+        tree.file("a.flac");
+        tree.file("b.ogg");
+
+        let collector = TrackCollector::spawn(tree.0.clone(), true, false);
+        let batches = collect_all(&collector);
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 2);
+    }
+
+    #[test]
+    fn each_junk_pattern_is_junk_even_with_hidden_files_shown() {
+        let tree = TempTree::new("junk");
+        let junk = [
+            "._01 Track.mp3",
+            ".DS_Store",
+            ".ds_store",
+            "Thumbs.db",
+            "THUMBS.DB",
+            "desktop.ini",
+            "Desktop.ini",
+        ];
+        for name in junk {
+            let path = tree.file(name);
+            assert!(is_junk_file(&path, false), "{name}");
+            assert!(is_junk_file(&path, true), "{name}");
+        }
+        let empty = tree.0.join("empty.mp3");
+        fs::write(&empty, b"").unwrap();
+        assert!(is_junk_file(&empty, true));
+    }
+
+    #[test]
+    fn show_hidden_only_overrides_plain_hidden_files() {
+        let tree = TempTree::new("junk-hidden");
+        let hidden = tree.file(".secret.mp3");
+        assert!(is_junk_file(&hidden, false));
+        assert!(!is_junk_file(&hidden, true));
+
+        let track = tree.file("01 Track.mp3");
+        assert!(!is_junk_file(&track, false));
+        // Una carpeta vacía no es un archivo vacío
+        let dir = tree.0.join("Album");
+        fs::create_dir_all(&dir).unwrap();
+        assert!(!is_junk_file(&dir, false));
+        // Sin nombre de archivo no hay nada que juzgar
+        assert!(!is_junk_file(Path::new("/"), false));
+    }
+
+    #[test]
+    fn walks_skip_junk_and_hidden_folders_unless_shown() {
+        let tree = TempTree::new("junk-walk");
+        let track = tree.file("Album/01.mp3");
+        tree.file("Album/._01.mp3");
+        tree.file("Album/.DS_Store");
+        let hidden_track = tree.file("Album/.02.mp3");
+        let in_hidden_dir = tree.file(".trash/03.mp3");
+        fs::write(tree.0.join("Album/04.mp3"), b"").unwrap();
+
+        assert_eq!(
+            walk_audio_files(&tree.0, true, false),
+            std::slice::from_ref(&track)
+        );
+        assert_eq!(
+            walk_audio_files(&tree.0, true, true),
+            [in_hidden_dir, hidden_track.clone(), track.clone()]
+        );
+        assert_eq!(
+            walk_audio_files_shallow(&tree.0.join("Album"), true),
+            [hidden_track, track]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_files_are_walked_once_by_their_real_path() {
+        let tree = TempTree::new("walk-links");
+        let track = tree.file("Album/01.mp3");
+        if !tree.link(&tree.0.join("Album"), "Alias") || !tree.link(&track, "Single.mp3") {
+            return;
+        }
+
+        let files: Vec<WalkedFile> = walk_unique_files(&tree.0, true, false)
+            .map(Result::unwrap)
+            .collect();
+        // "Album" va antes que "Alias" y "Single.mp3" por nombre
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, track);
+        assert_eq!(files[0].real_path, track.canonicalize().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn link_cycles_end_the_walk() {
+        let tree = TempTree::new("walk-cycle");
+        let track = tree.file("Album/01.mp3");
+        if !tree.link(&tree.0, "Album/Back") {
+            return;
+        }
+
+        assert_eq!(walk_audio_files(&tree.0, true, false), [track]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_links_are_skipped() {
+        let tree = TempTree::new("walk-dangling");
+        let track = tree.file("Album/01.mp3");
+        if !tree.link(&tree.0.join("gone.mp3"), "Album/00.mp3")
+            || !tree.link(&tree.0.join("gone"), "Album/Gone")
+        {
+            return;
+        }
+
+        let files: Vec<PathBuf> = walk_unique_files(&tree.0, true, false)
+            .map(|file| file.unwrap().path)
+            .collect();
+        assert_eq!(files, [track]);
+        // Solo la raíz que falta es un error
+        assert!(
+            walk_unique_files(&tree.0.join("missing"), true, false)
+                .next()
+                .unwrap()
+                .is_err()
+        );
+    }
+}
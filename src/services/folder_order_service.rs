@@ -0,0 +1,201 @@
+use crate::models::stored_path;
+use crate::services::config_service;
+use crate::utils::normalize_path;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Archivo de los órdenes propios: en el directorio de configuración guarda todas
+/// las carpetas; dentro de una carpeta, solo el de esa carpeta
+pub const ORDER_FILE_NAME: &str = ".rusted-order.json";
+
+/// Orden propio de una carpeta en el archivo del directorio de configuración
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredOrder {
+    #[serde(with = "stored_path")]
+    folder: PathBuf,
+    /// Nombres de archivo en el orden elegido, sin la carpeta
+    #[serde(with = "stored_path::vec")]
+    files: Vec<PathBuf>,
+}
+
+/// Orden propio guardado dentro de la carpeta, para que viaje con ella
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PortableOrder {
+    #[serde(with = "stored_path::vec")]
+    files: Vec<PathBuf>,
+}
+
+/// Órdenes de reproducción elegidos a mano para algunas carpetas
+#[derive(Debug, Default)]
+pub struct FolderOrderStore {
+    orders: HashMap<PathBuf, Vec<OsString>>,
+    /// Guarda el orden en la propia carpeta en lugar del directorio de configuración
+    portable: bool,
+}
+
+impl FolderOrderStore {
+    /// Carga los órdenes guardados; si no existen o están dañados empieza vacío
+    pub fn load(portable: bool) -> Self {
+        let stored: Vec<StoredOrder> = config_service::get_export_path(ORDER_FILE_NAME)
+            .and_then(fs::read_to_string)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            orders: stored
+                .into_iter()
+                .map(|order| (order.folder, file_names(&order.files)))
+                .collect(),
+            portable,
+        }
+    }
+
+    /// Orden propio de `dir`, `None` si sigue el orden normal
+    ///
+    /// Un archivo dentro de la carpeta tiene prioridad, aunque esta configuración
+    /// no los escriba: puede venir de otra máquina.
+    pub fn get(&self, dir: &Path) -> Option<Vec<OsString>> {
+        let portable = fs::read_to_string(dir.join(ORDER_FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<PortableOrder>(&contents).ok());
+        match portable {
+            Some(order) => Some(file_names(&order.files)),
+            None => self.orders.get(&normalize_path(dir)).cloned(),
+        }
+    }
+
+    /// Guarda el orden de las pistas de `dir`
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede escribir el archivo
+    pub fn save(&mut self, dir: &Path, tracks: &[PathBuf]) -> Result<(), std::io::Error> {
+        let files: Vec<PathBuf> = tracks
+            .iter()
+            .filter_map(|track| track.file_name().map(PathBuf::from))
+            .collect();
+        let portable_file = dir.join(ORDER_FILE_NAME);
+        if self.portable || portable_file.exists() {
+            let order = PortableOrder { files };
+            return fs::write(portable_file, serde_json::to_string_pretty(&order)?);
+        }
+        self.orders.insert(normalize_path(dir), file_names(&files));
+        self.write()
+    }
+
+    /// Olvida el orden propio de `dir`, que vuelve al orden normal
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede borrar o reescribir el archivo
+    pub fn remove(&mut self, dir: &Path) -> Result<(), std::io::Error> {
+        let portable_file = dir.join(ORDER_FILE_NAME);
+        if portable_file.exists() {
+            fs::remove_file(portable_file)?;
+        }
+        if self.orders.remove(&normalize_path(dir)).is_some() {
+            self.write()?;
+        }
+        Ok(())
+    }
+
+    fn write(&self) -> Result<(), std::io::Error> {
+        let mut stored: Vec<StoredOrder> = self
+            .orders
+            .iter()
+            .map(|(folder, files)| StoredOrder {
+                folder: folder.clone(),
+                files: files.iter().map(PathBuf::from).collect(),
+            })
+            .collect();
+        stored.sort_by(|a, b| a.folder.cmp(&b.folder));
+        let path = config_service::get_export_path(ORDER_FILE_NAME)?;
+        fs::write(path, serde_json::to_string_pretty(&stored)?)
+    }
+}
+
+fn file_names(files: &[PathBuf]) -> Vec<OsString> {
+    files
+        .iter()
+        .map(|file| file.as_os_str().to_owned())
+        .collect()
+}
+
+/// Ordena las pistas de una carpeta según su orden propio
+///
+/// Las que el orden no nombra (agregadas después de guardarlo) quedan al final
+/// en el orden que ya tenían; los nombres guardados de archivos que ya no
+/// están simplemente no coinciden con nada.
+pub fn sort_by_order<T>(order: &[OsString], items: &mut [T], name: impl Fn(&T) -> Option<&OsStr>) {
+    let positions: HashMap<&OsStr, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, file)| (file.as_os_str(), i))
+        .collect();
+    items.sort_by_key(|item| {
+        name(item)
+            .and_then(|file| positions.get(file).copied())
+            .unwrap_or(usize::MAX)
+    });
+}
+
+/// Cuántos nombres del orden guardado ya no están entre `present`
+pub fn stale_count(order: &[OsString], present: &[&OsStr]) -> usize {
+    order
+        .iter()
+        .filter(|file| !present.contains(&file.as_os_str()))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(files: &[&str]) -> Vec<OsString> {
+        files.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn new_files_go_last_and_gone_names_are_ignored() {
+        let order = names(&["03.mp3", "gone.mp3", "01.mp3"]);
+        let mut items = vec!["01.mp3", "02.mp3", "03.mp3", "04.mp3"];
+        sort_by_order(&order, &mut items, |item| Some(OsStr::new(*item)));
+        assert_eq!(items, ["03.mp3", "01.mp3", "02.mp3", "04.mp3"]);
+
+        let present: Vec<&OsStr> = items.iter().map(OsStr::new).collect();
+        assert_eq!(stale_count(&order, &present), 1);
+    }
+
+    #[test]
+    fn items_without_a_name_keep_their_place_at_the_end() {
+        let order = names(&["b", "a"]);
+        let mut items = vec![None, Some("a"), Some("b")];
+        sort_by_order(&order, &mut items, |item| item.map(OsStr::new));
+        assert_eq!(items, [Some("b"), Some("a"), None]);
+    }
+
+    #[test]
+    fn an_order_inside_the_folder_wins() {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-folder-order-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut store = FolderOrderStore::default();
+        store
+            .orders
+            .insert(normalize_path(&dir), names(&["a.mp3", "b.mp3"]));
+        assert_eq!(store.get(&dir), Some(names(&["a.mp3", "b.mp3"])));
+
+        // Guardar con un archivo en la carpeta lo reescribe a él
+        fs::write(dir.join(ORDER_FILE_NAME), r#"{"files": ["b.mp3"]}"#).unwrap();
+        assert_eq!(store.get(&dir), Some(names(&["b.mp3"])));
+        store
+            .save(&dir, &[dir.join("b.mp3"), dir.join("a.mp3")])
+            .unwrap();
+        assert_eq!(store.get(&dir), Some(names(&["b.mp3", "a.mp3"])));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
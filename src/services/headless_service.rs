@@ -0,0 +1,375 @@
+use crate::models::config::Config;
+use crate::services::file_walker_service;
+use crate::services::metadata_service::PlaylistService;
+use crate::services::player_service::{PlayerCommand, PlayerService, PlayerStatus};
+use crate::services::playlist_storage_service;
+use crate::services::shuffle_service::{self, ShuffleHistory};
+use crate::services::trim_service::TrimStore;
+use crate::utils;
+use serde_json::json;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Intervalo entre dos líneas de posición
+const POSITION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Qué reproducir cuando no hay interfaz
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlaybackSource {
+    /// Un archivo, un directorio (en orden) o una lista `.m3u`
+    Path(PathBuf),
+    /// Una playlist guardada, por nombre
+    Playlist(String),
+    /// Toda la biblioteca en orden aleatorio
+    ShuffleAll,
+    /// Pistas de alguno de los géneros, o de todos con `all`, en orden de la biblioteca
+    Genres { genres: Vec<String>, all: bool },
+}
+
+impl PlaybackSource {
+    /// Interpreta la lista de `--genres`: `Jazz,Blues` es cualquiera de los
+    /// géneros y `Jazz+Blues`, todos a la vez
+    ///
+    /// # Errores
+    /// - Retorna un mensaje si la lista está vacía o mezcla `,` y `+`
+    pub fn genres(list: &str) -> Result<Self, String> {
+        let all = list.contains('+');
+        if all && list.contains(',') {
+            return Err(
+                "--genres takes Jazz,Blues (any) or Jazz+Blues (all), not both".to_string(),
+            );
+        }
+        let genres: Vec<String> = list
+            .split([',', '+'])
+            .map(str::trim)
+            .filter(|genre| !genre.is_empty())
+            .map(str::to_string)
+            .collect();
+        if genres.is_empty() {
+            return Err("--genres needs at least one genre".to_string());
+        }
+        Ok(Self::Genres { genres, all })
+    }
+}
+
+/// Imprime un evento como una línea JSON en stdout
+///
+/// # Errores
+/// - Retorna error si no se puede escribir, por ejemplo `BrokenPipe` cuando el
+///   programa que lee la salida ya terminó (`... | head`)
+fn emit(event: serde_json::Value) -> io::Result<()> {
+    write_event(&mut io::stdout().lock(), &event)
+}
+
+/// Escribe un evento como una sola línea JSON
+fn write_event(out: &mut impl Write, event: &serde_json::Value) -> io::Result<()> {
+    writeln!(out, "{}", event)
+}
+
+/// Evento que se imprime para un estado del reproductor, `None` si no se informa
+fn status_event(status: &PlayerStatus) -> Option<serde_json::Value> {
+    let event = match status {
+        PlayerStatus::TrackChanged(path) => {
+            json!({ "event": "track_changed", "path": path_text(path) })
+        }
+        PlayerStatus::Position(position) => {
+            json!({ "event": "position", "seconds": position.as_secs_f64() })
+        }
+        PlayerStatus::Error(message) => json!({ "event": "error", "message": message }),
+        PlayerStatus::TrackError(path, problem) => json!({
+            "event": "track_error",
+            "path": path_text(path),
+            "reason": problem.code(),
+        }),
+        PlayerStatus::ResumedAfterSleep { paused } => {
+            json!({ "event": "resumed_after_sleep", "paused": paused })
+        }
+        PlayerStatus::Stopped => json!({ "event": "stopped" }),
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Ruta como texto para los eventos; un nombre que no es UTF-8 válido se muestra con `�`
+fn path_text(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Verifica si una ruta es un archivo de audio soportado
+fn is_audio_file(path: &Path) -> bool {
+    path.is_file() && utils::is_audio_file(path)
+}
+
+/// Escanea la biblioteca de la configuración, para los orígenes que necesitan etiquetas
+fn scan_library(config: &Config) -> Result<PlaylistService, String> {
+    let mut library = PlaylistService::new();
+    library.set_respect_nomedia(config.respect_nomedia);
+    library.set_show_hidden(config.show_hidden_files);
+    library
+        .scan_directory(&config.music_path)
+        .map_err(|e| e.to_string())?;
+    Ok(library)
+}
+
+/// Resuelve el origen a la lista de pistas a reproducir
+fn resolve_tracks(config: &Config, source: &PlaybackSource) -> Result<Vec<PathBuf>, String> {
+    match source {
+        PlaybackSource::Path(path) if path.is_dir() => Ok(file_walker_service::walk_audio_files(
+            path,
+            config.respect_nomedia,
+            config.show_hidden_files,
+        )),
+        PlaybackSource::Path(path) if playlist_storage_service::is_m3u_file(path) => {
+            playlist_storage_service::parse_m3u(path)
+                .map(|playlist| playlist.tracks.into_iter().map(|t| t.path).collect())
+                .map_err(|e| e.to_string())
+        }
+        PlaybackSource::Path(path) => Ok(vec![path.clone()]),
+        PlaybackSource::Playlist(name) => match playlist_storage_service::load_playlist(name) {
+            Ok(Some(playlist)) => Ok(playlist.tracks.into_iter().map(|t| t.path).collect()),
+            Ok(None) => Err(format!("Playlist '{}' not found", name)),
+            Err(e) => Err(e.to_string()),
+        },
+        PlaybackSource::ShuffleAll => {
+            // The filter needs tags, so only then is the library scanned
+            let tracks = if config.shuffle_filter.is_empty() {
+                file_walker_service::walk_audio_files(
+                    &config.music_path,
+                    config.respect_nomedia,
+                    config.show_hidden_files,
+                )
+            } else {
+                scan_library(config)?.filter_tracks(&config.shuffle_filter)
+            };
+            let window = config.shuffle_exclusion_window();
+            let queue = shuffle_service::shuffle_fresh(
+                tracks,
+                &ShuffleHistory::load(),
+                window,
+                playlist_storage_service::unix_now(),
+                &mut rand::rng(),
+            );
+            Ok(queue.tracks)
+        }
+        PlaybackSource::Genres { genres, all } => {
+            let library = scan_library(config)?;
+            let genres: Vec<&str> = genres.iter().map(String::as_str).collect();
+            Ok(if *all {
+                library.get_tracks_by_genres_all(&genres)
+            } else {
+                library.get_tracks_by_genres_any(&genres)
+            })
+        }
+    }
+}
+
+/// Pistas reproducibles del origen, descartando las que faltan
+///
+/// # Errores
+/// - Retorna un mensaje si el origen no existe o no tiene pistas reproducibles
+pub fn playable_tracks(config: &Config, source: &PlaybackSource) -> Result<Vec<PathBuf>, String> {
+    let tracks: Vec<PathBuf> = resolve_tracks(config, source)?
+        .into_iter()
+        .filter(|p| is_audio_file(p))
+        .collect();
+    if tracks.is_empty() {
+        return Err("No playable tracks found".to_string());
+    }
+    Ok(tracks)
+}
+
+/// Reproduce sin interfaz imprimiendo el estado como líneas JSON
+///
+/// Cada línea es un objeto con un campo `event`: `track_changed`, `position`
+/// (cada 5 segundos), `track_error` (pista saltada y motivo), `error` o
+/// `stopped`. Termina cuando la cola se acaba. Las pistas que suenan se anotan
+/// en el historial de aleatorio, igual que con la interfaz.
+///
+/// # Errores
+/// - Retorna `NotFound` si el origen no tiene pistas reproducibles
+/// - Retorna `Other` si el reproductor termina antes de la cola (sin dispositivo de audio)
+pub fn run_stdout_status(config: &Config, source: PlaybackSource) -> io::Result<()> {
+    let tracks = match playable_tracks(config, &source) {
+        Ok(tracks) => tracks,
+        Err(message) => {
+            let _ = emit(json!({ "event": "error", "message": message }));
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        }
+    };
+
+    let player = PlayerService::with_audio();
+    let _ = player.send(PlayerCommand::SetPreferNativeRate(
+        config.prefer_native_sample_rate,
+    ));
+    let _ = player.send(PlayerCommand::SetResumeAfterSleep(
+        config.resume_after_sleep,
+    ));
+    let _ = player.send(PlayerCommand::SetTrims(TrimStore::load().all()));
+    let _ = player.send(PlayerCommand::PlayAlbum(tracks));
+
+    let result = report_status(&player, &mut ShuffleHistory::load());
+    let _ = player.send(PlayerCommand::Quit);
+    result
+}
+
+/// Imprime los estados del reproductor hasta que la cola se acaba
+fn report_status(player: &PlayerService, history: &mut ShuffleHistory) -> io::Result<()> {
+    let mut finished = false;
+    let mut last_position = Instant::now();
+    // Si el hilo del reproductor termina, el canal se cierra y salimos del bucle
+    for status in player.receiver.iter() {
+        match &status {
+            PlayerStatus::TrackChanged(path) => {
+                last_position = Instant::now();
+                // Se guarda enseguida: sin interfaz lo normal es terminar con Ctrl+C
+                history.record(path, playlist_storage_service::unix_now());
+                let _ = history.save();
+            }
+            PlayerStatus::Position(_) if last_position.elapsed() < POSITION_INTERVAL => continue,
+            PlayerStatus::Position(_) => last_position = Instant::now(),
+            PlayerStatus::Stopped => finished = true,
+            _ => {}
+        }
+        let Some(event) = status_event(&status) else {
+            continue;
+        };
+        if let Err(e) = emit(event) {
+            // Quien leía la salida ya no está: no hay a quién informar, no es un fallo
+            return match e.kind() {
+                io::ErrorKind::BrokenPipe => Ok(()),
+                _ => Err(e),
+            };
+        }
+        if finished {
+            break;
+        }
+    }
+
+    if finished {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            "El reproductor terminó antes de completar la cola",
+        ))
+    }
+}
+
+/// Escanea la biblioteca e imprime sus estadísticas como JSON en stdout
+///
+/// # Errores
+/// - Retorna error si no se puede recorrer el directorio de música
+pub fn run_export_stats(config: &Config) -> io::Result<()> {
+    let mut library = PlaylistService::new();
+    library.set_artist_folding(config.fold_artist_names);
+    library.set_disc_merging(config.merge_disc_albums);
+    library.set_respect_nomedia(config.respect_nomedia);
+    library.set_show_hidden(config.show_hidden_files);
+    library
+        .scan_directory(&config.music_path)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let stats = serde_json::to_string_pretty(&library.get_stats())?;
+    match writeln!(io::stdout().lock(), "{}", stats) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::player_service::TrackProblem;
+
+    #[cfg(unix)]
+    #[test]
+    fn track_changed_event_survives_non_utf8_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"/music/caf\xe9.mp3"));
+        let event = status_event(&PlayerStatus::TrackChanged(path.to_path_buf())).unwrap();
+        assert_eq!(event["path"], "/music/caf\u{fffd}.mp3");
+    }
+
+    #[test]
+    fn track_error_reason_is_a_stable_code() {
+        let status =
+            PlayerStatus::TrackError(PathBuf::from("/music/drm.m4p"), TrackProblem::DrmProtected);
+        assert_eq!(status_event(&status).unwrap()["reason"], "drm_protected");
+    }
+
+    #[test]
+    fn path_text_keeps_valid_names() {
+        assert_eq!(
+            path_text(Path::new("/música/01 ♪.flac")),
+            "/música/01 ♪.flac"
+        );
+    }
+
+    #[test]
+    fn genre_lists_pick_any_or_all() {
+        let genres = |names: &[&str]| names.iter().map(|g| g.to_string()).collect();
+        assert_eq!(
+            PlaybackSource::genres("Jazz, Blues,"),
+            Ok(PlaybackSource::Genres {
+                genres: genres(&["Jazz", "Blues"]),
+                all: false
+            })
+        );
+        assert_eq!(
+            PlaybackSource::genres("Jazz+Blues"),
+            Ok(PlaybackSource::Genres {
+                genres: genres(&["Jazz", "Blues"]),
+                all: true
+            })
+        );
+        assert_eq!(
+            PlaybackSource::genres("Rock"),
+            Ok(PlaybackSource::Genres {
+                genres: genres(&["Rock"]),
+                all: false
+            })
+        );
+        assert!(PlaybackSource::genres("Jazz+Blues,Rock").is_err());
+        assert!(PlaybackSource::genres(" , +").is_err());
+    }
+
+    #[test]
+    fn status_lines_are_one_json_object_each() {
+        let statuses = [
+            PlayerStatus::TrackChanged(PathBuf::from("/music/01.mp3")),
+            PlayerStatus::Volume(0.5),
+            PlayerStatus::Position(Duration::from_millis(5500)),
+            PlayerStatus::TrackError(PathBuf::from("/music/02.mp3"), TrackProblem::DrmProtected),
+            PlayerStatus::Error("Audio device unavailable".to_string()),
+            PlayerStatus::ResumedAfterSleep { paused: true },
+            PlayerStatus::Stopped,
+        ];
+        let mut out = Vec::new();
+        for event in statuses.iter().filter_map(status_event) {
+            write_event(&mut out, &event).unwrap();
+        }
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        // El volumen no se informa
+        assert_eq!(
+            lines,
+            [
+                json!({ "event": "track_changed", "path": "/music/01.mp3" }),
+                json!({ "event": "position", "seconds": 5.5 }),
+                json!({
+                    "event": "track_error",
+                    "path": "/music/02.mp3",
+                    "reason": "drm_protected",
+                }),
+                json!({ "event": "error", "message": "Audio device unavailable" }),
+                json!({ "event": "resumed_after_sleep", "paused": true }),
+                json!({ "event": "stopped" }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use crate::services::config_service;
+use std::path::PathBuf;
+
+/// Archivo al que se exporta la referencia de atajos
+const KEYBINDINGS_FILE_NAME: &str = "keybindings.md";
+
+/// Vista en la que se pulsa una tecla, para saber qué atajos hacen algo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Pestaña de carpetas
+    Folders,
+    /// Pestaña de playlists, con la lista de playlists
+    Playlists,
+    /// Pestaña de playlists, dentro de una playlist
+    Playlist,
+    /// Pestaña de estadísticas
+    Stats,
+}
+
+impl View {
+    /// Cómo llegar a la vista, para las pistas
+    fn label(self) -> &'static str {
+        match self {
+            Self::Folders => "Folders (1)",
+            Self::Playlists => "Playlists (2)",
+            Self::Playlist => "an open playlist (2)",
+            Self::Stats => "Stats (3)",
+        }
+    }
+}
+
+/// Atajo de teclado con la acción que realiza
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    /// Teclas tal como se muestran, p. ej. `Ctrl+N` o `↑/↓`
+    pub keys: &'static str,
+    /// Dónde vale el atajo; `None` si vale en toda la aplicación
+    pub context: Option<&'static str>,
+    /// Qué hace
+    pub action: &'static str,
+    /// Vistas en las que la tecla hace algo; vacío si en todas
+    pub views: &'static [View],
+}
+
+impl KeyBinding {
+    /// Limita el atajo a las vistas dadas
+    const fn only(self, views: &'static [View]) -> Self {
+        Self { views, ..self }
+    }
+
+    /// El atajo hace algo en la vista; lo que vale en Playlists vale también dentro de una
+    pub fn is_available(&self, view: View) -> bool {
+        self.views.is_empty()
+            || self.views.contains(&view)
+            || (view == View::Playlist && self.views.contains(&View::Playlists))
+    }
+
+    /// Alguna de las teclas del atajo es `key`
+    ///
+    /// Las letras no distinguen mayúsculas, como en el manejo de teclas; con Shift
+    /// vale además la forma `Shift+X`.
+    fn matches_key(&self, key: char, shifted: bool) -> bool {
+        self.keys.split('/').any(|token| {
+            let token = if shifted {
+                token.strip_prefix("Shift+").unwrap_or(token)
+            } else {
+                token
+            };
+            let mut chars = token.chars();
+            matches!((chars.next(), chars.next()), (Some(c), None) if c.eq_ignore_ascii_case(&key))
+        })
+    }
+
+    /// Dónde vale, tal como se muestra en la referencia
+    fn place(&self) -> String {
+        match self.context {
+            Some(context) => context.to_string(),
+            None if self.views.is_empty() => "Anywhere".to_string(),
+            None => self
+                .views
+                .iter()
+                .map(|view| view.label())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+const fn bind(
+    keys: &'static str,
+    context: Option<&'static str>,
+    action: &'static str,
+) -> KeyBinding {
+    KeyBinding {
+        keys,
+        context,
+        action,
+        views: &[],
+    }
+}
+
+/// Referencia completa de atajos, en el orden en que se muestran
+pub const KEYBINDINGS: &[KeyBinding] = &[
+    bind("↑/↓", None, "Navigate"),
+    bind("Enter", None, "Select"),
+    bind("Space", None, "Pause or resume"),
+    bind("P", None, "Play album").only(&[View::Folders, View::Playlists]),
+    bind("s", None, "Shuffle tracks").only(&[View::Folders, View::Playlists]),
+    bind(
+        "Shift+S",
+        None,
+        "Shuffle albums, each played in track order",
+    )
+    .only(&[View::Folders, View::Playlists]),
+    bind("z/x", None, "Volume down/up"),
+    bind("Shift+V", None, "Set the volume to a percentage (0-200)"),
+    bind("Esc", None, "Back"),
+    bind(
+        "Alt+←/→",
+        Some("Folders"),
+        "Jump up a breadcrumb segment, or back down the way you came",
+    ),
+    bind("Alt+1..9", Some("Folders"), "Jump to breadcrumb segment n"),
+    bind(
+        "Right-click",
+        Some("Folders"),
+        "Open a track menu: play, queue, mark, add to playlist, stats",
+    ),
+    bind("N", None, "Play the selected track next"),
+    bind("Q", None, "Add the selected track to the queue"),
+    bind("Ctrl+N", None, "Skip to the next track"),
+    bind(
+        "Shift+M",
+        None,
+        "Play a mix flowing from the selected track",
+    ),
+    bind("Backspace", None, "Stop playback"),
+    bind(
+        "1/2/3",
+        None,
+        "Switch to the Folders, Playlists or Stats tab",
+    ),
+    bind("b", Some("Folders"), "Search").only(&[View::Folders]),
+    bind("Ctrl+V", None, "Paste into the search or the playlist name"),
+    bind("l", Some("Folders"), "Mark the track for a new playlist").only(&[View::Folders]),
+    bind("c", None, "Create a playlist from the marked tracks"),
+    bind("a", None, "Add the playing track to a playlist"),
+    bind(
+        ".",
+        None,
+        "Toggle the selected or playing track as favorite (♥)",
+    ),
+    bind("i", None, "Show the track's metadata and tag source"),
+    bind(
+        "s/e/c",
+        Some("Track info"),
+        "Set trim start/end at the current position, clear trims",
+    ),
+    bind("Shift+T", None, "Cycle the color theme"),
+    bind("Ctrl+Z", None, "Undo the last playlist change"),
+    bind(
+        "Ctrl+P",
+        None,
+        "Jump to a visited folder, playlist, artist, album or track, or run a command (> for commands only)",
+    ),
+    bind(
+        "Ctrl+Enter",
+        Some("Jump popup"),
+        "Play the selection instead of going there (also Alt+Enter)",
+    ),
+    bind(
+        "Ctrl+F",
+        None,
+        "Edit the shuffle filter (years, length, genres) and shuffle the library",
+    ),
+    bind("Ctrl+H", None, "Export this shortcut reference"),
+    bind("Ctrl+Q", None, "Quit"),
+    bind("F", None, "Play from the selected track to the end")
+        .only(&[View::Folders, View::Playlist]),
+    bind(
+        "Shift+G",
+        Some("Folders"),
+        "Guess tags from the file names of the folder's tracks, with a preview",
+    )
+    .only(&[View::Folders]),
+    bind(
+        "Shift+O",
+        Some("Folders"),
+        "Arrange the folder's tracks in a custom order (d in the popup reverts)",
+    )
+    .only(&[View::Folders]),
+    bind("Shift+↑/↓", Some("Playlist"), "Move the selected track"),
+    bind(
+        "o",
+        Some("Playlist"),
+        "Imported playlists: allow or skip tracks outside the library",
+    )
+    .only(&[View::Playlist]),
+    bind(
+        "#",
+        Some("Playlist"),
+        "Pick a letter and jump to the first track whose title starts with it",
+    )
+    .only(&[View::Playlist]),
+    bind(
+        "d",
+        Some("Playlists"),
+        "Delete the playlist (or the track, inside one)",
+    )
+    .only(&[View::Playlists]),
+    bind("m", Some("Playlists"), "Find tracks that no longer exist").only(&[View::Playlists]),
+    bind(
+        "↑/↓",
+        Some("Stats"),
+        "Move between sections (rows when expanded)",
+    ),
+    bind(
+        "Enter",
+        Some("Stats"),
+        "Expand the section; expanded, activate the row",
+    ),
+    bind("PgUp/PgDn", Some("Stats"), "Scroll the expanded section"),
+    bind("Esc", Some("Stats"), "Collapse the section"),
+    bind("e", Some("Stats"), "Export the selected genre as M3U").only(&[View::Stats]),
+    bind("r", Some("Stats"), "Show tracks added in the last 30 days").only(&[View::Stats]),
+    bind(
+        "t",
+        Some("Stats"),
+        "Rank the top lists by track count, total duration or listening time",
+    )
+    .only(&[View::Stats]),
+];
+
+/// Tabla Markdown con todos los atajos de `KEYBINDINGS`
+pub fn keybindings_markdown() -> String {
+    let mut markdown = String::from("# rusted-player keyboard shortcuts\n\n");
+    markdown.push_str("| Key | Where | Action |\n|-----|-------|--------|\n");
+    for binding in KEYBINDINGS {
+        markdown.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            binding.keys.replace('|', "\\|"),
+            binding.place(),
+            binding.action.replace('|', "\\|")
+        ));
+    }
+    markdown
+}
+
+/// Pista para una tecla que existe pero no hace nada en la vista actual
+///
+/// Retorna `None` si la tecla hace algo en `view` o si no es de ningún atajo.
+/// Solo considera teclas de un carácter sin modificadores (salvo Shift).
+pub fn unavailable_hint(key: char, shifted: bool, view: View) -> Option<String> {
+    let matching: Vec<&KeyBinding> = KEYBINDINGS
+        .iter()
+        // Los atajos de una ventana (Track info, ...) no cuentan: la ventana recibe la tecla antes
+        .filter(|binding| binding.context.is_none() || !binding.views.is_empty())
+        .filter(|binding| binding.matches_key(key, shifted))
+        .collect();
+    if matching.iter().any(|binding| binding.is_available(view)) {
+        return None;
+    }
+    // Con Shift se prefiere el atajo escrito como `Shift+X`
+    let shift_keys = format!("Shift+{}", key);
+    let binding = matching
+        .iter()
+        .find(|binding| shifted && binding.keys.split('/').any(|t| t == shift_keys))
+        .or_else(|| matching.iter().find(|binding| !binding.views.is_empty()))
+        .filter(|binding| !binding.views.is_empty())?;
+    let places: Vec<&str> = binding.views.iter().map(|view| view.label()).collect();
+    Some(format!(
+        "'{}' — {} — only in {}",
+        key,
+        binding.action,
+        places.join(" or ")
+    ))
+}
+
+/// Escribe la referencia de atajos en `keybindings.md` del directorio de configuración
+///
+/// Retorna la ruta del archivo escrito.
+///
+/// # Errores
+/// - Retorna error si no se puede escribir el archivo
+pub fn export_keybindings() -> Result<PathBuf, std::io::Error> {
+    config_service::save_report(KEYBINDINGS_FILE_NAME, &keybindings_markdown())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWS: [View; 4] = [View::Folders, View::Playlists, View::Playlist, View::Stats];
+
+    /// En qué vistas cada tecla debe dar pista, en el orden de `VIEWS`
+    #[test]
+    fn hints_follow_the_views_of_each_key() {
+        let table: &[(char, bool, [bool; 4])] = &[
+            ('p', false, [false, false, false, true]),
+            ('s', false, [false, false, false, true]),
+            ('S', true, [false, false, false, true]),
+            ('b', false, [false, true, true, true]),
+            ('l', false, [false, true, true, true]),
+            ('f', false, [false, true, false, true]),
+            ('G', true, [false, true, true, true]),
+            // Dentro de una playlist, Shift+O es la `o` de las importadas
+            ('O', true, [false, true, false, true]),
+            ('o', false, [true, true, false, true]),
+            ('#', false, [true, true, false, true]),
+            ('d', false, [true, false, false, true]),
+            ('m', false, [true, false, false, true]),
+            ('e', false, [true, true, true, false]),
+            ('r', false, [true, true, true, false]),
+            ('t', false, [true, true, true, false]),
+            // Atajos de toda la aplicación y teclas sin atajo
+            ('c', false, [false; 4]),
+            ('q', false, [false; 4]),
+            ('.', false, [false; 4]),
+            ('w', false, [false; 4]),
+        ];
+        for &(key, shifted, hinted) in table {
+            for (view, hinted) in VIEWS.into_iter().zip(hinted) {
+                assert_eq!(
+                    unavailable_hint(key, shifted, view).is_some(),
+                    hinted,
+                    "{key:?} shifted={shifted} in {view:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn the_hint_names_the_action_and_where_it_works() {
+        assert_eq!(
+            unavailable_hint('f', false, View::Playlists).as_deref(),
+            Some(
+                "'f' — Play from the selected track to the end — only in Folders (1) or an open playlist (2)"
+            )
+        );
+        assert_eq!(
+            unavailable_hint('e', false, View::Folders).as_deref(),
+            Some("'e' — Export the selected genre as M3U — only in Stats (3)")
+        );
+    }
+
+    #[test]
+    fn shift_picks_the_shifted_binding() {
+        assert_eq!(
+            unavailable_hint('S', true, View::Stats).as_deref(),
+            Some(
+                "'S' — Shuffle albums, each played in track order — only in Folders (1) or Playlists (2)"
+            )
+        );
+        assert_eq!(
+            unavailable_hint('s', false, View::Stats).as_deref(),
+            Some("'s' — Shuffle tracks — only in Folders (1) or Playlists (2)")
+        );
+    }
+
+    #[test]
+    fn window_shortcuts_are_not_hinted() {
+        // `s/e/c` de Track info no cuenta: fuera de Stats, 'e' sigue dando la pista de Stats
+        assert!(
+            unavailable_hint('e', false, View::Folders)
+                .unwrap()
+                .contains("Stats")
+        );
+        assert_eq!(unavailable_hint('c', false, View::Stats), None);
+    }
+}
@@ -0,0 +1,282 @@
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// Máximo de comandos de hook ejecutándose a la vez
+const MAX_CONCURRENT_HOOKS: usize = 4;
+
+/// Tiempo mínimo entre dos avisos de error de hooks
+const FAILURE_NOTICE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Datos de la pista disponibles como marcadores en las plantillas
+#[derive(Debug, Default)]
+pub struct HookContext<'a> {
+    pub artist: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub album: Option<&'a str>,
+    pub path: Option<&'a Path>,
+}
+
+impl HookContext<'_> {
+    /// Resuelve el valor de un marcador, `None` si el marcador no existe
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "artist" => Some(self.artist.unwrap_or_default().to_string()),
+            "title" => Some(self.title.unwrap_or_default().to_string()),
+            "album" => Some(self.album.unwrap_or_default().to_string()),
+            "path" => Some(
+                self.path
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Errores posibles al lanzar un hook
+#[derive(Debug)]
+pub enum HookError {
+    /// Ya hay demasiados hooks en ejecución
+    TooManyRunning,
+    /// No se pudo lanzar el proceso
+    Spawn(std::io::Error),
+    /// El proceso terminó con un código distinto de cero (o por una señal)
+    Exited(ExitStatus),
+}
+
+impl std::fmt::Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyRunning => write!(f, "Demasiados hooks en ejecución"),
+            Self::Spawn(e) => write!(f, "No se pudo ejecutar el hook: {}", e),
+            Self::Exited(status) => match status.code() {
+                Some(code) => write!(f, "El hook terminó con el código {}", code),
+                None => write!(f, "El hook terminó por una señal"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Estado de comillas mientras se recorre la plantilla
+#[derive(Clone, Copy, PartialEq)]
+enum QuoteState {
+    None,
+    Single,
+    Double,
+}
+
+/// Escapa un valor para insertarlo en la línea de comandos según el contexto de comillas
+#[cfg(not(windows))]
+fn escape_value(value: &str, state: QuoteState) -> String {
+    match state {
+        QuoteState::None => format!("'{}'", value.replace('\'', r"'\''")),
+        QuoteState::Single => value.replace('\'', r"'\''"),
+        QuoteState::Double => {
+            let mut escaped = String::with_capacity(value.len());
+            for c in value.chars() {
+                if matches!(c, '\\' | '"' | '$' | '`') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+    }
+}
+
+/// Escapa un valor para insertarlo en la línea de comandos según el contexto de comillas
+#[cfg(windows)]
+fn escape_value(value: &str, state: QuoteState) -> String {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, '"' | '%' | '^' | '&' | '|' | '<' | '>'))
+        .collect();
+    match state {
+        QuoteState::None => format!("\"{}\"", cleaned),
+        QuoteState::Single | QuoteState::Double => cleaned,
+    }
+}
+
+/// Sustituye los marcadores `{nombre}` de la plantilla escapando cada valor
+///
+/// Los marcadores desconocidos se dejan tal cual.
+pub fn render_command(template: &str, context: &HookContext) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut state = QuoteState::None;
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if state != QuoteState::Double => {
+                state = if state == QuoteState::Single {
+                    QuoteState::None
+                } else {
+                    QuoteState::Single
+                };
+                result.push(c);
+            }
+            '"' if state != QuoteState::Single => {
+                state = if state == QuoteState::Double {
+                    QuoteState::None
+                } else {
+                    QuoteState::Double
+                };
+                result.push(c);
+            }
+            '{' => {
+                let rest = &template[i + 1..];
+                let placeholder = rest.find('}').map(|end| &rest[..end]);
+                match placeholder.and_then(|name| context.value(name).map(|v| (name, v))) {
+                    Some((name, value)) => {
+                        result.push_str(&escape_value(&value, state));
+                        // Saltar el nombre y la llave de cierre
+                        for _ in 0..name.chars().count() + 1 {
+                            chars.next();
+                        }
+                    }
+                    None => result.push(c),
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Lanza comandos de usuario en segundo plano sin bloquear la interfaz
+#[derive(Default)]
+pub struct HookRunner {
+    children: Vec<Child>,
+    last_failure_notice: Option<Instant>,
+}
+
+impl HookRunner {
+    /// Crea un ejecutor de hooks vacío
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ejecuta la plantilla con los datos de la pista, sin esperar a que termine
+    ///
+    /// # Errores
+    /// - Retorna `TooManyRunning` si ya hay demasiados procesos vivos
+    /// - Retorna `Spawn` si el sistema no pudo lanzar el proceso
+    pub fn run(&mut self, template: &str, context: &HookContext) -> Result<(), HookError> {
+        if self.children.len() >= MAX_CONCURRENT_HOOKS {
+            return Err(HookError::TooManyRunning);
+        }
+
+        let command_line = render_command(template, context);
+        let child = Self::shell_command(&command_line)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(HookError::Spawn)?;
+        self.children.push(child);
+
+        Ok(())
+    }
+
+    /// Recoge los procesos que ya terminaron y retorna el primer fallo entre ellos
+    ///
+    /// Se llama seguido (en cada vuelta de la interfaz); los que siguen
+    /// ejecutándose quedan para la próxima.
+    pub fn reap(&mut self) -> Option<HookError> {
+        let mut failure = None;
+        self.children.retain_mut(|child| match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                if !status.success() {
+                    failure.get_or_insert(HookError::Exited(status));
+                }
+                false
+            }
+            Err(_) => false,
+        });
+        failure
+    }
+
+    /// Indica si corresponde avisar de un fallo (como mucho un aviso cada 30 segundos)
+    pub fn should_notify_failure(&mut self) -> bool {
+        let due = self
+            .last_failure_notice
+            .is_none_or(|last| last.elapsed() >= FAILURE_NOTICE_INTERVAL);
+        if due {
+            self.last_failure_notice = Some(Instant::now());
+        }
+        due
+    }
+
+    #[cfg(not(windows))]
+    fn shell_command(command_line: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+
+    #[cfg(windows)]
+    fn shell_command(command_line: &str) -> Command {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Espera a que terminen todos los hooks y retorna el fallo que hubo
+    fn reap_all(runner: &mut HookRunner) -> Option<HookError> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut failure = None;
+        while !runner.children.is_empty() {
+            assert!(Instant::now() < deadline, "the hook never finished");
+            failure = failure.or(runner.reap());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        failure
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn placeholders_are_quoted_for_the_shell() {
+        let context = HookContext {
+            artist: Some("Guns N' Roses"),
+            title: Some("$(rm -rf ~)"),
+            ..HookContext::default()
+        };
+        assert_eq!(
+            render_command("echo {artist} {unknown}", &context),
+            r"echo 'Guns N'\'' Roses' {unknown}"
+        );
+        assert_eq!(
+            render_command("echo \"{title}\"", &context),
+            r#"echo "\$(rm -rf ~)""#
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn non_zero_exit_is_reported() {
+        let mut runner = HookRunner::new();
+        runner.run("exit 3", &HookContext::default()).unwrap();
+        match reap_all(&mut runner) {
+            Some(HookError::Exited(status)) => assert_eq!(status.code(), Some(3)),
+            other => panic!("expected an exit failure, got {:?}", other),
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn successful_hooks_report_nothing() {
+        let mut runner = HookRunner::new();
+        runner.run("true", &HookContext::default()).unwrap();
+        assert!(reap_all(&mut runner).is_none());
+    }
+}
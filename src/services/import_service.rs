@@ -0,0 +1,651 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Errores posibles al importar una biblioteca de otro reproductor
+#[derive(Debug)]
+pub enum ImportError {
+    /// No se pudo leer el archivo
+    Io(std::io::Error),
+    /// El XML está mal formado
+    Xml(String),
+    /// El archivo no es de Rhythmbox ni de iTunes/Música
+    UnknownFormat,
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Error de E/S: {}", e),
+            Self::Xml(msg) => write!(f, "XML inválido: {}", msg),
+            Self::UnknownFormat => write!(
+                f,
+                "Formato desconocido: se esperaba playlists.xml de Rhythmbox o Library.xml de iTunes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Xml(_) | Self::UnknownFormat => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Por qué una pista de la biblioteca importada no se pudo resolver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnresolvedReason {
+    /// La ubicación no es un `file://` (streams, podcasts)
+    NotALocalFile,
+    /// El archivo no existe en este equipo
+    FileNotFound,
+    /// La playlist apunta a una pista que no está en la biblioteca
+    UnknownTrackId,
+}
+
+impl std::fmt::Display for UnresolvedReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotALocalFile => write!(f, "not a local file"),
+            Self::FileNotFound => write!(f, "file not found"),
+            Self::UnknownTrackId => write!(f, "unknown track id"),
+        }
+    }
+}
+
+/// Pista que no se pudo resolver, con su ubicación original
+#[derive(Debug, Clone)]
+pub struct UnresolvedTrack {
+    pub location: String,
+    pub reason: UnresolvedReason,
+}
+
+/// Playlist leída de otro reproductor
+#[derive(Debug, Clone)]
+pub struct ImportedPlaylist {
+    pub name: String,
+    /// Pistas encontradas, en el orden original
+    pub tracks: Vec<PathBuf>,
+    pub unresolved: Vec<UnresolvedTrack>,
+}
+
+/// Resultado de leer una biblioteca exportada
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub playlists: Vec<ImportedPlaylist>,
+}
+
+impl ImportSummary {
+    /// Total de pistas encontradas en todas las playlists
+    pub fn matched_count(&self) -> usize {
+        self.playlists.iter().map(|p| p.tracks.len()).sum()
+    }
+
+    /// Total de pistas sin resolver en todas las playlists
+    pub fn unresolved_count(&self) -> usize {
+        self.playlists.iter().map(|p| p.unresolved.len()).sum()
+    }
+}
+
+/// Cómo convertir las ubicaciones de la biblioteca en rutas locales
+pub struct PathRemap<'a> {
+    /// Prefijo de las rutas en el equipo de origen, p. ej. `/home/ana/Música`
+    pub old_prefix: Option<&'a str>,
+    /// Directorio que reemplaza al prefijo
+    pub music_path: &'a Path,
+}
+
+impl PathRemap<'_> {
+    /// Convierte una ubicación `file://` en una ruta local existente
+    fn resolve(&self, location: &str) -> Result<PathBuf, UnresolvedReason> {
+        let path = file_url_to_path(location).ok_or(UnresolvedReason::NotALocalFile)?;
+        let path = match self.old_prefix {
+            Some(prefix) => match path.strip_prefix(prefix.trim_end_matches('/')) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                    self.music_path.join(rest.trim_start_matches('/'))
+                }
+                _ => PathBuf::from(path),
+            },
+            None => PathBuf::from(path),
+        };
+        if path.is_file() {
+            Ok(path)
+        } else {
+            Err(UnresolvedReason::FileNotFound)
+        }
+    }
+}
+
+/// Elemento de un documento XML
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+#[derive(Debug)]
+enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+impl XmlElement {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Elementos hijos, sin los nodos de texto
+    fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|node| match node {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Texto directo del elemento
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|node| match node {
+                XmlNode::Text(text) => Some(text.as_str()),
+                XmlNode::Element(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Reemplaza las entidades XML (`&amp;`, `&#233;`, ...) por sus caracteres
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let decoded = after.find(';').and_then(|end| {
+            let entity = &after[..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Lee el nombre y los atributos de una etiqueta de apertura (sin `<` ni `>`)
+fn parse_tag(tag: &str) -> Result<XmlElement, ImportError> {
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let mut element = XmlElement {
+        name: tag[..name_end].to_string(),
+        ..Default::default()
+    };
+
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| ImportError::Xml(format!("atributo sin valor en <{}>", element.name)))?;
+        let key = rest[..eq].trim().to_string();
+        let value_part = rest[eq + 1..].trim_start();
+        let quote = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| {
+                ImportError::Xml(format!("atributo sin comillas en <{}>", element.name))
+            })?;
+        let value_end = value_part[1..].find(quote).ok_or_else(|| {
+            ImportError::Xml(format!("atributo sin cerrar en <{}>", element.name))
+        })?;
+        element
+            .attributes
+            .push((key, decode_entities(&value_part[1..value_end + 1])));
+        rest = value_part[value_end + 2..].trim_start();
+    }
+    Ok(element)
+}
+
+/// Posición del `>` que cierra la etiqueta, ignorando los que están entre comillas
+fn find_tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Lee un documento XML y retorna su elemento raíz
+///
+/// Alcanza para los formatos de exportación de Rhythmbox e iTunes: no valida
+/// DTDs ni resuelve entidades externas.
+fn parse_xml(input: &str) -> Result<XmlElement, ImportError> {
+    let unterminated = |what: &str| ImportError::Xml(format!("{} sin cerrar", what));
+    let mut stack = vec![XmlElement::default()];
+    let mut rest = input.trim_start_matches('\u{feff}');
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            let end = after
+                .find("-->")
+                .ok_or_else(|| unterminated("comentario"))?;
+            rest = &after[end + 3..];
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or_else(|| unterminated("CDATA"))?;
+            if let Some(parent) = stack.last_mut() {
+                parent
+                    .children
+                    .push(XmlNode::Text(after[..end].to_string()));
+            }
+            rest = &after[end + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest.find('>').ok_or_else(|| unterminated("declaración"))?;
+            rest = &rest[end + 1..];
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').ok_or_else(|| unterminated("etiqueta"))?;
+            let name = after[..end].trim();
+            let element = stack
+                .pop()
+                .filter(|element| element.name == name && !stack.is_empty())
+                .ok_or_else(|| ImportError::Xml(format!("cierre inesperado </{}>", name)))?;
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(XmlNode::Element(element));
+            }
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let end = find_tag_end(after).ok_or_else(|| unterminated("etiqueta"))?;
+            let tag = &after[..end];
+            match tag.strip_suffix('/') {
+                Some(tag) => {
+                    let element = parse_tag(tag.trim_end())?;
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(XmlNode::Element(element));
+                    }
+                }
+                None => stack.push(parse_tag(tag)?),
+            }
+            rest = &after[end + 1..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            if let Some(parent) = stack.last_mut() {
+                parent
+                    .children
+                    .push(XmlNode::Text(decode_entities(&rest[..end])));
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err(unterminated(&format!(
+            "<{}>",
+            stack.last().map(|e| e.name.as_str()).unwrap_or_default()
+        )));
+    }
+    stack
+        .pop()
+        .and_then(|document| {
+            document.children.into_iter().find_map(|node| match node {
+                XmlNode::Element(element) => Some(element),
+                XmlNode::Text(_) => None,
+            })
+        })
+        .ok_or_else(|| ImportError::Xml("documento vacío".to_string()))
+}
+
+/// Decodifica los `%XX` de una URL; las secuencias inválidas se dejan tal cual
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Convierte una URL `file://` en una ruta, `None` si no es un archivo local
+///
+/// Acepta `file:///home/...`, `file://localhost/home/...` y rutas de Windows
+/// como `file://localhost/C:/Users/...`.
+fn file_url_to_path(location: &str) -> Option<String> {
+    let rest = location.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let path = percent_decode(rest);
+    // "/C:/Users/..." -> "C:/Users/..."
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(path[1..].to_string());
+    }
+    Some(path)
+}
+
+/// Lee las playlists estáticas de un playlists.xml de Rhythmbox
+///
+/// Las playlists automáticas no tienen pistas propias y se omiten.
+fn import_rhythmbox(root: &XmlElement, remap: &PathRemap) -> ImportSummary {
+    let playlists = root
+        .elements()
+        .filter(|e| e.name == "playlist" && e.attribute("type") != Some("automatic"))
+        .map(|playlist| {
+            let mut imported = ImportedPlaylist {
+                name: playlist.attribute("name").unwrap_or("Imported").to_string(),
+                tracks: Vec::new(),
+                unresolved: Vec::new(),
+            };
+            for location in playlist.elements().filter(|e| e.name == "location") {
+                let location = location.text().trim().to_string();
+                match remap.resolve(&location) {
+                    Ok(path) => imported.tracks.push(path),
+                    Err(reason) => imported
+                        .unresolved
+                        .push(UnresolvedTrack { location, reason }),
+                }
+            }
+            imported
+        })
+        .collect();
+    ImportSummary { playlists }
+}
+
+/// Pares clave-valor de un `<dict>` de plist
+fn plist_entries(dict: &XmlElement) -> Vec<(String, &XmlElement)> {
+    let mut entries = Vec::new();
+    let mut elements = dict.elements();
+    while let Some(key) = elements.next() {
+        if key.name != "key" {
+            continue;
+        }
+        if let Some(value) = elements.next() {
+            entries.push((key.text(), value));
+        }
+    }
+    entries
+}
+
+fn plist_value<'a>(dict: &'a XmlElement, key: &str) -> Option<&'a XmlElement> {
+    plist_entries(dict)
+        .into_iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+/// Lee las playlists de un Library.xml de iTunes/Música
+///
+/// Se omiten la biblioteca completa, las listas especiales (Música, Podcasts...)
+/// y las carpetas de playlists.
+fn import_itunes(root: &XmlElement, remap: &PathRemap) -> Result<ImportSummary, ImportError> {
+    let library = root
+        .elements()
+        .find(|e| e.name == "dict")
+        .ok_or(ImportError::UnknownFormat)?;
+
+    // ID de pista -> ubicación
+    let locations: HashMap<String, String> = plist_value(library, "Tracks")
+        .map(|tracks| {
+            plist_entries(tracks)
+                .into_iter()
+                .filter_map(|(id, track)| {
+                    plist_value(track, "Location").map(|location| (id, location.text()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let playlists = plist_value(library, "Playlists")
+        .map(|array| {
+            array
+                .elements()
+                .filter(|playlist| {
+                    ["Master", "Distinguished Kind", "Folder"]
+                        .iter()
+                        .all(|key| match plist_value(playlist, key) {
+                            Some(value) => value.name == "false",
+                            None => true,
+                        })
+                })
+                .map(|playlist| {
+                    let mut imported = ImportedPlaylist {
+                        name: plist_value(playlist, "Name")
+                            .map(|name| name.text())
+                            .unwrap_or_else(|| "Imported".to_string()),
+                        tracks: Vec::new(),
+                        unresolved: Vec::new(),
+                    };
+                    let items = plist_value(playlist, "Playlist Items");
+                    for item in items.iter().flat_map(|items| items.elements()) {
+                        let Some(id) = plist_value(item, "Track ID").map(|id| id.text()) else {
+                            continue;
+                        };
+                        let location = locations.get(&id).cloned();
+                        let result = match &location {
+                            Some(location) => remap.resolve(location),
+                            None => Err(UnresolvedReason::UnknownTrackId),
+                        };
+                        match result {
+                            Ok(path) => imported.tracks.push(path),
+                            Err(reason) => imported.unresolved.push(UnresolvedTrack {
+                                location: location.unwrap_or_else(|| format!("Track ID {}", id)),
+                                reason,
+                            }),
+                        }
+                    }
+                    imported
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ImportSummary { playlists })
+}
+
+/// Lee las playlists de una biblioteca exportada por Rhythmbox o iTunes/Música
+///
+/// Detecta el formato por el elemento raíz. Las ubicaciones que empiezan con
+/// `remap.old_prefix` se reubican bajo `remap.music_path`. No guarda nada.
+///
+/// # Errores
+/// - Retorna `Io` si no se puede leer el archivo
+/// - Retorna `Xml` si el XML está mal formado
+/// - Retorna `UnknownFormat` si no es ninguno de los dos formatos
+pub fn import_library(path: &Path, remap: &PathRemap) -> Result<ImportSummary, ImportError> {
+    let bytes = std::fs::read(path)?;
+    let content = String::from_utf8_lossy(&bytes);
+    let root = parse_xml(&content)?;
+
+    match root.name.as_str() {
+        "rhythmdb-playlists" => Ok(import_rhythmbox(&root, remap)),
+        "plist" => import_itunes(&root, remap),
+        _ => Err(ImportError::UnknownFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Biblioteca de prueba con las pistas que existen en este equipo
+    struct Library {
+        dir: PathBuf,
+    }
+
+    impl Library {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "rusted-player-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            for track in ["Jazz/So What.mp3", "Blues/Café.flac"] {
+                let path = dir.join(track);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, b"audio").unwrap();
+            }
+            Self { dir }
+        }
+
+        fn import(&self, fixture: &str) -> ImportSummary {
+            let remap = PathRemap {
+                old_prefix: Some("/home/ana/Música/"),
+                music_path: &self.dir,
+            };
+            let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(fixture);
+            import_library(&fixture, &remap).unwrap()
+        }
+    }
+
+    impl Drop for Library {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn reasons(playlist: &ImportedPlaylist) -> Vec<(&str, UnresolvedReason)> {
+        playlist
+            .unresolved
+            .iter()
+            .map(|track| (track.location.as_str(), track.reason))
+            .collect()
+    }
+
+    #[test]
+    fn rhythmbox_static_playlists_are_imported() {
+        let library = Library::new("import-rhythmbox");
+        let summary = library.import("rhythmbox-playlists.xml");
+        let names: Vec<&str> = summary.playlists.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Jazz & Blues", "Empty"]);
+
+        let jazz = &summary.playlists[0];
+        assert_eq!(
+            jazz.tracks,
+            [
+                library.dir.join("Jazz/So What.mp3"),
+                library.dir.join("Blues/Café.flac")
+            ]
+        );
+        assert_eq!(
+            reasons(jazz),
+            [
+                (
+                    "file:///home/ana/M%C3%BAsica/Jazz/Missing.mp3",
+                    UnresolvedReason::FileNotFound
+                ),
+                (
+                    "http://radio.example.com/stream",
+                    UnresolvedReason::NotALocalFile
+                ),
+            ]
+        );
+        assert_eq!(
+            (summary.matched_count(), summary.unresolved_count()),
+            (2, 2)
+        );
+    }
+
+    #[test]
+    fn itunes_user_playlists_are_imported_in_order() {
+        let library = Library::new("import-itunes");
+        let summary = library.import("itunes-library.xml");
+        let names: Vec<&str> = summary.playlists.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Late Night"]);
+
+        let late_night = &summary.playlists[0];
+        assert_eq!(
+            late_night.tracks,
+            [
+                library.dir.join("Blues/Café.flac"),
+                library.dir.join("Jazz/So What.mp3")
+            ]
+        );
+        assert_eq!(
+            reasons(late_night),
+            [
+                (
+                    "https://podcasts.example.com/1.mp3",
+                    UnresolvedReason::NotALocalFile
+                ),
+                ("Track ID 999", UnresolvedReason::UnknownTrackId),
+            ]
+        );
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(matches!(parse_xml("<a><b></a>"), Err(ImportError::Xml(_))));
+        let root = parse_xml("<?xml version=\"1.0\"?><opml/>").unwrap();
+        assert_eq!(root.name, "opml");
+        assert!(parse_xml("<!-- nothing -->").is_err());
+    }
+
+    #[test]
+    fn file_urls_become_paths() {
+        let cases = [
+            ("file:///home/ana/a%20b.mp3", Some("/home/ana/a b.mp3")),
+            (
+                "file://localhost/C:/Users/ana/x.mp3",
+                Some("C:/Users/ana/x.mp3"),
+            ),
+            ("file:///bad%zz.mp3", Some("/bad%zz.mp3")),
+            ("http://example.com/x.mp3", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(file_url_to_path(url).as_deref(), expected, "{}", url);
+        }
+        assert_eq!(
+            decode_entities("R&amp;B &#233;&#x301; &bogus;"),
+            "R&B é\u{301} &bogus;"
+        );
+    }
+}
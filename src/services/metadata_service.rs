@@ -1,50 +1,725 @@
-use lofty::{Accessor, AudioFile, Probe, TaggedFileExt};
-use std::collections::HashMap;
+use lofty::{Accessor, AudioFile, ItemKey, Probe, Tag, TagType, TaggedFileExt};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
-/// Formatos de audio soportados
-pub const SUPPORTED_AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav", "m4a", "aac", "wma"];
+use crate::models::error::RustedPlayerError;
+use crate::models::track::TrackFilter;
+use crate::services::file_walker_service;
+use crate::services::playlist_storage_service;
+use crate::services::shuffle_service::AlbumPosition;
+use crate::utils::is_audio_file;
+
+/// Artista del álbum de los compilados; no cuenta como artista en los rankings
+const VARIOUS_ARTISTS: &str = "Various Artists";
+
+/// Marcadores que introducen artistas invitados (en minúsculas)
+const FEATURING_MARKERS: &[&str] = &[
+    "(feat. ",
+    "(feat ",
+    "(ft. ",
+    "(featuring ",
+    "[feat. ",
+    "[ft. ",
+    " feat. ",
+    " feat ",
+    " ft. ",
+    " ft ",
+    " featuring ",
+];
+
+/// Longitud máxima de los campos de texto en ID3v1
+const ID3V1_FIELD_LEN: usize = 30;
+
+/// Sonoridad de referencia de ReplayGain 2.0, en LUFS
+const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+
+/// Tipo de etiqueta del que proviene un valor de metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagSource {
+    Id3v24,
+    Id3v23,
+    Id3v22,
+    Ape,
+    Id3v1,
+    VorbisComments,
+    Mp4,
+    RiffInfo,
+    AiffText,
+}
+
+/// Prioridad por defecto cuando un archivo tiene varias etiquetas
+pub const DEFAULT_TAG_PRIORITY: &[TagSource] = &[
+    TagSource::Id3v24,
+    TagSource::Id3v23,
+    TagSource::Id3v22,
+    TagSource::Ape,
+    TagSource::Id3v1,
+];
+
+impl TagSource {
+    /// Nombre legible del tipo de etiqueta
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Id3v24 => "ID3v2.4",
+            Self::Id3v23 => "ID3v2.3",
+            Self::Id3v22 => "ID3v2.2",
+            Self::Ape => "APE",
+            Self::Id3v1 => "ID3v1",
+            Self::VorbisComments => "Vorbis",
+            Self::Mp4 => "MP4",
+            Self::RiffInfo => "RIFF INFO",
+            Self::AiffText => "AIFF",
+        }
+    }
+
+    /// Interpreta un nombre de la configuración (sin distinguir mayúsculas)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "id3v2.4" => Some(Self::Id3v24),
+            "id3v2.3" => Some(Self::Id3v23),
+            "id3v2.2" => Some(Self::Id3v22),
+            "ape" => Some(Self::Ape),
+            "id3v1" => Some(Self::Id3v1),
+            "vorbis" => Some(Self::VorbisComments),
+            "mp4" => Some(Self::Mp4),
+            "riff" => Some(Self::RiffInfo),
+            "aiff" => Some(Self::AiffText),
+            _ => None,
+        }
+    }
+
+    /// Convierte el tipo de lofty, usando la versión leída de la cabecera ID3v2
+    fn from_tag_type(tag_type: TagType, id3v2_version: Option<u8>) -> Option<Self> {
+        match tag_type {
+            TagType::Id3v2 => Some(match id3v2_version {
+                Some(2) => Self::Id3v22,
+                Some(3) => Self::Id3v23,
+                _ => Self::Id3v24,
+            }),
+            TagType::Ape => Some(Self::Ape),
+            TagType::Id3v1 => Some(Self::Id3v1),
+            TagType::VorbisComments => Some(Self::VorbisComments),
+            TagType::Mp4Ilst => Some(Self::Mp4),
+            TagType::RiffInfo => Some(Self::RiffInfo),
+            TagType::AiffText => Some(Self::AiffText),
+            _ => None,
+        }
+    }
+}
+
+/// Se serializa con el nombre que se muestra (`"ID3v2.4"`, `"APE"`, ...)
+impl Serialize for TagSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Serializa una ruta como texto UTF-8, reemplazando lo que no sea válido
+fn serialize_path_lossy<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// Serializa una duración como segundos enteros
+fn serialize_duration_as_secs<S: Serializer>(
+    duration: &std::time::Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+/// Serializa una duración opcional como segundos enteros o `null`
+fn serialize_optional_duration_as_secs<S: Serializer>(
+    duration: &Option<std::time::Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.as_secs()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Etiqueta de la que se tomó cada campo mostrado
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TagSources {
+    pub title: Option<TagSource>,
+    pub artist: Option<TagSource>,
+    pub album: Option<TagSource>,
+    pub genre: Option<TagSource>,
+    pub year: Option<TagSource>,
+}
+
+/// Lee una ganancia ReplayGain como `"-9.30 dB"` (la unidad es opcional)
+fn parse_gain_db(value: &str) -> Option<f64> {
+    let value = value.trim().to_ascii_lowercase();
+    let number = value.strip_suffix("db").unwrap_or(&value);
+    number
+        .trim()
+        .parse()
+        .ok()
+        .filter(|gain: &f64| gain.is_finite())
+}
+
+/// Lee la versión mayor de la cabecera ID3v2, si el archivo empieza con una
+fn read_id3v2_version(path: &Path) -> Option<u8> {
+    let mut header = [0u8; 4];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    (&header[..3] == b"ID3").then_some(header[3])
+}
+
+/// Compara dos valores de texto de etiquetas distintas
+///
+/// Ignora mayúsculas y espacios de borde, y acepta el recorte a 30 caracteres de ID3v1.
+fn tag_values_agree(a: &str, b: &str) -> bool {
+    let a = a.trim().to_lowercase();
+    let b = b.trim().to_lowercase();
+    if a == b {
+        return true;
+    }
+    let (short, long) = if a.chars().count() < b.chars().count() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    short.chars().count() >= ID3V1_FIELD_LEN - 1 && long.starts_with(&short)
+}
+
+/// Toma un campo de la primera etiqueta (por prioridad) que lo tenga
+///
+/// Retorna el valor, la etiqueta de origen y si alguna otra etiqueta lo contradice.
+fn pick_field<T>(
+    tags: &[(TagSource, &Tag)],
+    get: impl Fn(&Tag) -> Option<T>,
+    agree: impl Fn(&T, &T) -> bool,
+) -> (Option<T>, Option<TagSource>, bool) {
+    let mut values = tags
+        .iter()
+        .filter_map(|(source, tag)| get(tag).map(|value| (*source, value)));
+
+    match values.next() {
+        Some((source, value)) => {
+            let conflict = values.any(|(_, other)| !agree(&value, &other));
+            (Some(value), Some(source), conflict)
+        }
+        None => (None, None, false),
+    }
+}
 
 /// Metadata de una pista de audio
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct TrackMetadata {
     /// Ruta al archivo
+    #[serde(serialize_with = "serialize_path_lossy")]
     pub path: PathBuf,
+    /// Título de la pista
+    pub title: Option<String>,
     /// Nombre del álbum
     pub album: Option<String>,
     /// Nombre del artista
     pub artist: Option<String>,
+    /// Artista del álbum (TPE2, ALBUMARTIST, aART)
+    pub album_artist: Option<String>,
+    /// Número de pista dentro del disco
+    pub track_number: Option<u32>,
+    /// Total de pistas declarado en la etiqueta
+    pub track_total: Option<u32>,
+    /// Número de disco
+    pub disc_number: Option<u32>,
+    /// Artistas invitados extraídos del campo de artista ("feat.", "ft.", ...)
+    pub featured_artists: Vec<String>,
     /// Género musical
     pub genre: Option<String>,
     /// Año de lanzamiento
     pub year: Option<u32>,
-    /// Duración de la pista
+    /// Duración de la pista, en segundos al serializar
+    #[serde(serialize_with = "serialize_optional_duration_as_secs")]
     pub duration: Option<std::time::Duration>,
+    /// Tasa de bits del audio en kbps, si el formato la informa
+    pub bitrate_kbps: Option<u32>,
+    /// Alguna de sus etiquetas trae una imagen embebida
+    pub has_embedded_art: bool,
+    /// Ganancia ReplayGain de la pista en dB (`REPLAYGAIN_TRACK_GAIN`)
+    pub replay_gain_track: Option<f64>,
+    /// Etiqueta de la que proviene cada campo
+    pub tag_sources: TagSources,
+    /// Campos en los que las etiquetas del archivo no coinciden
+    pub tag_conflicts: Vec<&'static str>,
+    /// Ruta real del archivo, calculada una vez al recorrer la biblioteca
+    #[serde(skip)]
+    pub real_path: Option<PathBuf>,
+}
+
+/// Campo por el que se ordenan los resultados de búsqueda
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrackSortKey {
+    #[default]
+    Title,
+    Artist,
+    Album,
+    Year,
+    Duration,
+}
+
+impl TrackSortKey {
+    /// Nombre para mostrar
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::Album => "album",
+            Self::Year => "year",
+            Self::Duration => "length",
+        }
+    }
+
+    /// El campo siguiente, volviendo al primero después del último
+    pub fn next(self) -> Self {
+        match self {
+            Self::Title => Self::Artist,
+            Self::Artist => Self::Album,
+            Self::Album => Self::Year,
+            Self::Year => Self::Duration,
+            Self::Duration => Self::Title,
+        }
+    }
+}
+
+/// Número de pista más alto que se toma en serio; más allá es una etiqueta dañada
+const MAX_TRACK_NUMBER: u32 = 999;
+
+/// Pistas que se esperan como mucho después de la más alta encontrada, aunque
+/// el total declarado diga más
+const MAX_TRAILING_MISSING: u32 = 30;
+
+/// Calcula los números de pista que faltan en un álbum
+///
+/// Recibe `(disco, pista, total)` por cada pista numerada; sin disco se asume el 1.
+/// En cada disco se esperan las pistas desde 1 hasta el total declarado o, si no
+/// lo hay, hasta el número más alto encontrado. Retorna pares `(disco, pista)`.
+///
+/// Para que una etiqueta dañada no genere miles de huecos, los números mayores
+/// que `MAX_TRACK_NUMBER` se ignoran y el total declarado no pasa de
+/// `MAX_TRAILING_MISSING` pistas después de la más alta.
+pub fn find_missing_track_numbers(tracks: &[(Option<u32>, u32, Option<u32>)]) -> Vec<(u32, u32)> {
+    let mut discs: BTreeMap<u32, (BTreeSet<u32>, u32)> = BTreeMap::new();
+    for &(disc, number, total) in tracks {
+        if number == 0 || number > MAX_TRACK_NUMBER {
+            continue;
+        }
+        let (numbers, declared) = discs.entry(disc.unwrap_or(1)).or_default();
+        numbers.insert(number);
+        *declared = (*declared).max(total.unwrap_or(0));
+    }
+
+    discs
+        .into_iter()
+        .flat_map(|(disc, (numbers, declared))| {
+            let highest = numbers.last().copied().unwrap_or(0);
+            let expected = declared.clamp(highest, highest + MAX_TRAILING_MISSING);
+            (1..=expected)
+                .filter(move |n| !numbers.contains(n))
+                .map(move |n| (disc, n))
+        })
+        .collect()
+}
+
+/// Resumen de un álbum dentro de la discografía de un artista
+#[derive(Debug, Clone)]
+pub struct AlbumInfo {
+    /// Nombre del álbum
+    pub name: String,
+    /// Año de lanzamiento (el más antiguo entre sus pistas)
+    pub year: Option<u32>,
+    /// Cantidad de pistas del artista en el álbum
+    pub track_count: usize,
+}
+
+/// Álbum de la biblioteca con su artista, tal como se muestran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumRef {
+    /// Nombre lógico del álbum (sin sufijo de disco si se fusionan discos)
+    pub album: String,
+    /// Artista del álbum, el único de sus pistas o "Various Artists"
+    pub artist: String,
+    /// Clave de agrupación, para volver a encontrar sus pistas
+    key: (String, String),
+}
+
+/// Álbum al que le faltan pistas según su numeración
+#[derive(Debug, Clone)]
+pub struct IncompleteAlbum {
+    /// Nombre del álbum
+    pub album: String,
+    /// Artista del álbum (o de la pista si no hay artista de álbum)
+    pub artist: Option<String>,
+    /// Pistas faltantes como pares `(disco, pista)`
+    pub missing: Vec<(u32, u32)>,
+    /// Si el álbum tiene más de un disco
+    pub multi_disc: bool,
+}
+
+impl IncompleteAlbum {
+    /// Formatea las pistas faltantes: `"3, 6"` o `"1-3, 2-5"` en álbumes de varios discos
+    pub fn format_missing(&self) -> String {
+        self.missing
+            .iter()
+            .map(|&(disc, number)| {
+                if self.multi_disc {
+                    format!("{}-{}", disc, number)
+                } else {
+                    number.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 /// Servicio para gestionar la biblioteca de música y extraer metadata
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PlaylistService {
     tracks: Vec<TrackMetadata>,
     genres: HashMap<String, Vec<usize>>,
     artists: HashMap<String, Vec<usize>>,
+    /// Pistas en las que cada artista figura como invitado ("feat."), por la misma clave
+    featured: HashMap<String, Vec<usize>>,
+    /// Pistas por artista del álbum (o de la pista, si no hay), para agrupar álbumes
+    album_artists: HashMap<String, Vec<usize>>,
+    /// Posición de cada pista en `tracks` según su ruta
+    path_index: HashMap<PathBuf, usize>,
+    /// Posición de cada pista según su ruta real, sin enlaces simbólicos
+    real_path_index: HashMap<PathBuf, usize>,
+    /// Grafías originales de cada artista normalizado y cuántas veces aparecen
+    artist_spellings: HashMap<String, HashMap<String, usize>>,
+    /// Si se agrupan variantes del nombre de artista ("Beatles, The" = "The Beatles")
+    fold_artist_names: bool,
+    /// Si los álbumes "X (Disc 1)" y "X (Disc 2)" cuentan como uno solo
+    merge_discs: bool,
+    /// Orden de preferencia cuando un archivo tiene varias etiquetas
+    tag_priority: Vec<TagSource>,
+    /// Si se saltan las carpetas que tienen un archivo `.nomedia`
+    respect_nomedia: bool,
+    /// Si se escanean los archivos y carpetas ocultos
+    show_hidden: bool,
+}
+
+/// Atributos normalizados con los que se comparan pistas al armar una mezcla
+struct MixTraits {
+    artist: Option<String>,
+    album: Option<String>,
+    genre: Option<String>,
+    decade: Option<u32>,
+}
+
+impl MixTraits {
+    /// Parecido entre dos pistas: artista o álbum 4, género 2, década 1
+    fn similarity(&self, other: &MixTraits) -> u32 {
+        let same = |a: &Option<String>, b: &Option<String>| a.is_some() && a == b;
+        let mut score = 0;
+        if same(&self.artist, &other.artist) || same(&self.album, &other.album) {
+            score += 4;
+        }
+        if same(&self.genre, &other.genre) {
+            score += 2;
+        }
+        if self.decade.is_some() && self.decade == other.decade {
+            score += 1;
+        }
+        score
+    }
+}
+
+/// La pista cumple cada condición del filtro
+///
+/// Si a la pista le falta el dato de una condición, la cumple salvo en modo estricto.
+/// Los géneros se comparan normalizados.
+fn track_matches(track: &TrackMetadata, filter: &TrackFilter) -> bool {
+    let check = |value: Option<u64>, from: Option<u64>, to: Option<u64>| {
+        if from.is_none() && to.is_none() {
+            return true;
+        }
+        value.map_or(!filter.strict, |value| {
+            from.is_none_or(|from| value >= from) && to.is_none_or(|to| value <= to)
+        })
+    };
+    let year = track.year.map(u64::from);
+    let seconds = track.duration.map(|d| d.as_secs());
+    if !check(
+        year,
+        filter.year_from.map(u64::from),
+        filter.year_to.map(u64::from),
+    ) || !check(seconds, filter.min_seconds, filter.max_seconds)
+    {
+        return false;
+    }
+
+    let genre = track.genre.as_deref().map(normalize_genre);
+    let listed = |genres: &[String]| {
+        genre
+            .as_ref()
+            .is_some_and(|genre| genres.iter().any(|g| normalize_genre(g) == *genre))
+    };
+    if listed(&filter.exclude_genres) {
+        return false;
+    }
+    filter.genres.is_empty() || listed(&filter.genres) || (genre.is_none() && !filter.strict)
 }
 
-/// Normaliza un género para agrupación (minúsculas, sin caracteres especiales)
-fn normalize_genre(genre: &str) -> String {
+/// Normaliza un género para agrupación (NFC, minúsculas, sin caracteres especiales)
+pub fn normalize_genre(genre: &str) -> String {
     genre
+        .nfc()
+        .collect::<String>()
         .to_lowercase()
         .chars()
         .filter(|c| c.is_alphanumeric())
         .collect()
 }
 
-/// Verifica si una extensión corresponde a un archivo de audio soportado
-fn is_audio_file_ext(ext: &str) -> bool {
-    SUPPORTED_AUDIO_EXTENSIONS
+/// Separa el artista principal de los invitados, unidos en un solo texto
+///
+/// `"Daft Punk feat. Pharrell Williams & Nile Rodgers"` devuelve
+/// `("Daft Punk", Some("Pharrell Williams, Nile Rodgers"))`; sin invitados, `None`.
+/// Reconoce las mismas notaciones que `split_featured_artists`.
+pub fn normalize_featured_artist(artist: &str) -> (String, Option<String>) {
+    let (primary, featured) = split_featured_artists(artist);
+    let featured = (!featured.is_empty()).then(|| featured.join(", "));
+    (primary, featured)
+}
+
+/// Pliega un título para comparar prefijos: sin acentos y en minúsculas
+///
+/// Descompone el texto (NFD) y descarta las marcas combinantes, así `"Ángel"`
+/// empieza con `"a"` igual que `"angel"`.
+pub fn fold_title(title: &str) -> String {
+    title
+        .trim_start()
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Letra (`a`..=`z`) con la que empieza un título, `None` si empieza con otra cosa
+///
+/// Los títulos que empiezan con dígitos, símbolos u otros alfabetos van bajo `#`.
+pub fn title_initial(title: &str) -> Option<char> {
+    fold_title(title)
+        .chars()
+        .next()
+        .filter(char::is_ascii_lowercase)
+}
+
+/// Separa los artistas invitados del artista principal
+///
+/// `"Daft Punk feat. Pharrell Williams & Nile Rodgers"` devuelve
+/// `("Daft Punk", ["Pharrell Williams", "Nile Rodgers"])`.
+pub fn split_featured_artists(artist: &str) -> (String, Vec<String>) {
+    // to_ascii_lowercase conserva las posiciones en bytes del texto original
+    let lower = artist.to_ascii_lowercase();
+    let marker = FEATURING_MARKERS
+        .iter()
+        .filter_map(|m| lower.find(m).map(|i| (i, m.len())))
+        .min_by_key(|(i, _)| *i);
+
+    match marker {
+        Some((start, len)) if !artist[..start].trim().is_empty() => {
+            let primary = artist[..start].trim().to_string();
+            let featured = artist[start + len..]
+                .trim()
+                .trim_end_matches([')', ']'])
+                .split([',', '&'])
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect();
+            (primary, featured)
+        }
+        _ => (artist.trim().to_string(), Vec::new()),
+    }
+}
+
+/// Normaliza un nombre de artista para agrupación
+///
+/// Pasa a la forma NFC (la "ö" precompuesta y la "o" con diéresis combinante son
+/// iguales), colapsa espacios, pasa a minúsculas y convierte `"Beatles, The"` en
+/// `"the beatles"`.
+pub fn normalize_artist(artist: &str) -> String {
+    let lower = artist
+        .nfc()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    match lower.strip_suffix(", the") {
+        Some(base) => format!("the {}", base.trim_end()),
+        None => lower,
+    }
+}
+
+/// Palabras que indican el número de disco al final de un álbum o carpeta
+const DISC_WORDS: [&str; 3] = ["disc", "disk", "cd"];
+
+/// Separa el sufijo de disco de un nombre de álbum o carpeta
+///
+/// Reconoce `"Disc N"`, `"Disk N"` y `"CD N"` al final del nombre, con o sin
+/// espacio, entre paréntesis o corchetes y tras un guion: `"Album (Disc 2)"`,
+/// `"Album - CD2"` y `"Album [disc 2]"` devuelven `("Album", 2)`.
+pub fn split_disc_suffix(name: &str) -> Option<(String, u32)> {
+    let trimmed = name.trim_end();
+    let (inner, bracketed) = match trimmed
+        .strip_suffix(')')
+        .or_else(|| trimmed.strip_suffix(']'))
+    {
+        Some(inner) => (inner.trim_end(), true),
+        None => (trimmed, false),
+    };
+
+    let digits = inner.chars().rev().take_while(char::is_ascii_digit).count();
+    let number: u32 = inner[inner.len() - digits..].parse().ok()?;
+    let before = inner[..inner.len() - digits].trim_end();
+
+    // to_ascii_lowercase conserva las posiciones en bytes del texto original
+    let lower = before.to_ascii_lowercase();
+    let word = DISC_WORDS.iter().find(|w| lower.ends_with(*w))?;
+    let mut base = &before[..before.len() - word.len()];
+    // La palabra tiene que empezar en un límite ("Abcd 2" no es un disco)
+    if base.chars().last().is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    if bracketed {
+        base = base.trim_end().strip_suffix(['(', '['])?;
+    }
+
+    let base =
+        base.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | ',' | ':'));
+    if base.is_empty() {
+        return None;
+    }
+    Some((base.to_string(), number))
+}
+
+/// Si una carpeta se llama solo como un disco: `"CD1"`, `"Disc 2"`
+fn is_bare_disc_name(name: &str) -> bool {
+    let lower = name.trim().to_ascii_lowercase();
+    DISC_WORDS.iter().any(|word| {
+        lower.strip_prefix(word).is_some_and(|rest| {
+            let digits = rest.trim_start_matches([' ', '-', '_']);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        })
+    })
+}
+
+/// Nombres de portada buscados junto a las pistas, en orden de prioridad
+const COVER_NAMES: [&str; 4] = ["cover", "folder", "album", "front"];
+/// Extensiones de imagen aceptadas para la portada
+const COVER_EXTENSIONS: [&str; 3] = ["jpg", "jpeg", "png"];
+
+/// Busca la imagen de portada en la carpeta de la pista
+///
+/// Prueba `cover`, `folder`, `album` y `front` (en ese orden) con extensión
+/// `.jpg`, `.jpeg` o `.png`, sin distinguir mayúsculas. Se prefiere a la
+/// portada embebida: la comparten todas las pistas del álbum y no hay que
+/// decodificar etiquetas para leerla.
+pub fn get_album_art_path(track_path: &Path) -> Option<PathBuf> {
+    let dir = track_path.parent()?;
+    let images: Vec<(String, PathBuf)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| COVER_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        })
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_lowercase();
+            Some((stem, path))
+        })
+        .collect();
+
+    COVER_NAMES.iter().find_map(|name| {
+        images
+            .iter()
+            .find(|(stem, _)| stem == name)
+            .map(|(_, path)| path.clone())
+    })
+}
+
+/// Lee la primera imagen embebida en las etiquetas de la pista
+///
+/// Decodifica las etiquetas, así que conviene probar antes `get_album_art_path`.
+pub fn read_embedded_art(track_path: &Path) -> Option<Vec<u8>> {
+    let tagged_file = Probe::open(track_path).ok()?.read().ok()?;
+    tagged_file
+        .tags()
         .iter()
-        .any(|&e| ext.eq_ignore_ascii_case(e))
+        .find_map(|tag| tag.pictures().first())
+        .map(|picture| picture.data().to_vec())
+}
+
+/// Campos a escribir en las etiquetas de una pista; `None` deja el campo como está
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagEdit {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub year: Option<u32>,
+}
+
+impl TagEdit {
+    /// No cambia ningún campo
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Escribe los campos en la etiqueta principal del archivo, creándola si no tiene
+///
+/// Las demás etiquetas del archivo quedan como estaban.
+///
+/// # Errors
+/// Retorna error si el archivo no se puede leer o escribir, o si su formato no
+/// admite etiquetas
+pub fn write_tags(path: &Path, edit: &TagEdit) -> Result<(), RustedPlayerError> {
+    let mut tagged_file = Probe::open(path)?.read()?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| RustedPlayerError::TagWrite(format!("{}: sin etiquetas", path.display())))?;
+    if let Some(title) = &edit.title {
+        tag.set_title(title.clone());
+    }
+    if let Some(artist) = &edit.artist {
+        tag.set_artist(artist.clone());
+    }
+    if let Some(album) = &edit.album {
+        tag.set_album(album.clone());
+    }
+    if let Some(number) = edit.track_number {
+        tag.set_track(number);
+    }
+    if let Some(disc) = edit.disc_number {
+        tag.set_disk(disc);
+    }
+    if let Some(year) = edit.year {
+        tag.set_year(year);
+    }
+    tagged_file.save_to_path(path)?;
+    Ok(())
 }
 
 impl PlaylistService {
@@ -54,9 +729,236 @@ impl PlaylistService {
             tracks: Vec::new(),
             genres: HashMap::new(),
             artists: HashMap::new(),
+            featured: HashMap::new(),
+            album_artists: HashMap::new(),
+            path_index: HashMap::new(),
+            real_path_index: HashMap::new(),
+            artist_spellings: HashMap::new(),
+            fold_artist_names: true,
+            merge_discs: true,
+            tag_priority: DEFAULT_TAG_PRIORITY.to_vec(),
+            respect_nomedia: true,
+            show_hidden: false,
         }
     }
 
+    /// Biblioteca con estas pistas ya indexadas, sin leer el disco
+    #[cfg(test)]
+    pub fn from_tracks(tracks: Vec<TrackMetadata>) -> Self {
+        let mut service = Self::new();
+        for track in tracks {
+            service.tracks.push(track);
+            service.index_track(service.tracks.len() - 1);
+        }
+        service
+    }
+
+    /// Copia independiente de la biblioteca para previsualizar cambios
+    ///
+    /// Las pistas y todos los índices son propios, así que modificar la copia
+    /// no afecta al servicio original.
+    pub fn deep_clone(&self) -> Self {
+        self.clone()
+    }
+
+    /// Establece el orden de preferencia entre etiquetas (ID3v2, APE, ID3v1...)
+    ///
+    /// Las etiquetas que no aparecen en la lista quedan detrás de las listadas.
+    pub fn set_tag_priority(&mut self, priority: Vec<TagSource>) {
+        self.tag_priority = priority;
+    }
+
+    /// Posición de una etiqueta en el orden de preferencia
+    fn tag_rank(&self, source: TagSource) -> usize {
+        self.tag_priority
+            .iter()
+            .position(|&s| s == source)
+            .unwrap_or(self.tag_priority.len())
+    }
+
+    /// Activa o desactiva la agrupación de variantes del nombre de artista
+    ///
+    /// Si ya hay pistas cargadas se reconstruyen los índices con la nueva clave.
+    pub fn set_artist_folding(&mut self, enabled: bool) {
+        if self.fold_artist_names != enabled {
+            self.fold_artist_names = enabled;
+            self.rebuild_indices();
+        }
+    }
+
+    /// Activa o desactiva el salto de carpetas con `.nomedia` en los próximos escaneos
+    pub fn set_respect_nomedia(&mut self, enabled: bool) {
+        self.respect_nomedia = enabled;
+    }
+
+    /// Incluye o no los archivos y carpetas ocultos en los próximos escaneos
+    pub fn set_show_hidden(&mut self, enabled: bool) {
+        self.show_hidden = enabled;
+    }
+
+    /// Activa o desactiva la fusión de discos de un mismo álbum
+    pub fn set_disc_merging(&mut self, enabled: bool) {
+        self.merge_discs = enabled;
+    }
+
+    /// Nombre lógico de un álbum: sin el sufijo de disco si se fusionan discos
+    fn album_key(&self, album: &str) -> String {
+        match split_disc_suffix(album) {
+            Some((base, _)) if self.merge_discs => base,
+            _ => album.to_string(),
+        }
+    }
+
+    /// Disco de una pista: el de la etiqueta o, si falta, el del sufijo del álbum
+    fn disc_of(&self, track: &TrackMetadata) -> Option<u32> {
+        track.disc_number.or_else(|| {
+            let album = track.album.as_deref().filter(|_| self.merge_discs)?;
+            split_disc_suffix(album).map(|(_, disc)| disc)
+        })
+    }
+
+    /// Clave de agrupación de un artista según la configuración actual
+    fn artist_key(&self, artist: &str) -> String {
+        if self.fold_artist_names {
+            normalize_artist(&split_featured_artists(artist).0)
+        } else {
+            // Sin agrupar variantes, la misma grafía en otra forma Unicode sigue siendo igual
+            artist.nfc().collect()
+        }
+    }
+
+    /// Agrega la pista indicada a los índices de rutas, géneros y artistas
+    fn index_track(&mut self, index: usize) {
+        let track = &self.tracks[index];
+        self.path_index.insert(track.path.clone(), index);
+        if let Some(real) = &track.real_path {
+            self.real_path_index.insert(real.clone(), index);
+        }
+
+        // Agrupa por género normalizado
+        if let Some(ref genre) = track.genre {
+            let normalized = normalize_genre(genre);
+            // Usa el nombre normalizado como clave para agrupar variantes
+            self.genres.entry(normalized).or_default().push(index);
+        }
+
+        // Agrupa por artista, recordando la grafía original para mostrarla
+        if let Some(ref artist) = track.artist {
+            let key = self.artist_key(artist);
+            let spelling: String = if self.fold_artist_names {
+                split_featured_artists(artist).0.nfc().collect()
+            } else {
+                artist.nfc().collect()
+            };
+            *self
+                .artist_spellings
+                .entry(key.clone())
+                .or_default()
+                .entry(spelling)
+                .or_insert(0) += 1;
+            self.artists.entry(key).or_default().push(index);
+        }
+
+        // Los invitados no son artistas de la pista: van a un índice aparte
+        let mut guests: Vec<String> = self.tracks[index]
+            .featured_artists
+            .iter()
+            .map(|guest| self.artist_key(guest))
+            .collect();
+        guests.sort();
+        guests.dedup();
+        for guest in guests {
+            self.featured.entry(guest).or_default().push(index);
+        }
+
+        if let Some(artist) = self.album_artist_key(&self.tracks[index]) {
+            self.album_artists.entry(artist).or_default().push(index);
+        }
+    }
+
+    /// Artista del álbum normalizado; el de la pista si no hay etiqueta de álbum
+    fn album_artist_key(&self, track: &TrackMetadata) -> Option<String> {
+        track
+            .album_artist
+            .as_deref()
+            .or(track.artist.as_deref())
+            .map(|artist| self.artist_key(artist))
+    }
+
+    /// Álbum al que pertenece una pista: nombre lógico y artista del álbum
+    ///
+    /// Un compilado con ALBUMARTIST "Various Artists" forma un solo álbum aunque
+    /// cada pista tenga otro artista. Sin ALBUMARTIST lo que une las pistas es
+    /// la carpeta: el artista de la pista partiría un compilado en pedazos.
+    fn album_group(&self, track: &TrackMetadata) -> Option<(String, String)> {
+        let album = track.album.as_deref()?;
+        let owner = match track.album_artist.as_deref() {
+            Some(artist) => self.artist_key(artist),
+            None => format!("dir:{}", self.album_folder(&track.path).to_string_lossy()),
+        };
+        Some((self.album_key(album).to_lowercase(), owner))
+    }
+
+    /// Carpeta del álbum de una pista; una carpeta de disco cuenta como la del álbum
+    ///
+    /// `Album/CD1/01.mp3` y `Album CD2/01.mp3` (si se fusionan discos) caen en
+    /// `Album`, igual que sus hermanas de los otros discos.
+    fn album_folder(&self, path: &Path) -> PathBuf {
+        let parent = path.parent().unwrap_or(path);
+        let (Some(name), Some(grandparent)) = (parent.file_name(), parent.parent()) else {
+            return parent.to_path_buf();
+        };
+        let name = name.to_string_lossy();
+        if is_bare_disc_name(&name) {
+            return grandparent.to_path_buf();
+        }
+        match split_disc_suffix(&name).filter(|_| self.merge_discs) {
+            Some((base, _)) => grandparent.join(base),
+            None => parent.to_path_buf(),
+        }
+    }
+
+    /// Artista que se muestra para las pistas de un álbum
+    ///
+    /// El ALBUMARTIST si lo hay; si no, el artista de las pistas cuando es uno
+    /// solo y "Various Artists" cuando son varios.
+    fn album_display_artist(&self, tracks: &[&TrackMetadata]) -> String {
+        if let Some(artist) = tracks.iter().find_map(|t| t.album_artist.as_deref()) {
+            // Un ALBUMARTIST que no es artista de ninguna pista se muestra como está
+            let key = self.artist_key(artist);
+            return if self.artist_spellings.contains_key(&key) {
+                self.artist_display_name(&key)
+            } else {
+                artist.to_string()
+            };
+        }
+        let mut artists: Vec<String> = tracks
+            .iter()
+            .filter_map(|t| t.artist.as_deref())
+            .map(|artist| self.artist_key(artist))
+            .collect();
+        artists.sort();
+        artists.dedup();
+        match artists.as_slice() {
+            [] => String::new(),
+            [artist] => self.artist_display_name(artist),
+            _ => VARIOUS_ARTISTS.to_string(),
+        }
+    }
+
+    /// Grafía más común de un artista normalizado
+    fn artist_display_name(&self, key: &str) -> String {
+        self.artist_spellings
+            .get(key)
+            .and_then(|spellings| {
+                spellings
+                    .iter()
+                    .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                    .map(|(name, _)| name.clone())
+            })
+            .unwrap_or_else(|| key.to_string())
+    }
+
     /// Escanea un directorio recursivamente y extrae metadata de archivos de audio
     ///
     /// # Arguments
@@ -64,44 +966,79 @@ impl PlaylistService {
     ///
     /// # Errors
     /// Retorna error si falla el recorrido del directorio
-    pub fn scan_directory(&mut self, dir_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn scan_directory(&mut self, dir_path: &Path) -> Result<(), RustedPlayerError> {
         self.clear_database();
+        self.scan_tree(dir_path)
+    }
 
-        for entry in WalkDir::new(dir_path) {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_file() && self.is_audio_file(path) {
-                match self.extract_metadata(path) {
-                    Ok(metadata) => {
-                        let index = self.tracks.len();
-
-                        // Agrupa por género normalizado
-                        if let Some(ref genre) = metadata.genre {
-                            let normalized = normalize_genre(genre);
-                            // Usa el nombre normalizado como clave para agrupar variantes
-                            self.genres.entry(normalized).or_default().push(index);
-                        }
-
-                        // Agrupa por artista
-                        if let Some(ref artist) = metadata.artist {
-                            self.artists.entry(artist.clone()).or_default().push(index);
-                        }
-
-                        self.tracks.push(metadata);
-                    }
-                    Err(_e) => {
-                        // Errores de encoding son comunes en MP3, agregamos el archivo sin metadata
-                        self.tracks.push(TrackMetadata {
-                            path: path.to_path_buf(),
-                            album: None,
-                            artist: None,
-                            genre: None,
-                            year: None,
-                            duration: None,
-                        });
-                    }
-                }
+    /// Vuelve a escanear solo un subdirectorio de la biblioteca
+    ///
+    /// Descarta las pistas cuya ruta empieza con `path`, reconstruye los índices
+    /// con las que quedan (sin volver a leer sus etiquetas) y recorre de nuevo
+    /// únicamente ese subárbol.
+    ///
+    /// # Errors
+    /// Retorna error si falla el recorrido del directorio
+    pub fn rescan_path(&mut self, path: &Path) -> Result<(), RustedPlayerError> {
+        self.tracks.retain(|t| !t.path.starts_with(path));
+        self.rebuild_indices();
+        if path.exists() {
+            self.scan_tree(path)?;
+        }
+        Ok(())
+    }
+
+    /// Pistas que están en esta biblioteca y no en `reference`, comparando por ruta
+    ///
+    /// Pensado para después de un escaneo incremental: se guarda una copia con
+    /// `deep_clone` antes de `rescan_path` y se compara con ella al terminar, así
+    /// las pistas que solo se volvieron a indexar no cuentan como nuevas.
+    pub fn get_new_tracks_since(&self, reference: &PlaylistService) -> Vec<&TrackMetadata> {
+        self.tracks
+            .iter()
+            .filter(|track| !reference.path_index.contains_key(&track.path))
+            .collect()
+    }
+
+    /// Vuelve a generar los índices a partir de las pistas actuales
+    ///
+    /// Vacía los índices de género, artista, artista del álbum y rutas, y los
+    /// vuelve a llenar recorriendo `tracks` sin releer etiquetas. Se llama tras
+    /// cualquier cambio masivo de pistas o de las claves de agrupación.
+    pub fn rebuild_indices(&mut self) {
+        self.genres.clear();
+        self.artists.clear();
+        self.featured.clear();
+        self.album_artists.clear();
+        self.path_index.clear();
+        self.real_path_index.clear();
+        self.artist_spellings.clear();
+        for index in 0..self.tracks.len() {
+            self.index_track(index);
+        }
+    }
+
+    /// Recorre un directorio y agrega sus archivos de audio a la biblioteca
+    fn scan_tree(&mut self, dir_path: &Path) -> Result<(), RustedPlayerError> {
+        for entry in
+            file_walker_service::walk_unique_files(dir_path, self.respect_nomedia, self.show_hidden)
+        {
+            let file = entry?;
+            let path = file.path.as_path();
+
+            if is_audio_file(path) {
+                let mut metadata = match self.extract_metadata(path) {
+                    Ok(metadata) => metadata,
+                    // Errores de encoding son comunes en MP3, agregamos el archivo sin metadata
+                    Err(_e) => TrackMetadata {
+                        path: path.to_path_buf(),
+                        ..Default::default()
+                    },
+                };
+                metadata.real_path = Some(file.real_path);
+                let index = self.tracks.len();
+                self.tracks.push(metadata);
+                self.index_track(index);
             }
         }
 
@@ -109,27 +1046,245 @@ impl PlaylistService {
     }
 
     /// Extrae metadata de un archivo de audio
-    fn extract_metadata(&self, path: &Path) -> Result<TrackMetadata, Box<dyn std::error::Error>> {
+    fn extract_metadata(&self, path: &Path) -> Result<TrackMetadata, RustedPlayerError> {
         // Abrir y leer el archivo - lofty maneja internamente la mayoría de errores de encoding
         let tagged_file = Probe::open(path)?.read()?;
-
-        let tag = tagged_file
-            .primary_tag()
-            .or_else(|| tagged_file.first_tag());
         let properties = tagged_file.properties();
 
-        let tag_ref = tag.as_ref();
+        // Ordena todas las etiquetas del archivo según la prioridad configurada
+        let id3v2_version = read_id3v2_version(path);
+        let mut tags: Vec<(TagSource, &Tag)> = tagged_file
+            .tags()
+            .iter()
+            .filter_map(|t| TagSource::from_tag_type(t.tag_type(), id3v2_version).map(|s| (s, t)))
+            .collect();
+        tags.sort_by_key(|(source, _)| self.tag_rank(*source));
+
+        // lofty ya maneja internamente la conversión de strings, simplemente usamos los valores
+        let text = |get: fn(&Tag) -> Option<std::borrow::Cow<'_, str>>| {
+            pick_field(
+                &tags,
+                move |t| get(t).map(|s| s.to_string()),
+                |a: &String, b: &String| tag_values_agree(a, b),
+            )
+        };
+        let (title, title_source, title_conflict) = text(|t| t.title());
+        let (artist, artist_source, artist_conflict) = text(|t| t.artist());
+        let (album, album_source, album_conflict) = text(|t| t.album());
+        let (genre, genre_source, genre_conflict) = text(|t| t.genre());
+        let (year, year_source, year_conflict) = pick_field(&tags, |t| t.year(), |a, b| a == b);
+        // Campos secundarios: se toma el de mayor prioridad sin reportar conflictos
+        let first = |get: fn(&Tag) -> Option<u32>| tags.iter().find_map(|(_, t)| get(t));
+        let album_artist = tags
+            .iter()
+            .find_map(|(_, t)| t.get_string(&ItemKey::AlbumArtist))
+            .map(String::from);
+
+        let tag_conflicts = [
+            ("title", title_conflict),
+            ("artist", artist_conflict),
+            ("album", album_conflict),
+            ("genre", genre_conflict),
+            ("year", year_conflict),
+        ]
+        .into_iter()
+        .filter(|(_, conflict)| *conflict)
+        .map(|(field, _)| field)
+        .collect();
+
+        let featured_artists = artist
+            .as_deref()
+            .map(|a| split_featured_artists(a).1)
+            .unwrap_or_default();
+
+        let metadata = TrackMetadata {
+            path: path.to_path_buf(),
+            title,
+            album,
+            artist,
+            album_artist,
+            track_number: first(|t| t.track()),
+            track_total: first(|t| t.track_total()),
+            disc_number: first(|t| t.disk()),
+            featured_artists,
+            genre,
+            year,
+            duration: Some(properties.duration()),
+            bitrate_kbps: properties.audio_bitrate().filter(|&kbps| kbps > 0),
+            has_embedded_art: tagged_file.tags().iter().any(|t| t.picture_count() > 0),
+            replay_gain_track: tags
+                .iter()
+                .find_map(|(_, t)| t.get_string(&ItemKey::ReplayGainTrackGain))
+                .and_then(parse_gain_db),
+            tag_sources: TagSources {
+                title: title_source,
+                artist: artist_source,
+                album: album_source,
+                genre: genre_source,
+                year: year_source,
+            },
+            tag_conflicts,
+            real_path: None,
+        };
+        Ok(metadata)
+    }
+
+    /// Obtiene la metadata de una pista por su ruta en tiempo constante
+    ///
+    /// También acepta la ruta real de una pista a la que se llegó por un enlace
+    /// simbólico. No toca el disco: quien tenga otra ruta debe resolverla antes.
+    pub fn get_track_by_path(&self, path: &Path) -> Option<&TrackMetadata> {
+        self.path_index
+            .get(path)
+            .or_else(|| self.real_path_index.get(path))
+            .map(|&i| &self.tracks[i])
+    }
+
+    /// Álbum y lugar de una pista, para mezclar por álbumes
+    ///
+    /// Agrupa por álbum y artista del álbum como `find_incomplete_albums`; las
+    /// pistas sin etiqueta de álbum (o fuera de la biblioteca) se agrupan por carpeta.
+    pub fn album_position(&self, path: &Path) -> AlbumPosition {
+        let folder = || {
+            let parent = path.parent().unwrap_or(path);
+            format!("dir:{}", parent.to_string_lossy())
+        };
+        let Some(track) = self.get_track_by_path(path) else {
+            return AlbumPosition {
+                album: folder(),
+                disc: 1,
+                track: None,
+            };
+        };
+        let album = match self.album_group(track) {
+            Some((album, artist)) => format!("album:{}:{}", album, artist),
+            None => folder(),
+        };
+        AlbumPosition {
+            album,
+            disc: self.disc_of(track).unwrap_or(1),
+            track: track.track_number,
+        }
+    }
+
+    /// Busca pistas cuyo título, artista o álbum contengan el texto y las ordena
+    ///
+    /// La búsqueda no distingue mayúsculas. Las pistas sin el campo de orden
+    /// quedan al final y se retornan como mucho `limit` resultados.
+    pub fn search_tracks_sorted(
+        &self,
+        query: &str,
+        sort: TrackSortKey,
+        limit: usize,
+    ) -> Vec<&TrackMetadata> {
+        let query = query.to_lowercase();
+        let matches_query = |field: &Option<String>| {
+            field
+                .as_deref()
+                .is_some_and(|value| value.to_lowercase().contains(&query))
+        };
+        let mut results: Vec<&TrackMetadata> = self
+            .tracks
+            .iter()
+            .filter(|t| {
+                matches_query(&t.title) || matches_query(&t.artist) || matches_query(&t.album)
+            })
+            .collect();
+
+        let text_key = |field: &Option<String>| field.as_deref().map(str::to_lowercase);
+        match sort {
+            TrackSortKey::Title => results.sort_by_key(|t| (t.title.is_none(), text_key(&t.title))),
+            TrackSortKey::Artist => {
+                results.sort_by_key(|t| (t.artist.is_none(), text_key(&t.artist)))
+            }
+            TrackSortKey::Album => results.sort_by_key(|t| (t.album.is_none(), text_key(&t.album))),
+            TrackSortKey::Year => results.sort_by_key(|t| (t.year.is_none(), t.year)),
+            TrackSortKey::Duration => results.sort_by_key(|t| (t.duration.is_none(), t.duration)),
+        }
+        results.truncate(limit);
+        results
+    }
+
+    /// Obtiene las pistas agregadas en los últimos `days` días, las más nuevas primero
+    ///
+    /// Usa la fecha de creación del archivo o, si el sistema no la informa,
+    /// la de modificación.
+    pub fn get_tracks_added_recently(&self, days: u32) -> Vec<&TrackMetadata> {
+        let cutoff =
+            std::time::SystemTime::now() - std::time::Duration::from_secs(u64::from(days) * 86_400);
+        let mut recent: Vec<(std::time::SystemTime, &TrackMetadata)> = self
+            .tracks
+            .iter()
+            .filter_map(|track| {
+                let metadata = std::fs::metadata(&track.path).ok()?;
+                let added = metadata.created().or_else(|_| metadata.modified()).ok()?;
+                (added >= cutoff).then_some((added, track))
+            })
+            .collect();
+        recent.sort_by_key(|(added, _)| std::cmp::Reverse(*added));
+        recent.into_iter().map(|(_, track)| track).collect()
+    }
+
+    /// Obtiene las pistas cuyas etiquetas se contradicen entre sí
+    pub fn get_tag_conflicts(&self) -> Vec<&TrackMetadata> {
+        self.tracks
+            .iter()
+            .filter(|t| !t.tag_conflicts.is_empty())
+            .collect()
+    }
+
+    /// Obtiene las pistas cuyo título empieza con `prefix`, en el orden de la biblioteca
+    ///
+    /// No distingue mayúsculas ni acentos (ver `fold_title`); las pistas sin
+    /// título no cuentan.
+    pub fn get_tracks_by_title_prefix(&self, prefix: &str) -> Vec<&TrackMetadata> {
+        let prefix = fold_title(prefix);
+        self.tracks
+            .iter()
+            .filter(|t| {
+                t.title
+                    .as_deref()
+                    .is_some_and(|title| fold_title(title).starts_with(&prefix))
+            })
+            .collect()
+    }
+
+    /// Índices de pistas de cada género pedido (vacío si el género no existe)
+    fn genre_index_sets(&self, genres: &[&str]) -> Vec<BTreeSet<usize>> {
+        genres
+            .iter()
+            .map(|genre| {
+                self.genres
+                    .get(&normalize_genre(genre))
+                    .map(|indices| indices.iter().copied().collect())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
 
-        // lofty ya maneja internamente la conversión de strings, simplemente usamos los valores
-        let metadata = TrackMetadata {
-            path: path.to_path_buf(),
-            album: tag_ref.and_then(|t| t.album().map(|s| s.to_string())),
-            artist: tag_ref.and_then(|t| t.artist().map(|s| s.to_string())),
-            genre: tag_ref.and_then(|t| t.genre().map(|s| s.to_string())),
-            year: tag.and_then(|t| t.year()),
-            duration: Some(properties.duration()),
+    /// Pistas que pertenecen a todos los géneros indicados (intersección)
+    ///
+    /// Retorna una lista vacía si `genres` está vacío.
+    pub fn get_tracks_by_genres_all(&self, genres: &[&str]) -> Vec<PathBuf> {
+        let mut sets = self.genre_index_sets(genres).into_iter();
+        let Some(first) = sets.next() else {
+            return Vec::new();
         };
-        Ok(metadata)
+        sets.fold(first, |acc, set| &acc & &set)
+            .into_iter()
+            .map(|i| self.tracks[i].path.clone())
+            .collect()
+    }
+
+    /// Pistas que pertenecen a alguno de los géneros indicados (unión)
+    pub fn get_tracks_by_genres_any(&self, genres: &[&str]) -> Vec<PathBuf> {
+        self.genre_index_sets(genres)
+            .into_iter()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|i| self.tracks[i].path.clone())
+            .collect()
     }
 
     /// Genera una playlist con todas las pistas de un género
@@ -146,6 +1301,136 @@ impl PlaylistService {
             .unwrap_or_default()
     }
 
+    /// Rutas de las pistas de la biblioteca que cumplen el filtro, en orden de escaneo
+    ///
+    /// Es la base de las mezclas filtradas; sin condiciones devuelve toda la biblioteca.
+    pub fn filter_tracks(&self, filter: &TrackFilter) -> Vec<PathBuf> {
+        self.tracks
+            .iter()
+            .filter(|track| track_matches(track, filter))
+            .map(|track| track.path.clone())
+            .collect()
+    }
+
+    /// La pista cumple el filtro
+    ///
+    /// Una ruta que no está en la biblioteca no tiene ningún dato: pasa si no
+    /// hay condiciones o si el filtro no es estricto.
+    pub fn passes_filter(&self, path: &Path, filter: &TrackFilter) -> bool {
+        match self.get_track_by_path(path) {
+            Some(track) => track_matches(track, filter),
+            None => filter.is_empty() || !filter.strict,
+        }
+    }
+
+    /// Arma una mezcla que fluye a partir de una pista
+    ///
+    /// Empieza por `seed_path` y en cada paso elige, entre las pistas aún no
+    /// usadas, la que más se parece a la anterior: mismo artista o álbum pesa
+    /// más que mismo género, y este más que misma década. Los empates se
+    /// resuelven por orden en la biblioteca. Retorna como mucho `length` pistas;
+    /// vacío si la semilla no está en la biblioteca.
+    pub fn generate_mix(&self, seed_path: &Path, length: usize) -> Vec<PathBuf> {
+        let Some(&seed) = self.path_index.get(seed_path) else {
+            return Vec::new();
+        };
+        let traits: Vec<MixTraits> = self
+            .tracks
+            .iter()
+            .map(|track| MixTraits {
+                artist: track.artist.as_deref().map(|a| self.artist_key(a)),
+                album: track
+                    .album
+                    .as_deref()
+                    .map(|a| self.album_key(a).to_lowercase()),
+                genre: track.genre.as_deref().map(normalize_genre),
+                decade: track.year.map(|y| y / 10),
+            })
+            .collect();
+
+        let mut used = vec![false; self.tracks.len()];
+        let mut current = seed;
+        let mut mix = Vec::with_capacity(length.min(self.tracks.len()));
+        while mix.len() < length {
+            used[current] = true;
+            mix.push(self.tracks[current].path.clone());
+            let best = (0..self.tracks.len())
+                .filter(|&i| !used[i])
+                .max_by_key(|&i| (traits[current].similarity(&traits[i]), std::cmp::Reverse(i)));
+            match best {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        mix
+    }
+
+    /// Sugiere un nombre para una playlist con estas pistas
+    ///
+    /// Usa el álbum si todas lo comparten, si no `"Artista - Mix"` si comparten
+    /// artista, `"Género Mix"` si comparten género y, por último, `"My Playlist"`.
+    /// Las pistas que no están en la biblioteca impiden cualquier coincidencia.
+    /// El nombre sale ya saneado para guardarse (`AC/DC` queda `AC_DC`).
+    pub fn suggest_playlist_name(&self, tracks: &[PathBuf]) -> String {
+        let metadata: Option<Vec<&TrackMetadata>> = tracks
+            .iter()
+            .map(|path| self.get_track_by_path(path))
+            .collect();
+        let metadata = metadata.unwrap_or_default();
+        let common = |field: fn(&TrackMetadata) -> Option<&str>| -> Option<String> {
+            let first = field(metadata.first()?)?.trim();
+            let shared = !first.is_empty()
+                && metadata
+                    .iter()
+                    .all(|track| field(track).is_some_and(|value| value.trim() == first));
+            shared.then(|| first.to_string())
+        };
+        let name = if let Some(album) = common(|track| track.album.as_deref()) {
+            album
+        } else if let Some(artist) = common(|track| track.artist.as_deref()) {
+            format!("{} - Mix", artist)
+        } else if let Some(genre) = common(|track| track.genre.as_deref()) {
+            format!("{} Mix", genre)
+        } else {
+            "My Playlist".to_string()
+        };
+        playlist_storage_service::sanitize_playlist_name(&name)
+    }
+
+    /// Exporta todas las pistas de un género como M3U extendido
+    ///
+    /// Cada entrada lleva una línea `#EXTINF` con duración, artista y título
+    /// cuando la metadata está disponible.
+    ///
+    /// # Errors
+    /// Retorna error si falla la escritura del archivo
+    pub fn export_m3u_by_genre(&self, genre: &str, dest: &Path) -> Result<(), std::io::Error> {
+        let mut content = String::from("#EXTM3U\n");
+        for path in self.get_playlist_by_genre(genre) {
+            let info = self.get_track_by_path(&path);
+            let seconds = info
+                .and_then(|t| t.duration)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(-1);
+            let title = info.and_then(|t| t.title.clone()).unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            let label = match info.and_then(|t| t.artist.as_deref()) {
+                Some(artist) => format!("{} - {}", artist, title),
+                None => title,
+            };
+            content.push_str(&format!(
+                "#EXTINF:{},{}\n{}\n",
+                seconds,
+                label,
+                path.display()
+            ));
+        }
+        std::fs::write(dest, content)
+    }
+
     /// Obtiene lista de géneros únicos
     pub fn get_genres(&self) -> Vec<String> {
         let mut genres: Vec<_> = self.genres.keys().cloned().collect();
@@ -153,31 +1438,225 @@ impl PlaylistService {
         genres
     }
 
-    /// Obtiene los 5 artistas con más pistas
+    /// Obtiene los artistas ordenados de más a menos pistas
+    ///
+    /// Cuenta por artista de la pista; "Various Artists" no es un artista y se omite.
     pub fn get_top_artists(&self) -> Vec<(String, usize)> {
+        let various = self.artist_key(VARIOUS_ARTISTS);
         let mut artists: Vec<(String, usize)> = self
             .artists
             .iter()
-            .map(|(artist, tracks)| (artist.clone(), tracks.len()))
+            .filter(|(artist, _)| **artist != various)
+            .map(|(artist, tracks)| (self.artist_display_name(artist), tracks.len()))
             .collect();
-        artists.sort_by(|a, b| b.1.cmp(&a.1));
-        artists.truncate(5);
+        artists.sort_by_key(|a| std::cmp::Reverse(a.1));
         artists
     }
 
+    /// Géneros ordenados de mayor a menor peso, sumando `weight` de cada pista
+    ///
+    /// Sirve para ordenar por duración o por tiempo escuchado en lugar de por
+    /// cantidad de pistas. Una pista suma su peso completo en cada género en el
+    /// que está; los géneros que suman cero se omiten.
+    pub fn get_top_genres_by(
+        &self,
+        weight: impl Fn(&TrackMetadata) -> std::time::Duration,
+    ) -> Vec<(String, std::time::Duration)> {
+        Self::rank_by_weight(&self.tracks, self.genres.iter(), weight)
+    }
+
+    /// Artistas ordenados de mayor a menor peso, como `get_top_genres_by`
+    ///
+    /// Igual que `get_top_artists`, "Various Artists" se omite.
+    pub fn get_top_artists_by(
+        &self,
+        weight: impl Fn(&TrackMetadata) -> std::time::Duration,
+    ) -> Vec<(String, std::time::Duration)> {
+        let various = self.artist_key(VARIOUS_ARTISTS);
+        let artists = self
+            .artists
+            .iter()
+            .filter(|(artist, _)| **artist != various);
+        Self::rank_by_weight(&self.tracks, artists, weight)
+            .into_iter()
+            .map(|(artist, total)| (self.artist_display_name(&artist), total))
+            .collect()
+    }
+
+    /// Suma el peso de las pistas de cada grupo de un índice y ordena de mayor a menor
+    fn rank_by_weight<'a>(
+        tracks: &[TrackMetadata],
+        groups: impl Iterator<Item = (&'a String, &'a Vec<usize>)>,
+        weight: impl Fn(&TrackMetadata) -> std::time::Duration,
+    ) -> Vec<(String, std::time::Duration)> {
+        let mut ranked: Vec<(String, std::time::Duration)> = groups
+            .map(|(key, indices)| {
+                let total: std::time::Duration = indices.iter().map(|&i| weight(&tracks[i])).sum();
+                (key.clone(), total)
+            })
+            .filter(|(_, total)| !total.is_zero())
+            .collect();
+        ranked.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+        ranked
+    }
+
+    /// Obtiene la discografía de un artista ordenada por año
+    ///
+    /// Incluye los álbumes en los que figura como artista del álbum. Los
+    /// álbumes sin año quedan al final, ordenados por nombre.
+    pub fn get_artist_discography(&self, artist: &str) -> Vec<AlbumInfo> {
+        let mut albums: HashMap<String, AlbumInfo> = HashMap::new();
+        for &i in self
+            .album_artists
+            .get(&self.artist_key(artist))
+            .into_iter()
+            .flatten()
+        {
+            let track = &self.tracks[i];
+            if let Some(album) = track.album.as_deref() {
+                let name = self.album_key(album);
+                let info = albums.entry(name.clone()).or_insert_with(|| AlbumInfo {
+                    name,
+                    year: None,
+                    track_count: 0,
+                });
+                info.track_count += 1;
+                info.year = match (info.year, track.year) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+        }
+
+        let mut discography: Vec<AlbumInfo> = albums.into_values().collect();
+        discography.sort_by(|a, b| {
+            (a.year.is_none(), a.year, &a.name).cmp(&(b.year.is_none(), b.year, &b.name))
+        });
+        discography
+    }
+
+    /// Busca álbumes con huecos en la numeración de pistas
+    ///
+    /// Agrupa por álbum y artista del álbum. Las pistas sin número se ignoran,
+    /// así que los álbumes sin ninguna pista numerada no se reportan.
+    pub fn find_incomplete_albums(&self) -> Vec<IncompleteAlbum> {
+        let mut albums: HashMap<(String, String), Vec<&TrackMetadata>> = HashMap::new();
+        for track in &self.tracks {
+            if let (Some(group), Some(_)) = (self.album_group(track), track.track_number) {
+                albums.entry(group).or_default().push(track);
+            }
+        }
+
+        let mut incomplete: Vec<IncompleteAlbum> = albums
+            .into_values()
+            .filter_map(|tracks| {
+                let numbers: Vec<_> = tracks
+                    .iter()
+                    .filter_map(|t| t.track_number.map(|n| (self.disc_of(t), n, t.track_total)))
+                    .collect();
+                let missing = find_missing_track_numbers(&numbers);
+                if missing.is_empty() {
+                    return None;
+                }
+                let artist = self.album_display_artist(&tracks);
+                Some(IncompleteAlbum {
+                    album: tracks[0]
+                        .album
+                        .as_deref()
+                        .map(|album| self.album_key(album))
+                        .unwrap_or_default(),
+                    artist: Some(artist).filter(|artist| !artist.is_empty()),
+                    multi_disc: numbers.iter().any(|(disc, _, _)| disc.unwrap_or(1) != 1),
+                    missing,
+                })
+            })
+            .collect();
+        incomplete.sort_by(|a, b| (&a.artist, &a.album).cmp(&(&b.artist, &b.album)));
+        incomplete
+    }
+
+    /// Obtiene los álbumes de un artista, ordenados alfabéticamente
+    ///
+    /// Cuenta los álbumes en los que figura como artista del álbum, así que
+    /// las apariciones en compilados no suman. El nombre se normaliza igual
+    /// que en el índice: "Beatles, The" y "The Beatles" devuelven lo mismo.
+    pub fn get_albums_for_artist(&self, artist: &str) -> Vec<String> {
+        let mut albums: Vec<String> = self
+            .album_artists
+            .get(&self.artist_key(artist))
+            .map(|indices| {
+                indices
+                    .iter()
+                    .filter_map(|&i| self.tracks[i].album.as_deref())
+                    .map(|album| self.album_key(album))
+                    .collect::<std::collections::HashSet<_>>()
+                    .into_iter()
+                    .collect()
+            })
+            .unwrap_or_default();
+        albums.sort();
+        albums
+    }
+
+    /// Todos los álbumes de la biblioteca, ordenados por artista y nombre
+    ///
+    /// Agrupa igual que `album_position`: un compilado cuenta una sola vez.
+    pub fn get_album_list(&self) -> Vec<AlbumRef> {
+        let mut albums: HashMap<(String, String), Vec<&TrackMetadata>> = HashMap::new();
+        for track in &self.tracks {
+            if let Some(key) = self.album_group(track) {
+                albums.entry(key).or_default().push(track);
+            }
+        }
+        let mut list: Vec<AlbumRef> = albums
+            .into_iter()
+            .map(|(key, tracks)| AlbumRef {
+                album: self.album_key(tracks[0].album.as_deref().unwrap_or_default()),
+                artist: self.album_display_artist(&tracks),
+                key,
+            })
+            .collect();
+        list.sort_by_cached_key(|album| (self.artist_key(&album.artist), album.key.clone()));
+        list
+    }
+
+    /// Pistas de un álbum en orden de disco y número de pista
+    pub fn get_album_tracks(&self, album: &AlbumRef) -> Vec<PathBuf> {
+        let mut tracks: Vec<&TrackMetadata> = self
+            .tracks
+            .iter()
+            .filter(|track| self.album_group(track).as_ref() == Some(&album.key))
+            .collect();
+        tracks.sort_by_key(|track| (self.disc_of(track), track.track_number, &track.path));
+        tracks.into_iter().map(|track| track.path.clone()).collect()
+    }
+
+    /// Cantidad de pistas de otros artistas en las que `artist` figura como invitado
+    ///
+    /// No cuenta las pistas propias del artista (ver `get_artist_tracks`).
+    pub fn count_featured_credits(&self, artist: &str) -> usize {
+        self.featured
+            .get(&self.artist_key(artist))
+            .map_or(0, Vec::len)
+    }
+
+    /// Pistas de un artista ordenadas por ruta (su orden en carpetas)
+    pub fn get_artist_tracks(&self, artist: &str) -> Vec<PathBuf> {
+        let mut tracks: Vec<PathBuf> = self
+            .artists
+            .get(&self.artist_key(artist))
+            .into_iter()
+            .flatten()
+            .map(|&i| self.tracks[i].path.clone())
+            .collect();
+        tracks.sort();
+        tracks
+    }
+
     /// Limpia toda la base de datos de tracks
     fn clear_database(&mut self) {
         self.tracks.clear();
-        self.genres.clear();
-        self.artists.clear();
-    }
-
-    /// Verifica si un archivo es de audio soportado
-    fn is_audio_file(&self, path: &Path) -> bool {
-        path.extension()
-            .and_then(|s| s.to_str())
-            .map(is_audio_file_ext)
-            .unwrap_or(false)
+        self.rebuild_indices();
     }
 
     /// Obtiene estadísticas de la biblioteca
@@ -185,11 +1664,12 @@ impl PlaylistService {
         let total_duration: std::time::Duration =
             self.tracks.iter().filter_map(|t| t.duration).sum();
 
-        // Cuenta álbumes únicos
+        // Cuenta álbumes únicos por nombre y artista del álbum (los discos de
+        // un mismo álbum cuentan una vez)
         let total_albums = self
             .tracks
             .iter()
-            .filter_map(|t| t.album.as_ref())
+            .filter_map(|t| self.album_group(t))
             .collect::<std::collections::HashSet<_>>()
             .len();
 
@@ -201,6 +1681,88 @@ impl PlaylistService {
         }
     }
 
+    /// Ganancia ReplayGain promedio de la biblioteca y cuánto varía entre pistas
+    ///
+    /// Las pistas sin ganancia solo se cuentan; si ninguna la tiene, el promedio
+    /// y la dispersión quedan en 0.
+    pub fn get_loudness_stats(&self) -> LoudnessStats {
+        let gains: Vec<f64> = self
+            .tracks
+            .iter()
+            .filter_map(|t| t.replay_gain_track)
+            .collect();
+        let tracks_without_replaygain = self.tracks.len() - gains.len();
+        if gains.is_empty() {
+            return LoudnessStats {
+                average_gain: 0.0,
+                range_db: 0.0,
+                tracks_without_replaygain,
+            };
+        }
+        let count = gains.len() as f64;
+        let average_gain = gains.iter().sum::<f64>() / count;
+        let variance = gains
+            .iter()
+            .map(|gain| (gain - average_gain).powi(2))
+            .sum::<f64>()
+            / count;
+        LoudnessStats {
+            average_gain,
+            range_db: variance.sqrt(),
+            tracks_without_replaygain,
+        }
+    }
+
+    /// Revisa la biblioteca en busca de problemas comunes
+    ///
+    /// Las portadas se buscan en disco (una vez por carpeta), así que conviene
+    /// calcularlo una vez tras el escaneo y no en cada dibujo.
+    pub fn analyze_library_health(&self) -> LibraryHealthReport {
+        let mut report = LibraryHealthReport {
+            total_tracks: self.tracks.len(),
+            total_albums: self.get_stats().total_albums,
+            incomplete_albums: self.find_incomplete_albums().len(),
+            ..Default::default()
+        };
+
+        let mut copies: HashMap<(String, String), usize> = HashMap::new();
+        // Por carpeta: una pista cualquiera y si alguna trae portada embebida
+        let mut folders: HashMap<&Path, (&Path, bool)> = HashMap::new();
+        for track in &self.tracks {
+            if track.title.is_none() && track.artist.is_none() {
+                report.untagged_count += 1;
+            }
+            if track.year.is_none() {
+                report.missing_year_count += 1;
+            }
+            if track
+                .bitrate_kbps
+                .is_some_and(|kbps| kbps < LOW_QUALITY_KBPS)
+            {
+                report.low_quality_count += 1;
+            }
+            if track.duration.is_some_and(|d| d > VERY_LONG_TRACK) {
+                report.very_long_tracks += 1;
+            }
+            if let (Some(artist), Some(title)) = (&track.artist, &track.title) {
+                let title: String = title.trim().nfc().collect::<String>().to_lowercase();
+                *copies.entry((self.artist_key(artist), title)).or_default() += 1;
+            }
+            if let Some(folder) = track.path.parent() {
+                let entry = folders.entry(folder).or_insert((&track.path, false));
+                entry.1 |= track.has_embedded_art;
+            }
+        }
+
+        report.duplicate_count = copies.values().map(|count| count - 1).sum();
+        report.total_folders = folders.len();
+        report.missing_album_art = folders
+            .values()
+            .filter(|(track, embedded)| !embedded && get_album_art_path(track).is_none())
+            .count();
+        report
+    }
+
     /// Agrupa pistas por década según su año
     pub fn get_tracks_by_decade(&self) -> HashMap<String, u64> {
         let mut decades = HashMap::new();
@@ -214,8 +1776,96 @@ impl PlaylistService {
     }
 }
 
+/// Por debajo de esta tasa de bits (kbps) una pista cuenta como de baja calidad
+const LOW_QUALITY_KBPS: u32 = 128;
+/// Más largas que esto suelen ser audiolibros o mezclas continuas
+const VERY_LONG_TRACK: std::time::Duration = std::time::Duration::from_secs(2 * 3600);
+
+/// Problemas encontrados en la biblioteca, con los totales para ponderarlos
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryHealthReport {
+    /// Total de pistas revisadas
+    pub total_tracks: usize,
+    /// Total de álbumes únicos
+    pub total_albums: usize,
+    /// Total de carpetas con pistas
+    pub total_folders: usize,
+    /// Pistas sin título ni artista
+    pub untagged_count: usize,
+    /// Carpetas sin portada, ni como imagen en la carpeta ni embebida
+    pub missing_album_art: usize,
+    /// Copias de más de una pista con el mismo artista y título
+    pub duplicate_count: usize,
+    /// Pistas por debajo de 128 kbps
+    pub low_quality_count: usize,
+    /// Álbumes con huecos en la numeración
+    pub incomplete_albums: usize,
+    /// Pistas sin año
+    pub missing_year_count: usize,
+    /// Pistas de más de dos horas (probablemente audiolibros mezclados)
+    pub very_long_tracks: usize,
+}
+
+impl LibraryHealthReport {
+    /// Puntaje de 0 a 100
+    ///
+    /// Cada problema resta hasta su peso según la proporción de pistas (o de
+    /// álbumes y carpetas) afectadas; una biblioteca vacía tiene 100.
+    pub fn score(&self) -> u32 {
+        let ratio = |count: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                (count as f64 / total as f64).min(1.0)
+            }
+        };
+        let penalty = 25.0 * ratio(self.untagged_count, self.total_tracks)
+            + 15.0 * ratio(self.missing_album_art, self.total_folders)
+            + 15.0 * ratio(self.duplicate_count, self.total_tracks)
+            + 15.0 * ratio(self.low_quality_count, self.total_tracks)
+            + 10.0 * ratio(self.incomplete_albums, self.total_albums)
+            + 10.0 * ratio(self.missing_year_count, self.total_tracks)
+            + 10.0 * ratio(self.very_long_tracks, self.total_tracks);
+        (100.0 - penalty).round() as u32
+    }
+
+    /// Descripción y cantidad de cada problema, en el orden del informe
+    pub fn issues(&self) -> Vec<(&'static str, usize)> {
+        vec![
+            ("Untagged tracks", self.untagged_count),
+            ("Folders without album art", self.missing_album_art),
+            ("Duplicate tracks", self.duplicate_count),
+            ("Low quality (< 128 kbps)", self.low_quality_count),
+            ("Incomplete albums", self.incomplete_albums),
+            ("Tracks without year", self.missing_year_count),
+            ("Very long tracks (> 2 h)", self.very_long_tracks),
+        ]
+    }
+}
+
+/// Sonoridad de la biblioteca según las ganancias ReplayGain de las pistas
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LoudnessStats {
+    /// Ganancia promedio en dB de las pistas que la tienen
+    pub average_gain: f64,
+    /// Desviación estándar de esas ganancias, en dB
+    pub range_db: f64,
+    /// Pistas sin `REPLAYGAIN_TRACK_GAIN`
+    pub tracks_without_replaygain: usize,
+}
+
+impl LoudnessStats {
+    /// Sonoridad promedio en LUFS
+    ///
+    /// La ganancia es lo que falta para llegar a la referencia de ReplayGain 2.0
+    /// (-18 LUFS), así que la sonoridad es la referencia menos la ganancia.
+    pub fn average_loudness_lufs(&self) -> f64 {
+        REPLAYGAIN_REFERENCE_LUFS - self.average_gain
+    }
+}
+
 /// Estadísticas de la biblioteca musical
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct PlaylistStats {
     /// Total de pistas
     pub total_tracks: usize,
@@ -223,22 +1873,718 @@ pub struct PlaylistStats {
     pub total_genres: usize,
     /// Total de álbumes únicos
     pub total_albums: usize,
-    /// Duración total de todas las pistas
+    /// Duración total de todas las pistas, en segundos al serializar
+    #[serde(serialize_with = "serialize_duration_as_secs")]
     pub total_duration: std::time::Duration,
 }
 
-impl PlaylistStats {
-    /// Formatea la duración total en formato legible
-    pub fn format_duration(&self) -> String {
-        let total_seconds = self.total_duration.as_secs();
-        let hours = total_seconds / 3600;
-        let minutes = (total_seconds % 3600) / 60;
-        let seconds = total_seconds % 60;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if hours > 0 {
-            format!("{}h {}m {}s", hours, minutes, seconds)
-        } else {
-            format!("{}m {}s", minutes, seconds)
+    #[test]
+    fn gaps_are_found_per_disc() {
+        let tracks = [
+            (None, 1, Some(4)),
+            (None, 3, Some(4)),
+            (Some(2), 2, None),
+            (Some(2), 4, None),
+        ];
+        assert_eq!(
+            find_missing_track_numbers(&tracks),
+            vec![(1, 2), (1, 4), (2, 1), (2, 3)]
+        );
+    }
+
+    #[test]
+    fn complete_and_unnumbered_albums_have_no_gaps() {
+        assert!(find_missing_track_numbers(&[(None, 1, Some(2)), (None, 2, Some(2))]).is_empty());
+        assert!(find_missing_track_numbers(&[(None, 0, None)]).is_empty());
+        assert!(find_missing_track_numbers(&[]).is_empty());
+    }
+
+    #[test]
+    fn absurd_numbers_and_totals_are_capped() {
+        // Un número dañado no cuenta
+        assert!(find_missing_track_numbers(&[(None, 1, None), (None, u32::MAX, None)]).is_empty());
+        // Un total exagerado solo agrega unas pocas pistas después de la más alta
+        let missing = find_missing_track_numbers(&[(None, 1, Some(u32::MAX))]);
+        assert_eq!(missing.len(), MAX_TRAILING_MISSING as usize);
+        assert_eq!(missing.last(), Some(&(1, 1 + MAX_TRAILING_MISSING)));
+    }
+
+    /// Biblioteca en memoria con las pistas dadas, sin leer el disco
+    fn library(tracks: Vec<TrackMetadata>) -> PlaylistService {
+        PlaylistService::from_tracks(tracks)
+    }
+
+    fn track(path: &str, artist: &str, album: &str) -> TrackMetadata {
+        TrackMetadata {
+            path: PathBuf::from(path),
+            artist: Some(artist.to_string()),
+            album: Some(album.to_string()),
+            ..TrackMetadata::default()
+        }
+    }
+
+    #[test]
+    fn suggested_names_can_be_saved() {
+        let service = library(vec![
+            track("/m/1.mp3", "AC/DC", "Back in Black"),
+            track("/m/2.mp3", "AC/DC", "Highway to Hell"),
+            track("/m/3.mp3", "Various", "Live: 1991?"),
+        ]);
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(
+            service.suggest_playlist_name(&paths(&["/m/1.mp3", "/m/2.mp3"])),
+            "AC_DC - Mix"
+        );
+        assert_eq!(
+            service.suggest_playlist_name(&paths(&["/m/3.mp3"])),
+            "Live_ 1991_"
+        );
+        assert_eq!(
+            service.suggest_playlist_name(&paths(&["/m/1.mp3", "/m/3.mp3"])),
+            "My Playlist"
+        );
+    }
+
+    #[test]
+    fn a_compilation_without_album_artist_stays_one_album() {
+        let service = library(vec![
+            track("/m/Hits 1999/01.mp3", "Blur", "Hits 1999"),
+            track("/m/Hits 1999/02.mp3", "Oasis", "Hits 1999"),
+            track("/m/Hits 1999/03.mp3", "Pulp", "Hits 1999"),
+            // Mismo nombre en otra carpeta: es otro álbum
+            track("/m/Other/Hits 1999/01.mp3", "Suede", "Hits 1999"),
+        ]);
+        let albums = service.get_album_list();
+        let artists: Vec<&str> = albums.iter().map(|a| a.artist.as_str()).collect();
+        assert_eq!(artists, vec!["Suede", VARIOUS_ARTISTS]);
+        assert_eq!(service.get_album_tracks(&albums[1]).len(), 3);
+        assert_eq!(service.get_stats().total_albums, 2);
+    }
+
+    #[test]
+    fn disc_folders_without_album_artist_join_their_album() {
+        let mut service = library(vec![
+            track("/m/Live/CD1/01.mp3", "Blur", "Live"),
+            track("/m/Live/CD2/01.mp3", "Blur", "Live"),
+            track("/m/Box (Disc 1)/01.mp3", "Pulp", "Box"),
+            track("/m/Box (Disc 2)/01.mp3", "Pulp", "Box"),
+        ]);
+        service.set_disc_merging(true);
+        let albums = service.get_album_list();
+        assert_eq!(albums.len(), 2);
+        assert!(
+            albums
+                .iter()
+                .all(|a| service.get_album_tracks(a).len() == 2)
+        );
+
+        service.set_disc_merging(false);
+        assert_eq!(service.get_album_list().len(), 3);
+    }
+
+    #[test]
+    fn album_artist_still_groups_across_folders() {
+        let mut tracks = vec![
+            track("/m/a/01.mp3", "Blur", "Hits"),
+            track("/m/b/02.mp3", "Oasis", "Hits"),
+        ];
+        for track in &mut tracks {
+            track.album_artist = Some(VARIOUS_ARTISTS.to_string());
+        }
+        let service = library(tracks);
+        let albums = service.get_album_list();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums[0].artist, VARIOUS_ARTISTS);
+    }
+
+    /// Biblioteca chica con géneros, artistas e invitados
+    fn sample_library() -> PlaylistService {
+        let mut tracks = vec![
+            track("/m/Blur/01.mp3", "Blur", "Parklife"),
+            track("/m/Blur/02.mp3", "Blur feat. Phil Daniels", "Parklife"),
+            track("/m/Pulp/01.mp3", "Pulp", "Different Class"),
+            track("/m/Miles/01.mp3", "Miles Davis", "Kind of Blue"),
+        ];
+        for (track, genre) in tracks
+            .iter_mut()
+            .zip(["Britpop", "britpop", "Britpop", "Jazz"])
+        {
+            track.genre = Some(genre.to_string());
+        }
+        tracks[1].featured_artists = vec!["Phil Daniels".to_string()];
+        library(tracks)
+    }
+
+    /// Respuestas de las consultas que usan los índices, en un orden estable
+    type Answers = (
+        Vec<String>,
+        Vec<(String, usize)>,
+        Vec<Vec<PathBuf>>,
+        Vec<Vec<PathBuf>>,
+        usize,
+        Vec<Option<PathBuf>>,
+    );
+
+    fn answers(service: &PlaylistService) -> Answers {
+        let mut top = service.get_top_artists();
+        top.sort();
+        let artists = ["Blur", "Pulp", "Miles Davis"];
+        (
+            service.get_genres(),
+            top,
+            artists.map(|a| service.get_artist_tracks(a)).to_vec(),
+            ["Britpop", "Jazz"]
+                .map(|g| service.get_playlist_by_genre(g))
+                .to_vec(),
+            service.count_featured_credits("Phil Daniels"),
+            ["/m/Blur/02.mp3", "/m/Miles/01.mp3", "/m/none.mp3"]
+                .map(|p| {
+                    service
+                        .get_track_by_path(Path::new(p))
+                        .map(|t| t.path.clone())
+                })
+                .to_vec(),
+        )
+    }
+
+    #[test]
+    fn a_deep_clone_answers_like_the_original_and_stays_independent() {
+        let service = sample_library();
+        let mut copy = service.deep_clone();
+        assert_eq!(answers(&copy), answers(&service));
+        assert_eq!(copy.get_stats().total_tracks, 4);
+
+        copy.tracks.truncate(1);
+        copy.rebuild_indices();
+        assert_eq!(copy.get_stats().total_tracks, 1);
+        assert_eq!(service.get_stats().total_tracks, 4);
+        assert_eq!(service.get_artist_tracks("Blur").len(), 2);
+    }
+
+    #[test]
+    fn corrupt_indices_are_rebuilt_from_the_tracks() {
+        let expected = answers(&sample_library());
+        let mut service = sample_library();
+        // Índices que apuntan a otras pistas, a pistas que no existen o a nada
+        service.genres.insert("jazz".to_string(), vec![0, 99]);
+        service.genres.remove("britpop");
+        service.artists.clear();
+        service
+            .featured
+            .insert("phil daniels".to_string(), vec![3, 3, 3]);
+        service
+            .path_index
+            .insert(PathBuf::from("/m/Blur/02.mp3"), 3);
+        service.path_index.insert(PathBuf::from("/m/none.mp3"), 0);
+        service
+            .artist_spellings
+            .insert("blur".to_string(), HashMap::from([("bLuR".to_string(), 9)]));
+
+        service.rebuild_indices();
+        assert_eq!(answers(&service), expected);
+    }
+
+    fn tagged(
+        path: &str,
+        year: Option<u32>,
+        seconds: Option<u64>,
+        genre: Option<&str>,
+    ) -> TrackMetadata {
+        TrackMetadata {
+            path: PathBuf::from(path),
+            year,
+            duration: seconds.map(std::time::Duration::from_secs),
+            genre: genre.map(str::to_string),
+            ..TrackMetadata::default()
+        }
+    }
+
+    /// Nombres de archivo de las pistas que pasan el filtro, en orden de escaneo
+    fn filtered(service: &PlaylistService, filter: &TrackFilter) -> Vec<String> {
+        service
+            .filter_tracks(filter)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn filter_library() -> PlaylistService {
+        library(vec![
+            tagged("/m/a.mp3", Some(1985), Some(200), Some("Rock")),
+            tagged("/m/b.mp3", Some(1990), Some(120), Some("Hip-Hop")),
+            tagged("/m/c.mp3", Some(1999), Some(600), Some("Jazz")),
+            tagged("/m/d.mp3", None, None, None),
+        ])
+    }
+
+    #[test]
+    fn an_empty_filter_keeps_the_whole_library() {
+        let service = filter_library();
+        assert_eq!(
+            filtered(&service, &TrackFilter::default()),
+            ["a.mp3", "b.mp3", "c.mp3", "d.mp3"]
+        );
+    }
+
+    #[test]
+    fn year_and_length_bounds_are_inclusive() {
+        let service = filter_library();
+        let years = TrackFilter {
+            year_from: Some(1990),
+            year_to: Some(1999),
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &years), ["b.mp3", "c.mp3", "d.mp3"]);
+
+        let length = TrackFilter {
+            min_seconds: Some(120),
+            max_seconds: Some(200),
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &length), ["a.mp3", "b.mp3", "d.mp3"]);
+    }
+
+    #[test]
+    fn strict_mode_drops_tracks_missing_the_filtered_data() {
+        let service = filter_library();
+        let filter = TrackFilter {
+            year_from: Some(1980),
+            strict: true,
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &filter), ["a.mp3", "b.mp3", "c.mp3"]);
+        // Sin condiciones, el modo estricto no deja afuera a nadie
+        let strict_only = TrackFilter {
+            strict: true,
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &strict_only).len(), 4);
+    }
+
+    #[test]
+    fn genres_are_compared_normalized_and_exclusion_wins() {
+        let service = filter_library();
+        let only = TrackFilter {
+            genres: vec!["hip hop".to_string(), "JAZZ".to_string()],
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &only), ["b.mp3", "c.mp3", "d.mp3"]);
+
+        let excluded = TrackFilter {
+            genres: vec!["rock".to_string(), "jazz".to_string()],
+            exclude_genres: vec!["Jazz".to_string()],
+            strict: true,
+            ..TrackFilter::default()
+        };
+        assert_eq!(filtered(&service, &excluded), ["a.mp3"]);
+    }
+
+    #[test]
+    fn paths_outside_the_library_pass_only_loose_filters() {
+        let service = filter_library();
+        let outside = Path::new("/elsewhere/x.mp3");
+        let mut filter = TrackFilter {
+            year_from: Some(2000),
+            ..TrackFilter::default()
+        };
+        assert!(service.passes_filter(outside, &filter));
+        assert!(!service.passes_filter(Path::new("/m/a.mp3"), &filter));
+        filter.strict = true;
+        assert!(!service.passes_filter(outside, &filter));
+        assert!(service.passes_filter(
+            outside,
+            &TrackFilter {
+                strict: true,
+                ..TrackFilter::default()
+            }
+        ));
+    }
+
+    fn timed(path: &str, artist: &str, genre: &str, seconds: u64) -> TrackMetadata {
+        TrackMetadata {
+            genre: Some(genre.to_string()),
+            duration: Some(std::time::Duration::from_secs(seconds)),
+            ..track(path, artist, "Album")
+        }
+    }
+
+    fn length(track: &TrackMetadata) -> std::time::Duration {
+        track.duration.unwrap_or_default()
+    }
+
+    #[test]
+    fn top_lists_rank_by_summed_weight_and_skip_empty_groups() {
+        let service = library(vec![
+            timed("/m/1.mp3", "Blur", "Rock", 200),
+            timed("/m/2.mp3", "Blur", "rock", 100),
+            timed("/m/3.mp3", "Miles Davis", "Jazz", 600),
+            timed("/m/4.mp3", VARIOUS_ARTISTS, "Pop", 900),
+            timed("/m/5.mp3", "Silence", "Ambient", 0),
+        ]);
+        let secs = |ranked: Vec<(String, std::time::Duration)>| {
+            ranked
+                .into_iter()
+                .map(|(name, total)| (name, total.as_secs()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            secs(service.get_top_genres_by(length)),
+            [
+                ("pop".to_string(), 900),
+                ("jazz".to_string(), 600),
+                ("rock".to_string(), 300)
+            ]
+        );
+        // Various Artists no es un artista, igual que al contar pistas
+        assert_eq!(
+            secs(service.get_top_artists_by(length)),
+            [("Miles Davis".to_string(), 600), ("Blur".to_string(), 300)]
+        );
+    }
+
+    #[test]
+    fn a_track_in_several_groups_counts_in_full_in_each() {
+        let tracks = vec![
+            timed("/m/1.mp3", "A", "Rock", 100),
+            timed("/m/2.mp3", "B", "Jazz", 50),
+        ];
+        // La pista 0 figura en dos géneros, como una etiqueta "Rock; Jazz"
+        let rock = "rock".to_string();
+        let jazz = "jazz".to_string();
+        let groups = [(&rock, &vec![0]), (&jazz, &vec![0, 1])];
+        let ranked = PlaylistService::rank_by_weight(&tracks, groups.into_iter(), length);
+        assert_eq!(
+            ranked,
+            [
+                (jazz.clone(), std::time::Duration::from_secs(150)),
+                (rock.clone(), std::time::Duration::from_secs(100))
+            ]
+        );
+    }
+
+    #[test]
+    fn search_results_follow_the_sort_key_with_missing_values_last() {
+        let mut untitled = timed("/m/4.mp3", "Pulp", "Britpop", 180);
+        untitled.album = Some("Different Class".to_string());
+        let service = library(vec![
+            TrackMetadata {
+                title: Some("Girls & Boys".to_string()),
+                year: Some(1994),
+                ..timed("/m/1.mp3", "Blur", "Britpop", 290)
+            },
+            TrackMetadata {
+                title: Some("beetlebum".to_string()),
+                year: Some(1997),
+                ..timed("/m/2.mp3", "Blur", "Britpop", 305)
+            },
+            TrackMetadata {
+                title: Some("Song 2".to_string()),
+                ..track("/m/3.mp3", "BLUR", "Blur")
+            },
+            untitled,
+        ]);
+        let paths = |sort: TrackSortKey, limit: usize| {
+            service
+                .search_tracks_sorted("blur", sort, limit)
+                .iter()
+                .map(|t| t.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        // Sin distinguir mayúsculas, en título, artista o álbum
+        assert_eq!(
+            paths(TrackSortKey::Title, 10),
+            ["/m/2.mp3", "/m/1.mp3", "/m/3.mp3"]
+        );
+        assert_eq!(
+            paths(TrackSortKey::Year, 10),
+            ["/m/1.mp3", "/m/2.mp3", "/m/3.mp3"]
+        );
+        assert_eq!(
+            paths(TrackSortKey::Duration, 10),
+            ["/m/1.mp3", "/m/2.mp3", "/m/3.mp3"]
+        );
+        assert_eq!(paths(TrackSortKey::Album, 2), ["/m/1.mp3", "/m/2.mp3"]);
+        assert!(
+            service
+                .search_tracks_sorted("oasis", TrackSortKey::Artist, 10)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn a_discography_is_sorted_by_year_with_undated_albums_last() {
+        let dated = |path: &str, album: &str, year: Option<u32>| TrackMetadata {
+            year,
+            ..track(path, "Blur", album)
+        };
+        let service = library(vec![
+            dated("/m/p/1.mp3", "Parklife", Some(1994)),
+            dated("/m/p/2.mp3", "Parklife", None),
+            dated("/m/l/1.mp3", "Leisure", Some(1991)),
+            dated("/m/b/1.mp3", "B-Sides", None),
+            dated("/m/a/1.mp3", "Airport Demos", None),
+            // El año más antiguo entre sus pistas
+            dated("/m/t/1.mp3", "The Great Escape", Some(1996)),
+            dated("/m/t/2.mp3", "The Great Escape", Some(1995)),
+            track("/m/o/1.mp3", "Oasis", "Definitely Maybe"),
+        ]);
+        let albums: Vec<(String, Option<u32>, usize)> = service
+            .get_artist_discography("blur")
+            .into_iter()
+            .map(|album| (album.name, album.year, album.track_count))
+            .collect();
+        assert_eq!(
+            albums,
+            [
+                ("Leisure".to_string(), Some(1991), 1),
+                ("Parklife".to_string(), Some(1994), 2),
+                ("The Great Escape".to_string(), Some(1995), 2),
+                ("Airport Demos".to_string(), None, 1),
+                ("B-Sides".to_string(), None, 1),
+            ]
+        );
+        assert!(service.get_artist_discography("Suede").is_empty());
+    }
+
+    #[test]
+    fn only_files_added_since_the_copy_are_new() {
+        let root = std::env::temp_dir().join(format!(
+            "rusted-player-test-new-tracks-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("Album")).unwrap();
+        std::fs::write(root.join("Album/01.mp3"), b"audio").unwrap();
+        std::fs::write(root.join("Other.mp3"), b"audio").unwrap();
+        let mut service = PlaylistService::new();
+        service.scan_directory(&root).unwrap();
+
+        let before = service.deep_clone();
+        std::fs::write(root.join("Album/02.mp3"), b"audio").unwrap();
+        service.rescan_path(&root.join("Album")).unwrap();
+        let new: Vec<&Path> = service
+            .get_new_tracks_since(&before)
+            .iter()
+            .map(|track| track.path.as_path())
+            .collect();
+        assert_eq!(new, [root.join("Album/02.mp3")]);
+        // La copia no vio el escaneo
+        assert!(
+            before
+                .get_track_by_path(&root.join("Album/02.mp3"))
+                .is_none()
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn genre_union_and_intersection_compare_normalized_names() {
+        let service = library(vec![
+            timed("/m/1.mp3", "A", "Jazz", 1),
+            timed("/m/2.mp3", "B", "Blues", 1),
+            timed("/m/3.mp3", "C", "jazz ", 1),
+            timed("/m/4.mp3", "D", "Rock", 1),
+        ]);
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(
+            service.get_tracks_by_genres_any(&["JAZZ", "blues", "Polka"]),
+            paths(&["/m/1.mp3", "/m/2.mp3", "/m/3.mp3"])
+        );
+        assert_eq!(
+            service.get_tracks_by_genres_all(&["Jazz", "jazz"]),
+            paths(&["/m/1.mp3", "/m/3.mp3"])
+        );
+        // Una pista tiene un solo género: dos distintos no se cruzan
+        assert!(
+            service
+                .get_tracks_by_genres_all(&["Jazz", "Blues"])
+                .is_empty()
+        );
+        assert!(service.get_tracks_by_genres_all(&[]).is_empty());
+        assert!(service.get_tracks_by_genres_any(&[]).is_empty());
+    }
+
+    #[test]
+    fn messy_artist_names_normalize_to_one_key() {
+        let table = [
+            ("The Beatles", "the beatles"),
+            ("Beatles, The", "the beatles"),
+            ("  BEATLES,   THE ", "the beatles"),
+            ("the\tbeatles", "the beatles"),
+            ("The The", "the the"),
+            ("Theatre of Tragedy", "theatre of tragedy"),
+            // La diéresis combinante se compone
+            ("Bjo\u{308}rk", "björk"),
+            ("Sigur  Rós", "sigur rós"),
+            ("", ""),
+        ];
+        for (artist, expected) in table {
+            assert_eq!(normalize_artist(artist), expected, "{artist:?}");
+        }
+    }
+
+    #[test]
+    fn featured_artists_split_off_the_primary_one() {
+        let table: [(&str, &str, &[&str]); 9] = [
+            (
+                "Daft Punk feat. Pharrell Williams & Nile Rodgers",
+                "Daft Punk",
+                &["Pharrell Williams", "Nile Rodgers"],
+            ),
+            (
+                "Mark Ronson (feat. Bruno Mars)",
+                "Mark Ronson",
+                &["Bruno Mars"],
+            ),
+            ("Artist [ft. A, B]", "Artist", &["A", "B"]),
+            ("Artist FEAT. Guest", "Artist", &["Guest"]),
+            ("  Spaced   ft. Guest  ", "Spaced", &["Guest"]),
+            // Sin artista principal no hay nada que separar
+            ("feat. Someone", "feat. Someone", &[]),
+            ("Featuring Artists", "Featuring Artists", &[]),
+            ("Simon & Garfunkel", "Simon & Garfunkel", &[]),
+            ("Daft Punk", "Daft Punk", &[]),
+        ];
+        for (artist, primary, featured) in table {
+            assert_eq!(
+                split_featured_artists(artist),
+                (
+                    primary.to_string(),
+                    featured.iter().map(|s| s.to_string()).collect()
+                ),
+                "{artist:?}"
+            );
+        }
+        assert_eq!(
+            normalize_featured_artist("A feat. B & C"),
+            ("A".to_string(), Some("B, C".to_string()))
+        );
+        assert_eq!(normalize_featured_artist("A"), ("A".to_string(), None));
+    }
+
+    #[test]
+    fn artist_variants_group_unless_folding_is_off() {
+        let mut service = library(vec![
+            track("/m/1.mp3", "The Beatles", "A"),
+            track("/m/2.mp3", "Beatles, The", "B"),
+            track("/m/3.mp3", "the beatles feat. Billy Preston", "C"),
+            track("/m/4.mp3", "Bj\u{f6}rk", "D"),
+            track("/m/5.mp3", "Bjo\u{308}rk", "E"),
+        ]);
+        let count = |service: &PlaylistService, artist| service.get_artist_tracks(artist).len();
+        assert_eq!(count(&service, "THE BEATLES"), 3);
+        assert_eq!(count(&service, "Björk"), 2);
+
+        service.set_artist_folding(false);
+        assert_eq!(count(&service, "The Beatles"), 1);
+        assert_eq!(count(&service, "THE BEATLES"), 0);
+        // La forma Unicode nunca separa a un artista
+        assert_eq!(count(&service, "Björk"), 2);
+    }
+
+    /// Etiqueta en memoria con título y año
+    fn tag(tag_type: TagType, title: Option<&str>, year: Option<u32>) -> Tag {
+        let mut tag = Tag::new(tag_type);
+        if let Some(title) = title {
+            tag.set_title(title.to_string());
+        }
+        if let Some(year) = year {
+            tag.set_year(year);
+        }
+        tag
+    }
+
+    #[test]
+    fn disagreeing_tags_keep_the_preferred_value_and_report_a_conflict() {
+        let title = |tags: &[(TagSource, &Tag)]| {
+            pick_field(
+                tags,
+                |t| t.title().map(|s| s.to_string()),
+                |a, b| tag_values_agree(a, b),
+            )
+        };
+        let v24 = tag(TagType::Id3v2, Some("Hey Jude"), Some(1968));
+        let ape = tag(TagType::Ape, Some("Let It Be"), Some(1970));
+        let v1 = tag(TagType::Id3v1, Some("  HEY JUDE "), None);
+        let untitled = tag(TagType::Ape, None, Some(1968));
+
+        // (etiquetas por prioridad, valor, origen, conflicto)
+        let table = [
+            (
+                vec![(TagSource::Id3v24, &v24), (TagSource::Ape, &ape)],
+                Some("Hey Jude"),
+                Some(TagSource::Id3v24),
+                true,
+            ),
+            (
+                vec![(TagSource::Ape, &ape), (TagSource::Id3v24, &v24)],
+                Some("Let It Be"),
+                Some(TagSource::Ape),
+                true,
+            ),
+            // Mayúsculas y espacios de borde no son un conflicto
+            (
+                vec![(TagSource::Id3v24, &v24), (TagSource::Id3v1, &v1)],
+                Some("Hey Jude"),
+                Some(TagSource::Id3v24),
+                false,
+            ),
+            // Sin valor en la preferida se toma el de la siguiente
+            (
+                vec![(TagSource::Ape, &untitled), (TagSource::Id3v1, &v1)],
+                Some("  HEY JUDE "),
+                Some(TagSource::Id3v1),
+                false,
+            ),
+            (vec![(TagSource::Ape, &untitled)], None, None, false),
+            (vec![], None, None, false),
+        ];
+        for (i, (tags, value, source, conflict)) in table.into_iter().enumerate() {
+            let (found, found_source, found_conflict) = title(&tags);
+            assert_eq!(
+                (found.as_deref(), found_source, found_conflict),
+                (value, source, conflict),
+                "row {i}"
+            );
+        }
+
+        let years = [(TagSource::Id3v24, &v24), (TagSource::Ape, &untitled)];
+        assert_eq!(
+            pick_field(&years, |t| t.year(), |a, b| a == b),
+            (Some(1968), Some(TagSource::Id3v24), false)
+        );
+    }
+
+    #[test]
+    fn id3v1_truncation_is_not_a_conflict() {
+        let long = "Sgt. Pepper's Lonely Hearts Club Band";
+        let table = [
+            (long, &long[..30], true),
+            (long, &long[..29], true),
+            // Más corto que el límite de ID3v1 ya es otro valor
+            (long, &long[..20], false),
+            ("Help!", "help! ", true),
+            ("Help!", "Help", false),
+        ];
+        for (a, b, agree) in table {
+            assert_eq!(tag_values_agree(a, b), agree, "{a:?} vs {b:?}");
+            assert_eq!(tag_values_agree(b, a), agree, "{b:?} vs {a:?}");
         }
     }
+
+    #[test]
+    fn tag_priority_orders_unlisted_tags_last() {
+        let mut service = PlaylistService::new();
+        service.set_tag_priority(vec![TagSource::Ape, TagSource::Id3v1]);
+        assert!(service.tag_rank(TagSource::Ape) < service.tag_rank(TagSource::Id3v1));
+        assert!(service.tag_rank(TagSource::Id3v1) < service.tag_rank(TagSource::Id3v24));
+        assert_eq!(
+            service.tag_rank(TagSource::Id3v24),
+            service.tag_rank(TagSource::Mp4)
+        );
+    }
 }
@@ -1,5 +1,30 @@
+pub mod accessible_service;
+pub mod breadcrumb_service;
+pub mod clipboard_service;
 pub mod config_service;
+pub mod cover_cache_service;
+pub mod dir_scanner_service;
+pub mod file_walker_service;
+pub mod folder_order_service;
+pub mod headless_service;
+pub mod help_service;
+pub mod hook_service;
+pub mod import_service;
 pub mod metadata_service;
+pub mod notification_service;
+pub mod offline_service;
+pub mod palette_service;
 pub mod player_service;
 pub mod playlist_storage_service;
+pub mod queue_time_service;
+pub mod render_service;
+pub mod search_service;
+pub mod session_log_service;
+pub mod session_service;
+pub mod shuffle_service;
+pub mod stats_section_service;
+pub mod status_format_service;
+pub mod tag_guess_service;
+pub mod trim_service;
 pub mod ui_manager;
+pub mod volume_service;
@@ -0,0 +1,243 @@
+//! Avisos del sistema operativo (notificaciones de escritorio)
+//!
+//! `App` solo conoce el trait `Notifier`; las diferencias entre plataformas
+//! quedan en `OsNotifier`.
+
+use crate::models::config::NotificationBackend;
+use crate::services::config_service;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Nombre de la aplicación con el que se firman los avisos
+const APP_NAME: &str = "rusted-player";
+
+/// Carpeta de los íconos de aviso dentro del directorio de caché
+#[cfg(all(unix, not(target_os = "macos")))]
+const ICONS_DIR_NAME: &str = "notification-icons";
+
+/// Íconos que se conservan: `notify-send` abre el archivo después de lanzarse,
+/// así que los recientes no se borran mientras puedan estar en uso
+#[cfg(all(unix, not(target_os = "macos")))]
+const KEPT_ICONS: usize = 8;
+
+/// Archivo del registro de avisos dentro del directorio de caché
+const LOG_FILE_NAME: &str = "notifications.log";
+
+/// Algo capaz de mostrar un aviso con título, texto e ícono opcional
+///
+/// `icon` son los bytes de una imagen (la portada, por ejemplo); cada
+/// implementación decide si puede usarla.
+pub trait Notifier: Send {
+    fn notify(&self, title: &str, body: &str, icon: Option<&[u8]>);
+}
+
+/// Notificaciones de escritorio reales
+///
+/// En Linux y los BSD usa `notify-send`, en macOS `osascript`; en otras
+/// plataformas no hace nada. Si el programa no está instalado el aviso se
+/// pierde sin error: no vale la pena interrumpir la reproducción por eso.
+#[derive(Debug, Default)]
+pub struct OsNotifier;
+
+impl Notifier for OsNotifier {
+    fn notify(&self, title: &str, body: &str, icon: Option<&[u8]>) {
+        if let Some(command) = os_command(title, body, icon) {
+            spawn_detached(command);
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn os_command(title: &str, body: &str, icon: Option<&[u8]>) -> Option<Command> {
+    let mut command = Command::new("notify-send");
+    command.arg(format!("--app-name={}", APP_NAME));
+    // notify-send solo acepta el ícono como archivo
+    if let Some(icon_path) = icon.and_then(icon_file) {
+        command.arg(format!("--icon={}", icon_path.display()));
+    }
+    command.arg("--").arg(title).arg(body);
+    Some(command)
+}
+
+#[cfg(target_os = "macos")]
+fn os_command(title: &str, body: &str, _icon: Option<&[u8]>) -> Option<Command> {
+    // AppleScript no permite elegir el ícono: se usa el de la terminal
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(title)
+    ));
+    Some(command)
+}
+
+#[cfg(not(unix))]
+fn os_command(_title: &str, _body: &str, _icon: Option<&[u8]>) -> Option<Command> {
+    None
+}
+
+/// Guarda el ícono en la caché del usuario y retorna su ruta
+///
+/// El nombre sale de un hash del contenido: la misma portada reutiliza su archivo
+/// y una nueva no pisa la que otro aviso todavía puede estar leyendo. Se escribe
+/// primero un temporal para que nunca se lea una imagen a medias.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn icon_file(bytes: &[u8]) -> Option<PathBuf> {
+    let dir = config_service::get_cache_dir().ok()?.join(ICONS_DIR_NAME);
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("{:016x}", crate::utils::fnv1a(bytes)));
+    if !path.is_file() {
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes).ok()?;
+        std::fs::rename(&partial, &path).ok()?;
+        prune_icons(&dir, KEPT_ICONS);
+    }
+    Some(path)
+}
+
+/// Borra los íconos más viejos hasta dejar `keep`
+#[cfg(all(unix, not(target_os = "macos")))]
+fn prune_icons(dir: &std::path::Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut icons: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    icons.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in icons.into_iter().skip(keep) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Lanza el comando sin salida en la terminal y lo espera en otro hilo
+fn spawn_detached(mut command: Command) {
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        std::thread::spawn(move || child.wait());
+    }
+}
+
+/// Agrega cada aviso como una línea en un archivo de registro
+///
+/// No se usa stderr porque es la misma terminal que dibuja la interfaz. Un
+/// aviso que no se puede escribir se pierde.
+#[derive(Debug)]
+pub struct LogNotifier {
+    path: PathBuf,
+}
+
+impl LogNotifier {
+    /// Registro que agrega al final de `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Notifier for LogNotifier {
+    fn notify(&self, title: &str, body: &str, _icon: Option<&[u8]>) {
+        let line = format!(
+            "{} [{}] {}: {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            APP_NAME,
+            title,
+            body
+        );
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+/// No muestra nada
+#[derive(Debug, Default)]
+pub struct NullNotifier;
+
+impl Notifier for NullNotifier {
+    fn notify(&self, _title: &str, _body: &str, _icon: Option<&[u8]>) {}
+}
+
+/// Implementación que corresponde al destino configurado
+pub fn notifier_for(backend: NotificationBackend) -> Box<dyn Notifier> {
+    match backend {
+        NotificationBackend::Off => Box::new(NullNotifier),
+        NotificationBackend::Os => Box::new(OsNotifier),
+        NotificationBackend::Log => match config_service::get_cache_dir() {
+            Ok(dir) => Box::new(LogNotifier::new(dir.join(LOG_FILE_NAME))),
+            Err(_) => Box::new(NullNotifier),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn log_notifier_appends_one_line_per_notice() {
+        let dir = temp_dir("notify-log");
+        let notifier = LogNotifier::new(dir.join(LOG_FILE_NAME));
+        notifier.notify("Now playing", "Artist - One", None);
+        notifier.notify("Now playing", "Artist - Two", Some(b"png"));
+
+        let log = fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("[rusted-player] Now playing: Artist - One"));
+        assert!(lines[1].ends_with("Artist - Two"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn prune_keeps_the_newest_icons() {
+        let dir = temp_dir("notify-icons");
+        for i in 0..5 {
+            let path = dir.join(format!("icon{i}"));
+            fs::write(&path, [i]).unwrap();
+            let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000 + i as u64);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        prune_icons(&dir, 2);
+
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["icon3", "icon4"]);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
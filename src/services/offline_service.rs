@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// Carpetas donde el sistema suele montar discos externos, con la cantidad de
+/// componentes que forman la raíz del volumen
+/// (p. ej. `/media/usuario/ExtDisk` son cuatro: `/`, `media`, `usuario`, `ExtDisk`)
+const MOUNT_PREFIXES: &[(&[&str], usize)] = &[
+    (&["media"], 4),
+    (&["run", "media"], 5),
+    (&["mnt"], 3),
+    (&["Volumes"], 3),
+];
+
+/// Raíz del volumen que contiene la ruta
+///
+/// Usa la raíz de biblioteca que la contenga; si no hay ninguna, reconoce los
+/// puntos de montaje habituales y las unidades de Windows. Retorna `None` si la
+/// ruta no está en un volumen reconocible.
+pub fn volume_root(path: &Path, library_roots: &[PathBuf]) -> Option<PathBuf> {
+    if let Some(root) = library_roots.iter().find(|root| path.starts_with(root)) {
+        return Some(root.clone());
+    }
+
+    let components: Vec<Component> = path.components().collect();
+    match components.as_slice() {
+        [Component::Prefix(_), Component::RootDir, _, ..] => {
+            return Some(components[..2].iter().collect());
+        }
+        [Component::RootDir, rest @ ..] => {
+            for (prefix, depth) in MOUNT_PREFIXES {
+                let matches_prefix = rest.len() >= *depth
+                    && prefix
+                        .iter()
+                        .zip(rest)
+                        .all(|(name, component)| component.as_os_str() == *name);
+                if matches_prefix {
+                    return Some(components[..*depth].iter().collect());
+                }
+            }
+        }
+        _ => {}
+    }
+    None
+}
+
+/// Volúmenes no disponibles entre las pistas
+///
+/// Comprueba cada raíz una sola vez con `is_available`, no cada pista.
+pub fn find_offline_roots<'a>(
+    tracks: impl IntoIterator<Item = &'a Path>,
+    library_roots: &[PathBuf],
+    is_available: impl Fn(&Path) -> bool,
+) -> HashSet<PathBuf> {
+    let roots: HashSet<PathBuf> = tracks
+        .into_iter()
+        .filter_map(|track| volume_root(track, library_roots))
+        .collect();
+    roots
+        .into_iter()
+        .filter(|root| !is_available(root))
+        .collect()
+}
+
+/// Fila de una lista de pistas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackRow {
+    /// Una pista, por su índice en la playlist
+    Track(usize),
+    /// Todas las pistas de un volumen no disponible, agrupadas en la posición de la primera
+    Offline {
+        first: usize,
+        root: PathBuf,
+        count: usize,
+    },
+}
+
+impl TrackRow {
+    /// Índice de la pista que representa la fila
+    pub fn track_index(&self) -> usize {
+        match self {
+            Self::Track(index) => *index,
+            Self::Offline { first, .. } => *first,
+        }
+    }
+
+    /// Texto de la fila agrupada: "— 34 tracks on offline volume 'ExtDisk' —"
+    pub fn offline_label(root: &Path, count: usize) -> String {
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.to_string_lossy().into_owned());
+        let noun = if count == 1 { "track" } else { "tracks" };
+        format!("— {} {} on offline volume '{}' —", count, noun, name)
+    }
+}
+
+/// Arma las filas de una playlist, colapsando las pistas de volúmenes no disponibles
+pub fn group_track_rows(
+    tracks: &[impl AsRef<Path>],
+    offline_roots: &HashSet<PathBuf>,
+    library_roots: &[PathBuf],
+) -> Vec<TrackRow> {
+    let mut rows: Vec<TrackRow> = Vec::new();
+    for (index, track) in tracks.iter().enumerate() {
+        let offline_root =
+            volume_root(track.as_ref(), library_roots).filter(|root| offline_roots.contains(root));
+        let Some(root) = offline_root else {
+            rows.push(TrackRow::Track(index));
+            continue;
+        };
+
+        let existing = rows.iter_mut().find_map(|row| match row {
+            TrackRow::Offline {
+                root: row_root,
+                count,
+                ..
+            } if *row_root == root => Some(count),
+            _ => None,
+        });
+        match existing {
+            Some(count) => *count += 1,
+            None => rows.push(TrackRow::Offline {
+                first: index,
+                root,
+                count: 1,
+            }),
+        }
+    }
+    rows
+}
+
+/// Verifica si la pista está en un volumen no disponible
+pub fn is_on_offline_volume(
+    track: &Path,
+    offline_roots: &HashSet<PathBuf>,
+    library_roots: &[PathBuf],
+) -> bool {
+    !offline_roots.is_empty()
+        && volume_root(track, library_roots).is_some_and(|root| offline_roots.contains(&root))
+}
+
+/// Pistas de una playlist cuyos archivos ya no existen
+#[derive(Debug, Clone)]
+pub struct DanglingPlaylist {
+    /// Nombre de la playlist
+    pub name: String,
+    /// Rutas que faltan, en el orden de la playlist
+    pub missing: Vec<PathBuf>,
+}
+
+/// Busca en cada playlist las pistas cuyo archivo no existe
+///
+/// Las pistas de volúmenes no disponibles no cuentan como faltantes, así un disco
+/// desconectado no provoca una limpieza. Solo se incluyen playlists con faltantes.
+pub fn find_dangling_tracks(
+    playlists: &[(String, Vec<PathBuf>)],
+    library_roots: &[PathBuf],
+) -> Vec<DanglingPlaylist> {
+    let offline_roots = find_offline_roots(
+        playlists
+            .iter()
+            .flat_map(|(_, tracks)| tracks.iter().map(PathBuf::as_path)),
+        library_roots,
+        Path::exists,
+    );
+    playlists
+        .iter()
+        .filter_map(|(name, tracks)| {
+            let missing: Vec<PathBuf> = tracks
+                .iter()
+                .filter(|track| {
+                    !is_on_offline_volume(track, &offline_roots, library_roots) && !track.exists()
+                })
+                .cloned()
+                .collect();
+            (!missing.is_empty()).then(|| DanglingPlaylist {
+                name: name.clone(),
+                missing,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn paths(list: &[&str]) -> Vec<PathBuf> {
+        list.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn volume_root_table() {
+        let library = paths(&["/media/me/Music/Library"]);
+        let table = [
+            (
+                "/media/me/Music/Library/a.mp3",
+                Some("/media/me/Music/Library"),
+            ),
+            ("/media/me/ExtDisk/a.mp3", Some("/media/me/ExtDisk")),
+            (
+                "/run/media/me/ExtDisk/Album/a.mp3",
+                Some("/run/media/me/ExtDisk"),
+            ),
+            ("/mnt/usb/a.mp3", Some("/mnt/usb")),
+            ("/Volumes/ExtDisk/a.mp3", Some("/Volumes/ExtDisk")),
+            // Sin llegar a la raíz del volumen no hay volumen
+            ("/media/me", None),
+            ("/home/me/Music/a.mp3", None),
+            ("relative/a.mp3", None),
+        ];
+        for (path, expected) in table {
+            assert_eq!(
+                volume_root(Path::new(path), &library),
+                expected.map(PathBuf::from),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn each_root_is_checked_once() {
+        let checked = RefCell::new(Vec::new());
+        let tracks = paths(&[
+            "/media/me/A/1.mp3",
+            "/media/me/A/2.mp3",
+            "/media/me/B/1.mp3",
+            "/home/me/1.mp3",
+        ]);
+        let offline = find_offline_roots(tracks.iter().map(PathBuf::as_path), &[], |root| {
+            checked.borrow_mut().push(root.to_path_buf());
+            !root.ends_with("B")
+        });
+
+        assert_eq!(offline, HashSet::from([PathBuf::from("/media/me/B")]));
+        let mut checked = checked.into_inner();
+        checked.sort();
+        assert_eq!(checked, paths(&["/media/me/A", "/media/me/B"]));
+    }
+
+    #[test]
+    fn offline_tracks_collapse_at_their_first_position() {
+        let tracks = paths(&[
+            "/home/me/1.mp3",
+            "/media/me/B/1.mp3",
+            "/media/me/A/1.mp3",
+            "/media/me/B/2.mp3",
+            "/home/me/2.mp3",
+            "/media/me/B/3.mp3",
+        ]);
+        let offline = HashSet::from([PathBuf::from("/media/me/B")]);
+
+        assert_eq!(
+            group_track_rows(&tracks, &offline, &[]),
+            [
+                TrackRow::Track(0),
+                TrackRow::Offline {
+                    first: 1,
+                    root: PathBuf::from("/media/me/B"),
+                    count: 3,
+                },
+                TrackRow::Track(2),
+                TrackRow::Track(4),
+            ]
+        );
+        assert!(is_on_offline_volume(&tracks[3], &offline, &[]));
+        assert!(!is_on_offline_volume(&tracks[2], &offline, &[]));
+        assert!(!is_on_offline_volume(&tracks[0], &offline, &[]));
+        assert!(
+            group_track_rows(&tracks, &HashSet::new(), &[])
+                .iter()
+                .all(|row| matches!(row, TrackRow::Track(_)))
+        );
+    }
+
+    #[test]
+    fn offline_label_counts_tracks() {
+        let root = Path::new("/media/me/ExtDisk");
+        assert_eq!(
+            TrackRow::offline_label(root, 1),
+            "— 1 track on offline volume 'ExtDisk' —"
+        );
+        assert_eq!(
+            TrackRow::offline_label(root, 34),
+            "— 34 tracks on offline volume 'ExtDisk' —"
+        );
+    }
+
+    #[test]
+    fn missing_files_on_an_unplugged_volume_are_not_dangling() {
+        let library = std::env::temp_dir().join(format!(
+            "rusted-player-test-dangling-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&library);
+        std::fs::create_dir_all(&library).unwrap();
+        let present = library.join("here.mp3");
+        std::fs::write(&present, b"audio").unwrap();
+        let gone = library.join("gone.mp3");
+        let unplugged = PathBuf::from("/media/rusted-player-test/NoSuchDisk/a.mp3");
+        let playlists = [
+            (
+                "Mix".to_string(),
+                vec![present.clone(), gone.clone(), unplugged.clone()],
+            ),
+            ("Away".to_string(), vec![unplugged]),
+        ];
+
+        let dangling = find_dangling_tracks(&playlists, std::slice::from_ref(&library));
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].name, "Mix");
+        assert_eq!(dangling[0].missing, [gone]);
+        let _ = std::fs::remove_dir_all(library);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::services::metadata_service::{AlbumRef, PlaylistService, TrackSortKey};
+use crate::services::search_service::{self, SearchFields};
+use std::path::{Path, PathBuf};
+
+/// Lugar al que se puede saltar desde la paleta (Ctrl+P)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteTarget {
+    /// Carpeta visitada en esta sesión
+    Folder(PathBuf),
+    /// Playlist guardada, por nombre
+    Playlist(String),
+    Artist(String),
+    Album(AlbumRef),
+    /// Pista de la biblioteca que coincide con la búsqueda, como "Título — Artista"
+    Track {
+        path: PathBuf,
+        label: String,
+    },
+    /// Comando de la interfaz, con el atajo que hace lo mismo (vacío si no tiene)
+    Command {
+        name: &'static str,
+        keys: &'static str,
+        description: &'static str,
+    },
+}
+
+impl PaletteTarget {
+    /// Etiqueta del tipo, que se muestra antes del nombre
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Self::Folder(_) => "DIR",
+            Self::Playlist(_) => "PL",
+            Self::Artist(_) => "ARTIST",
+            Self::Album(_) => "ALBUM",
+            Self::Track { .. } => "TRACK",
+            Self::Command { .. } => "CMD",
+        }
+    }
+
+    /// Texto sobre el que se busca y que se muestra
+    pub fn label(&self) -> String {
+        match self {
+            Self::Folder(path) => path.display().to_string(),
+            Self::Playlist(name) | Self::Artist(name) | Self::Track { label: name, .. } => {
+                name.clone()
+            }
+            Self::Album(album) => format!("{} — {}", album.album, album.artist),
+            Self::Command { name, .. } => name.to_string(),
+        }
+    }
+}
+
+/// Qué hacer con el destino elegido
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Abrir la carpeta en la pestaña de carpetas
+    Browse(PathBuf),
+    /// Reproducir la carpeta y sus subcarpetas
+    PlayFolder(PathBuf),
+    /// Abrir la playlist en la pestaña de listas
+    OpenPlaylist(String),
+    PlayPlaylist(String),
+    /// Reproducir estas pistas en orden, con el nombre que se muestra como origen
+    PlayTracks {
+        source: String,
+        tracks: Vec<PathBuf>,
+    },
+    /// Ejecutar el comando con este nombre
+    RunCommand(&'static str),
+    /// El destino ya no tiene pistas en la biblioteca
+    Nothing,
+}
+
+/// Índices de `targets` que coinciden con la búsqueda, de mejor a peor
+///
+/// Con la búsqueda vacía se devuelven todos en su orden. Una búsqueda que
+/// empieza con `>` solo mira los comandos, que coinciden por nombre o por
+/// descripción.
+pub fn filter(targets: &[PaletteTarget], query: &str) -> Vec<usize> {
+    let (query, only_commands) = match query.trim_start().strip_prefix('>') {
+        Some(rest) => (rest, true),
+        None => (query, false),
+    };
+    let candidates = targets
+        .iter()
+        .enumerate()
+        .filter(|(_, target)| !only_commands || matches!(target, PaletteTarget::Command { .. }));
+    if query.trim().is_empty() {
+        return candidates.map(|(i, _)| i).collect();
+    }
+    let mut scored: Vec<(usize, u32)> = candidates
+        .filter_map(|(i, target)| {
+            let label = target.label();
+            let fields = match target {
+                PaletteTarget::Command { description, .. } => SearchFields {
+                    title: Some(&label),
+                    file_name: description,
+                    ..Default::default()
+                },
+                _ => SearchFields {
+                    file_name: &label,
+                    ..Default::default()
+                },
+            };
+            search_service::score_match(query, &fields).map(|m| (i, m.score))
+        })
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Pistas que se buscan como mucho en la biblioteca para la paleta
+const TRACK_RESULTS: usize = 20;
+
+/// Pistas cuyo título, artista o álbum contienen la búsqueda, ordenadas por `sort`
+///
+/// Van después de los demás destinos, que se filtran con `filter`; hacen falta
+/// al menos dos letras y una búsqueda de comandos (`>`) no busca pistas.
+pub fn track_targets(
+    library: &PlaylistService,
+    query: &str,
+    sort: TrackSortKey,
+) -> Vec<PaletteTarget> {
+    let query = query.trim();
+    if query.starts_with('>') || query.chars().count() < 2 {
+        return Vec::new();
+    }
+    library
+        .search_tracks_sorted(query, sort, TRACK_RESULTS)
+        .into_iter()
+        .map(|track| {
+            let title = track.title.clone().unwrap_or_else(|| {
+                track
+                    .path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            let label = match &track.artist {
+                Some(artist) => format!("{} — {}", title, artist),
+                None => title,
+            };
+            PaletteTarget::Track {
+                path: track.path.clone(),
+                label,
+            }
+        })
+        .collect()
+}
+
+/// Decide qué significa ir a (o reproducir, con `play`) cada tipo de destino
+///
+/// Artistas y álbumes no tienen vista propia: ir a ellos abre la carpeta más
+/// profunda que contiene todas sus pistas, y a una pista, su carpeta. Un comando se ejecuta igual con o
+/// sin `play`.
+pub fn resolve(target: &PaletteTarget, play: bool, library: &PlaylistService) -> PaletteAction {
+    let tracks = match target {
+        PaletteTarget::Command { name, .. } => return PaletteAction::RunCommand(name),
+        PaletteTarget::Folder(path) if play => return PaletteAction::PlayFolder(path.clone()),
+        PaletteTarget::Folder(path) => return PaletteAction::Browse(path.clone()),
+        PaletteTarget::Playlist(name) if play => return PaletteAction::PlayPlaylist(name.clone()),
+        PaletteTarget::Playlist(name) => return PaletteAction::OpenPlaylist(name.clone()),
+        PaletteTarget::Artist(artist) => library.get_artist_tracks(artist),
+        PaletteTarget::Album(album) => library.get_album_tracks(album),
+        PaletteTarget::Track { path, .. } => vec![path.clone()],
+    };
+    if tracks.is_empty() {
+        return PaletteAction::Nothing;
+    }
+    if play {
+        let source = match target {
+            PaletteTarget::Album(album) => album.album.clone(),
+            _ => target.label(),
+        };
+        return PaletteAction::PlayTracks { source, tracks };
+    }
+    match common_folder(&tracks) {
+        Some(folder) => PaletteAction::Browse(folder),
+        None => PaletteAction::Nothing,
+    }
+}
+
+/// Carpeta más profunda que contiene todas las rutas
+fn common_folder(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut common = paths.first()?.parent()?.to_path_buf();
+    for path in &paths[1..] {
+        while !path.starts_with(&common) {
+            common = common.parent().map(Path::to_path_buf)?;
+        }
+    }
+    Some(common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::metadata_service::TrackMetadata;
+
+    fn command(name: &'static str, description: &'static str) -> PaletteTarget {
+        PaletteTarget::Command {
+            name,
+            keys: "",
+            description,
+        }
+    }
+
+    fn targets() -> Vec<PaletteTarget> {
+        vec![
+            command("Shuffle library", "Shuffle every track"),
+            command("Rescan library", "Read the music folder again"),
+            PaletteTarget::Folder(PathBuf::from("/music/Rock")),
+            PaletteTarget::Playlist("Road trip".to_string()),
+            PaletteTarget::Artist("Library Tapes".to_string()),
+        ]
+    }
+
+    fn track(path: &str, artist: &str, album: &str) -> TrackMetadata {
+        TrackMetadata {
+            path: PathBuf::from(path),
+            title: Some(format!("Song {}", path.len())),
+            artist: Some(artist.to_string()),
+            album: Some(album.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_table() {
+        let targets = targets();
+        // (búsqueda, índices esperados en orden)
+        let table: [(&str, &[usize]); 7] = [
+            ("", &[0, 1, 2, 3, 4]),
+            ("  ", &[0, 1, 2, 3, 4]),
+            // El nombre de un comando pesa más que la etiqueta de otro destino
+            ("library", &[0, 1, 4]),
+            ("ROAD", &[3]),
+            ("rock", &[2]),
+            // Solo comandos, también por descripción
+            (">", &[0, 1]),
+            ("> music folder", &[1]),
+        ];
+        for (query, expected) in table {
+            assert_eq!(filter(&targets, query), expected, "{query:?}");
+        }
+        assert!(filter(&targets, ">road").is_empty());
+    }
+
+    #[test]
+    fn resolve_table() {
+        let library = PlaylistService::from_tracks(vec![
+            track("/music/Tapes/A/1.mp3", "Library Tapes", "A"),
+            track("/music/Tapes/B/1.mp3", "Library Tapes", "B"),
+        ]);
+        let folder = PathBuf::from("/music/Rock");
+        let cases = [
+            (
+                command("Shuffle library", ""),
+                false,
+                PaletteAction::RunCommand("Shuffle library"),
+            ),
+            (
+                command("Shuffle library", ""),
+                true,
+                PaletteAction::RunCommand("Shuffle library"),
+            ),
+            (
+                PaletteTarget::Folder(folder.clone()),
+                false,
+                PaletteAction::Browse(folder.clone()),
+            ),
+            (
+                PaletteTarget::Folder(folder.clone()),
+                true,
+                PaletteAction::PlayFolder(folder),
+            ),
+            (
+                PaletteTarget::Playlist("Mix".to_string()),
+                false,
+                PaletteAction::OpenPlaylist("Mix".to_string()),
+            ),
+            (
+                PaletteTarget::Playlist("Mix".to_string()),
+                true,
+                PaletteAction::PlayPlaylist("Mix".to_string()),
+            ),
+            // Un artista abre la carpeta más profunda con todas sus pistas
+            (
+                PaletteTarget::Artist("Library Tapes".to_string()),
+                false,
+                PaletteAction::Browse(PathBuf::from("/music/Tapes")),
+            ),
+            (
+                PaletteTarget::Artist("Library Tapes".to_string()),
+                true,
+                PaletteAction::PlayTracks {
+                    source: "Library Tapes".to_string(),
+                    tracks: vec![
+                        PathBuf::from("/music/Tapes/A/1.mp3"),
+                        PathBuf::from("/music/Tapes/B/1.mp3"),
+                    ],
+                },
+            ),
+            (
+                PaletteTarget::Artist("Nobody".to_string()),
+                true,
+                PaletteAction::Nothing,
+            ),
+            (
+                PaletteTarget::Track {
+                    path: PathBuf::from("/music/Tapes/B/1.mp3"),
+                    label: "Song — Library Tapes".to_string(),
+                },
+                false,
+                PaletteAction::Browse(PathBuf::from("/music/Tapes/B")),
+            ),
+        ];
+        for (target, play, expected) in cases {
+            assert_eq!(
+                resolve(&target, play, &library),
+                expected,
+                "{target:?} {play}"
+            );
+        }
+
+        let album = library
+            .get_album_list()
+            .into_iter()
+            .find(|album| album.album == "B")
+            .unwrap();
+        assert_eq!(
+            resolve(&PaletteTarget::Album(album), true, &library),
+            PaletteAction::PlayTracks {
+                source: "B".to_string(),
+                tracks: vec![PathBuf::from("/music/Tapes/B/1.mp3")],
+            }
+        );
+    }
+
+    #[test]
+    fn common_folder_of_unrelated_paths_is_the_root() {
+        let paths = [PathBuf::from("/a/b/1.mp3"), PathBuf::from("/c/2.mp3")];
+        assert_eq!(common_folder(&paths), Some(PathBuf::from("/")));
+        assert_eq!(common_folder(&[]), None);
+    }
+}
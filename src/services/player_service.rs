@@ -1,12 +1,59 @@
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use crate::services::shuffle_service;
+use crate::services::trim_service::TrimPoints;
+use lofty::{AudioFile, FileType, ParseOptions, Probe};
+use rodio::cpal::{self, SampleRate, traits::HostTrait};
+use rodio::decoder::DecoderError;
+use rodio::{Decoder, DeviceTrait, OutputStream, OutputStreamHandle, Sink, Source};
 use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::HashMap,
     fs::File,
     io::BufReader,
-    path::PathBuf,
-    sync::mpsc::{self, Receiver, Sender},
-    thread,
+    path::{Path, PathBuf},
+    sync::{
+        Once,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
 };
 
+/// Intervalo con el que el hilo revisa si terminó la pista actual
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Intervalo con el que el reproductor reporta la posición de la pista actual
+const STATUS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Salto del reloj entre dos ticks a partir del cual se supone que el equipo se suspendió
+const SLEEP_GAP: Duration = Duration::from_secs(5);
+
+/// Tiempo que la posición puede quedar quieta, sonando, antes de dar la salida por muerta
+const STALL_LIMIT: Duration = Duration::from_secs(3);
+
+/// Tiempo que se espera a la posición después de un salto del reloj
+const WAKE_STALL_LIMIT: Duration = Duration::from_secs(1);
+
+/// Espera máxima entre dos intentos de reabrir una salida que sigue muerta
+const MAX_REOPEN_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Nombre del hilo de audio; su panic no se imprime porque rompería la interfaz
+const PLAYER_THREAD_NAME: &str = "player";
+
+static QUIET_PANIC_HOOK: Once = Once::new();
+
+/// Reinicios del hilo de audio permitidos dentro de `RESTART_WINDOW`; después se abandona
+const MAX_RESTARTS: usize = 3;
+
+/// Ventana en la que se cuentan los reinicios recientes
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// Espera antes del segundo reinicio dentro de la ventana; se duplica en cada uno más
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Bucle que corre en el hilo de audio
+type PlayerLoop = fn(Receiver<PlayerCommand>, Sender<PlayerStatus>) -> Result<(), PlayerError>;
+
 /// Comandos que pueden enviarse al reproductor de audio
 #[derive(Debug, Clone)]
 pub enum PlayerCommand {
@@ -20,23 +67,193 @@ pub enum PlayerCommand {
     TogglePause,
     /// Establece el volumen (0.0 a 2.0)
     SetVolume(f32),
-    /// Incrementa el volumen en 0.1
-    VolumeUp,
-    /// Decrementa el volumen en 0.1
-    VolumeDown,
     /// Detiene la reproducción
     Stop,
     /// Salta a la siguiente pista
     SkipNext,
+    /// Inserta una pista justo después de la actual
+    InsertNext(PathBuf),
+    /// Agrega una pista al final de la cola
+    Enqueue(PathBuf),
+    /// Agrega pistas en posiciones al azar entre las que todavía no sonaron
+    ///
+    /// Sirve para seguir llenando una cola aleatoria por tandas sin que las
+    /// primeras tandas suenen siempre antes.
+    EnqueueShuffled(Vec<PathBuf>),
+    /// Reabre la salida a la frecuencia de cada pista cuando el dispositivo la admite
+    SetPreferNativeRate(bool),
+    /// Tras reabrir la salida después de una suspensión, seguir sonando (`true`) o pausar
+    SetResumeAfterSleep(bool),
+    /// Reemplaza los puntos de inicio y fin por pista que se aplican al reproducir
+    SetTrims(HashMap<PathBuf, TrimPoints>),
+    /// Pide una copia de la cola, que llega como `PlayerStatus::QueueSnapshot`
+    QueueStatus,
     /// Cierra el reproductor
     Quit,
+    /// Provoca un panic en el hilo de audio, para probar la recuperación
+    #[cfg(test)]
+    PanicNow,
 }
 
 /// Estados que el reproductor puede reportar
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum PlayerStatus {
     /// Volumen actual (0.0 - 2.0)
     Volume(f32),
+    /// Comenzó a sonar una nueva pista de la cola
+    TrackChanged(PathBuf),
+    /// Posición de reproducción dentro de la pista actual
+    Position(Duration),
+    /// La cola terminó o se detuvo la reproducción
+    Stopped,
+    /// Error no fatal (dispositivo no disponible, salida remuestreada); el
+    /// mensaje va en inglés y la interfaz lo muestra tal cual
+    Error(String),
+    /// Una pista de la cola no se pudo reproducir y se saltó
+    TrackError(PathBuf, TrackProblem),
+    /// Frecuencia de muestreo de la pista actual y de la salida, en Hz
+    AudioFormat { track_rate: u32, output_rate: u32 },
+    /// Copia de la cola y de la pista que suena; vacía si no suena nada
+    QueueSnapshot {
+        current_index: usize,
+        paths: Vec<PathBuf>,
+    },
+    /// Se reabrió la salida tras una suspensión y la pista siguió donde estaba
+    ResumedAfterSleep { paused: bool },
+}
+
+/// Qué hacer con la salida cuando la pista tiene otra frecuencia de muestreo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    /// Mantener la salida actual (misma frecuencia o preferencia desactivada)
+    Keep,
+    /// Reabrir la salida a la frecuencia indicada
+    Reopen(u32),
+    /// El dispositivo no admite la frecuencia: se remuestrea
+    Resample,
+}
+
+/// Decide si reabrir la salida a la frecuencia nativa de la pista
+///
+/// `supported` son los rangos `(mínimo, máximo)` de frecuencias que acepta el dispositivo.
+pub fn choose_output_rate(
+    track_rate: u32,
+    output_rate: u32,
+    supported: &[(u32, u32)],
+    prefer_native: bool,
+) -> RateDecision {
+    if !prefer_native || track_rate == output_rate {
+        RateDecision::Keep
+    } else if supported
+        .iter()
+        .any(|&(min, max)| (min..=max).contains(&track_rate))
+    {
+        RateDecision::Reopen(track_rate)
+    } else {
+        RateDecision::Resample
+    }
+}
+
+/// Motivo por el que una pista no se pudo reproducir
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackProblem {
+    /// No se pudo abrir el archivo
+    Unreadable,
+    /// El archivo está vacío
+    Empty,
+    /// Compra protegida con DRM (por ejemplo, AAC de la vieja tienda de iTunes)
+    DrmProtected,
+    /// El contenedor se reconoce pero su códec no se puede decodificar
+    UnsupportedCodec,
+    /// El formato no se reconoce
+    UnknownFormat,
+    /// El formato se reconoce pero los datos están dañados
+    Corrupted,
+}
+
+impl TrackProblem {
+    /// Código estable para la salida JSON de `--stdout-status`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Unreadable => "unreadable",
+            Self::Empty => "empty",
+            Self::DrmProtected => "drm_protected",
+            Self::UnsupportedCodec => "unsupported_codec",
+            Self::UnknownFormat => "unknown_format",
+            Self::Corrupted => "corrupted",
+        }
+    }
+}
+
+/// Texto en inglés, como el resto de la interfaz
+impl std::fmt::Display for TrackProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreadable => write!(f, "can't open the file"),
+            Self::Empty => write!(f, "empty file"),
+            Self::DrmProtected => write!(f, "DRM protected"),
+            Self::UnsupportedCodec => write!(f, "unsupported codec"),
+            Self::UnknownFormat => write!(f, "unknown format"),
+            Self::Corrupted => write!(f, "corrupted file"),
+        }
+    }
+}
+
+/// Lo que se sabe de un archivo que el decodificador rechazó
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileHints {
+    /// Tamaño en bytes
+    pub len: u64,
+    /// Tipo detectado por lofty a partir del contenido
+    pub file_type: Option<FileType>,
+    /// El contenedor MP4 declara una pista cifrada (`drms`)
+    pub drm_protected: bool,
+}
+
+impl FileHints {
+    /// Reúne las pistas disponibles; lo que no se puede leer queda vacío
+    fn read(path: &Path) -> Self {
+        let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let file_type = Probe::open(path)
+            .and_then(|probe| Ok(probe.guess_file_type()?))
+            .ok()
+            .and_then(|probe| probe.file_type());
+        let drm_protected = file_type == Some(FileType::Mp4)
+            && File::open(path)
+                .ok()
+                .and_then(|mut file| {
+                    lofty::mp4::Mp4File::read_from(&mut file, ParseOptions::new()).ok()
+                })
+                .is_some_and(|mp4| mp4.properties().is_drm_protected());
+        Self {
+            len,
+            file_type,
+            drm_protected,
+        }
+    }
+}
+
+/// Decide por qué el decodificador rechazó un archivo
+///
+/// El tamaño y el DRM pesan más que el error: un archivo cifrado también
+/// aparece como formato no reconocido.
+pub fn classify_decode_failure(hints: &FileHints, error: &DecoderError) -> TrackProblem {
+    if hints.len == 0 {
+        TrackProblem::Empty
+    } else if hints.drm_protected {
+        TrackProblem::DrmProtected
+    } else {
+        match error {
+            DecoderError::UnrecognizedFormat | DecoderError::NoStreams => {
+                if hints.file_type.is_some() {
+                    TrackProblem::UnsupportedCodec
+                } else {
+                    TrackProblem::UnknownFormat
+                }
+            }
+            _ => TrackProblem::Corrupted,
+        }
+    }
 }
 
 /// Error posibles al inicializar el reproductor
@@ -46,11 +263,12 @@ pub enum PlayerError {
     AudioDeviceError(String),
 }
 
+/// Texto en inglés: llega a la interfaz como `PlayerStatus::Error`
 impl std::fmt::Display for PlayerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PlayerError::AudioDeviceError(msg) => {
-                write!(f, "Error al inicializar el dispositivo de audio: {}", msg)
+                write!(f, "Can't start the audio device: {}", msg)
             }
         }
     }
@@ -63,197 +281,993 @@ pub struct PlayerService {
     sender: Sender<PlayerCommand>,
     /// Canal para recibir actualizaciones de estado del reproductor
     pub receiver: Receiver<PlayerStatus>,
+    /// Hilo de audio, si hay uno; se revisa para detectar un panic
+    thread: Option<JoinHandle<()>>,
+    /// Ajustes enviados, para reaplicarlos si hay que reiniciar el hilo
+    settings: RefCell<PlayerSettings>,
+    /// Cuántas veces se reemplazó o vació la cola
+    generation: Cell<u64>,
+    /// Bucle del hilo de audio, el mismo en cada reinicio
+    player_loop: PlayerLoop,
+    /// Momentos de los últimos panics, para limitar los reinicios
+    crashes: Vec<Instant>,
+    /// Panic que espera su reinicio y desde cuándo se puede reiniciar
+    pending_restart: Option<(String, Instant)>,
+}
+
+/// Qué pasó con el hilo de audio después de un panic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrashRecovery {
+    /// Se lanzó un hilo nuevo; lleva el mensaje del panic
+    Restarted(String),
+    /// Falló demasiadas veces seguidas y ya no se reinicia
+    GaveUp(String),
+}
+
+/// Últimos ajustes enviados al hilo de audio
+#[derive(Debug, Default)]
+struct PlayerSettings {
+    volume: Option<f32>,
+    prefer_native_rate: Option<bool>,
+    resume_after_sleep: Option<bool>,
+    trims: Option<HashMap<PathBuf, TrimPoints>>,
+}
+
+impl PlayerSettings {
+    /// Guarda el ajuste que lleva el comando, si lleva uno
+    fn remember(&mut self, cmd: &PlayerCommand) {
+        match cmd {
+            PlayerCommand::SetVolume(volume) => self.volume = Some(*volume),
+            PlayerCommand::SetPreferNativeRate(prefer) => self.prefer_native_rate = Some(*prefer),
+            PlayerCommand::SetResumeAfterSleep(resume) => self.resume_after_sleep = Some(*resume),
+            PlayerCommand::SetTrims(trims) => self.trims = Some(trims.clone()),
+            _ => {}
+        }
+    }
+
+    /// Comandos que devuelven un hilo nuevo a estos ajustes
+    fn commands(&self) -> Vec<PlayerCommand> {
+        let mut commands = Vec::new();
+        if let Some(volume) = self.volume {
+            commands.push(PlayerCommand::SetVolume(volume));
+        }
+        if let Some(prefer) = self.prefer_native_rate {
+            commands.push(PlayerCommand::SetPreferNativeRate(prefer));
+        }
+        if let Some(resume) = self.resume_after_sleep {
+            commands.push(PlayerCommand::SetResumeAfterSleep(resume));
+        }
+        if let Some(trims) = &self.trims {
+            commands.push(PlayerCommand::SetTrims(trims.clone()));
+        }
+        commands
+    }
+}
+
+/// Reproductor sin hilo ni salida de audio, para entornos sin dispositivo
+///
+/// Los comandos fallan con `Err` (el canal está desconectado) y nunca llega ningún estado.
+impl Default for PlayerService {
+    fn default() -> Self {
+        let (cmd_tx, _) = mpsc::channel();
+        let (_, status_rx) = mpsc::channel();
+        Self {
+            sender: cmd_tx,
+            receiver: status_rx,
+            thread: None,
+            settings: RefCell::default(),
+            generation: Cell::new(0),
+            player_loop: Self::player_loop,
+            crashes: Vec::new(),
+            pending_restart: None,
+        }
+    }
 }
 
 impl PlayerService {
-    /// Crea un nuevo servicio de reproducción
+    /// Crea un servicio de reproducción con salida de audio real
     ///
     /// Inicializa el hilo de reproducción en segundo plano. Si no hay dispositivo
     /// de audio disponible, el hilo termina inmediatamente pero el servicio sigue
     /// funcionando (los comandos se ignorarán silenciosamente).
-    pub fn new() -> Self {
-        let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (status_tx, status_rx) = mpsc::channel();
+    pub fn with_audio() -> Self {
+        Self::with_loop(Self::player_loop)
+    }
 
-        thread::spawn(move || {
-            let _ = Self::player_loop(cmd_rx, status_tx);
+    /// Servicio cuyo hilo corre `player_loop`; las pruebas lo usan sin dispositivo
+    fn with_loop(player_loop: PlayerLoop) -> Self {
+        QUIET_PANIC_HOOK.call_once(|| {
+            let default_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if thread::current().name() != Some(PLAYER_THREAD_NAME) {
+                    default_hook(info);
+                }
+            }));
         });
 
+        let (sender, receiver, thread) = Self::spawn_player(player_loop);
         Self {
-            sender: cmd_tx,
-            receiver: status_rx,
+            sender,
+            receiver,
+            thread,
+            settings: RefCell::default(),
+            generation: Cell::new(0),
+            player_loop,
+            crashes: Vec::new(),
+            pending_restart: None,
         }
     }
 
+    fn spawn_player(
+        player_loop: PlayerLoop,
+    ) -> (
+        Sender<PlayerCommand>,
+        Receiver<PlayerStatus>,
+        Option<JoinHandle<()>>,
+    ) {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (status_tx, status_rx) = mpsc::channel();
+
+        let thread = thread::Builder::new()
+            .name(PLAYER_THREAD_NAME.to_string())
+            .spawn(move || {
+                if let Err(e) = player_loop(cmd_rx, status_tx.clone()) {
+                    let _ = status_tx.send(PlayerStatus::Error(e.to_string()));
+                }
+            })
+            .ok();
+
+        (cmd_tx, status_rx, thread)
+    }
+
     /// Envía un comando al reproductor
     ///
     /// Retorna `Err` si el hilo de reproducción ha terminado
     pub fn send(&self, cmd: PlayerCommand) -> Result<(), mpsc::SendError<PlayerCommand>> {
+        self.settings.borrow_mut().remember(&cmd);
+        if matches!(
+            cmd,
+            PlayerCommand::PlaySong(_)
+                | PlayerCommand::PlayAlbum(_)
+                | PlayerCommand::PlayShuffle(_)
+                | PlayerCommand::Stop
+        ) {
+            self.generation.set(self.generation.get() + 1);
+        }
         self.sender.send(cmd)
     }
 
+    /// Cuenta que cambia cada vez que se envía algo que reemplaza o vacía la cola
+    ///
+    /// Quien sigue agregando pistas a una cola que empezó puede compararla para
+    /// saber si mientras tanto se empezó a reproducir otra cosa.
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    /// Reinicia el hilo de audio si terminó por un panic
+    ///
+    /// El hilo nuevo recibe el último volumen, la preferencia de frecuencia y los
+    /// recortes enviados, pero empieza con la cola vacía. El primer panic se
+    /// reinicia enseguida; si vuelve a fallar dentro de `RESTART_WINDOW` se espera
+    /// `RESTART_BACKOFF`, el doble la vez siguiente, y pasados `MAX_RESTARTS` se
+    /// abandona. Si el hilo sigue vivo, terminó normalmente (`Quit`, sin
+    /// dispositivo) o el reinicio todavía espera, retorna `None`.
+    pub fn restart_if_crashed(&mut self) -> Option<CrashRecovery> {
+        self.restart_if_crashed_at(Instant::now())
+    }
+
+    fn restart_if_crashed_at(&mut self, now: Instant) -> Option<CrashRecovery> {
+        if self.pending_restart.is_none() {
+            if !self.thread.as_ref()?.is_finished() {
+                return None;
+            }
+            let payload = self.thread.take()?.join().err()?;
+            let message = panic_message(payload.as_ref());
+
+            self.crashes
+                .retain(|crash| now.saturating_duration_since(*crash) < RESTART_WINDOW);
+            self.crashes.push(now);
+            let recent = self.crashes.len();
+            if recent > MAX_RESTARTS {
+                return Some(CrashRecovery::GaveUp(message));
+            }
+            let delay = match recent {
+                1 => Duration::ZERO,
+                n => RESTART_BACKOFF * 2u32.pow(n as u32 - 2),
+            };
+            self.pending_restart = Some((message, now + delay));
+        }
+        let (_, at) = self.pending_restart.as_ref()?;
+        if now < *at {
+            return None;
+        }
+        let (message, _) = self.pending_restart.take()?;
+
+        let (sender, receiver, thread) = Self::spawn_player(self.player_loop);
+        self.sender = sender;
+        self.receiver = receiver;
+        self.thread = thread;
+        for cmd in self.settings.borrow().commands() {
+            let _ = self.sender.send(cmd);
+        }
+        Some(CrashRecovery::Restarted(message))
+    }
+
     fn player_loop(
         rx: Receiver<PlayerCommand>,
         status_tx: Sender<PlayerStatus>,
     ) -> Result<(), PlayerError> {
-        let (_stream, handle) = OutputStream::try_default().map_err(|e| {
-            PlayerError::AudioDeviceError(format!(
-                "No se pudo obtener dispositivo por defecto: {}",
-                e
-            ))
+        let (stream, handle) = OutputStream::try_default().map_err(|e| {
+            PlayerError::AudioDeviceError(format!("no default output device: {}", e))
         })?;
 
-        let mut sink: Option<Sink> = None;
-        let mut current_volume = 1.0f32;
+        let mut playback = Playback::new(stream, handle, status_tx);
 
-        while let Ok(cmd) = rx.recv() {
-            match cmd {
-                PlayerCommand::PlaySong(path) => {
-                    let _ = Self::play_single_song(&handle, &mut sink, &path, current_volume);
-                }
-                PlayerCommand::PlayAlbum(tracks) => {
-                    let _ = Self::play_tracks(&handle, &mut sink, &tracks, current_volume);
+        loop {
+            let cmd = match rx.recv_timeout(TICK_INTERVAL) {
+                Ok(cmd) => cmd,
+                Err(RecvTimeoutError::Timeout) => {
+                    playback.tick();
+                    continue;
                 }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            match cmd {
+                PlayerCommand::PlaySong(path) => playback.play(vec![path]),
+                PlayerCommand::PlayAlbum(tracks) => playback.play(tracks),
                 PlayerCommand::PlayShuffle(mut tracks) => {
                     use rand::seq::SliceRandom;
                     tracks.shuffle(&mut rand::rng());
-                    let _ = Self::play_tracks(&handle, &mut sink, &tracks, current_volume);
-                }
-                PlayerCommand::TogglePause => {
-                    if let Some(ref s) = sink {
-                        if s.is_paused() {
-                            s.play();
-                        } else {
-                            s.pause();
-                        }
-                    }
-                }
-                PlayerCommand::SetVolume(volume) => {
-                    current_volume = Self::update_volume(&sink, volume.clamp(0.0, 2.0), &status_tx);
-                }
-                PlayerCommand::VolumeUp => {
-                    current_volume =
-                        Self::update_volume(&sink, (current_volume + 0.1).min(2.0), &status_tx);
-                }
-                PlayerCommand::VolumeDown => {
-                    current_volume =
-                        Self::update_volume(&sink, (current_volume - 0.1).max(0.0), &status_tx);
-                }
-                PlayerCommand::Stop => {
-                    if let Some(s) = sink.take() {
-                        s.stop();
-                    }
-                }
-                PlayerCommand::SkipNext => {
-                    if let Some(ref s) = sink {
-                        s.skip_one();
-                    }
+                    playback.play(tracks);
                 }
+                PlayerCommand::TogglePause => playback.toggle_pause(),
+                PlayerCommand::SetVolume(volume) => playback.set_volume(volume),
+                PlayerCommand::Stop => playback.stop(),
+                PlayerCommand::SkipNext => playback.skip_next(),
+                PlayerCommand::InsertNext(path) => playback.insert_next(path),
+                PlayerCommand::Enqueue(path) => playback.enqueue(path),
+                PlayerCommand::EnqueueShuffled(tracks) => playback.enqueue_shuffled(tracks),
+                PlayerCommand::SetPreferNativeRate(prefer) => playback.prefer_native_rate = prefer,
+                PlayerCommand::SetResumeAfterSleep(resume) => playback.resume_after_sleep = resume,
+                PlayerCommand::SetTrims(trims) => playback.trims = trims,
+                PlayerCommand::QueueStatus => playback.send_queue_snapshot(),
                 PlayerCommand::Quit => {
                     // Limpieza explícita antes de salir
-                    if let Some(s) = sink.take() {
-                        s.stop();
-                    }
+                    playback.stop();
                     break;
                 }
+                #[cfg(test)]
+                PlayerCommand::PanicNow => panic!("PanicNow"),
             }
+
+            playback.tick();
         }
 
         Ok(())
     }
+}
+
+/// Texto de un panic, que suele ser `&str` o `String`
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Dispositivo de salida por defecto y su frecuencia de muestreo (0 si no se conoce)
+fn default_output() -> (Option<cpal::Device>, u32) {
+    let device = cpal::default_host().default_output_device();
+    let output_rate = device
+        .as_ref()
+        .and_then(|d| d.default_output_config().ok())
+        .map(|c| c.sample_rate().0)
+        .unwrap_or(0);
+    (device, output_rate)
+}
+
+/// Detecta que la salida de audio quedó muerta tras una suspensión del equipo
+///
+/// La prueba es una posición que no avanza mientras la pista debería sonar. Un
+/// salto grande del reloj entre dos ticks solo acorta la espera: puede ser una
+/// suspensión o un ajuste de NTP, y en el segundo caso el audio sigue vivo. En
+/// Linux el reloj monótono no corre durante la suspensión, por eso el salto se
+/// mide también con el del sistema.
+#[derive(Debug)]
+struct WakeDetector {
+    /// Cuándo fue el tick anterior, en reloj monótono y del sistema
+    last_tick: Option<(Instant, SystemTime)>,
+    /// Última posición vista mientras sonaba y desde cuándo no cambia
+    still_since: Option<(Duration, Instant)>,
+    /// Cuánto puede seguir quieta la posición antes de dar la salida por muerta
+    patience: Duration,
+}
+
+impl Default for WakeDetector {
+    fn default() -> Self {
+        Self {
+            last_tick: None,
+            still_since: None,
+            patience: STALL_LIMIT,
+        }
+    }
+}
+
+impl WakeDetector {
+    /// Registra un tick; si desde el anterior pasó más de `SLEEP_GAP` la
+    /// posición tiene solo `WAKE_STALL_LIMIT` para moverse
+    fn clock_tick(&mut self, now: Instant, wall: SystemTime) {
+        let gap = self.last_tick.map(|(last, last_wall)| {
+            let wall_gap = wall.duration_since(last_wall).unwrap_or_default();
+            now.duration_since(last).max(wall_gap)
+        });
+        self.last_tick = Some((now, wall));
+        if gap.is_some_and(|gap| gap > SLEEP_GAP) {
+            self.patience = self.patience.min(WAKE_STALL_LIMIT);
+        }
+    }
+
+    /// Registra la posición de la pista, `None` si está en pausa o no suena nada
+    ///
+    /// Retorna `true` cuando la posición lleva `patience` sin avanzar. Mientras
+    /// siga quieta vuelve a retornar `true` con esperas que se duplican hasta
+    /// `MAX_REOPEN_BACKOFF`; cuando avanza la espera vuelve a `STALL_LIMIT`.
+    fn stalled(&mut self, position: Option<Duration>, now: Instant) -> bool {
+        let Some(position) = position else {
+            self.still_since = None;
+            return false;
+        };
+        match self.still_since {
+            Some((last, since)) if last == position => {
+                if now.duration_since(since) < self.patience {
+                    return false;
+                }
+                self.still_since = Some((position, now));
+                self.patience = (self.patience * 2).clamp(STALL_LIMIT, MAX_REOPEN_BACKOFF);
+                true
+            }
+            Some(_) => {
+                self.still_since = Some((position, now));
+                self.patience = STALL_LIMIT;
+                false
+            }
+            None => {
+                self.still_since = Some((position, now));
+                false
+            }
+        }
+    }
+}
 
-    fn play_single_song(
-        handle: &OutputStreamHandle,
-        sink: &mut Option<Sink>,
-        path: &PathBuf,
-        volume: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+/// Estado de reproducción del hilo de audio
+///
+/// La cola vive aquí y no en el sink: el sink solo contiene la pista actual,
+/// lo que permite insertar o agregar pistas en cualquier posición.
+struct Playback {
+    // El stream debe seguir vivo mientras se use su handle; solo falta mientras se reabre
+    _stream: Option<OutputStream>,
+    handle: OutputStreamHandle,
+    /// Dispositivo de salida, necesario para reabrir el stream a otra frecuencia
+    device: Option<cpal::Device>,
+    /// Frecuencia de muestreo con la que está abierta la salida
+    output_rate: u32,
+    prefer_native_rate: bool,
+    /// Frecuencia de la última pista por la que se avisó que se remuestrea
+    resample_warned: Option<u32>,
+    status_tx: Sender<PlayerStatus>,
+    sink: Option<Sink>,
+    volume: f32,
+    paused: bool,
+    queue: Vec<PathBuf>,
+    current_index: Option<usize>,
+    last_position_report: Instant,
+    trims: HashMap<PathBuf, TrimPoints>,
+    /// Posición en la que termina la pista actual por un recorte
+    current_end: Option<Duration>,
+    /// Tras reabrir la salida por una suspensión, seguir sonando o quedar en pausa
+    resume_after_sleep: bool,
+    wake: WakeDetector,
+}
+
+impl Playback {
+    fn new(
+        stream: OutputStream,
+        handle: OutputStreamHandle,
+        status_tx: Sender<PlayerStatus>,
+    ) -> Self {
+        let (device, output_rate) = default_output();
+        Self {
+            _stream: Some(stream),
+            handle,
+            device,
+            output_rate,
+            prefer_native_rate: false,
+            resample_warned: None,
+            status_tx,
+            sink: None,
+            volume: 1.0,
+            paused: false,
+            queue: Vec::new(),
+            current_index: None,
+            last_position_report: Instant::now(),
+            trims: HashMap::new(),
+            current_end: None,
+            resume_after_sleep: true,
+            wake: WakeDetector::default(),
+        }
+    }
+
+    /// Reemplaza la cola y empieza por la primera pista
+    fn play(&mut self, tracks: Vec<PathBuf>) {
+        self.queue = tracks;
+        self.paused = false;
+        self.start_from(0);
+    }
+
+    /// Inicia la primera pista reproducible desde `index`
+    ///
+    /// Los archivos que no se pueden decodificar se omiten y se informan con
+    /// `PlayerStatus::TrackError`.
+    fn start_from(&mut self, index: usize) {
         // Detener reproducción anterior antes de iniciar nueva
-        if let Some(s) = sink.take() {
+        if let Some(s) = self.sink.take() {
             s.stop();
         }
 
-        let file = File::open(path)
-            .map_err(|e| format!("No se pudo abrir '{}': {}", path.display(), e))?;
+        for i in index..self.queue.len() {
+            let source = match Self::open_source(&self.queue[i]) {
+                Ok(source) => source,
+                Err(problem) => {
+                    let _ = self
+                        .status_tx
+                        .send(PlayerStatus::TrackError(self.queue[i].clone(), problem));
+                    continue;
+                }
+            };
+            let track_rate = source.sample_rate();
+            let trim = self
+                .trims
+                .get(&self.queue[i])
+                .map(|trim| trim.clamped(source.total_duration()))
+                .unwrap_or_default();
+            self.apply_rate_policy(track_rate);
+            let Ok(new_sink) = Sink::try_new(&self.handle) else {
+                break;
+            };
+
+            new_sink.set_volume(self.volume);
+            new_sink.append(source);
+            if let Some(start) = trim.start()
+                && let Err(e) = new_sink.try_seek(start)
+            {
+                let _ = self.status_tx.send(PlayerStatus::Error(format!(
+                    "Can't seek to the trimmed start: {}",
+                    e
+                )));
+            }
+            if self.paused {
+                new_sink.pause();
+            }
+            self.sink = Some(new_sink);
+            self.current_end = trim.end();
+            self.current_index = Some(i);
+            let _ = self
+                .status_tx
+                .send(PlayerStatus::TrackChanged(self.queue[i].clone()));
+            let _ = self.status_tx.send(PlayerStatus::AudioFormat {
+                track_rate,
+                output_rate: self.output_rate,
+            });
+            return;
+        }
+
+        if self.current_index.take().is_some() || index == 0 {
+            let _ = self.status_tx.send(PlayerStatus::Stopped);
+        }
+    }
+
+    /// Reabre la salida a la frecuencia de la pista si corresponde
+    ///
+    /// Si el dispositivo no la admite se sigue con la salida actual (rodio
+    /// remuestrea) y se avisa una vez por frecuencia; si falla al reabrir se
+    /// vuelve a la configuración por defecto del dispositivo y se informa el motivo.
+    fn apply_rate_policy(&mut self, track_rate: u32) {
+        // Sin preferencia no hace falta consultar el dispositivo en cada pista
+        if !self.prefer_native_rate || track_rate == self.output_rate {
+            self.resample_warned = None;
+            return;
+        }
+        let Some(device) = &self.device else {
+            return;
+        };
+        let Ok(default_config) = device.default_output_config() else {
+            return;
+        };
+        let channels = default_config.channels();
+        let ranges: Vec<_> = device
+            .supported_output_configs()
+            .map(|configs| configs.filter(|c| c.channels() == channels).collect())
+            .unwrap_or_default();
+        let supported: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|c| (c.min_sample_rate().0, c.max_sample_rate().0))
+            .collect();
+
+        match choose_output_rate(
+            track_rate,
+            self.output_rate,
+            &supported,
+            self.prefer_native_rate,
+        ) {
+            RateDecision::Keep => {}
+            RateDecision::Resample => {
+                if self.resample_warned != Some(track_rate) {
+                    self.resample_warned = Some(track_rate);
+                    let _ = self.status_tx.send(PlayerStatus::Error(format!(
+                        "Output doesn't support {} Hz, resampling to {} Hz",
+                        track_rate, self.output_rate
+                    )));
+                }
+            }
+            RateDecision::Reopen(rate) => {
+                self.resample_warned = None;
+                let config = ranges
+                    .iter()
+                    .find(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&rate))
+                    .map(|c| c.with_sample_rate(SampleRate(rate)));
+                // Hay dispositivos que no se dejan abrir dos veces: se cierra antes el actual
+                self._stream = None;
+                let reopened = config
+                    .ok_or_else(|| "no output configuration for that rate".to_string())
+                    .and_then(|config| {
+                        OutputStream::try_from_device_config(device, config)
+                            .map_err(|e| e.to_string())
+                    });
+                match reopened {
+                    Ok((stream, handle)) => {
+                        self._stream = Some(stream);
+                        self.handle = handle;
+                        self.output_rate = rate;
+                    }
+                    Err(e) => {
+                        if let Ok((stream, handle)) = OutputStream::try_from_device(device) {
+                            self._stream = Some(stream);
+                            self.handle = handle;
+                            self.output_rate = default_config.sample_rate().0;
+                        }
+                        let _ = self.status_tx.send(PlayerStatus::Error(format!(
+                            "Can't open the output at {} Hz: {}",
+                            rate, e
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Abre y decodifica un archivo de audio
+    fn open_source(path: &Path) -> Result<Decoder<BufReader<File>>, TrackProblem> {
+        let file = File::open(path).map_err(|_| TrackProblem::Unreadable)?;
 
-        // Intentar decodificar el archivo
         // Nota: rodio/symphonia decodifica frames de audio MP3. Si hay frames corruptos,
         // fallará aquí. No hay forma de hacerlo más permisivo sin cambiar bibliotecas,
-        // pero al menos manejamos el error gracefully.
-        let source = match Decoder::new(BufReader::new(file)) {
-            Ok(src) => src,
-            Err(e) => {
+        // pero al menos se informa el motivo.
+        match Decoder::new(BufReader::new(file)) {
+            Ok(src) => Ok(src),
+            Err(_) => {
                 // Intentar una segunda vez con un buffer más pequeño (a veces ayuda)
-                let file2 = File::open(path).map_err(|_| {
-                    format!(
-                        "Archivo corrupto o formato no soportado: {}",
-                        path.display()
-                    )
-                })?;
-                match Decoder::new(BufReader::with_capacity(4096, file2)) {
-                    Ok(src) => src,
-                    Err(_) => {
-                        return Err(format!(
-                            "El archivo tiene frames corruptos o encoding inválido: {}",
-                            path.display()
-                        )
-                        .into());
-                    }
-                }
+                let file2 = File::open(path).map_err(|_| TrackProblem::Unreadable)?;
+                Decoder::new(BufReader::with_capacity(4096, file2))
+                    .map_err(|e| classify_decode_failure(&FileHints::read(path), &e))
             }
-        };
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        if let Some(ref s) = self.sink {
+            if s.is_paused() {
+                s.play();
+            } else {
+                s.pause();
+            }
+            self.paused = s.is_paused();
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 2.0);
+        if let Some(ref s) = self.sink {
+            s.set_volume(self.volume);
+        }
+        if self
+            .status_tx
+            .send(PlayerStatus::Volume(self.volume))
+            .is_err()
+        {
+            // El receptor se ha desconectado, continuamos de todos modos
+        }
+    }
 
-        let new_sink = Sink::try_new(handle).map_err(|e| format!("Error de audio: {}", e))?;
+    fn stop(&mut self) {
+        if let Some(s) = self.sink.take() {
+            s.stop();
+        }
+        self.queue.clear();
+        if self.current_index.take().is_some() {
+            let _ = self.status_tx.send(PlayerStatus::Stopped);
+        }
+    }
 
-        new_sink.set_volume(volume);
-        new_sink.append(source);
-        *sink = Some(new_sink);
+    fn skip_next(&mut self) {
+        if let Some(index) = self.current_index {
+            self.start_from(index + 1);
+        }
+    }
 
-        Ok(())
+    fn insert_next(&mut self, path: PathBuf) {
+        match self.current_index {
+            Some(index) => self.queue.insert(index + 1, path),
+            None => self.play(vec![path]),
+        }
     }
 
-    fn play_tracks(
-        handle: &OutputStreamHandle,
-        sink: &mut Option<Sink>,
-        tracks: &[PathBuf],
-        volume: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Detener reproducción anterior antes de iniciar nueva
-        if let Some(s) = sink.take() {
+    fn enqueue(&mut self, path: PathBuf) {
+        self.queue.push(path);
+        if self.current_index.is_none() {
+            let last = self.queue.len() - 1;
+            self.start_from(last);
+        }
+    }
+
+    fn enqueue_shuffled(&mut self, tracks: Vec<PathBuf>) {
+        let first_pending = self
+            .current_index
+            .map_or(self.queue.len(), |index| index + 1);
+        shuffle_service::insert_shuffled(&mut self.queue, first_pending, tracks, &mut rand::rng());
+        if self.current_index.is_none() && first_pending < self.queue.len() {
+            self.start_from(first_pending);
+        }
+    }
+
+    fn send_queue_snapshot(&self) {
+        let snapshot = match self.current_index {
+            Some(current_index) => PlayerStatus::QueueSnapshot {
+                current_index,
+                paths: self.queue.clone(),
+            },
+            None => PlayerStatus::QueueSnapshot {
+                current_index: 0,
+                paths: Vec::new(),
+            },
+        };
+        let _ = self.status_tx.send(snapshot);
+    }
+
+    /// Reabre la salida y retoma la pista actual en la última posición
+    ///
+    /// Tras una suspensión el stream queda muerto aunque el sink siga "sonando".
+    /// Si no se puede abrir el dispositivo se deja todo como estaba y se informa.
+    fn reopen_output(&mut self, index: usize) {
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok(output) => output,
+            Err(e) => {
+                let _ = self.status_tx.send(PlayerStatus::Error(format!(
+                    "Can't reopen the audio output: {}",
+                    e
+                )));
+                return;
+            }
+        };
+        let position = self.sink.as_ref().map_or(Duration::ZERO, Sink::get_pos);
+        if let Some(s) = self.sink.take() {
             s.stop();
         }
+        self._stream = Some(stream);
+        self.handle = handle;
+        (self.device, self.output_rate) = default_output();
+
+        let source = match Self::open_source(&self.queue[index]) {
+            Ok(source) => source,
+            Err(problem) => {
+                let _ = self
+                    .status_tx
+                    .send(PlayerStatus::TrackError(self.queue[index].clone(), problem));
+                self.start_from(index + 1);
+                return;
+            }
+        };
+        let track_rate = source.sample_rate();
+        self.apply_rate_policy(track_rate);
+        let Ok(sink) = Sink::try_new(&self.handle) else {
+            self.stop();
+            return;
+        };
+        sink.set_volume(self.volume);
+        sink.append(source);
+        if let Err(e) = sink.try_seek(position) {
+            let _ = self.status_tx.send(PlayerStatus::Error(format!(
+                "Can't return to the track position: {}",
+                e
+            )));
+        }
+        if !self.resume_after_sleep {
+            self.paused = true;
+        }
+        if self.paused {
+            sink.pause();
+        }
+        self.sink = Some(sink);
+        let _ = self.status_tx.send(PlayerStatus::ResumedAfterSleep {
+            paused: self.paused,
+        });
+        let _ = self.status_tx.send(PlayerStatus::AudioFormat {
+            track_rate,
+            output_rate: self.output_rate,
+        });
+        let _ = self.status_tx.send(PlayerStatus::Position(position));
+    }
 
-        let new_sink = Sink::try_new(handle)
-            .map_err(|e| format!("No se pudo crear el sink de audio: {}", e))?;
+    /// Avanza la cola cuando termina la pista y reporta la posición periódicamente
+    ///
+    /// También reabre la salida si parece que el equipo estuvo suspendido.
+    fn tick(&mut self) {
+        self.wake.clock_tick(Instant::now(), SystemTime::now());
+        let Some(index) = self.current_index else {
+            return;
+        };
 
-        new_sink.set_volume(volume);
+        // Llegar al fin recortado cuenta como terminar la pista
+        let past_end = self
+            .current_end
+            .zip(self.sink.as_ref())
+            .is_some_and(|(end, s)| s.get_pos() >= end);
+        if past_end || self.sink.as_ref().is_none_or(|s| s.empty()) {
+            self.start_from(index + 1);
+            return;
+        }
 
-        for path in tracks {
-            if let Ok(file) = File::open(path) {
-                // Intentar decodificar con buffer estándar
-                if let Ok(source) = Decoder::new(BufReader::new(file)) {
-                    new_sink.append(source);
-                } else if let Ok(file2) = File::open(path) {
-                    // Fallback: intentar con buffer pequeño
-                    if let Ok(source) = Decoder::new(BufReader::with_capacity(4096, file2)) {
-                        new_sink.append(source);
-                    }
-                }
+        let playing_position = self
+            .sink
+            .as_ref()
+            .filter(|s| !s.is_paused())
+            .map(Sink::get_pos);
+        if self.wake.stalled(playing_position, Instant::now()) {
+            self.reopen_output(index);
+            return;
+        }
+
+        if self.last_position_report.elapsed() >= STATUS_INTERVAL {
+            self.last_position_report = Instant::now();
+            if let Some(ref s) = self.sink {
+                let _ = self.status_tx.send(PlayerStatus::Position(s.get_pos()));
             }
-            // Los archivos que fallan se omiten silenciosamente
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_rate_follows_the_track_only_when_preferred_and_supported() {
+        let supported = [(44_100, 48_000), (88_200, 96_000)];
+        assert_eq!(
+            choose_output_rate(96_000, 48_000, &supported, false),
+            RateDecision::Keep
+        );
+        assert_eq!(
+            choose_output_rate(48_000, 48_000, &supported, true),
+            RateDecision::Keep
+        );
+        assert_eq!(
+            choose_output_rate(96_000, 48_000, &supported, true),
+            RateDecision::Reopen(96_000)
+        );
+        assert_eq!(
+            choose_output_rate(192_000, 48_000, &supported, true),
+            RateDecision::Resample
+        );
+        assert_eq!(
+            choose_output_rate(44_100, 48_000, &[], true),
+            RateDecision::Resample
+        );
+    }
 
-        *sink = Some(new_sink);
+    #[test]
+    fn default_service_has_no_thread_and_never_reports() {
+        let mut service = PlayerService::default();
+        assert!(service.send(PlayerCommand::SetVolume(0.5)).is_err());
+        assert!(service.send(PlayerCommand::Stop).is_err());
+        assert_eq!(service.generation(), 1);
+        assert!(matches!(
+            service.receiver.try_recv(),
+            Err(mpsc::TryRecvError::Disconnected)
+        ));
+        assert!(service.restart_if_crashed().is_none());
+    }
+
+    /// Bucle sin audio: repite el volumen como estado y muere con `PanicNow`
+    fn fake_loop(
+        rx: Receiver<PlayerCommand>,
+        status_tx: Sender<PlayerStatus>,
+    ) -> Result<(), PlayerError> {
+        for cmd in rx {
+            match cmd {
+                PlayerCommand::PanicNow => panic!("PanicNow"),
+                PlayerCommand::SetVolume(volume) => {
+                    let _ = status_tx.send(PlayerStatus::Error(format!("volume {volume}")));
+                }
+                PlayerCommand::Quit => break,
+                _ => {}
+            }
+        }
         Ok(())
     }
 
-    fn update_volume(sink: &Option<Sink>, volume: f32, status_tx: &Sender<PlayerStatus>) -> f32 {
-        if let Some(s) = sink {
-            s.set_volume(volume);
+    fn crash(service: &PlayerService) {
+        service.send(PlayerCommand::PanicNow).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !service.thread.as_ref().unwrap().is_finished() {
+            assert!(Instant::now() < deadline, "the player thread never died");
+            thread::sleep(Duration::from_millis(5));
         }
-        if status_tx.send(PlayerStatus::Volume(volume)).is_err() {
-            // El receptor se ha desconectado, continuamos de todos modos
+    }
+
+    #[test]
+    fn a_panicking_thread_is_replaced_with_the_same_settings() {
+        let mut service = PlayerService::with_loop(fake_loop);
+        service.send(PlayerCommand::SetVolume(0.5)).unwrap();
+        assert_eq!(service.restart_if_crashed(), None);
+
+        crash(&service);
+        assert!(service.send(PlayerCommand::SetVolume(0.5)).is_err());
+        assert_eq!(
+            service.restart_if_crashed(),
+            Some(CrashRecovery::Restarted("PanicNow".to_string()))
+        );
+        // El hilo nuevo recibió el volumen guardado y acepta comandos
+        let status = service.receiver.recv_timeout(Duration::from_secs(10));
+        assert!(matches!(status, Ok(PlayerStatus::Error(text)) if text == "volume 0.5"));
+        assert!(service.send(PlayerCommand::QueueStatus).is_ok());
+    }
+
+    #[test]
+    fn repeated_panics_back_off_and_then_give_up() {
+        let mut service = PlayerService::with_loop(fake_loop);
+        let start = Instant::now();
+        let restarted = Some(CrashRecovery::Restarted("PanicNow".to_string()));
+
+        crash(&service);
+        assert_eq!(service.restart_if_crashed_at(start), restarted);
+
+        crash(&service);
+        let second = start + Duration::from_secs(1);
+        assert_eq!(service.restart_if_crashed_at(second), None);
+        assert_eq!(
+            service.restart_if_crashed_at(second + RESTART_BACKOFF),
+            restarted
+        );
+
+        crash(&service);
+        let third = start + Duration::from_secs(5);
+        assert_eq!(service.restart_if_crashed_at(third), None);
+        assert_eq!(service.restart_if_crashed_at(third + RESTART_BACKOFF), None);
+        assert_eq!(
+            service.restart_if_crashed_at(third + RESTART_BACKOFF * 2),
+            restarted
+        );
+
+        crash(&service);
+        assert_eq!(
+            service.restart_if_crashed_at(start + Duration::from_secs(10)),
+            Some(CrashRecovery::GaveUp("PanicNow".to_string()))
+        );
+        assert_eq!(
+            service.restart_if_crashed_at(start + Duration::from_secs(20)),
+            None
+        );
+    }
+
+    #[test]
+    fn crashes_outside_the_window_are_forgotten() {
+        let mut service = PlayerService::with_loop(fake_loop);
+        let mut now = Instant::now();
+        for _ in 0..MAX_RESTARTS + 2 {
+            crash(&service);
+            assert_eq!(
+                service.restart_if_crashed_at(now),
+                Some(CrashRecovery::Restarted("PanicNow".to_string()))
+            );
+            now += RESTART_WINDOW;
         }
-        volume
+    }
+
+    const TICK: Duration = Duration::from_millis(50);
+
+    /// Ticks con el reloj del sistema corrido `offset` en el medio; retorna en
+    /// qué ticks la salida se dio por muerta
+    fn run_ticks(
+        detector: &mut WakeDetector,
+        ticks: u32,
+        wall_jump_at: u32,
+        offset: Duration,
+        position: impl Fn(u32) -> Duration,
+    ) -> Vec<u32> {
+        let start = Instant::now();
+        let wall_start = SystemTime::now();
+        (0..ticks)
+            .filter(|&i| {
+                let now = start + TICK * i;
+                let mut wall = wall_start + TICK * i;
+                if i >= wall_jump_at {
+                    wall += offset;
+                }
+                detector.clock_tick(now, wall);
+                detector.stalled(Some(position(i)), now)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_forward_clock_jump_with_audio_flowing_does_not_reopen() {
+        let mut detector = WakeDetector::default();
+        let reopened = run_ticks(&mut detector, 200, 20, Duration::from_secs(3600), |i| {
+            TICK * i
+        });
+        assert!(reopened.is_empty());
+        assert_eq!(detector.patience, STALL_LIMIT);
+    }
+
+    #[test]
+    fn a_clock_jump_with_a_frozen_position_reopens_quickly() {
+        let mut detector = WakeDetector::default();
+        let frozen = |i: u32| TICK * i.min(20);
+        let reopened = run_ticks(&mut detector, 60, 21, Duration::from_secs(3600), frozen);
+        // Quieta desde el tick 20: un segundo son 20 ticks
+        assert_eq!(reopened, vec![40]);
+    }
+
+    #[test]
+    fn a_backward_clock_jump_is_ignored() {
+        let mut detector = WakeDetector::default();
+        let start = Instant::now();
+        let wall = SystemTime::now();
+        detector.clock_tick(start, wall);
+        detector.clock_tick(start + TICK, wall - Duration::from_secs(3600));
+        assert_eq!(detector.patience, STALL_LIMIT);
+    }
+
+    #[test]
+    fn a_dead_output_is_retried_with_growing_waits() {
+        let mut detector = WakeDetector::default();
+        let ticks_per_second = 20;
+        let reopened = run_ticks(
+            &mut detector,
+            200 * ticks_per_second,
+            0,
+            Duration::ZERO,
+            |_| Duration::from_secs(7),
+        );
+        let seconds: Vec<u32> = reopened.iter().map(|i| i / ticks_per_second).collect();
+        assert_eq!(seconds, vec![3, 9, 21, 45, 75, 105, 135, 165, 195]);
+    }
+
+    #[test]
+    fn moving_again_resets_the_wait() {
+        let mut detector = WakeDetector::default();
+        let start = Instant::now();
+        let position = Duration::from_secs(7);
+        assert!(!detector.stalled(Some(position), start));
+        assert!(detector.stalled(Some(position), start + STALL_LIMIT));
+        assert_eq!(detector.patience, STALL_LIMIT * 2);
+
+        let later = start + STALL_LIMIT + TICK;
+        assert!(!detector.stalled(Some(position + TICK), later));
+        assert_eq!(detector.patience, STALL_LIMIT);
+        assert!(!detector.stalled(None, later + TICK));
     }
 }
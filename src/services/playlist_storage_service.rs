@@ -1,10 +1,24 @@
-use serde::{Deserialize, Serialize};
+use crate::models::track::TrackRef;
+use crate::utils::is_within_roots;
+use serde::de::{IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
 use std::env;
-use std::path::PathBuf;
+use std::fs::DirEntry;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const PLAYLISTS_SUBDIR: &str = "playlists";
 const PLAYLIST_EXTENSION: &str = "json";
 const CONFIG_DIR_NAME: &str = "rusted-player";
+/// Archivo de favoritos, fuera del directorio de playlists
+const FAVORITES_FILE: &str = "favorites.json";
+/// Nombre con el que se muestra la playlist de favoritos
+pub const FAVORITES_NAME: &str = "♥ Favorites";
+/// Máximo de registros de cantidad de pistas que se guardan por playlist
+const HISTORY_LIMIT: usize = 30;
+/// Caracteres que podrían causar path traversal o son inválidos en nombres de archivo
+const INVALID_NAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
 
 /// Obtiene el directorio de configuración del usuario
 ///
@@ -83,6 +97,8 @@ pub enum PlaylistError {
     Io(std::io::Error),
     Serialization(serde_json::Error),
     InvalidName(String),
+    /// Se intentó guardar una playlist de la que solo se leyó el resumen
+    NotLoaded(String),
 }
 
 impl std::fmt::Display for PlaylistError {
@@ -91,6 +107,7 @@ impl std::fmt::Display for PlaylistError {
             Self::Io(e) => write!(f, "Error de E/S: {}", e),
             Self::Serialization(e) => write!(f, "Error de serialización: {}", e),
             Self::InvalidName(s) => write!(f, "Nombre de playlist inválido: {}", s),
+            Self::NotLoaded(s) => write!(f, "La playlist '{}' no tiene sus pistas cargadas", s),
         }
     }
 }
@@ -100,7 +117,7 @@ impl std::error::Error for PlaylistError {
         match self {
             Self::Io(e) => Some(e),
             Self::Serialization(e) => Some(e),
-            Self::InvalidName(_) => None,
+            Self::InvalidName(_) | Self::NotLoaded(_) => None,
         }
     }
 }
@@ -117,13 +134,219 @@ impl From<serde_json::Error> for PlaylistError {
     }
 }
 
+/// Segundos transcurridos desde la época Unix
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cantidad de pistas de una playlist en un momento dado
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackCountSnapshot {
+    /// Momento del registro (segundos Unix)
+    pub timestamp: u64,
+    /// Cantidad de pistas en ese momento
+    pub track_count: usize,
+}
+
 /// Representa una lista de reproducción con nombre y pistas
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Playlist {
     /// Nombre de la lista (usado como nombre de archivo)
     pub name: String,
-    /// Rutas a los archivos de audio
-    pub tracks: Vec<PathBuf>,
+    /// Pistas de la lista; los archivos antiguos guardan solo la ruta
+    pub tracks: Vec<TrackRef>,
+    /// Fecha de creación (segundos Unix); ausente en archivos antiguos
+    #[serde(default)]
+    pub created: Option<u64>,
+    /// Fecha del último guardado (segundos Unix)
+    #[serde(default)]
+    pub modified: Option<u64>,
+    /// Registro de la cantidad de pistas en cada guardado que la cambió
+    #[serde(default)]
+    pub history: Vec<TrackCountSnapshot>,
+    /// Viene de otro programa (M3U, biblioteca importada) y sus rutas no son de confianza
+    ///
+    /// Las playlists que guarda el programa siempre lo escriben; un archivo que no
+    /// lo tiene llegó de afuera y se trata como importado.
+    #[serde(default = "assume_imported")]
+    pub imported: bool,
+    /// Permite reproducir las pistas de una playlist importada que están fuera de la biblioteca
+    #[serde(default)]
+    pub allow_external: bool,
+    /// Playlist de favoritos: se guarda aparte y no se puede eliminar
+    #[serde(skip)]
+    pub is_favorites: bool,
+    /// Cantidad de pistas cuando solo se leyó el resumen; `tracks` está vacío
+    #[serde(skip)]
+    summary_count: Option<usize>,
+}
+
+/// Resumen de una playlist: todo menos las pistas, que solo se cuentan
+#[derive(Deserialize)]
+struct PlaylistHeader {
+    name: String,
+    #[serde(deserialize_with = "count_tracks")]
+    tracks: usize,
+    #[serde(default)]
+    created: Option<u64>,
+    #[serde(default)]
+    modified: Option<u64>,
+    #[serde(default)]
+    history: Vec<TrackCountSnapshot>,
+    #[serde(default = "assume_imported")]
+    imported: bool,
+    #[serde(default)]
+    allow_external: bool,
+}
+
+/// Valor de `imported` cuando el archivo no lo dice
+fn assume_imported() -> bool {
+    true
+}
+
+/// Cuenta los elementos de la lista de pistas sin construirlos
+fn count_tracks<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    struct CountVisitor;
+
+    impl<'de> Visitor<'de> for CountVisitor {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("una lista de pistas")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+            let mut count = 0;
+            while seq.next_element::<IgnoredAny>()?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    deserializer.deserialize_seq(CountVisitor)
+}
+
+impl Playlist {
+    /// Crea una playlist nueva, sin fechas hasta que se guarde
+    pub fn new(name: String, tracks: Vec<PathBuf>) -> Self {
+        Self {
+            name,
+            tracks: tracks.into_iter().map(TrackRef::from).collect(),
+            created: None,
+            modified: None,
+            history: Vec::new(),
+            imported: false,
+            allow_external: false,
+            is_favorites: false,
+            summary_count: None,
+        }
+    }
+
+    /// Las pistas están en memoria (no es solo el resumen)
+    pub fn is_loaded(&self) -> bool {
+        self.summary_count.is_none()
+    }
+
+    /// Cantidad de pistas, esté cargada o no
+    pub fn track_count(&self) -> usize {
+        self.summary_count.unwrap_or(self.tracks.len())
+    }
+
+    /// Lee las pistas del disco si solo se tenía el resumen
+    ///
+    /// # Errores
+    /// - Retorna `Io` si el archivo ya no existe o no se puede leer
+    /// - Retorna `Serialization` si el archivo está dañado
+    pub fn load_tracks(&mut self) -> Result<(), PlaylistError> {
+        if self.is_loaded() {
+            return Ok(());
+        }
+        self.load_tracks_from(&playlist_file_path(&self.name)?)
+    }
+
+    /// Igual que `load_tracks`, leyendo el archivo indicado
+    fn load_tracks_from(&mut self, path: &Path) -> Result<(), PlaylistError> {
+        let full = read_playlist(path)?
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        self.tracks = full.tracks;
+        self.history = full.history;
+        self.created = full.created.or(self.created);
+        self.modified = full.modified.or(self.modified);
+        self.summary_count = None;
+        Ok(())
+    }
+
+    /// Libera las pistas y se queda con el resumen; los favoritos no se liberan
+    pub fn unload(&mut self) {
+        if self.is_favorites || !self.is_loaded() {
+            return;
+        }
+        self.summary_count = Some(self.tracks.len());
+        self.tracks = Vec::new();
+    }
+
+    /// La pista es de una playlist importada, queda fuera de `roots` y no se permitió
+    ///
+    /// Estas pistas se muestran marcadas y no se reproducen.
+    pub fn is_blocked_external(&self, track: &Path, roots: &[PathBuf]) -> bool {
+        self.imported && !self.allow_external && !is_within_roots(track, roots)
+    }
+
+    /// Crea la playlist de favoritos vacía
+    pub fn favorites() -> Self {
+        Self {
+            is_favorites: true,
+            ..Self::new(FAVORITES_NAME.to_string(), Vec::new())
+        }
+    }
+
+    /// Agrega la pista si no está o la quita si ya está
+    ///
+    /// Retorna `true` si la pista quedó en la playlist.
+    pub fn toggle_track(&mut self, path: &Path) -> bool {
+        match self.tracks.iter().position(|track| track.path == path) {
+            Some(index) => {
+                self.tracks.remove(index);
+                false
+            }
+            None => {
+                self.tracks.push(TrackRef::from(path.to_path_buf()));
+                true
+            }
+        }
+    }
+
+    /// Diferencia de pistas respecto del último registro anterior a `since`
+    ///
+    /// Retorna `None` si no hay registros tan antiguos.
+    pub fn track_delta_since(&self, since: u64) -> Option<i64> {
+        self.history
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.timestamp <= since)
+            .map(|snapshot| self.track_count() as i64 - snapshot.track_count as i64)
+    }
+
+    /// Actualiza las fechas y registra la cantidad de pistas si cambió
+    fn touch(&mut self) {
+        let now = unix_now();
+        self.created.get_or_insert(now);
+        self.modified = Some(now);
+        if self.history.last().map(|s| s.track_count) != Some(self.tracks.len()) {
+            self.history.push(TrackCountSnapshot {
+                timestamp: now,
+                track_count: self.tracks.len(),
+            });
+        }
+        if self.history.len() > HISTORY_LIMIT {
+            let excess = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..excess);
+        }
+    }
 }
 
 /// Valida que el nombre de playlist sea seguro para usar como nombre de archivo
@@ -137,9 +360,7 @@ fn validate_playlist_name(name: &str) -> Result<(), PlaylistError> {
         ));
     }
 
-    // Caracteres que podrían causar path traversal o son inválidos en nombres de archivo
-    let invalid_chars = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
-    if let Some(ch) = name.chars().find(|c| invalid_chars.contains(c)) {
+    if let Some(ch) = name.chars().find(|c| INVALID_NAME_CHARS.contains(c)) {
         return Err(PlaylistError::InvalidName(format!(
             "El nombre contiene caracteres inválidos: '{}'",
             ch
@@ -158,6 +379,41 @@ fn validate_playlist_name(name: &str) -> Result<(), PlaylistError> {
     Ok(())
 }
 
+/// Reemplaza por `_` los caracteres no permitidos en nombres de playlist
+pub fn sanitize_playlist_name(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if INVALID_NAME_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if validate_playlist_name(&sanitized).is_ok() {
+        sanitized
+    } else {
+        "Imported".to_string()
+    }
+}
+
+/// Nombre válido basado en `base` que ninguna de `existing` usa todavía
+///
+/// Se sanea como `sanitize_playlist_name` y, si ya está tomado, se numera:
+/// `Nombre (2)`, `Nombre (3)`, …
+pub fn unique_playlist_name(base: &str, existing: &[Playlist]) -> String {
+    let base = sanitize_playlist_name(base);
+    let mut name = base.clone();
+    let mut attempt = 1;
+    while existing.iter().any(|p| p.name == name) {
+        attempt += 1;
+        name = format!("{} ({})", base, attempt);
+    }
+    name
+}
+
 /// Construye la ruta al archivo de una playlist
 fn playlist_file_path(name: &str) -> Result<PathBuf, PlaylistError> {
     validate_playlist_name(name)?;
@@ -165,43 +421,126 @@ fn playlist_file_path(name: &str) -> Result<PathBuf, PlaylistError> {
 }
 
 /// Verifica si un archivo tiene extensión JSON (case-insensitive)
-fn is_json_file(path: &PathBuf) -> bool {
+fn is_json_file(path: &Path) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.eq_ignore_ascii_case(PLAYLIST_EXTENSION))
         == Some(true)
 }
 
+/// Verifica si un archivo es una lista M3U (`.m3u` o `.m3u8`, case-insensitive)
+pub fn is_m3u_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("m3u8"))
+}
+
+/// Lee una lista M3U/M3U8 como playlist
+///
+/// El nombre de la playlist es el del archivo sin extensión. Las rutas relativas
+/// se resuelven contra el directorio del archivo; se ignoran los comentarios
+/// (`#EXTM3U`, `#EXTINF`, ...) y las URLs. No verifica que las pistas existan.
+/// La playlist queda marcada como importada: las pistas fuera de la biblioteca
+/// no se reproducen hasta que se permitan.
+///
+/// # Errores
+/// - Retorna `Io` si no se puede leer el archivo
+pub fn parse_m3u(path: &Path) -> Result<Playlist, PlaylistError> {
+    let bytes = std::fs::read(path)?;
+    // Las listas .m3u antiguas no siempre son UTF-8
+    let content = String::from_utf8_lossy(&bytes);
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let tracks = content
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.contains("://"))
+        .map(|line| {
+            // Listas creadas en Windows usan '\\' como separador
+            #[cfg(not(windows))]
+            let line = line.replace('\\', "/");
+            base_dir.join(line)
+        })
+        .collect();
+
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Imported")
+        .to_string();
+    Ok(Playlist {
+        imported: true,
+        ..Playlist::new(name, tracks)
+    })
+}
+
 /// Guarda una playlist en disco como archivo JSON
 ///
-/// Guarda en ~/.config/rusted-player/playlists/
+/// Guarda en ~/.config/rusted-player/playlists/ (los favoritos en favorites.json)
+/// Actualiza la fecha de modificación y el registro de cantidad de pistas.
 ///
 /// # Errores
 /// - Retorna `InvalidName` si el nombre contiene caracteres inválidos
+/// - Retorna `NotLoaded` si la playlist es solo un resumen
 /// - Retorna `Io` si falla la escritura
 /// - Retorna `Serialization` si falla la serialización
-pub fn save_playlist(playlist: &Playlist) -> Result<(), PlaylistError> {
-    let playlist_path = playlist_file_path(&playlist.name)?;
+pub fn save_playlist(playlist: &mut Playlist) -> Result<(), PlaylistError> {
+    // Guardar un resumen sobrescribiría el archivo con una lista vacía
+    if !playlist.is_loaded() {
+        return Err(PlaylistError::NotLoaded(playlist.name.clone()));
+    }
+    let playlist_path = if playlist.is_favorites {
+        get_config_dir()?.join(FAVORITES_FILE)
+    } else {
+        playlist_file_path(&playlist.name)?
+    };
+    write_playlist(playlist, &playlist_path)
+}
+
+/// Escribe la playlist en `path`, actualizando sus fechas
+fn write_playlist(playlist: &mut Playlist, path: &Path) -> Result<(), PlaylistError> {
+    if playlist.is_favorites {
+        // Una pista solo puede estar una vez en favoritos
+        let mut seen = HashSet::new();
+        playlist
+            .tracks
+            .retain(|track| seen.insert(track.path.clone()));
+    }
+    playlist.touch();
     let playlist_json = serde_json::to_string_pretty(playlist)?;
-    std::fs::write(playlist_path, playlist_json)?;
+    std::fs::write(path, playlist_json)?;
 
     Ok(())
 }
 
-/// Carga todas las playlists del directorio
+/// Fecha de modificación del archivo (segundos Unix)
+fn file_modified_secs(entry: &DirEntry) -> Option<u64> {
+    entry
+        .metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+/// Carga el resumen de todas las playlists del directorio
 ///
 /// Busca en ~/.config/rusted-player/playlists/
-/// Ignora archivos que no sean JSON válidos o que tengan nombres inválidos.
-/// Retorna vector vacío si el directorio no existe.
+/// Lee nombre, fechas y cantidad de pistas sin construir las pistas; se
+/// cargan después con `Playlist::load_tracks`. Ignora archivos que no sean
+/// JSON válidos. Retorna vector vacío si el directorio no existe.
 ///
 /// # Errores
 /// - Retorna `Io` si falla la lectura del directorio
-pub fn load_playlists() -> Result<Vec<Playlist>, PlaylistError> {
-    let playlists_dir = match playlists_dir() {
-        Ok(dir) => dir,
-        Err(_) => return Ok(Vec::new()),
-    };
+pub fn load_playlist_summaries() -> Result<Vec<Playlist>, PlaylistError> {
+    match playlists_dir() {
+        Ok(dir) => read_playlist_summaries(&dir),
+        Err(_) => Ok(Vec::new()),
+    }
+}
 
+/// Lee el resumen de las playlists guardadas en `playlists_dir`
+fn read_playlist_summaries(playlists_dir: &Path) -> Result<Vec<Playlist>, PlaylistError> {
     if !playlists_dir.exists() {
         return Ok(Vec::new());
     }
@@ -214,30 +553,84 @@ pub fn load_playlists() -> Result<Vec<Playlist>, PlaylistError> {
         };
 
         let path = entry.path();
-        if path.is_file() && is_json_file(&path) {
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                if let Ok(playlist) = serde_json::from_str::<Playlist>(&content) {
-                    playlists.push(playlist);
-                }
+        if path.is_file()
+            && is_json_file(&path)
+            && let Ok(content) = std::fs::read_to_string(&path)
+            && let Ok(header) = serde_json::from_str::<PlaylistHeader>(&content)
+        {
+            let mut playlist = Playlist {
+                created: header.created,
+                modified: header.modified,
+                history: header.history,
+                imported: header.imported,
+                allow_external: header.allow_external,
+                summary_count: Some(header.tracks),
+                ..Playlist::new(header.name, Vec::new())
+            };
+            // Archivos anteriores a las fechas: se usa la del archivo hasta el próximo guardado
+            if playlist.modified.is_none() {
+                let file_time = file_modified_secs(&entry);
+                playlist.modified = file_time;
+                playlist.created = playlist.created.or(file_time);
             }
+            playlists.push(playlist);
         }
     }
 
     Ok(playlists)
 }
 
+/// Carga la playlist de favoritos
+///
+/// Busca en ~/.config/rusted-player/favorites.json
+/// Retorna la playlist vacía si el archivo no existe.
+///
+/// # Errores
+/// - Retorna `Io` si no se puede leer el archivo
+/// - Retorna `Serialization` si el archivo está dañado; antes se renombra a
+///   `favorites.json.<segundos Unix>.bak`, así el próximo guardado no lo pisa
+pub fn load_favorites() -> Result<Playlist, PlaylistError> {
+    read_favorites(&get_config_dir()?.join(FAVORITES_FILE))
+}
+
+fn read_favorites(path: &Path) -> Result<Playlist, PlaylistError> {
+    if !path.exists() {
+        return Ok(Playlist::favorites());
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let playlist: Playlist = match serde_json::from_str(&content) {
+        Ok(playlist) => playlist,
+        Err(e) => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(format!(".{}.bak", unix_now()));
+            std::fs::rename(path, backup)?;
+            return Err(e.into());
+        }
+    };
+    // Solo el programa escribe favoritos: sus pistas las marcó el usuario
+    Ok(Playlist {
+        name: FAVORITES_NAME.to_string(),
+        is_favorites: true,
+        imported: false,
+        ..playlist
+    })
+}
+
 /// Carga una playlist específica por nombre
 ///
 /// Busca en ~/.config/rusted-player/playlists/
 /// Retorna `Ok(None)` si la playlist no existe.
 pub fn load_playlist(name: &str) -> Result<Option<Playlist>, PlaylistError> {
-    let path = playlist_file_path(name)?;
+    read_playlist(&playlist_file_path(name)?)
+}
 
+fn read_playlist(path: &Path) -> Result<Option<Playlist>, PlaylistError> {
     if !path.exists() {
         return Ok(None);
     }
 
-    let content = std::fs::read_to_string(&path)?;
+    let content = std::fs::read_to_string(path)?;
     Ok(Some(serde_json::from_str(&content)?))
 }
 
@@ -258,3 +651,295 @@ pub fn delete_playlist(playlist_name: &str) -> Result<bool, PlaylistError> {
         Err(e) => Err(e.into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_without_the_imported_flag_counts_as_imported() {
+        let json = r#"{"name": "Dropped", "tracks": ["/elsewhere/a.mp3"]}"#;
+        let playlist: Playlist = serde_json::from_str(json).unwrap();
+        assert!(playlist.imported);
+        assert!(playlist.is_blocked_external(
+            Path::new("/elsewhere/a.mp3"),
+            &[PathBuf::from("/nonexistent-library")]
+        ));
+        let header: PlaylistHeader = serde_json::from_str(json).unwrap();
+        assert!(header.imported);
+    }
+
+    #[test]
+    fn saved_playlists_keep_their_flag() {
+        let own = Playlist::new("Mine".to_string(), vec![PathBuf::from("/m/a.mp3")]);
+        let reloaded: Playlist =
+            serde_json::from_str(&serde_json::to_string(&own).unwrap()).unwrap();
+        assert!(!reloaded.imported);
+
+        let allowed = Playlist {
+            imported: true,
+            allow_external: true,
+            ..own
+        };
+        let reloaded: Playlist =
+            serde_json::from_str(&serde_json::to_string(&allowed).unwrap()).unwrap();
+        assert!(reloaded.imported && reloaded.allow_external);
+        assert!(!reloaded.is_blocked_external(Path::new("/elsewhere/a.mp3"), &[]));
+    }
+
+    #[test]
+    fn unique_names_are_sanitized_and_numbered() {
+        let existing = vec![
+            Playlist::new("Road Trip".to_string(), Vec::new()),
+            Playlist::new("Road Trip (2)".to_string(), Vec::new()),
+        ];
+        assert_eq!(
+            unique_playlist_name("Road Trip", &existing),
+            "Road Trip (3)"
+        );
+        assert_eq!(unique_playlist_name("Other", &existing), "Other");
+        assert_eq!(
+            unique_playlist_name("AC/DC: Live", &existing),
+            "AC_DC_ Live"
+        );
+        assert_eq!(unique_playlist_name("   ", &existing), "Imported");
+    }
+
+    #[test]
+    fn damaged_favorites_are_moved_aside() {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-favorites-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(FAVORITES_FILE);
+        assert!(read_favorites(&path).unwrap().tracks.is_empty());
+
+        std::fs::write(&path, r#"{"name": "x", "tracks": ["/m/a.mp3""#).unwrap();
+        assert!(matches!(
+            read_favorites(&path),
+            Err(PlaylistError::Serialization(_))
+        ));
+        assert!(!path.exists());
+        let backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&backups[0]).unwrap(),
+            r#"{"name": "x", "tracks": ["/m/a.mp3""#
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn m3u_paths_resolve_against_the_list_folder() {
+        let dir =
+            std::env::temp_dir().join(format!("rusted-player-test-m3u-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Lists")).unwrap();
+        let list = dir.join("Lists/Road Trip.m3u8");
+        let mut content = "\u{feff}#EXTM3U\n#EXTINF:123,Artist - Song\n"
+            .as_bytes()
+            .to_vec();
+        content.extend_from_slice(
+            b"Album/01.mp3\n\
+              ../Other/02.mp3\n\
+              /abs/03.mp3\n\
+              Album\\04.mp3\n\
+              http://radio.example/stream\n\
+              \n   \n\
+              gone.mp3\r\n\
+              caf\xe9.mp3\n",
+        );
+        std::fs::write(&list, content).unwrap();
+
+        let playlist = parse_m3u(&list).unwrap();
+        let base = dir.join("Lists");
+        let paths: Vec<&Path> = playlist.tracks.iter().map(|t| t.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                base.join("Album/01.mp3"),
+                base.join("../Other/02.mp3"),
+                PathBuf::from("/abs/03.mp3"),
+                base.join("Album/04.mp3"),
+                // Las pistas que faltan se conservan; la interfaz las marca
+                base.join("gone.mp3"),
+                base.join("caf\u{fffd}.mp3"),
+            ]
+        );
+        assert_eq!(playlist.name, "Road Trip");
+        assert!(playlist.imported);
+
+        assert!(matches!(
+            parse_m3u(&dir.join("missing.m3u")),
+            Err(PlaylistError::Io(_))
+        ));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn old_playlist_files_load_and_save_in_the_new_format() {
+        // Rutas sueltas, sin fechas, registro ni marca de importada
+        let old =
+            r#"{"name": "Old", "tracks": ["/m/a.mp3", {"path": "/m/b.mp3", "cached_title": "B"}]}"#;
+        let playlist: Playlist = serde_json::from_str(old).unwrap();
+        assert_eq!(
+            playlist.tracks,
+            [
+                TrackRef::from(PathBuf::from("/m/a.mp3")),
+                TrackRef {
+                    path: PathBuf::from("/m/b.mp3"),
+                    cached_title: Some("B".to_string()),
+                    cached_duration: None,
+                },
+            ]
+        );
+        assert_eq!((playlist.created, playlist.modified), (None, None));
+        assert!(playlist.history.is_empty());
+
+        let header: PlaylistHeader = serde_json::from_str(old).unwrap();
+        assert_eq!((header.name.as_str(), header.tracks), ("Old", 2));
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&playlist).unwrap()).unwrap();
+        assert_eq!(
+            saved["tracks"][0],
+            serde_json::json!({ "path": "/m/a.mp3" })
+        );
+        assert_eq!(saved["imported"], true);
+        let reloaded: Playlist = serde_json::from_value(saved).unwrap();
+        assert_eq!(reloaded.tracks, playlist.tracks);
+    }
+
+    #[test]
+    fn track_delta_compares_with_the_last_count_before_a_moment() {
+        let snapshot = |timestamp, track_count| TrackCountSnapshot {
+            timestamp,
+            track_count,
+        };
+        let tracks = (0..7)
+            .map(|i| PathBuf::from(format!("/m/{i}.mp3")))
+            .collect();
+        let playlist = Playlist {
+            history: vec![snapshot(100, 5), snapshot(200, 8), snapshot(300, 6)],
+            ..Playlist::new("Mix".to_string(), tracks)
+        };
+        let table = [
+            (50, None),
+            (100, Some(2)),
+            (199, Some(2)),
+            (250, Some(-1)),
+            (300, Some(1)),
+            (1000, Some(1)),
+        ];
+        for (since, delta) in table {
+            assert_eq!(playlist.track_delta_since(since), delta, "since {since}");
+        }
+
+        // Un resumen usa la cantidad leída del archivo
+        let mut summary = playlist.clone();
+        summary.unload();
+        assert_eq!(summary.track_delta_since(250), Some(-1));
+    }
+
+    #[test]
+    fn saves_record_only_count_changes_and_keep_the_last_ones() {
+        let mut playlist = Playlist::new("Mix".to_string(), vec![PathBuf::from("/m/a.mp3")]);
+        playlist.touch();
+        playlist.touch();
+        assert_eq!(playlist.history.len(), 1);
+        assert!(playlist.created.is_some() && playlist.modified.is_some());
+
+        for i in 0..HISTORY_LIMIT + 5 {
+            playlist.toggle_track(Path::new(&format!("/m/{i}.extra")));
+            playlist.touch();
+        }
+        assert_eq!(playlist.history.len(), HISTORY_LIMIT);
+        assert_eq!(
+            playlist.history.last().unwrap().track_count,
+            playlist.tracks.len()
+        );
+    }
+
+    #[test]
+    fn favorites_round_trip_through_their_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-favorites-round-trip-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(FAVORITES_FILE);
+
+        let mut favorites = Playlist::favorites();
+        assert!(favorites.toggle_track(Path::new("/m/a.mp3")));
+        assert!(favorites.toggle_track(Path::new("/m/b.mp3")));
+        assert!(!favorites.toggle_track(Path::new("/m/a.mp3")));
+        // Una entrada repetida a mano se guarda una sola vez
+        favorites
+            .tracks
+            .push(TrackRef::from(PathBuf::from("/m/b.mp3")));
+        write_playlist(&mut favorites, &path).unwrap();
+
+        let loaded = read_favorites(&path).unwrap();
+        let paths: Vec<&Path> = loaded.tracks.iter().map(|t| t.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/m/b.mp3")]);
+        assert!(loaded.is_favorites && !loaded.imported);
+        assert_eq!(loaded.name, FAVORITES_NAME);
+        assert!(loaded.created.is_some());
+
+        // Los favoritos nunca se descargan a un resumen
+        let mut loaded = loaded;
+        loaded.unload();
+        assert!(loaded.is_loaded());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn summaries_promote_to_full_playlists_with_the_same_count() {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-summaries-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tracks: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(format!("/m/{i}.mp3")))
+            .collect();
+        let mut full = Playlist::new("Mix".to_string(), tracks.clone());
+        let path = dir.join("Mix.json");
+        write_playlist(&mut full, &path).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a playlist").unwrap();
+        std::fs::write(dir.join("Broken.json"), "{").unwrap();
+
+        let mut summaries = read_playlist_summaries(&dir).unwrap();
+        assert_eq!(summaries.len(), 1);
+        let mut summary = summaries.remove(0);
+        assert!(!summary.is_loaded());
+        assert!(summary.tracks.is_empty());
+        assert_eq!(summary.track_count(), 3);
+        assert_eq!(summary.modified, full.modified);
+        // Un resumen nunca se escribe encima del archivo completo
+        assert!(matches!(
+            save_playlist(&mut summary),
+            Err(PlaylistError::NotLoaded(_))
+        ));
+
+        summary.load_tracks_from(&path).unwrap();
+        assert!(summary.is_loaded());
+        assert_eq!(summary.track_count(), 3);
+        let paths: Vec<PathBuf> = summary.tracks.iter().map(|t| t.path.clone()).collect();
+        assert_eq!(paths, tracks);
+        assert_eq!(summary.history.len(), 1);
+
+        summary.unload();
+        assert_eq!((summary.is_loaded(), summary.track_count()), (false, 3));
+        std::fs::remove_file(&path).unwrap();
+        assert!(summary.load_tracks_from(&path).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
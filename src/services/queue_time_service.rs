@@ -0,0 +1,140 @@
+use crate::models::symbols::Symbols;
+use crate::utils::format_duration_hms;
+use chrono::{DateTime, Local};
+use std::time::{Duration, SystemTime};
+
+/// Tiempo que le queda a la cola de reproducción
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueTime {
+    /// Lo que falta de la pista actual más las pistas que siguen
+    pub remaining: Duration,
+    /// Alguna pista sin duración conocida se contó con la estimación
+    pub estimated: bool,
+    /// Hora en que termina la cola; `None` en pausa, porque el reloj no avanza
+    pub ends_at: Option<SystemTime>,
+}
+
+impl QueueTime {
+    /// Texto para la línea de estado, p. ej. "2h 14m remaining, ends ~23:40"
+    ///
+    /// Con duraciones estimadas el tiempo restante lleva "≈" adelante.
+    pub fn summary(&self, symbols: &Symbols) -> String {
+        let mut text = format!(
+            "{}{} remaining",
+            if self.estimated {
+                symbols.get("≈")
+            } else {
+                ""
+            },
+            format_duration_hms(self.remaining)
+        );
+        if let Some(ends_at) = self.ends_at {
+            let local: DateTime<Local> = ends_at.into();
+            text.push_str(&format!(", ends ~{}", local.format("%H:%M")));
+        }
+        text
+    }
+}
+
+/// Calcula el tiempo restante de la cola
+///
+/// `durations` tiene la duración de cada pista de la cola (ya recortada), `None`
+/// si no se conoce; esas pistas cuentan como `unknown_estimate`. De la pista en
+/// `current_index` se descuenta `elapsed`. Retorna `None` si el índice queda
+/// fuera de la cola.
+pub fn queue_time(
+    durations: &[Option<Duration>],
+    current_index: usize,
+    elapsed: Duration,
+    paused: bool,
+    now: SystemTime,
+    unknown_estimate: Duration,
+) -> Option<QueueTime> {
+    let pending = durations.get(current_index..).filter(|d| !d.is_empty())?;
+    let estimated = pending.iter().any(Option::is_none);
+    let mut lengths = pending.iter().map(|d| d.unwrap_or(unknown_estimate));
+    let current = lengths.next().unwrap_or_default().saturating_sub(elapsed);
+    let remaining = current + lengths.sum::<Duration>();
+    Some(QueueTime {
+        remaining,
+        estimated,
+        ends_at: (!paused).then(|| now + remaining),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000)
+    }
+
+    #[test]
+    fn remaining_time_counts_from_the_current_track() {
+        let durations = [Some(MINUTE), Some(3 * MINUTE), Some(2 * MINUTE)];
+        let time = queue_time(&durations, 1, MINUTE, false, now(), 4 * MINUTE).unwrap();
+        assert_eq!(time.remaining, 4 * MINUTE);
+        assert!(!time.estimated);
+        assert_eq!(time.ends_at, Some(now() + 4 * MINUTE));
+    }
+
+    #[test]
+    fn unknown_durations_use_the_estimate() {
+        let durations = [Some(3 * MINUTE), None, None];
+        let time = queue_time(&durations, 0, Duration::ZERO, false, now(), 4 * MINUTE).unwrap();
+        assert_eq!(time.remaining, 11 * MINUTE);
+        assert!(time.estimated);
+        // Las pistas ya pasadas no cuentan
+        let time = queue_time(
+            &[None, Some(MINUTE)],
+            1,
+            Duration::ZERO,
+            false,
+            now(),
+            MINUTE,
+        )
+        .unwrap();
+        assert!(!time.estimated);
+    }
+
+    #[test]
+    fn paused_queues_have_no_end_time() {
+        let durations = [Some(3 * MINUTE), Some(MINUTE)];
+        let time = queue_time(&durations, 0, MINUTE, true, now(), MINUTE).unwrap();
+        assert_eq!(time.remaining, 3 * MINUTE);
+        assert_eq!(time.ends_at, None);
+    }
+
+    #[test]
+    fn elapsed_past_the_end_and_bad_indices_are_safe() {
+        let durations = [Some(MINUTE), Some(MINUTE)];
+        let time = queue_time(&durations, 0, 5 * MINUTE, false, now(), MINUTE).unwrap();
+        assert_eq!(time.remaining, MINUTE);
+        assert_eq!(
+            queue_time(&durations, 2, Duration::ZERO, false, now(), MINUTE),
+            None
+        );
+        assert_eq!(
+            queue_time(&[], 0, Duration::ZERO, false, now(), MINUTE),
+            None
+        );
+    }
+
+    #[test]
+    fn summary_marks_estimates_with_the_symbol_set() {
+        use crate::models::symbols::SymbolSet;
+        let time = QueueTime {
+            remaining: 134 * MINUTE,
+            estimated: true,
+            ends_at: None,
+        };
+        assert_eq!(time.summary(&Symbols::default()), "≈2h 14m remaining");
+        assert_eq!(
+            time.summary(&Symbols::new(SymbolSet::Ascii)),
+            "~2h 14m remaining"
+        );
+    }
+}
@@ -0,0 +1,645 @@
+use crate::models::config::Config;
+use crate::services::player_service::panic_message;
+use crate::services::playlist_storage_service;
+use crate::utils;
+use lofty::{Accessor, AudioFile, TaggedFileExt};
+use rodio::source::UniformSourceIterator;
+use rodio::{Decoder, Source};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+/// Frecuencia de muestreo del archivo generado
+const OUTPUT_SAMPLE_RATE: u32 = 44_100;
+/// Canales del archivo generado
+const OUTPUT_CHANNELS: u16 = 2;
+/// Bytes por cuadro (todos los canales, 16 bits)
+const BYTES_PER_FRAME: u64 = OUTPUT_CHANNELS as u64 * 2;
+/// Tamaño máximo de un WAV: el formato guarda el tamaño en 32 bits
+pub const MAX_OUTPUT_BYTES: u64 = u32::MAX as u64;
+/// Tamaño a partir del cual conviene avisar antes de generar
+pub const LARGE_OUTPUT_BYTES: u64 = 1 << 30;
+/// Cada cuántas muestras se revisa si se pidió cancelar
+const CANCEL_CHECK_INTERVAL: usize = 1 << 16;
+/// Cuadros por segundo en las marcas de tiempo de un archivo .cue
+const CUE_FRAMES_PER_SECOND: u64 = 75;
+
+/// Errores posibles al generar el archivo
+#[derive(Debug)]
+pub enum RenderError {
+    /// La lista no tiene pistas
+    Empty,
+    /// El resultado superaría el tamaño máximo de un WAV
+    TooLarge(u64),
+    /// No se pudo decodificar una pista
+    Decode(PathBuf, String),
+    /// Error al escribir el WAV
+    Wav(hound::Error),
+    /// Error de E/S
+    Io(std::io::Error),
+    /// Se pidió a la vez un hueco y un fundido entre pistas
+    GapAndCrossfade,
+    /// Se canceló antes de terminar
+    Cancelled,
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "La lista no tiene pistas"),
+            Self::TooLarge(bytes) => write!(
+                f,
+                "El archivo ocuparía {} MB, más de lo que admite un WAV",
+                bytes / (1 << 20)
+            ),
+            Self::Decode(path, e) => write!(f, "No se pudo leer {}: {}", path.display(), e),
+            Self::Wav(e) => write!(f, "Error al escribir el WAV: {}", e),
+            Self::Io(e) => write!(f, "Error de E/S: {}", e),
+            Self::GapAndCrossfade => write!(
+                f,
+                "render_gap_ms y render_crossfade_ms no se pueden usar a la vez"
+            ),
+            Self::Cancelled => write!(f, "Cancelado"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<hound::Error> for RenderError {
+    fn from(e: hound::Error) -> Self {
+        Self::Wav(e)
+    }
+}
+
+impl From<std::io::Error> for RenderError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Comienzo de una pista dentro del archivo generado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueEntry {
+    /// Título que se escribe en el .cue
+    pub title: String,
+    /// Cuadro (muestra por canal) donde empieza la pista
+    pub start_frame: u64,
+}
+
+/// Cómo se pasa de una pista a la siguiente
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// Silencio entre pistas; sin duración quedan pegadas
+    Gap(Duration),
+    /// El final de cada pista se funde con el comienzo de la siguiente
+    Crossfade(Duration),
+}
+
+impl Transition {
+    /// Transición configurada con `render_gap_ms` o `render_crossfade_ms`
+    ///
+    /// # Errores
+    /// - `GapAndCrossfade` si las dos opciones tienen un valor
+    pub fn from_config(config: &Config) -> Result<Self, RenderError> {
+        match (config.render_gap_ms, config.render_crossfade_ms) {
+            (gap, 0) => Ok(Self::Gap(Duration::from_millis(gap))),
+            (0, fade) => Ok(Self::Crossfade(Duration::from_millis(fade))),
+            _ => Err(RenderError::GapAndCrossfade),
+        }
+    }
+
+    /// Cuadros que se agregan (positivo) o se superponen (negativo) entre dos pistas
+    fn frames_between(self) -> i64 {
+        match self {
+            Self::Gap(gap) => duration_frames(gap) as i64,
+            Self::Crossfade(fade) => -(duration_frames(fade) as i64),
+        }
+    }
+}
+
+/// Cuadros que ocupa una duración a la frecuencia de salida
+fn duration_frames(duration: Duration) -> u64 {
+    (duration.as_secs_f64() * f64::from(OUTPUT_SAMPLE_RATE)).round() as u64
+}
+
+/// Pista a generar, con lo que se leyó de sus etiquetas
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderTrack {
+    pub path: PathBuf,
+    /// Título para el .cue: la etiqueta o, si no hay, el nombre del archivo
+    pub title: String,
+    /// Duración según las etiquetas; `None` si no se pudo leer
+    pub duration: Option<Duration>,
+}
+
+impl RenderTrack {
+    /// Lee título y duración de la pista, abriendo el archivo una sola vez
+    pub fn probe(path: PathBuf) -> Self {
+        let file = lofty::read_from_path(&path).ok();
+        let title = file
+            .as_ref()
+            .and_then(|file| file.primary_tag())
+            .and_then(|tag| tag.title().map(|t| t.into_owned()))
+            .unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+        let duration = file.map(|file| file.properties().duration());
+        Self {
+            path,
+            title,
+            duration,
+        }
+    }
+}
+
+/// Tamaño aproximado del WAV a partir de las duraciones de las pistas
+///
+/// Las pistas cuya duración no se pudo leer no suman.
+pub fn estimate_size(tracks: &[RenderTrack], transition: Transition) -> u64 {
+    let audio: Duration = tracks.iter().filter_map(|track| track.duration).sum();
+    let between = transition.frames_between() * tracks.len().saturating_sub(1) as i64;
+    let frames = duration_frames(audio).saturating_add_signed(between);
+    frames * BYTES_PER_FRAME
+}
+
+/// Marca de tiempo `mm:ss:ff` de un .cue (75 cuadros por segundo)
+fn cue_timestamp(frame: u64) -> String {
+    let cue_frames = frame * CUE_FRAMES_PER_SECOND / u64::from(OUTPUT_SAMPLE_RATE);
+    let seconds = cue_frames / CUE_FRAMES_PER_SECOND;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 60,
+        seconds % 60,
+        cue_frames % CUE_FRAMES_PER_SECOND
+    )
+}
+
+/// Contenido del .cue que acompaña al WAV
+pub fn cue_sheet(wav_name: &str, entries: &[CueEntry]) -> String {
+    let mut sheet = format!("FILE \"{}\" WAVE\n", wav_name.replace('"', "'"));
+    for (i, entry) in entries.iter().enumerate() {
+        sheet.push_str(&format!("  TRACK {:02} AUDIO\n", i + 1));
+        sheet.push_str(&format!(
+            "    TITLE \"{}\"\n",
+            entry.title.replace('"', "'")
+        ));
+        sheet.push_str(&format!(
+            "    INDEX 01 {}\n",
+            cue_timestamp(entry.start_frame)
+        ));
+    }
+    sheet
+}
+
+/// Escribe muestras al WAV controlando el tamaño y la cancelación
+struct Output<'a> {
+    writer: hound::WavWriter<std::io::BufWriter<File>>,
+    samples: u64,
+    cancel: &'a AtomicBool,
+}
+
+impl Output<'_> {
+    fn write(&mut self, samples: impl Iterator<Item = i16>) -> Result<(), RenderError> {
+        for (i, sample) in samples.enumerate() {
+            if i % CANCEL_CHECK_INTERVAL == 0 {
+                if self.cancel.load(Ordering::Relaxed) {
+                    return Err(RenderError::Cancelled);
+                }
+                let bytes = (self.samples + CANCEL_CHECK_INTERVAL as u64) * 2;
+                if bytes > MAX_OUTPUT_BYTES {
+                    return Err(RenderError::TooLarge(bytes));
+                }
+            }
+            self.writer.write_sample(sample)?;
+            self.samples += 1;
+        }
+        Ok(())
+    }
+
+    fn frames(&self) -> u64 {
+        self.samples / u64::from(OUTPUT_CHANNELS)
+    }
+}
+
+/// Mezcla el final de una pista (`tail`) con el comienzo de la siguiente (`head`)
+///
+/// La primera baja y la segunda sube en línea recta a lo largo de `tail`; si
+/// `head` es más corta, lo que falta cuenta como silencio.
+fn crossfade(tail: &[i16], head: &[i16]) -> Vec<i16> {
+    let channels = usize::from(OUTPUT_CHANNELS);
+    let frames = (tail.len() / channels).max(1) as f32;
+    tail.iter()
+        .enumerate()
+        .map(|(i, &out)| {
+            let fade_in = (i / channels) as f32 / frames;
+            let incoming = head.get(i).copied().unwrap_or(0);
+            let mixed = f32::from(out) * (1.0 - fade_in) + f32::from(incoming) * fade_in;
+            mixed.round() as i16
+        })
+        .collect()
+}
+
+/// Decodifica las pistas en orden y las escribe en un solo WAV de 44.1 kHz estéreo
+///
+/// Entre pistas aplica la `transition` (silencio o fundido) y escribe junto al
+/// WAV un `.cue` con el comienzo de cada una; con fundido, una pista empieza
+/// donde empieza a subir. `progress` recibe el índice de la pista que empieza
+/// y el total. Si `cancel` se activa se borra el archivo a medio escribir.
+///
+/// # Errores
+/// - `Empty` si no hay pistas
+/// - `TooLarge` si el resultado no cabe en un WAV
+/// - `Decode` si una pista no se puede leer
+/// - `Cancelled` si se pidió cancelar
+pub fn render_tracks(
+    tracks: &[RenderTrack],
+    output: &Path,
+    transition: Transition,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<CueEntry>, RenderError> {
+    if tracks.is_empty() {
+        return Err(RenderError::Empty);
+    }
+    let estimated = estimate_size(tracks, transition);
+    if estimated > MAX_OUTPUT_BYTES {
+        return Err(RenderError::TooLarge(estimated));
+    }
+
+    let spec = hound::WavSpec {
+        channels: OUTPUT_CHANNELS,
+        sample_rate: OUTPUT_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut out = Output {
+        writer: hound::WavWriter::create(output, spec)?,
+        samples: 0,
+        cancel,
+    };
+
+    // Con fundido, el final de cada pista se guarda hasta mezclarlo con la siguiente
+    let overlap = match transition {
+        Transition::Crossfade(fade) => {
+            duration_frames(fade) as usize * usize::from(OUTPUT_CHANNELS)
+        }
+        Transition::Gap(_) => 0,
+    };
+    let result = (|| {
+        let mut entries = Vec::with_capacity(tracks.len());
+        let mut tail: VecDeque<i16> = VecDeque::with_capacity(overlap + 1);
+        for (index, track) in tracks.iter().enumerate() {
+            progress(index, tracks.len());
+            if index > 0
+                && let Transition::Gap(gap) = transition
+            {
+                let silence = duration_frames(gap) * u64::from(OUTPUT_CHANNELS);
+                out.write(std::iter::repeat_n(0, silence as usize))?;
+            }
+
+            let file = File::open(&track.path)?;
+            let decoder = Decoder::new(BufReader::new(file))
+                .map_err(|e| RenderError::Decode(track.path.clone(), e.to_string()))?;
+            entries.push(CueEntry {
+                title: track.title.clone(),
+                start_frame: out.frames(),
+            });
+            let mut samples: UniformSourceIterator<_, i16> = UniformSourceIterator::new(
+                decoder.convert_samples::<i16>(),
+                OUTPUT_CHANNELS,
+                OUTPUT_SAMPLE_RATE,
+            );
+            if !tail.is_empty() {
+                let head: Vec<i16> = samples.by_ref().take(tail.len()).collect();
+                out.write(crossfade(tail.make_contiguous(), &head).into_iter())?;
+                tail.clear();
+            }
+            out.write(samples.filter_map(|sample| {
+                tail.push_back(sample);
+                if tail.len() > overlap {
+                    tail.pop_front()
+                } else {
+                    None
+                }
+            }))?;
+        }
+        out.write(tail.into_iter())?;
+        Ok(entries)
+    })();
+
+    match result {
+        Ok(entries) => {
+            out.writer.finalize()?;
+            let wav_name = output
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            std::fs::write(output.with_extension("cue"), cue_sheet(&wav_name, &entries))?;
+            Ok(entries)
+        }
+        Err(e) => {
+            drop(out);
+            let _ = std::fs::remove_file(output);
+            Err(e)
+        }
+    }
+}
+
+/// Genera el WAV de una playlist guardada mostrando el progreso en stderr
+///
+/// Corre en un hilo aparte; Ctrl+C cancela y borra el archivo a medio escribir.
+///
+/// # Errores
+/// - Retorna `NotFound` si la playlist no existe o no tiene pistas legibles
+/// - Retorna `Other` si la generación falla o se cancela
+pub fn run_render_playlist(config: &Config, name: &str, output: &Path) -> io::Result<()> {
+    let playlist = match playlist_storage_service::load_playlist(name) {
+        Ok(Some(playlist)) => playlist,
+        Ok(None) => {
+            eprintln!("Error: playlist '{}' not found.", name);
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return Err(io::Error::other(e.to_string()));
+        }
+    };
+
+    let (tracks, skipped): (Vec<PathBuf>, Vec<PathBuf>) = playlist
+        .tracks
+        .into_iter()
+        .map(|track| track.path)
+        .partition(|path| path.is_file() && utils::is_audio_file(path));
+    for path in &skipped {
+        eprintln!("Skipping missing track: {}", path.display());
+    }
+    if tracks.is_empty() {
+        eprintln!("Error: playlist '{}' has no playable tracks.", name);
+        return Err(io::Error::from(io::ErrorKind::NotFound));
+    }
+
+    let transition = match Transition::from_config(config) {
+        Ok(transition) => transition,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return Err(io::Error::other(e.to_string()));
+        }
+    };
+    let tracks: Vec<RenderTrack> = tracks.into_iter().map(RenderTrack::probe).collect();
+    let estimated = estimate_size(&tracks, transition);
+    if estimated > LARGE_OUTPUT_BYTES {
+        eprintln!(
+            "Warning: the output will take about {} MB.",
+            estimated / (1 << 20)
+        );
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&cancel))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let worker = std::thread::spawn({
+        let output = output.to_path_buf();
+        let cancel = Arc::clone(&cancel);
+        let tracks = tracks.clone();
+        move || {
+            render_tracks(&tracks, &output, transition, &cancel, |index, total| {
+                let _ = sender.send((index, total));
+            })
+        }
+    });
+    for (index, total) in receiver {
+        eprintln!(
+            "[{}/{}] {}",
+            index + 1,
+            total,
+            tracks[index]
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default()
+        );
+    }
+
+    match worker.join() {
+        Ok(Ok(entries)) => {
+            eprintln!(
+                "Wrote {} tracks to {} (cue sheet: {})",
+                entries.len(),
+                output.display(),
+                output.with_extension("cue").display()
+            );
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            eprintln!("Error: {}", e);
+            Err(io::Error::other(e.to_string()))
+        }
+        Err(payload) => {
+            // El hilo no llegó a borrar lo que había escrito
+            let _ = std::fs::remove_file(output);
+            let message = panic_message(payload.as_ref());
+            eprintln!("Error: the render stopped unexpectedly: {}", message);
+            Err(io::Error::other(message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Carpeta temporal propia de cada prueba
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// WAV de `frames` cuadros con un valor constante en todas las muestras
+    fn tone(
+        dir: &Path,
+        name: &str,
+        rate: u32,
+        channels: u16,
+        frames: u32,
+        value: i16,
+    ) -> RenderTrack {
+        let path = dir.join(name);
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..frames * u32::from(channels) {
+            writer.write_sample(value).unwrap();
+        }
+        writer.finalize().unwrap();
+        RenderTrack::probe(path)
+    }
+
+    fn render(
+        dir: &Path,
+        tracks: &[RenderTrack],
+        transition: Transition,
+    ) -> (Vec<CueEntry>, Vec<i16>) {
+        let output = dir.join("out.wav");
+        let cancel = AtomicBool::new(false);
+        let entries = render_tracks(tracks, &output, transition, &cancel, |_, _| {}).unwrap();
+        let samples = hound::WavReader::open(&output)
+            .unwrap()
+            .into_samples::<i16>()
+            .map(Result::unwrap)
+            .collect();
+        (entries, samples)
+    }
+
+    fn starts(entries: &[CueEntry]) -> Vec<u64> {
+        entries.iter().map(|entry| entry.start_frame).collect()
+    }
+
+    #[test]
+    fn gaps_add_silence_between_tracks() {
+        let dir = temp_dir("render-gap");
+        let tracks = [
+            tone(&dir, "a.wav", 44_100, 2, 4_410, 1_000),
+            tone(&dir, "b.wav", 44_100, 2, 2_205, 2_000),
+        ];
+        assert_eq!(tracks[0].duration, Some(Duration::from_millis(100)));
+        let gap = Transition::Gap(Duration::from_millis(50));
+
+        let (entries, samples) = render(&dir, &tracks, gap);
+        assert_eq!(starts(&entries), [0, 4_410 + 2_205]);
+        assert_eq!(samples.len(), (4_410 + 2_205 + 2_205) * 2);
+        assert_eq!(samples[4_410 * 2..(4_410 + 2_205) * 2], vec![0; 2_205 * 2]);
+        assert_eq!(estimate_size(&tracks, gap), samples.len() as u64 * 2);
+        assert_eq!(entries[0].title, "a");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn other_rates_and_mono_are_converted() {
+        let dir = temp_dir("render-convert");
+        let tracks = [
+            tone(&dir, "mono.wav", 22_050, 1, 2_205, 500),
+            tone(&dir, "stereo.wav", 44_100, 2, 441, 500),
+        ];
+        let (entries, samples) = render(&dir, &tracks, Transition::Gap(Duration::ZERO));
+        // 0,1 s a 22,05 kHz son 4410 cuadros a 44,1 kHz; se admite un cuadro de redondeo
+        let first = entries[1].start_frame;
+        assert!(first.abs_diff(4_410) <= 1, "{}", first);
+        assert_eq!(samples.len() as u64, (first + 441) * 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn crossfades_overlap_tracks() {
+        let dir = temp_dir("render-crossfade");
+        let tracks = [
+            tone(&dir, "a.wav", 44_100, 2, 4_410, 1_000),
+            tone(&dir, "b.wav", 44_100, 2, 4_410, 3_000),
+        ];
+        let fade = Transition::Crossfade(Duration::from_millis(50));
+        let (entries, samples) = render(&dir, &tracks, fade);
+
+        assert_eq!(starts(&entries), [0, 4_410 - 2_205]);
+        assert_eq!(samples.len(), (4_410 * 2 - 2_205) * 2);
+        assert_eq!(estimate_size(&tracks, fade), samples.len() as u64 * 2);
+        // El fundido va de un valor al otro sin saltos
+        let mix = &samples[(4_410 - 2_205) * 2..4_410 * 2];
+        assert_eq!(mix[0], 1_000);
+        assert!(mix.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(*mix.last().unwrap() > 2_990);
+        assert_eq!(samples[4_410 * 2], 3_000);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn a_track_shorter_than_the_crossfade_fits_inside_it() {
+        let dir = temp_dir("render-short");
+        let tracks = [
+            tone(&dir, "a.wav", 44_100, 2, 4_410, 1_000),
+            tone(&dir, "b.wav", 44_100, 2, 441, 1_000),
+        ];
+        let (entries, samples) = render(
+            &dir,
+            &tracks,
+            Transition::Crossfade(Duration::from_millis(50)),
+        );
+        assert_eq!(starts(&entries), [0, 4_410 - 2_205]);
+        assert_eq!(samples.len(), 4_410 * 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn empty_lists_and_cancelled_renders_leave_no_file() {
+        let dir = temp_dir("render-cancel");
+        let output = dir.join("out.wav");
+        let cancel = AtomicBool::new(true);
+        let gap = Transition::Gap(Duration::ZERO);
+        assert!(matches!(
+            render_tracks(&[], &output, gap, &cancel, |_, _| {}),
+            Err(RenderError::Empty)
+        ));
+        let tracks = [tone(&dir, "a.wav", 44_100, 2, 441, 1)];
+        assert!(matches!(
+            render_tracks(&tracks, &output, gap, &cancel, |_, _| {}),
+            Err(RenderError::Cancelled)
+        ));
+        assert!(!output.exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn gap_and_crossfade_are_exclusive() {
+        let config = |gap, fade| Config {
+            render_gap_ms: gap,
+            render_crossfade_ms: fade,
+            ..Config::default()
+        };
+        assert_eq!(
+            Transition::from_config(&config(0, 0)).unwrap(),
+            Transition::Gap(Duration::ZERO)
+        );
+        assert_eq!(
+            Transition::from_config(&config(0, 3_000)).unwrap(),
+            Transition::Crossfade(Duration::from_secs(3))
+        );
+        assert!(matches!(
+            Transition::from_config(&config(500, 3_000)),
+            Err(RenderError::GapAndCrossfade)
+        ));
+    }
+
+    #[test]
+    fn cue_timestamps_use_75_frames_per_second() {
+        let entries = [
+            CueEntry {
+                title: "Intro".to_string(),
+                start_frame: 0,
+            },
+            CueEntry {
+                title: "Say \"Hi\"".to_string(),
+                start_frame: 44_100 * 61 + 44_100 / 2,
+            },
+        ];
+        assert_eq!(
+            cue_sheet("mix.wav", &entries),
+            "FILE \"mix.wav\" WAVE\n  TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    TITLE \"Say 'Hi'\"\n    INDEX 01 01:01:37\n"
+        );
+    }
+}
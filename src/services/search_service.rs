@@ -0,0 +1,222 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Campo en el que coincidió una búsqueda
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchField {
+    Title,
+    Artist,
+    Album,
+    FileName,
+    PathSegment,
+}
+
+impl MatchField {
+    /// Peso base del campo: título > artista > álbum > archivo > carpeta
+    ///
+    /// Entre dos campos hay más distancia que la suma de todas las bonificaciones.
+    fn weight(self) -> u32 {
+        match self {
+            Self::Title => 500,
+            Self::Artist => 400,
+            Self::Album => 300,
+            Self::FileName => 200,
+            Self::PathSegment => 100,
+        }
+    }
+
+    /// Nombre del campo para mostrar como contexto
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::Album => "album",
+            Self::FileName => "file name",
+            Self::PathSegment => "folder",
+        }
+    }
+}
+
+/// Bonificación cuando el campo empieza con la búsqueda
+const PREFIX_BONUS: u32 = 15;
+
+/// Bonificación cuando la búsqueda coincide con palabras completas
+const WHOLE_WORD_BONUS: u32 = 10;
+
+/// Textos de una entrada sobre los que se busca
+#[derive(Debug, Default)]
+pub struct SearchFields<'a> {
+    pub title: Option<&'a str>,
+    pub artist: Option<&'a str>,
+    pub album: Option<&'a str>,
+    pub file_name: &'a str,
+    /// Carpetas de la ruta, sin el nombre del archivo
+    pub path_segments: Vec<&'a str>,
+}
+
+/// Mejor coincidencia de una búsqueda en una entrada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Puntaje: mayor es mejor
+    pub score: u32,
+    /// Campo en el que coincidió
+    pub field: MatchField,
+    /// Texto del campo que coincidió
+    pub text: String,
+}
+
+/// Puntúa un solo texto, `None` si no contiene la búsqueda
+///
+/// `query` debe venir en forma NFC y en minúsculas.
+fn score_text(query: &str, text: &str, field: MatchField) -> Option<u32> {
+    let lower = text.nfc().collect::<String>().to_lowercase();
+    let position = lower.find(query)?;
+
+    let mut score = field.weight();
+    if position == 0 {
+        score += PREFIX_BONUS;
+    }
+    let starts_word = lower[..position]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !c.is_alphanumeric());
+    let ends_word = lower[position + query.len()..]
+        .chars()
+        .next()
+        .is_none_or(|c| !c.is_alphanumeric());
+    if starts_word && ends_word {
+        score += WHOLE_WORD_BONUS;
+    }
+    Some(score)
+}
+
+/// Busca el campo que mejor coincide con la búsqueda
+///
+/// No distingue mayúsculas ni formas Unicode (NFC). El peso del campo domina; las coincidencias al
+/// inicio y por palabra completa suman puntos. Retorna `None` si ningún campo
+/// contiene la búsqueda.
+pub fn score_match(query: &str, fields: &SearchFields) -> Option<SearchMatch> {
+    let query = query.trim().nfc().collect::<String>().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    let candidates = [
+        (MatchField::Title, fields.title),
+        (MatchField::Artist, fields.artist),
+        (MatchField::Album, fields.album),
+        (MatchField::FileName, Some(fields.file_name)),
+    ]
+    .into_iter()
+    .chain(
+        fields
+            .path_segments
+            .iter()
+            .map(|segment| (MatchField::PathSegment, Some(*segment))),
+    );
+
+    candidates
+        .filter_map(|(field, text)| {
+            let text = text?;
+            score_text(&query, text, field).map(|score| SearchMatch {
+                score,
+                field,
+                text: text.to_string(),
+            })
+        })
+        .max_by_key(|m| m.score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields<'a>(
+        title: Option<&'a str>,
+        artist: Option<&'a str>,
+        file_name: &'a str,
+        path_segments: Vec<&'a str>,
+    ) -> SearchFields<'a> {
+        SearchFields {
+            title,
+            artist,
+            album: None,
+            file_name,
+            path_segments,
+        }
+    }
+
+    #[test]
+    fn field_weight_beats_every_bonus() {
+        // (búsqueda, campos de la que debe ganar, campos de la que pierde)
+        let table = [
+            (
+                "love",
+                fields(Some("Crazy Love Song"), None, "a.mp3", vec![]),
+                fields(None, Some("Love"), "b.mp3", vec![]),
+            ),
+            (
+                "love",
+                fields(None, Some("Glovers"), "a.mp3", vec![]),
+                fields(None, None, "love.mp3", vec![]),
+            ),
+            (
+                "love",
+                fields(None, None, "glovebox.mp3", vec![]),
+                fields(None, None, "b.mp3", vec!["Love"]),
+            ),
+            // Dentro del mismo campo cuentan las bonificaciones
+            (
+                "love",
+                fields(Some("Love Me Do"), None, "a.mp3", vec![]),
+                fields(Some("All You Need Is Love"), None, "b.mp3", vec![]),
+            ),
+            (
+                "love",
+                fields(Some("All You Need Is Love"), None, "a.mp3", vec![]),
+                fields(Some("Gloves Off"), None, "b.mp3", vec![]),
+            ),
+        ];
+        for (query, better, worse) in table {
+            let better = score_match(query, &better).unwrap();
+            let worse = score_match(query, &worse).unwrap();
+            assert!(
+                better.score > worse.score,
+                "{query}: {better:?} should beat {worse:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn best_field_is_reported_with_its_text() {
+        let table = [
+            ("echoes", MatchField::Title, "Echoes"),
+            ("pink", MatchField::Artist, "Pink Floyd"),
+            ("meddle", MatchField::PathSegment, "Meddle"),
+            ("06", MatchField::FileName, "06 Echoes.mp3"),
+        ];
+        let entry = fields(
+            Some("Echoes"),
+            Some("Pink Floyd"),
+            "06 Echoes.mp3",
+            vec!["Pink Floyd", "Meddle"],
+        );
+        for (query, field, text) in table {
+            let found = score_match(query, &entry).unwrap();
+            assert_eq!((found.field, found.text.as_str()), (field, text), "{query}");
+        }
+    }
+
+    #[test]
+    fn case_and_unicode_forms_are_ignored() {
+        let entry = fields(Some("Café del Mar"), None, "a.mp3", vec![]);
+        for query in ["CAFÉ", "cafe\u{301}", "  del mar "] {
+            assert_eq!(
+                score_match(query, &entry).map(|m| m.field),
+                Some(MatchField::Title),
+                "{query}"
+            );
+        }
+        assert_eq!(score_match("cafe", &entry), None);
+        assert_eq!(score_match("   ", &entry), None);
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Saltos de posición mayores a esto se toman como búsquedas y no suman tiempo escuchado
+const MAX_POSITION_STEP: Duration = Duration::from_secs(5);
+
+/// Línea del registro por cada pista reproducida
+#[derive(Debug, Serialize)]
+struct LogEntry<'a> {
+    /// Segundos Unix en los que empezó la pista
+    timestamp: u64,
+    path: &'a Path,
+    title: Option<&'a str>,
+    artist: Option<&'a str>,
+    duration_played_secs: u64,
+}
+
+/// Lo que se lee de cada línea del registro para sumar el tiempo escuchado
+#[derive(Debug, Deserialize)]
+struct LoggedPlay {
+    path: PathBuf,
+    duration_played_secs: u64,
+}
+
+/// Pista que está sonando y el tiempo que lleva escuchado
+#[derive(Debug)]
+struct CurrentTrack {
+    path: PathBuf,
+    title: Option<String>,
+    artist: Option<String>,
+    started_at: u64,
+    played: Duration,
+    last_position: Duration,
+}
+
+/// Registro de escucha de la sesión, una línea JSON por pista
+///
+/// El tiempo escuchado se calcula sumando los avances de `PlayerStatus::Position`,
+/// así que las pausas y los saltos dentro de la pista no cuentan.
+#[derive(Debug)]
+pub struct SessionLog {
+    file: File,
+    current: Option<CurrentTrack>,
+}
+
+impl SessionLog {
+    /// Abre el archivo de registro agregando al final, creándolo si no existe
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede abrir el archivo
+    pub fn open(path: &Path) -> Result<Self, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file,
+            current: None,
+        })
+    }
+
+    /// Cierra la pista anterior y empieza a contar la nueva
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede escribir la línea de la pista anterior
+    pub fn track_started(
+        &mut self,
+        path: &Path,
+        title: Option<&str>,
+        artist: Option<&str>,
+        now: u64,
+    ) -> Result<(), io::Error> {
+        let result = self.finish_track();
+        self.current = Some(CurrentTrack {
+            path: path.to_path_buf(),
+            title: title.map(str::to_string),
+            artist: artist.map(str::to_string),
+            started_at: now,
+            played: Duration::ZERO,
+            last_position: Duration::ZERO,
+        });
+        result
+    }
+
+    /// Suma el avance desde la última posición informada
+    pub fn position(&mut self, position: Duration) {
+        let Some(current) = &mut self.current else {
+            return;
+        };
+        if let Some(step) = position.checked_sub(current.last_position)
+            && step <= MAX_POSITION_STEP
+        {
+            current.played += step;
+        }
+        current.last_position = position;
+    }
+
+    /// Escribe la línea de la pista actual, si hay una
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede escribir en el archivo
+    pub fn finish_track(&mut self) -> Result<(), io::Error> {
+        let Some(current) = self.current.take() else {
+            return Ok(());
+        };
+        let entry = LogEntry {
+            timestamp: current.started_at,
+            path: &current.path,
+            title: current.title.as_deref(),
+            artist: current.artist.as_deref(),
+            duration_played_secs: current.played.as_secs(),
+        };
+        let line = serde_json::to_string(&entry)?;
+        writeln!(self.file, "{}", line)
+    }
+}
+
+/// Tiempo escuchado de cada pista según el registro, sumando todas sus líneas
+///
+/// Las líneas que no se pueden interpretar se saltan y un registro que todavía
+/// no existe no tiene tiempo escuchado. Para agrupar por artista o género se
+/// cruza con la metadata de la biblioteca por ruta.
+///
+/// # Errores
+/// - Retorna error si el archivo existe pero no se puede leer
+pub fn read_listening_time(path: &Path) -> Result<HashMap<PathBuf, Duration>, io::Error> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+    let mut listened: HashMap<PathBuf, Duration> = HashMap::new();
+    for line in BufReader::new(file).lines() {
+        let Ok(play) = serde_json::from_str::<LoggedPlay>(&line?) else {
+            continue;
+        };
+        *listened.entry(play.path).or_default() += Duration::from_secs(play.duration_played_secs);
+    }
+    Ok(listened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn listening_time_adds_up_every_play_and_skips_garbage() {
+        let path = log_path("listening");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"timestamp":1,"path":"/m/a.mp3","title":null,"artist":null,"duration_played_secs":30}"#,
+                "\n",
+                "not json\n",
+                r#"{"path":"/m/b.mp3"}"#,
+                "\n",
+                r#"{"timestamp":2,"path":"/m/a.mp3","title":"A","artist":"X","duration_played_secs":45}"#,
+                "\n",
+                r#"{"timestamp":3,"path":"/m/b.mp3","title":null,"artist":null,"duration_played_secs":0}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        let listened = read_listening_time(&path).unwrap();
+        assert_eq!(listened.len(), 2);
+        assert_eq!(listened[Path::new("/m/a.mp3")], Duration::from_secs(75));
+        assert_eq!(listened[Path::new("/m/b.mp3")], Duration::ZERO);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn a_missing_log_has_no_listening_time() {
+        let listened = read_listening_time(&log_path("listening-missing")).unwrap();
+        assert!(listened.is_empty());
+    }
+
+    #[test]
+    fn seeks_do_not_count_as_listening() {
+        let path = log_path("listening-seek");
+        let mut log = SessionLog::open(&path).unwrap();
+        log.track_started(Path::new("/m/a.mp3"), None, None, 1)
+            .unwrap();
+        for secs in [1, 2, 3, 60, 61, 62, 10] {
+            log.position(Duration::from_secs(secs));
+        }
+        log.finish_track().unwrap();
+        let listened = read_listening_time(&path).unwrap();
+        assert_eq!(listened[Path::new("/m/a.mp3")], Duration::from_secs(5));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::models::stored_path;
+use crate::services::config_service;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Archivo donde se guarda la sesión mientras la aplicación está abierta
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// Parte del estado de la interfaz que se puede restaurar tras un cierre inesperado
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartialAppState {
+    /// Biblioteca con la que se guardó; con otra la sesión no sirve
+    #[serde(with = "stored_path")]
+    pub music_path: PathBuf,
+    /// Carpeta que se estaba navegando
+    #[serde(with = "stored_path")]
+    pub current_dir: PathBuf,
+    /// Fila seleccionada en la carpeta
+    pub selected: usize,
+    /// Pestaña activa (0 carpetas, 1 listas, 2 estadísticas)
+    pub active_tab: usize,
+    /// Amplitud del reproductor, no el porcentaje: no depende de la curva de volumen
+    pub volume: f32,
+    pub search_query: String,
+    /// Nombre de la playlist abierta, si había una
+    pub viewing_playlist: Option<String>,
+    /// Pistas marcadas para crear una playlist
+    #[serde(with = "stored_path::vec")]
+    pub marked_tracks: Vec<PathBuf>,
+}
+
+/// Guarda el estado en el directorio de configuración
+///
+/// # Errores
+/// - Retorna error si no se puede escribir el archivo
+pub fn save_session(state: &PartialAppState) -> Result<(), std::io::Error> {
+    let path = config_service::get_export_path(SESSION_FILE_NAME)?;
+    fs::write(path, serde_json::to_string(state)?)
+}
+
+/// Carga la sesión que quedó de un cierre inesperado
+///
+/// Retorna `None` si no hay sesión, si está dañada o si se guardó con otra
+/// `music_path` (apuntaría a otra biblioteca).
+pub fn load_session(music_path: &Path) -> Option<PartialAppState> {
+    let path = config_service::get_export_path(SESSION_FILE_NAME).ok()?;
+    parse_session(&fs::read_to_string(path).ok()?, music_path)
+}
+
+/// Interpreta el contenido de `session.json` para la biblioteca `music_path`
+fn parse_session(contents: &str, music_path: &Path) -> Option<PartialAppState> {
+    let state: PartialAppState = serde_json::from_str(contents).ok()?;
+    (state.music_path == music_path).then_some(state)
+}
+
+/// Borra la sesión guardada; se llama al salir normalmente
+pub fn clear_session() {
+    if let Ok(path) = config_service::get_export_path(SESSION_FILE_NAME) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(music_path: &str) -> PartialAppState {
+        PartialAppState {
+            music_path: PathBuf::from(music_path),
+            current_dir: PathBuf::from(music_path).join("Jazz"),
+            selected: 4,
+            volume: 0.5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn a_session_is_restored_only_for_the_library_it_was_saved_with() {
+        let saved = serde_json::to_string(&state("/music")).unwrap();
+        assert_eq!(
+            parse_session(&saved, Path::new("/music")),
+            Some(state("/music"))
+        );
+        assert_eq!(parse_session(&saved, Path::new("/other")), None);
+    }
+
+    #[test]
+    fn old_or_damaged_sessions_are_discarded() {
+        let without_library = r#"{"current_dir": "/music/Jazz", "selected": 4}"#;
+        assert_eq!(parse_session(without_library, Path::new("/music")), None);
+        assert_eq!(parse_session("{\"selected\":", Path::new("/music")), None);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::services::config_service;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Archivo donde se guarda el historial entre sesiones
+const HISTORY_FILE_NAME: &str = "shuffle_history.json";
+/// Máximo de pistas recordadas; al superarlo se olvidan las más antiguas
+const HISTORY_CAPACITY: usize = 5000;
+/// Mínimo de pistas nuevas para aplicar la exclusión
+const MIN_FRESH_TRACKS: usize = 20;
+
+/// Pistas reproducidas recientemente, con la hora (segundos Unix) de la última vez
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ShuffleHistory {
+    played: HashMap<PathBuf, u64>,
+}
+
+impl ShuffleHistory {
+    /// Carga el historial guardado; si no existe o está dañado empieza vacío
+    pub fn load() -> Self {
+        config_service::get_export_path(HISTORY_FILE_NAME)
+            .and_then(fs::read_to_string)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Guarda el historial en el directorio de configuración
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede escribir el archivo
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let path = config_service::get_export_path(HISTORY_FILE_NAME)?;
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Anota que una pista empezó a sonar en `now`
+    pub fn record(&mut self, path: &Path, now: u64) {
+        self.played.insert(path.to_path_buf(), now);
+        if self.played.len() > HISTORY_CAPACITY {
+            let mut times: Vec<u64> = self.played.values().copied().collect();
+            let cutoff_index = times.len() - HISTORY_CAPACITY;
+            let (_, cutoff, _) = times.select_nth_unstable(cutoff_index);
+            let cutoff = *cutoff;
+            self.played.retain(|_, played_at| *played_at >= cutoff);
+        }
+    }
+
+    /// La pista sonó dentro de la ventana que termina en `now`
+    pub fn played_within(&self, path: &Path, window: Duration, now: u64) -> bool {
+        self.played
+            .get(path)
+            .is_some_and(|played_at| now.saturating_sub(*played_at) < window.as_secs())
+    }
+}
+
+/// Cola aleatoria armada teniendo en cuenta el historial
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShuffleQueue {
+    /// Pistas en el orden en que se van a reproducir
+    pub tracks: Vec<PathBuf>,
+    /// Pistas dejadas afuera por haber sonado hace poco
+    pub excluded: usize,
+    /// Quedaban muy pocas pistas nuevas y se usaron todas
+    pub fell_back: bool,
+}
+
+/// Mezcla las pistas dejando afuera las que sonaron dentro de `window`
+///
+/// Si excluirlas dejara menos de `MIN_FRESH_TRACKS` (o, en listas chicas,
+/// si dejara alguna afuera) se mezclan todas y se marca `fell_back`.
+/// Una ventana de cero desactiva la exclusión.
+pub fn shuffle_fresh<R: Rng + ?Sized>(
+    tracks: Vec<PathBuf>,
+    history: &ShuffleHistory,
+    window: Duration,
+    now: u64,
+    rng: &mut R,
+) -> ShuffleQueue {
+    let total = tracks.len();
+    let (mut fresh, recent): (Vec<PathBuf>, Vec<PathBuf>) = tracks
+        .into_iter()
+        .partition(|path| window.is_zero() || !history.played_within(path, window, now));
+
+    let fell_back = !recent.is_empty() && fresh.len() < MIN_FRESH_TRACKS.min(total);
+    let excluded = if fell_back {
+        fresh.extend(recent);
+        0
+    } else {
+        recent.len()
+    };
+    fresh.shuffle(rng);
+
+    ShuffleQueue {
+        tracks: fresh,
+        excluded,
+        fell_back,
+    }
+}
+
+/// Mezcla pistas nuevas en la parte pendiente de una cola
+///
+/// Cada pista va a una posición al azar a partir de `first_pending`; lo que está
+/// antes (lo ya escuchado y la pista actual) no se mueve.
+pub fn insert_shuffled<R: Rng + ?Sized>(
+    queue: &mut Vec<PathBuf>,
+    first_pending: usize,
+    tracks: Vec<PathBuf>,
+    rng: &mut R,
+) {
+    let first_pending = first_pending.min(queue.len());
+    for path in tracks {
+        let position = rng.random_range(first_pending..=queue.len());
+        queue.insert(position, path);
+    }
+}
+
+/// Cómo se mezcla la reproducción actual
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShuffleMode {
+    /// En orden
+    #[default]
+    Off,
+    /// Pistas en orden aleatorio
+    Tracks,
+    /// Álbumes en orden aleatorio, cada uno con sus pistas en orden
+    Albums,
+}
+
+/// Álbum al que pertenece una pista y su lugar dentro de él
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumPosition {
+    /// Clave que agrupa las pistas del mismo álbum
+    pub album: String,
+    /// Disco, 1 si no se sabe
+    pub disc: u32,
+    /// Número de pista; las pistas sin número van al final, por nombre de archivo
+    pub track: Option<u32>,
+}
+
+/// Mezcla el orden de los álbumes manteniendo el orden de las pistas de cada uno
+///
+/// `position` dice a qué álbum pertenece cada pista; los álbumes de una sola
+/// pista también entran. Dentro de un álbum se ordena por disco, número de
+/// pista y ruta. Un álbum queda afuera si más de la mitad de sus pistas sonó
+/// dentro de `window`, con la misma vuelta atrás que `shuffle_fresh`.
+pub fn shuffle_fresh_albums<R: Rng + ?Sized>(
+    tracks: Vec<PathBuf>,
+    position: impl Fn(&Path) -> AlbumPosition,
+    history: &ShuffleHistory,
+    window: Duration,
+    now: u64,
+    rng: &mut R,
+) -> ShuffleQueue {
+    let albums = group_albums(tracks, position);
+    let total: usize = albums.iter().map(Vec::len).sum();
+    let (mut fresh, recent): (Vec<_>, Vec<_>) = albums.into_iter().partition(|album| {
+        let heard = album
+            .iter()
+            .filter(|(_, path)| history.played_within(path, window, now))
+            .count();
+        window.is_zero() || heard * 2 <= album.len()
+    });
+
+    let fresh_tracks: usize = fresh.iter().map(Vec::len).sum();
+    let fell_back = !recent.is_empty() && fresh_tracks < MIN_FRESH_TRACKS.min(total);
+    let excluded = if fell_back {
+        fresh.extend(recent);
+        0
+    } else {
+        total - fresh_tracks
+    };
+    fresh.shuffle(rng);
+
+    ShuffleQueue {
+        tracks: fresh.into_iter().flatten().map(|(_, path)| path).collect(),
+        excluded,
+        fell_back,
+    }
+}
+
+/// Agrupa las pistas por álbum, cada álbum en orden de disco y número de pista
+fn group_albums(
+    tracks: Vec<PathBuf>,
+    position: impl Fn(&Path) -> AlbumPosition,
+) -> Vec<Vec<(AlbumPosition, PathBuf)>> {
+    // Los grupos quedan en orden de aparición para que la mezcla dependa solo del rng
+    let mut albums: Vec<Vec<(AlbumPosition, PathBuf)>> = Vec::new();
+    let mut album_index: HashMap<String, usize> = HashMap::new();
+    for path in tracks {
+        let track = position(&path);
+        let index = *album_index.entry(track.album.clone()).or_insert_with(|| {
+            albums.push(Vec::new());
+            albums.len() - 1
+        });
+        albums[index].push((track, path));
+    }
+
+    for album in &mut albums {
+        album.sort_by(|(a, a_path), (b, b_path)| {
+            (a.disc, a.track.is_none(), a.track, a_path).cmp(&(
+                b.disc,
+                b.track.is_none(),
+                b.track,
+                b_path,
+            ))
+        });
+    }
+    albums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn paths(range: std::ops::Range<usize>) -> Vec<PathBuf> {
+        range
+            .map(|i| PathBuf::from(format!("/m/{i}.mp3")))
+            .collect()
+    }
+
+    #[test]
+    fn batches_join_the_pending_part_of_the_queue() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // Primera tanda en reproducción; ya sonaron tres pistas
+        let mut queue = paths(0..200);
+        let current = 3;
+        let played = queue[..=current].to_vec();
+
+        let mut expected = queue.clone();
+        for start in (200..10_200).step_by(1000) {
+            let batch = paths(start..start + 1000);
+            expected.extend(batch.iter().cloned());
+            insert_shuffled(&mut queue, current + 1, batch, &mut rng);
+        }
+
+        assert_eq!(&queue[..=current], &played[..]);
+        assert_eq!(queue.len(), expected.len());
+        let mut sorted = queue.clone();
+        sorted.sort();
+        expected.sort();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn batches_after_the_end_of_the_queue_are_appended() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut queue = paths(0..2);
+        insert_shuffled(&mut queue, 10, paths(2..4), &mut rng);
+        assert_eq!(&queue[..2], &paths(0..2)[..]);
+        assert_eq!(queue.len(), 4);
+    }
+
+    #[test]
+    fn recent_tracks_are_excluded_unless_too_few_remain() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut history = ShuffleHistory::default();
+        let tracks = paths(0..100);
+        for track in &tracks[..10] {
+            history.record(track, 1_000);
+        }
+        let window = Duration::from_secs(3600);
+
+        let queue = shuffle_fresh(tracks.clone(), &history, window, 2_000, &mut rng);
+        assert_eq!(queue.tracks.len(), 90);
+        assert_eq!(queue.excluded, 10);
+        assert!(!queue.fell_back);
+
+        let queue = shuffle_fresh(tracks[..15].to_vec(), &history, window, 2_000, &mut rng);
+        assert_eq!(queue.tracks.len(), 15);
+        assert!(queue.fell_back);
+
+        let queue = shuffle_fresh(tracks.clone(), &history, Duration::ZERO, 2_000, &mut rng);
+        assert_eq!(queue.tracks.len(), 100);
+    }
+
+    /// Álbum y número de pista a partir de rutas como `/m/3/07.mp3`
+    fn album_of(path: &Path) -> AlbumPosition {
+        let track = path.file_stem().unwrap().to_string_lossy().parse().ok();
+        AlbumPosition {
+            album: path.parent().unwrap().to_string_lossy().into_owned(),
+            disc: 1,
+            track,
+        }
+    }
+
+    fn albums(count: usize, tracks: usize) -> Vec<PathBuf> {
+        (0..count)
+            .flat_map(|album| {
+                (1..=tracks).map(move |track| PathBuf::from(format!("/m/{album}/{track:02}.mp3")))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn album_shuffle_keeps_albums_whole_and_in_order() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut tracks = albums(10, 4);
+        tracks.reverse();
+        let history = ShuffleHistory::default();
+        let window = Duration::from_secs(3600);
+        let queue = shuffle_fresh_albums(tracks, album_of, &history, window, 0, &mut rng);
+        assert_eq!(queue.tracks.len(), 40);
+        for album in queue.tracks.chunks(4) {
+            let numbers: Vec<_> = album.iter().map(|p| album_of(p).track).collect();
+            assert_eq!(numbers, vec![Some(1), Some(2), Some(3), Some(4)]);
+            assert!(
+                album
+                    .iter()
+                    .all(|p| album_of(p).album == album_of(&album[0]).album)
+            );
+        }
+    }
+
+    #[test]
+    fn mostly_heard_albums_are_left_out() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let tracks = albums(10, 4);
+        let mut history = ShuffleHistory::default();
+        // Álbum 0 entero, álbum 1 tres de cuatro, álbum 2 solo dos: este sigue entrando
+        for track in tracks[..7].iter().chain(&tracks[8..10]) {
+            history.record(track, 1_000);
+        }
+        let window = Duration::from_secs(3600);
+
+        let queue =
+            shuffle_fresh_albums(tracks.clone(), album_of, &history, window, 2_000, &mut rng);
+        assert_eq!(queue.excluded, 8);
+        assert_eq!(queue.tracks.len(), 32);
+        assert!(!queue.fell_back);
+        assert!(
+            !queue
+                .tracks
+                .iter()
+                .any(|p| p.starts_with("/m/0") || p.starts_with("/m/1"))
+        );
+
+        // Con pocas pistas nuevas se usan todas
+        let queue = shuffle_fresh_albums(
+            tracks[..12].to_vec(),
+            album_of,
+            &history,
+            window,
+            2_000,
+            &mut rng,
+        );
+        assert!(queue.fell_back);
+        assert_eq!(queue.tracks.len(), 12);
+
+        let queue =
+            shuffle_fresh_albums(tracks, album_of, &history, Duration::ZERO, 2_000, &mut rng);
+        assert_eq!(queue.tracks.len(), 40);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::models::theme::Theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    widgets::{BarChart, Block, Borders, List, ListItem, ListState, Row, Table, TableState},
+};
+
+/// Filas que muestra una sección de ranking mientras está colapsada
+const COLLAPSED_RANKING_ROWS: usize = 5;
+
+/// Qué hace Enter sobre una fila activable
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatsAction {
+    /// Abre el detalle de álbumes incompletos
+    IncompleteAlbums,
+    /// Reproduce todas las pistas del género
+    PlayGenre(String),
+}
+
+/// Estado con el que se dibuja una sección
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SectionView {
+    /// Colores de la interfaz
+    pub theme: Theme,
+    /// La sección tiene el cursor de foco
+    pub focused: bool,
+    /// La sección ocupa toda la pestaña
+    pub expanded: bool,
+    /// Fila activable seleccionada, índice dentro de `rows()`
+    pub selected_row: Option<usize>,
+    /// Primera línea visible cuando está expandida
+    pub scroll: usize,
+}
+
+/// Panel de la pestaña de estadísticas
+///
+/// Colapsada, una sección ocupa `height_hint` líneas en la columna de
+/// secciones; expandida recibe toda la pestaña. Las secciones que exponen
+/// filas activables reciben la selección en `SectionView::selected_row`.
+pub trait StatsSection {
+    /// Título del borde de la sección
+    fn title(&self) -> String;
+
+    /// Alto preferido en líneas cuando está colapsada, bordes incluidos
+    fn height_hint(&self) -> u16;
+
+    /// Cantidad de líneas de contenido cuando está expandida
+    fn content_len(&self) -> usize;
+
+    /// Dibuja la sección en el área dada
+    fn render(&self, f: &mut Frame, area: Rect, view: &SectionView);
+
+    /// Filas que se pueden seleccionar y activar con Enter
+    fn rows(&self) -> Vec<StatsAction> {
+        Vec::new()
+    }
+}
+
+/// Bloque con borde resaltado cuando la sección tiene el foco
+fn section_block(title: String, view: &SectionView) -> Block<'static> {
+    let border_style = if view.focused {
+        Style::default().fg(view.theme.accent)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border_style)
+}
+
+fn highlight_style(view: &SectionView) -> Style {
+    Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(view.theme.accent)
+}
+
+/// Totales de la biblioteca en forma de tabla
+pub struct SummarySection {
+    /// Etiqueta, valor y acción opcional de cada fila
+    pub rows: Vec<(String, String, Option<StatsAction>)>,
+}
+
+impl StatsSection for SummarySection {
+    fn title(&self) -> String {
+        "Stats (r recently added)".to_string()
+    }
+
+    fn height_hint(&self) -> u16 {
+        self.rows.len() as u16 + 2
+    }
+
+    fn content_len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn render(&self, f: &mut Frame, area: Rect, view: &SectionView) {
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .skip(view.scroll)
+            .map(|(label, value, _)| Row::new(vec![label.clone(), value.clone()]))
+            .collect();
+        let table = Table::new(
+            rows,
+            &[Constraint::Percentage(50), Constraint::Percentage(50)],
+        )
+        .block(section_block(self.title(), view))
+        .highlight_style(highlight_style(view));
+
+        // La selección cuenta solo filas activables; se traduce a la fila de la tabla
+        let selected = view.selected_row.and_then(|selected| {
+            self.rows
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, _, action))| action.is_some())
+                .nth(selected)
+                .and_then(|(i, _)| i.checked_sub(view.scroll))
+        });
+        let mut state = TableState::default().with_selected(selected.filter(|_| view.focused));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn rows(&self) -> Vec<StatsAction> {
+        self.rows
+            .iter()
+            .filter_map(|(_, _, action)| action.clone())
+            .collect()
+    }
+}
+
+/// Salud de la biblioteca: el puntaje en el título y un problema por fila
+pub struct HealthSection {
+    /// Puntaje de 0 a 100
+    pub score: u32,
+    /// Descripción y cantidad de cada problema
+    pub issues: Vec<(String, usize)>,
+}
+
+impl StatsSection for HealthSection {
+    fn title(&self) -> String {
+        format!("Library Health: {}/100", self.score)
+    }
+
+    fn height_hint(&self) -> u16 {
+        self.issues.len() as u16 + 2
+    }
+
+    fn content_len(&self) -> usize {
+        self.issues.len()
+    }
+
+    fn render(&self, f: &mut Frame, area: Rect, view: &SectionView) {
+        let rows: Vec<Row> = self
+            .issues
+            .iter()
+            .skip(view.scroll)
+            .map(|(label, count)| {
+                let style = if *count > 0 {
+                    Style::default().fg(view.theme.text)
+                } else {
+                    Style::default().fg(view.theme.dim)
+                };
+                Row::new(vec![label.clone(), count.to_string()]).style(style)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            &[Constraint::Percentage(70), Constraint::Percentage(30)],
+        )
+        .block(section_block(self.title(), view));
+        f.render_widget(table, area);
+    }
+}
+
+/// Lista ordenada (géneros, artistas): las primeras filas colapsada, todas expandida
+pub struct RankingSection {
+    pub title: String,
+    /// Texto y acción opcional de cada entrada, en orden de ranking
+    pub entries: Vec<(String, Option<StatsAction>)>,
+}
+
+impl StatsSection for RankingSection {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn height_hint(&self) -> u16 {
+        self.entries.len().clamp(1, COLLAPSED_RANKING_ROWS) as u16 + 2
+    }
+
+    fn content_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn render(&self, f: &mut Frame, area: Rect, view: &SectionView) {
+        let shown = if view.expanded {
+            self.entries.len()
+        } else {
+            COLLAPSED_RANKING_ROWS
+        };
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .take(shown)
+            .enumerate()
+            .map(|(i, (label, _))| ListItem::new(format!("{}. {}", i + 1, label)))
+            .collect();
+        let list = List::new(items)
+            .block(section_block(self.title(), view))
+            .highlight_style(highlight_style(view));
+
+        let selected = view.selected_row.and_then(|selected| {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, action))| action.is_some())
+                .nth(selected)
+                .map(|(i, _)| i)
+        });
+        let offset = if view.expanded { view.scroll } else { 0 };
+        let mut state = ListState::default()
+            .with_selected(selected.filter(|_| view.focused))
+            .with_offset(offset);
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn rows(&self) -> Vec<StatsAction> {
+        self.entries
+            .iter()
+            .filter_map(|(_, action)| action.clone())
+            .collect()
+    }
+}
+
+/// Pistas por década como gráfico de barras
+pub struct DecadesSection {
+    /// Década y cantidad de pistas, en orden cronológico
+    pub decades: Vec<(String, u64)>,
+}
+
+impl StatsSection for DecadesSection {
+    fn title(&self) -> String {
+        "Decades".to_string()
+    }
+
+    fn height_hint(&self) -> u16 {
+        12
+    }
+
+    fn content_len(&self) -> usize {
+        self.decades.len()
+    }
+
+    fn render(&self, f: &mut Frame, area: Rect, view: &SectionView) {
+        // Expandida, el desplazamiento salta décadas desde la izquierda
+        let skip = if view.expanded { view.scroll } else { 0 };
+        let data: Vec<(&str, u64)> = self
+            .decades
+            .iter()
+            .skip(skip)
+            .map(|(decade, count)| (decade.as_str(), *count))
+            .collect();
+        let chart = BarChart::default()
+            .block(section_block(self.title(), view))
+            .data(data.as_slice())
+            .bar_width(9)
+            .bar_style(Style::default().fg(view.theme.chart))
+            .value_style(
+                Style::default()
+                    .fg(view.theme.chart_value)
+                    .bg(view.theme.chart),
+            );
+        f.render_widget(chart, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    /// Dibuja la sección en un área del tamaño dado y retorna sus líneas
+    fn snapshot(section: &dyn StatsSection, view: SectionView, width: u16) -> Vec<String> {
+        let height = if view.expanded {
+            section.content_len() as u16 + 2
+        } else {
+            section.height_hint()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| section.render(f, f.size(), &view))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(width as usize)
+            .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect()
+    }
+
+    fn ranking(entries: usize) -> RankingSection {
+        RankingSection {
+            title: "Top".to_string(),
+            entries: (1..=entries)
+                .map(|i| {
+                    (
+                        format!("Genre {i}"),
+                        Some(StatsAction::PlayGenre(format!("g{i}"))),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn summary_snapshot() {
+        let summary = SummarySection {
+            rows: vec![
+                ("Total Tracks".to_string(), "3".to_string(), None),
+                (
+                    "Incomplete".to_string(),
+                    "1".to_string(),
+                    Some(StatsAction::IncompleteAlbums),
+                ),
+            ],
+        };
+        assert_eq!(
+            snapshot(&summary, SectionView::default(), 30),
+            [
+                "┌Stats (r recently added)────┐",
+                "│Total Tracks   3            │",
+                "│Incomplete     1            │",
+                "└────────────────────────────┘",
+            ]
+        );
+        assert_eq!(summary.rows(), [StatsAction::IncompleteAlbums]);
+    }
+
+    #[test]
+    fn collapsed_ranking_snapshot() {
+        assert_eq!(
+            snapshot(&ranking(7), SectionView::default(), 20),
+            [
+                "┌Top───────────────┐",
+                "│1. Genre 1        │",
+                "│2. Genre 2        │",
+                "│3. Genre 3        │",
+                "│4. Genre 4        │",
+                "│5. Genre 5        │",
+                "└──────────────────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn expanded_ranking_snapshot_scrolls() {
+        let section = ranking(7);
+        let view = SectionView {
+            expanded: true,
+            scroll: 5,
+            ..SectionView::default()
+        };
+        let lines = snapshot(&section, view, 20);
+        assert_eq!(lines[1], "│6. Genre 6        │");
+        assert_eq!(lines[2], "│7. Genre 7        │");
+        assert_eq!(section.rows().len(), 7);
+    }
+
+    #[test]
+    fn health_snapshot() {
+        let health = HealthSection {
+            score: 90,
+            issues: vec![("Missing covers".to_string(), 2)],
+        };
+        assert_eq!(
+            snapshot(&health, SectionView::default(), 30),
+            [
+                "┌Library Health: 90/100──────┐",
+                "│Missing covers      2       │",
+                "└────────────────────────────┘",
+            ]
+        );
+        assert!(health.rows().is_empty());
+    }
+}
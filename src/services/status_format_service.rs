@@ -0,0 +1,285 @@
+use crate::models::symbols::Symbols;
+use crate::services::shuffle_service::ShuffleMode;
+use crate::utils::format_duration_clock;
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marca que se agrega al recortar un texto
+const ELLIPSIS: &str = "…";
+
+/// Datos de la reproducción disponibles como marcadores en la plantilla
+#[derive(Debug, Default)]
+pub struct StatusContext<'a> {
+    /// Estado con su ícono, p. ej. "♪ Playing:" o "⏸ Paused:"
+    pub state: &'a str,
+    /// Qué se está reproduciendo: carpeta o playlist
+    pub source: &'a str,
+    pub artist: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub elapsed: Option<Duration>,
+    pub duration: Option<Duration>,
+    pub shuffle: ShuffleMode,
+    /// Filtro de la mezcla y cantidad de pistas, p. ej. "1990–1999, 412 tracks"
+    pub shuffle_filter: Option<&'a str>,
+    /// Volumen en porcentaje (100 es el volumen original)
+    pub volume: u32,
+    /// Lo que falta de la cola, p. ej. "2h 14m remaining, ends ~23:40"
+    pub queue: Option<&'a str>,
+    /// Juego de símbolos para la marca de recorte; los demás campos ya vienen armados
+    pub symbols: Symbols,
+}
+
+impl StatusContext<'_> {
+    /// Resuelve el valor de un marcador, `None` si el marcador no existe
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let clock = |d: Option<Duration>| d.map(format_duration_clock).unwrap_or_default();
+        match placeholder {
+            "state" => Some(self.state.to_string()),
+            "source" => Some(self.source.to_string()),
+            "artist" => Some(self.artist.unwrap_or_default().to_string()),
+            "title" => Some(self.title.unwrap_or_default().to_string()),
+            "elapsed" => Some(clock(self.elapsed)),
+            "duration" => Some(clock(self.duration)),
+            "shuffle" => {
+                let mode = match self.shuffle {
+                    ShuffleMode::Off => return Some(String::new()),
+                    ShuffleMode::Tracks => " in shuffle mode",
+                    ShuffleMode::Albums => " in album shuffle mode",
+                };
+                Some(match self.shuffle_filter {
+                    Some(filter) => format!("{} (shuffle: {})", mode, filter),
+                    None => mode.to_string(),
+                })
+            }
+            "volume" => Some(self.volume.to_string()),
+            "queue" => Some(
+                self.queue
+                    .map(|queue| format!(" | queue: {}", queue))
+                    .unwrap_or_default(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Trozo de la plantilla ya resuelto
+struct Segment {
+    /// Marcador del que proviene, `None` para texto literal
+    placeholder: Option<String>,
+    text: String,
+}
+
+/// Separa la plantilla en texto literal y marcadores resueltos
+///
+/// Los marcadores desconocidos se dejan tal cual.
+fn resolve(template: &str, context: &StatusContext) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        literal.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let resolved = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            context.value(name).map(|value| (name, value, end))
+        });
+        match resolved {
+            Some((name, value, end)) => {
+                if !literal.is_empty() {
+                    segments.push(Segment {
+                        placeholder: None,
+                        text: std::mem::take(&mut literal),
+                    });
+                }
+                segments.push(Segment {
+                    placeholder: Some(name.to_string()),
+                    text: value,
+                });
+                rest = &after[end + 1..];
+            }
+            None => {
+                literal.push('{');
+                rest = after;
+            }
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        segments.push(Segment {
+            placeholder: None,
+            text: literal,
+        });
+    }
+    segments
+}
+
+/// Recorta el texto a `max_width` columnas sin partir grafemas, con "…" al final
+pub fn truncate(text: &str, max_width: usize) -> String {
+    truncate_with(text, max_width, ELLIPSIS)
+}
+
+/// Igual que `truncate`, con otra marca de recorte
+fn truncate_with(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let room = max_width.saturating_sub(ellipsis.width());
+    let mut result = String::new();
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if width + grapheme_width > room {
+            break;
+        }
+        result.push_str(grapheme);
+        width += grapheme_width;
+    }
+    result.push_str(ellipsis);
+    result
+}
+
+fn join(segments: &[Segment]) -> String {
+    segments.iter().map(|s| s.text.as_str()).collect()
+}
+
+/// Arma la línea de estado a partir de la plantilla
+///
+/// Si no entra en `max_width` columnas recorta primero el título y luego el
+/// artista, con "…". El resto de la plantilla no se recorta.
+pub fn render_status(template: &str, context: &StatusContext, max_width: usize) -> String {
+    let mut segments = resolve(template, context);
+    let mut line = join(&segments);
+
+    for placeholder in ["title", "artist"] {
+        let excess = line.width().saturating_sub(max_width);
+        if excess == 0 {
+            break;
+        }
+        for segment in segments
+            .iter_mut()
+            .filter(|s| s.placeholder.as_deref() == Some(placeholder))
+        {
+            let target = segment.text.width().saturating_sub(excess);
+            segment.text = truncate_with(&segment.text, target, context.symbols.get(ELLIPSIS));
+        }
+        line = join(&segments);
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::symbols::SymbolSet;
+
+    fn context(symbols: Symbols) -> StatusContext<'static> {
+        StatusContext {
+            state: "Playing:",
+            source: "Album",
+            artist: Some("Sigur Rós"),
+            title: Some("Svefn-g-englar — live ♥"),
+            symbols,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn titles_keep_their_symbols_in_ascii_mode() {
+        let ascii = context(Symbols::new(SymbolSet::Ascii));
+        assert_eq!(
+            render_status("{artist}: {title}", &ascii, 80),
+            "Sigur Rós: Svefn-g-englar — live ♥"
+        );
+    }
+
+    #[test]
+    fn truncation_uses_the_symbol_set_and_fits_the_width() {
+        let template = "{state} {title}";
+        let ascii = render_status(template, &context(Symbols::new(SymbolSet::Ascii)), 20);
+        assert_eq!(ascii, "Playing: Svefn-g-...");
+        assert_eq!(ascii.width(), 20);
+
+        let unicode = render_status(template, &context(Symbols::default()), 20);
+        assert_eq!(unicode, "Playing: Svefn-g-en…");
+        assert_eq!(unicode.width(), 20);
+    }
+
+    #[test]
+    fn default_template_matches_the_classic_status_line() {
+        let template = crate::models::config::Config::default().status_format;
+        let playing = StatusContext {
+            state: "♪ Playing:",
+            source: "Kind of Blue",
+            ..Default::default()
+        };
+        assert_eq!(
+            render_status(&template, &playing, 80),
+            "♪ Playing: Kind of Blue"
+        );
+        let paused = StatusContext {
+            state: "⏸ Paused:",
+            source: "Kind of Blue",
+            shuffle: ShuffleMode::Tracks,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_status(&template, &paused, 80),
+            "⏸ Paused: Kind of Blue in shuffle mode"
+        );
+    }
+
+    #[test]
+    fn missing_metadata_renders_empty_and_unknown_placeholders_stay() {
+        let context = StatusContext {
+            title: Some("Untitled"),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_status(
+                "[{artist}] {title} {elapsed}/{duration} {nope}",
+                &context,
+                80
+            ),
+            "[] Untitled / {nope}"
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_graphemes_whole() {
+        let cases = [
+            // "é" escrita como e + acento combinado ocupa una columna
+            ("Cafe\u{301} del Mar", 5, "Cafe\u{301}…"),
+            // Cada ideograma ocupa dos columnas: no entra medio
+            ("東京事変", 6, "東京…"),
+            ("東京事変", 5, "東京…"),
+            ("short", 10, "short"),
+            ("anything", 0, ""),
+        ];
+        for (text, width, expected) in cases {
+            let truncated = truncate(text, width);
+            assert_eq!(truncated, expected, "{} at {}", text, width);
+            assert!(truncated.width() <= width);
+        }
+    }
+
+    #[test]
+    fn the_title_is_cut_before_the_artist() {
+        let context = StatusContext {
+            artist: Some("Artist"),
+            title: Some("A Very Long Title"),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_status("{artist} - {title}", &context, 15),
+            "Artist - A Ver…"
+        );
+        assert_eq!(render_status("{artist} - {title}", &context, 6), "Ar… - ");
+    }
+}
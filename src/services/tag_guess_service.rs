@@ -0,0 +1,316 @@
+use crate::services::metadata_service::TagEdit;
+use std::path::Path;
+
+/// Patrones que se ofrecen para adivinar etiquetas, en el orden en que se recorren
+pub const BUILTIN_PATTERNS: &[&str] = &[
+    "{track} - {artist} - {title}",
+    "{track} - {title}",
+    "{artist} - {title}",
+    "{track}. {title}",
+    "{artist} - {album} - {track} - {title}",
+    "{disc}-{track} {title}",
+];
+
+/// Campo de la etiqueta que toma un marcador del patrón
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Track,
+    Disc,
+    Year,
+    Artist,
+    Album,
+    Title,
+    /// `{_}`: texto que se salta, como un código de catálogo
+    Skip,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "track" => Some(Self::Track),
+            "disc" => Some(Self::Disc),
+            "year" => Some(Self::Year),
+            "artist" => Some(Self::Artist),
+            "album" => Some(Self::Album),
+            "title" => Some(Self::Title),
+            "_" => Some(Self::Skip),
+            _ => None,
+        }
+    }
+
+    /// El texto capturado sirve para el campo
+    fn accepts(self, text: &str) -> bool {
+        let digits = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+        match self {
+            Self::Track | Self::Disc => digits && text.len() <= 3,
+            Self::Year => digits && text.len() == 4,
+            Self::Artist | Self::Album | Self::Title | Self::Skip => !text.trim().is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(String),
+    Field(Field),
+}
+
+/// Cómo se reparte un separador que aparece también dentro de un campo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Split {
+    /// Cada campo toma lo menos posible: el sobrante queda en el último
+    /// (`"A - B - C"` con `{artist} - {title}` da el título `"B - C"`)
+    #[default]
+    Lazy,
+    /// Cada campo toma lo más posible: el sobrante queda en el primero
+    /// (el mismo nombre da el artista `"A - B"`)
+    Greedy,
+}
+
+/// Patrón de nombre de archivo como `"{track} - {artist} - {title}"`
+///
+/// Se compara con el nombre sin extensión. Los marcadores son `{track}`,
+/// `{disc}`, `{year}`, `{artist}`, `{album}`, `{title}` y `{_}` para saltar texto;
+/// el resto del patrón tiene que aparecer tal cual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenamePattern {
+    tokens: Vec<Token>,
+}
+
+impl FilenamePattern {
+    /// Analiza un patrón
+    ///
+    /// # Errors
+    /// Retorna un mensaje si el patrón no tiene marcadores, tiene uno desconocido o
+    /// sin cerrar, o dos marcadores seguidos (sin texto entre ellos no se sabe
+    /// dónde termina uno y empieza el otro)
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            let Some(open) = rest.find('{') else {
+                tokens.push(Token::Literal(rest.to_string()));
+                break;
+            };
+            if open > 0 {
+                tokens.push(Token::Literal(rest[..open].to_string()));
+            }
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| "Unclosed {".to_string())?;
+            let name = &rest[open + 1..open + close];
+            let field =
+                Field::from_name(name).ok_or_else(|| format!("Unknown field {{{}}}", name))?;
+            if matches!(tokens.last(), Some(Token::Field(_))) {
+                return Err(format!(
+                    "{{{}}} needs text between it and the field before",
+                    name
+                ));
+            }
+            tokens.push(Token::Field(field));
+            rest = &rest[open + close + 1..];
+        }
+        if !tokens.iter().any(|token| matches!(token, Token::Field(_))) {
+            return Err("The pattern has no fields".to_string());
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Campos que se leen del nombre del archivo, `None` si el nombre no encaja
+    pub fn apply(&self, path: &Path, split: Split) -> Option<TagEdit> {
+        let stem = path.file_stem()?.to_str()?;
+        let mut captures = Vec::new();
+        if !match_tokens(&self.tokens, stem, split, &mut captures) {
+            return None;
+        }
+        let mut edit = TagEdit::default();
+        for (field, text) in captures {
+            let text = text.trim();
+            match field {
+                Field::Track => edit.track_number = text.parse().ok(),
+                Field::Disc => edit.disc_number = text.parse().ok(),
+                Field::Year => edit.year = text.parse().ok(),
+                Field::Artist => edit.artist = Some(text.to_string()),
+                Field::Album => edit.album = Some(text.to_string()),
+                Field::Title => edit.title = Some(text.to_string()),
+                Field::Skip => {}
+            }
+        }
+        Some(edit)
+    }
+}
+
+/// Compara los marcadores con el texto, probando cortes hasta que todo encaje
+fn match_tokens<'a>(
+    tokens: &[Token],
+    text: &'a str,
+    split: Split,
+    captures: &mut Vec<(Field, &'a str)>,
+) -> bool {
+    match tokens {
+        [] => text.is_empty(),
+        [Token::Literal(literal), rest @ ..] => text
+            .strip_prefix(literal.as_str())
+            .is_some_and(|text| match_tokens(rest, text, split, captures)),
+        [Token::Field(field), rest @ ..] => {
+            // Fin posible del campo: donde aparece el texto que sigue, o el final
+            let mut ends: Vec<usize> = match rest.first() {
+                Some(Token::Literal(next)) => text
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .filter(|&i| text[i..].starts_with(next.as_str()))
+                    .collect(),
+                _ => vec![text.len()],
+            };
+            if split == Split::Greedy {
+                ends.reverse();
+            }
+            for end in ends {
+                let value = &text[..end];
+                if !field.accepts(value) {
+                    continue;
+                }
+                captures.push((*field, value));
+                if match_tokens(rest, &text[end..], split, captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guess(pattern: &str, file: &str, split: Split) -> Option<TagEdit> {
+        FilenamePattern::parse(pattern)
+            .unwrap()
+            .apply(Path::new(file), split)
+    }
+
+    #[test]
+    fn bad_patterns_are_rejected() {
+        let cases = [
+            ("Just text", "The pattern has no fields"),
+            ("", "The pattern has no fields"),
+            ("{track - {title}", "Unknown field {track - {title}"),
+            ("{track} - {title", "Unclosed {"),
+            ("{tracks} - {title}", "Unknown field {tracks}"),
+            (
+                "{artist}{title}",
+                "{title} needs text between it and the field before",
+            ),
+        ];
+        for (pattern, error) in cases {
+            assert_eq!(
+                FilenamePattern::parse(pattern),
+                Err(error.to_string()),
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_builtin_pattern_parses() {
+        for pattern in BUILTIN_PATTERNS {
+            assert!(FilenamePattern::parse(pattern).is_ok(), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn fields_are_read_and_trimmed() {
+        let edit = guess(
+            "{artist} - {album} - {track} - {title}",
+            "/m/Blur - Parklife - 03 -  Badhead .mp3",
+            Split::Lazy,
+        )
+        .unwrap();
+        assert_eq!(
+            edit,
+            TagEdit {
+                artist: Some("Blur".to_string()),
+                album: Some("Parklife".to_string()),
+                track_number: Some(3),
+                title: Some("Badhead".to_string()),
+                ..TagEdit::default()
+            }
+        );
+
+        let edit = guess("{disc}-{track} {title}", "/m/2-07 Song.flac", Split::Lazy).unwrap();
+        assert_eq!((edit.disc_number, edit.track_number), (Some(2), Some(7)));
+        let edit = guess(
+            "{year} {_} {title}",
+            "/m/1994 CAT-12 Girls.mp3",
+            Split::Lazy,
+        )
+        .unwrap();
+        assert_eq!(
+            (edit.year, edit.title.as_deref()),
+            (Some(1994), Some("Girls"))
+        );
+    }
+
+    #[test]
+    fn lazy_and_greedy_split_a_repeated_separator_differently() {
+        let file = "/m/A - B - C.mp3";
+        let lazy = guess("{artist} - {title}", file, Split::Lazy).unwrap();
+        assert_eq!(
+            (lazy.artist.as_deref(), lazy.title.as_deref()),
+            (Some("A"), Some("B - C"))
+        );
+        let greedy = guess("{artist} - {title}", file, Split::Greedy).unwrap();
+        assert_eq!(
+            (greedy.artist.as_deref(), greedy.title.as_deref()),
+            (Some("A - B"), Some("C"))
+        );
+    }
+
+    #[test]
+    fn numeric_fields_backtrack_to_a_cut_that_fits() {
+        // El primer " - " deja "Intro" como número de pista; el corte siguiente sí encaja
+        let edit = guess(
+            "{title} - {track}",
+            "/m/Side A - Intro - 12.mp3",
+            Split::Lazy,
+        )
+        .unwrap();
+        assert_eq!(edit.title.as_deref(), Some("Side A - Intro"));
+        assert_eq!(edit.track_number, Some(12));
+    }
+
+    #[test]
+    fn names_that_do_not_fit_give_nothing() {
+        let cases = [
+            ("{track} - {title}", "/m/Intro - Song.mp3"),
+            ("{track} - {title}", "/m/1234 - Song.mp3"),
+            ("{year} {title}", "/m/99 Song.mp3"),
+            ("{artist} - {title}", "/m/No separator.mp3"),
+            ("{artist} - {title}", "/m/ - Song.mp3"),
+            ("{track}. {title}", "/m/01. .mp3"),
+        ];
+        for (pattern, file) in cases {
+            assert_eq!(
+                guess(pattern, file, Split::Lazy),
+                None,
+                "{pattern} on {file}"
+            );
+            assert_eq!(
+                guess(pattern, file, Split::Greedy),
+                None,
+                "{pattern} on {file}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_are_skipped() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/m/01 - caf\xe9.mp3"));
+        let pattern = FilenamePattern::parse("{track} - {title}").unwrap();
+        assert_eq!(pattern.apply(path, Split::Lazy), None);
+    }
+}
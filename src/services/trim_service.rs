@@ -0,0 +1,96 @@
+use crate::services::config_service;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Archivo donde se guardan los recortes
+const TRIMS_FILE_NAME: &str = "trims.json";
+
+/// Puntos de inicio y fin con los que se reproduce una pista
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrimPoints {
+    /// Milisegundos a saltar al empezar la pista
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_ms: Option<u64>,
+    /// Milisegundo en el que la pista se da por terminada
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_ms: Option<u64>,
+}
+
+impl TrimPoints {
+    pub fn start(&self) -> Option<Duration> {
+        self.start_ms.map(Duration::from_millis)
+    }
+
+    pub fn end(&self) -> Option<Duration> {
+        self.end_ms.map(Duration::from_millis)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start_ms.is_none() && self.end_ms.is_none()
+    }
+
+    /// Recortes aplicables a una pista de la duración dada
+    ///
+    /// Si el archivo se reemplazó por uno más corto, se descartan los puntos
+    /// que quedan fuera de la pista y el fin que no esté después del inicio.
+    pub fn clamped(&self, duration: Option<Duration>) -> TrimPoints {
+        let within =
+            |point: Option<Duration>| point.filter(|p| duration.is_none_or(|total| *p < total));
+        let start = within(self.start());
+        let end = within(self.end()).filter(|end| start.is_none_or(|start| *end > start));
+        TrimPoints {
+            start_ms: start.map(|d| d.as_millis() as u64),
+            end_ms: end.map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+/// Recortes guardados por ruta de pista
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TrimStore {
+    trims: HashMap<PathBuf, TrimPoints>,
+}
+
+impl TrimStore {
+    /// Carga los recortes guardados; si no existen o están dañados empieza vacío
+    pub fn load() -> Self {
+        config_service::get_export_path(TRIMS_FILE_NAME)
+            .and_then(fs::read_to_string)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Guarda los recortes en el directorio de configuración
+    ///
+    /// # Errores
+    /// - Retorna error si no se puede escribir el archivo
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let path = config_service::get_export_path(TRIMS_FILE_NAME)?;
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Recortes de una pista; vacío si no tiene
+    pub fn get(&self, path: &Path) -> TrimPoints {
+        self.trims.get(path).copied().unwrap_or_default()
+    }
+
+    /// Modifica los recortes de una pista, olvidándola si quedan vacíos
+    pub fn update(&mut self, path: &Path, edit: impl FnOnce(&mut TrimPoints)) {
+        let mut trim = self.get(path);
+        edit(&mut trim);
+        if trim.is_empty() {
+            self.trims.remove(path);
+        } else {
+            self.trims.insert(path.to_path_buf(), trim);
+        }
+    }
+
+    /// Todos los recortes, para enviarlos al reproductor
+    pub fn all(&self) -> HashMap<PathBuf, TrimPoints> {
+        self.trims.clone()
+    }
+}
@@ -1,58 +1,172 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsString;
 use std::io::{self, stdout};
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant, SystemTime};
 
 use crossterm::{
-    event::{self, Event, KeyCode},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use rand::seq::SliceRandom;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::{Backend, CrosstermBackend, Frame, Terminal},
     style::{Color, Modifier, Style},
-    widgets::{
-        BarChart, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
-    },
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::models::config::{Config, ConfirmStyle, NotificationBackend, StatsRanking};
+use crate::models::symbols::Symbols;
+use crate::models::theme::{Theme, theme_for_preset};
+use crate::models::track::{TrackFilter, TrackRef};
+use crate::services::breadcrumb_service::{self, Crumb};
+use crate::services::clipboard_service;
+use crate::services::config_service;
+use crate::services::cover_cache_service::{self, CoverCache};
+use crate::services::dir_scanner_service::{
+    self, DirEntry, DirScanner, EntryKind, LeafAlbumAction, SortMode,
+};
+use crate::services::file_walker_service::{self, CollectorMessage, TrackCollector};
+use crate::services::folder_order_service::{self, FolderOrderStore};
+use crate::services::headless_service::{self, PlaybackSource};
+use crate::services::help_service::{self, View};
+use crate::services::hook_service::{HookContext, HookRunner};
+use crate::services::import_service::{self, ImportSummary, PathRemap};
+use crate::services::metadata_service::{
+    self, DEFAULT_TAG_PRIORITY, IncompleteAlbum, LibraryHealthReport, PlaylistService, TagEdit,
+    TagSource, TrackMetadata, TrackSortKey, get_album_art_path, normalize_featured_artist,
+    normalize_genre, read_embedded_art, split_disc_suffix, title_initial,
+};
+use crate::services::notification_service::{self, Notifier};
+use crate::services::offline_service::{self, DanglingPlaylist, TrackRow};
+use crate::services::palette_service::{self, PaletteAction, PaletteTarget};
+use crate::services::player_service::{
+    CrashRecovery, PlayerCommand, PlayerService, PlayerStatus, TrackProblem,
+};
+use crate::services::playlist_storage_service::{self, FAVORITES_NAME, Playlist, PlaylistError};
+use crate::services::queue_time_service::{self, QueueTime};
+use crate::services::search_service::{self, MatchField, SearchFields, SearchMatch};
+use crate::services::session_log_service::{self, SessionLog};
+use crate::services::session_service::{self, PartialAppState};
+use crate::services::shuffle_service::{self, ShuffleHistory, ShuffleMode};
+use crate::services::stats_section_service::{
+    DecadesSection, HealthSection, RankingSection, SectionView, StatsAction, StatsSection,
+    SummarySection,
+};
+use crate::services::status_format_service::{self, StatusContext};
+use crate::services::tag_guess_service::{self, FilenamePattern, Split};
+use crate::services::trim_service::{TrimPoints, TrimStore};
+use crate::services::volume_service;
+use crate::utils::{
+    format_count, format_duration_clock, format_duration_hms, is_audio_file, is_within_roots,
+    library_subdir, normalize_path, parse_duration,
 };
-use walkdir::WalkDir;
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::models::config::Config;
-use crate::services::metadata_service::PlaylistService;
-use crate::services::player_service::{PlayerCommand, PlayerService, PlayerStatus};
-use crate::services::playlist_storage_service::{self, Playlist};
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(3);
+const UNDO_HISTORY_LIMIT: usize = 20;
+// Playlists whose full track lists stay in memory; the rest keep only a summary
+const LOADED_PLAYLISTS_LIMIT: usize = 8;
+// Time to press any key before an album starts playing on its own
+const AUTO_PLAY_GRACE: Duration = Duration::from_millis(1500);
+// Longest pasted input, in graphemes so an accent is never cut off its letter
+const MAX_INPUT_LEN: usize = 200;
+const SECONDS_PER_DAY: u64 = 86_400;
+const RECENTLY_ADDED_DAYS: u32 = 30;
+const IMPORT_UNRESOLVED_SHOWN: usize = 20;
+const STATS_PAGE: usize = 10;
+// Name and formatted value of each entry of a Stats top list, best first
+type TopList = Vec<(String, String)>;
+// The Stats tab's sections, shared between the cache and whoever is reading them
+type StatsSections = Rc<Vec<Box<dyn StatsSection>>>;
+// Trim start and trimmed length of each track in the player's queue
+type QueueSpans = (Vec<Duration>, Vec<Option<Duration>>);
+// Tracks in a Shift+M mix
+const MIX_LENGTH: usize = 50;
+// How often the session is written for crash recovery, when it changed
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(5);
 
-pub fn run(config: &Config) -> io::Result<()> {
+// What to do right after the library is scanned
+#[derive(Default)]
+pub struct StartOptions<'a> {
+    pub import_from: Option<&'a Path>,
+    pub offer_restore: bool,
+    // Folder to open, relative to the music path or absolute
+    pub start_dir: Option<&'a Path>,
+    // File, folder or .m3u to start playing
+    pub play: Option<PathBuf>,
+}
+
+pub fn run(config: &Config, options: StartOptions) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = stdout();
     stdout.execute(EnterAlternateScreen)?;
+    stdout.execute(EnableBracketedPaste)?;
+    if config.mouse_capture {
+        stdout.execute(EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(&config.music_path);
+    let mut app = App::new(config);
 
     draw_loading_screen(
         &mut terminal,
+        &app.theme,
         "loading incredible musical data... please wait a few seconds.",
     )?;
     app.scan_directory();
+    if let Some(path) = options.import_from {
+        app.import_library(path);
+    }
+    if let Some(dir) = options.start_dir {
+        app.open_start_dir(dir);
+    }
+    if let Some(path) = options.play {
+        app.play_start_path(path);
+    }
+    app.start_dangling_check(false);
+    if options.offer_restore {
+        app.restore_prompt = session_service::load_session(&app.config.music_path);
+    }
 
     app.run(&mut terminal)?;
+    session_service::clear_session();
+    let _ = app.player.send(PlayerCommand::Quit);
+    let _ = app.shuffle_history.save();
+    if let Some(log) = &mut app.session_log {
+        let _ = log.finish_track();
+    }
 
     disable_raw_mode()?;
+    terminal.backend_mut().execute(DisableBracketedPaste)?;
+    if config.mouse_capture {
+        terminal.backend_mut().execute(DisableMouseCapture)?;
+    }
     terminal.backend_mut().execute(LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
     Ok(())
 }
 
-fn draw_loading_screen<B: Backend>(terminal: &mut Terminal<B>, message: &str) -> io::Result<()> {
+fn draw_loading_screen<B: Backend>(
+    terminal: &mut Terminal<B>,
+    theme: &Theme,
+    message: &str,
+) -> io::Result<()> {
     terminal.draw(|f| {
         let size = f.size();
         let loading_text = Paragraph::new(message)
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(theme.text))
             .alignment(Alignment::Center)
             .block(Block::default().title("Loading").borders(Borders::ALL));
         f.render_widget(loading_text, size);
@@ -60,6 +174,773 @@ fn draw_loading_screen<B: Backend>(terminal: &mut Terminal<B>, message: &str) ->
     Ok(())
 }
 
+// Unix seconds to a UTC YYYY-MM-DD date (civil-from-days algorithm)
+fn format_date(secs: u64) -> String {
+    let z = (secs / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{} min ago", secs / 60),
+        3_600..SECONDS_PER_DAY => format!("{} hours ago", secs / 3_600),
+        _ => format!("{} days ago", secs / SECONDS_PER_DAY),
+    }
+}
+
+// Which state colours a track row when several coincide. Marked wins over playing and
+// playing over selected; the ▶ prefix and the selection bar still show the others
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowEmphasis {
+    Marked,
+    Playing,
+    Selected,
+    Plain,
+}
+
+fn row_emphasis(marked: bool, playing: bool, selected: bool) -> RowEmphasis {
+    if marked {
+        RowEmphasis::Marked
+    } else if playing {
+        RowEmphasis::Playing
+    } else if selected {
+        RowEmphasis::Selected
+    } else {
+        RowEmphasis::Plain
+    }
+}
+
+// Suffix for the playing source's name, e.g. " (album shuffle)"
+fn shuffle_label(mode: ShuffleMode) -> &'static str {
+    match mode {
+        ShuffleMode::Off => "",
+        ShuffleMode::Tracks => " (shuffle)",
+        ShuffleMode::Albums => " (album shuffle)",
+    }
+}
+
+// "0:12 – 3:40", with "start"/"end" standing in for an unset point
+fn trim_label(trim: &TrimPoints) -> String {
+    format!(
+        "{} – {}",
+        trim.start()
+            .map_or("start".to_string(), format_duration_clock),
+        trim.end().map_or("end".to_string(), format_duration_clock)
+    )
+}
+
+fn playlist_history_line(playlist: &Playlist, now: u64) -> String {
+    let mut parts = Vec::new();
+    if let Some(created) = playlist.created {
+        parts.push(format!("created {}", format_date(created)));
+    }
+    if let Some(modified) = playlist.modified {
+        parts.push(format!(
+            "modified {}",
+            format_age(now.saturating_sub(modified))
+        ));
+    }
+    let mut count = format!("{} tracks", playlist.track_count());
+    if let Some(delta) = playlist.track_delta_since(now.saturating_sub(30 * SECONDS_PER_DAY))
+        && delta != 0
+    {
+        count.push_str(&format!(" ({:+} this month)", delta));
+    }
+    parts.push(count);
+    parts.join(", ")
+}
+
+// First visible row for a list, keeping `scrolloff` rows of context around the
+// selection and moving as little as possible from the previous offset
+fn scroll_offset(
+    previous: usize,
+    selected: usize,
+    len: usize,
+    height: usize,
+    scrolloff: usize,
+    centered: bool,
+) -> usize {
+    if height == 0 || len <= height {
+        return 0;
+    }
+    let max_offset = len - height;
+    let offset = if centered {
+        selected.saturating_sub(height / 2)
+    } else {
+        let margin = scrolloff.min((height - 1) / 2);
+        if selected < previous + margin {
+            selected.saturating_sub(margin)
+        } else if selected + margin >= previous + height {
+            selected + margin + 1 - height
+        } else {
+            previous
+        }
+    };
+    offset.min(max_offset)
+}
+
+// Favorites always come first so they can't get lost among the saved playlists
+const FAVORITES_INDEX: usize = 0;
+
+// A favorites file that can't be read is reported; a damaged one was already moved aside
+fn load_all_playlists() -> (Vec<Playlist>, Option<PlaylistError>) {
+    let (favorites, error) = match playlist_storage_service::load_favorites() {
+        Ok(favorites) => (favorites, None),
+        Err(e) => (Playlist::favorites(), Some(e)),
+    };
+    let playlists = std::iter::once(favorites)
+        .chain(playlist_storage_service::load_playlist_summaries().unwrap_or_default())
+        .collect();
+    (playlists, error)
+}
+
+// Row showing the given track index, falling back to the first row
+fn row_position(rows: &[TrackRow], track_index: usize) -> usize {
+    rows.iter()
+        .position(|row| row.track_index() == track_index)
+        .unwrap_or(0)
+}
+
+fn insert_str(input: &mut String, text: &str) {
+    // Pasted line breaks become single spaces; the input stays one line
+    let text = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    let room = MAX_INPUT_LEN.saturating_sub(input.graphemes(true).count());
+    input.extend(text.graphemes(true).take(room));
+}
+
+// Identical presses closer together than this count as the key being held
+const KEY_REPEAT_GAP: Duration = Duration::from_millis(600);
+const VOLUME_STEP: f32 = 0.1;
+const VOLUME_STEP_SMALL: f32 = 0.02;
+const VOLUME_STEP_LARGE: f32 = 0.05;
+// Resend a volume request if the player hasn't answered the previous one by then
+const VOLUME_CONFIRM_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Detects auto-repeat from the spacing between identical key presses
+struct KeyRepeat {
+    key: Option<KeyCode>,
+    held_since: Instant,
+    last_press: Instant,
+}
+
+impl KeyRepeat {
+    fn new() -> Self {
+        let now = Instant::now();
+        KeyRepeat {
+            key: None,
+            held_since: now,
+            last_press: now,
+        }
+    }
+
+    // Records a press and returns how long the key has been held so far
+    fn press(&mut self, key: KeyCode, now: Instant) -> Duration {
+        let repeating =
+            self.key == Some(key) && now.duration_since(self.last_press) <= KEY_REPEAT_GAP;
+        if !repeating {
+            self.key = Some(key);
+            self.held_since = now;
+        }
+        self.last_press = now;
+        now.duration_since(self.held_since)
+    }
+
+    fn release(&mut self) {
+        self.key = None;
+    }
+}
+
+// Keeps at most one SetVolume in flight: presses that arrive before the player
+// reports back only move the target, which is sent once the report comes in
+#[derive(Default)]
+struct VolumeLimiter {
+    target: Option<f32>,
+    sent_at: Option<Instant>,
+    queued: bool,
+}
+
+impl VolumeLimiter {
+    // Records a new target and returns it if it should be sent right away
+    fn request(&mut self, target: f32, now: Instant) -> Option<f32> {
+        self.target = Some(target);
+        let waiting = self
+            .sent_at
+            .is_some_and(|sent| now.duration_since(sent) < VOLUME_CONFIRM_TIMEOUT);
+        if waiting {
+            self.queued = true;
+            None
+        } else {
+            self.sent_at = Some(now);
+            self.queued = false;
+            Some(target)
+        }
+    }
+
+    // The player reported its volume; returns a queued target still to be sent
+    fn confirm(&mut self, now: Instant) -> Option<f32> {
+        if self.queued {
+            self.queued = false;
+            self.sent_at = Some(now);
+            return self.target;
+        }
+        self.target = None;
+        self.sent_at = None;
+        None
+    }
+
+    // What the UI should show: the pending target, else the player's level
+    fn current(&self, reported: f32) -> f32 {
+        self.target.unwrap_or(reported)
+    }
+}
+
+// A chord must be completed within this long; a press exactly at the limit still counts
+const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Dangerous actions that chord mode confirms with a second press of the same key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChordAction {
+    DeletePlaylist(usize),
+    Quit,
+    ClearQueue,
+}
+
+impl ChordAction {
+    // Key in Config::confirm_style_overrides
+    fn config_name(self) -> &'static str {
+        match self {
+            ChordAction::DeletePlaylist(_) => "delete_playlist",
+            ChordAction::Quit => "quit",
+            ChordAction::ClearQueue => "clear_queue",
+        }
+    }
+}
+
+// Waits for the second press of a chord; any other key or the timeout cancels it
+#[derive(Default)]
+struct ChordDetector {
+    pending: Option<(KeyCode, KeyModifiers, ChordAction, Instant)>,
+}
+
+impl ChordDetector {
+    // Returns true when this press completes the chord started for the same action
+    fn press(&mut self, key: event::KeyEvent, action: ChordAction, now: Instant) -> bool {
+        let completes = self.pending.is_some_and(|(code, modifiers, pending, at)| {
+            code == key.code
+                && modifiers == key.modifiers
+                && pending == action
+                && now.duration_since(at) <= CHORD_TIMEOUT
+        });
+        self.pending = if completes {
+            None
+        } else {
+            Some((key.code, key.modifiers, action, now))
+        };
+        completes
+    }
+
+    // A different key interrupts the chord before it's handled
+    fn interrupt(&mut self, key: event::KeyEvent) {
+        if self
+            .pending
+            .is_some_and(|(code, modifiers, _, _)| code != key.code || modifiers != key.modifiers)
+        {
+            self.pending = None;
+        }
+    }
+
+    // Action still waiting for its second press, and the time left
+    fn waiting(&self, now: Instant) -> Option<(ChordAction, Duration)> {
+        let (_, _, action, at) = self.pending?;
+        let elapsed = now.duration_since(at);
+        (elapsed <= CHORD_TIMEOUT).then(|| (action, CHORD_TIMEOUT - elapsed))
+    }
+}
+
+// Entries of the right-click menu on a track, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContextAction {
+    Play,
+    PlayNext,
+    AddToQueue,
+    Mark,
+    AddToPlaylist,
+    ShowInStats,
+}
+
+impl ContextAction {
+    const ALL: [Self; 6] = [
+        Self::Play,
+        Self::PlayNext,
+        Self::AddToQueue,
+        Self::Mark,
+        Self::AddToPlaylist,
+        Self::ShowInStats,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Play => "Play",
+            Self::PlayNext => "Play Next",
+            Self::AddToQueue => "Add to Queue",
+            Self::Mark => "Mark",
+            Self::AddToPlaylist => "Add to Playlist...",
+            Self::ShowInStats => "Show in Stats",
+        }
+    }
+}
+
+// Right-click menu, anchored where the click happened
+struct ContextMenu {
+    track: PathBuf,
+    column: u16,
+    row: u16,
+    selected: usize,
+}
+
+impl ContextMenu {
+    const WIDTH: u16 = 22;
+    const HEIGHT: u16 = ContextAction::ALL.len() as u16 + 2;
+
+    // Below and right of the click, pushed back inside the screen
+    fn area(&self, screen: Rect) -> Rect {
+        let width = Self::WIDTH.min(screen.width);
+        let height = Self::HEIGHT.min(screen.height);
+        Rect {
+            x: self.column.min(screen.x + screen.width - width),
+            y: self.row.min(screen.y + screen.height - height),
+            width,
+            height,
+        }
+    }
+}
+
+// Folder shuffle gathered in the background; track shuffle starts with the first batch
+struct ShuffleCollection {
+    collector: TrackCollector,
+    source: Option<String>,
+    mode: ShuffleMode,
+    found: usize,
+    // Album shuffle needs every track to group them, so it waits for the end
+    pending: Vec<PathBuf>,
+    // Queue generation being fed; a different one means other playback took over
+    generation: u64,
+    started: bool,
+}
+
+// A command the Ctrl+P palette can run; keys is the shortcut that does the same, if any
+struct Command {
+    name: &'static str,
+    keys: &'static str,
+    description: &'static str,
+    action: fn(&mut App) -> io::Result<()>,
+}
+
+// Listed first in the palette, and alone when the query starts with ">"
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "Shuffle library",
+        keys: "",
+        description: "Shuffle every track that passes the shuffle filter",
+        action: |app| {
+            app.apply_shuffle_filter(app.shuffle_filter.clone());
+            Ok(())
+        },
+    },
+    Command {
+        name: "Edit shuffle filter",
+        keys: "Ctrl+F",
+        description: "Years, length and genres the library shuffle keeps",
+        action: |app| {
+            app.filter_editor = Some(FilterEditor::new(&app.shuffle_filter));
+            Ok(())
+        },
+    },
+    Command {
+        name: "Mix from highlighted track",
+        keys: "Shift+M",
+        description: "Play a mix that flows through similar tracks",
+        action: |app| {
+            app.play_mix();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Next track",
+        keys: "Ctrl+N",
+        description: "Skip to the next track in the queue",
+        action: |app| {
+            let _ = app.player.send(PlayerCommand::SkipNext);
+            Ok(())
+        },
+    },
+    Command {
+        name: "Set volume",
+        keys: "Shift+V",
+        description: "Type an exact volume in percent (up to 200%, 110% with the logarithmic curve)",
+        action: |app| {
+            app.volume_input = Some(String::new());
+            Ok(())
+        },
+    },
+    Command {
+        name: "Recently added",
+        keys: "r (Stats)",
+        description: "Tracks added to the library lately",
+        action: |app| {
+            app.active_tab = ActiveTab::Stats;
+            app.open_recently_added();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Incomplete albums",
+        keys: "",
+        description: "Albums with gaps in their track numbers",
+        action: |app| {
+            app.viewing_incomplete_albums = true;
+            app.incomplete_album_selected = 0;
+            Ok(())
+        },
+    },
+    Command {
+        name: "Check playlists for missing files",
+        keys: "m (Playlists)",
+        description: "Find playlist entries whose files are gone",
+        action: |app| {
+            app.start_dangling_check(true);
+            Ok(())
+        },
+    },
+    Command {
+        name: "Rescan library",
+        keys: "",
+        description: "Read the tags of the whole library again and count the new tracks",
+        action: |app| {
+            app.notify("Rescanning the library".to_string());
+            app.defer(AppAction::RescanDirectory(app.music_path.clone()));
+            Ok(())
+        },
+    },
+    Command {
+        name: "Regroup library",
+        keys: "",
+        description: "Group genres, artists and albums again from the tags already read",
+        action: |app| {
+            app.notify("Regrouping the library".to_string());
+            app.defer(AppAction::RebuildIndex);
+            Ok(())
+        },
+    },
+    Command {
+        name: "Guess tags from file names",
+        keys: "Shift+G (Folders)",
+        description: "Tag the current folder's tracks from their names, with a preview",
+        action: |app| {
+            app.open_tag_guess();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Arrange folder order",
+        keys: "Shift+O (Folders)",
+        description: "Save a custom play order for the current folder",
+        action: |app| {
+            app.open_folder_reorder();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Undo",
+        keys: "Ctrl+Z",
+        description: "Undo the last playlist change",
+        action: |app| {
+            app.undo();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Cycle theme",
+        keys: "Shift+T",
+        description: "Switch to the next color theme",
+        action: |app| {
+            app.cycle_theme();
+            Ok(())
+        },
+    },
+    Command {
+        name: "Export shortcuts",
+        keys: "Ctrl+H",
+        description: "Write the shortcut reference to a file",
+        action: |app| {
+            let path = help_service::export_keybindings()?;
+            app.notify(format!("Shortcuts exported to {}", path.display()));
+            Ok(())
+        },
+    },
+];
+
+// Ctrl+P jump-anywhere popup; candidates are gathered when it opens, and the
+// library tracks matching the query are searched again on every keystroke
+struct Palette {
+    query: String,
+    targets: Vec<PaletteTarget>,
+    // How many of `targets` were gathered on open; the track results follow them
+    fixed: usize,
+    track_sort: TrackSortKey,
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl Palette {
+    fn new(targets: Vec<PaletteTarget>) -> Self {
+        let matches = palette_service::filter(&targets, "");
+        Self {
+            query: String::new(),
+            fixed: targets.len(),
+            targets,
+            track_sort: TrackSortKey::default(),
+            matches,
+            selected: 0,
+        }
+    }
+
+    fn refilter(&mut self, library: &PlaylistService) {
+        self.targets.truncate(self.fixed);
+        self.matches = palette_service::filter(&self.targets, &self.query);
+        self.targets.extend(palette_service::track_targets(
+            library,
+            &self.query,
+            self.track_sort,
+        ));
+        self.matches.extend(self.fixed..self.targets.len());
+        self.selected = 0;
+    }
+
+    fn current(&self) -> Option<&PaletteTarget> {
+        self.matches.get(self.selected).map(|&i| &self.targets[i])
+    }
+}
+
+// Shift+O popup: the folder's tracks in the order being arranged
+struct FolderReorder {
+    dir: PathBuf,
+    tracks: Vec<PathBuf>,
+    selected: usize,
+}
+
+impl FolderReorder {
+    // Swaps the selected track with its neighbour, keeping it selected
+    fn move_selected(&mut self, down: bool) {
+        let target = if down {
+            self.selected + 1
+        } else {
+            self.selected.wrapping_sub(1)
+        };
+        if target < self.tracks.len() {
+            self.tracks.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+}
+
+// `#` popup in an open playlist: A-Z plus `#` for titles that don't start with a letter
+#[derive(Default)]
+struct LetterPicker {
+    selected: usize,
+}
+
+impl LetterPicker {
+    const COLUMNS: usize = 9;
+    const LABELS: [char; 27] = [
+        'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R',
+        'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '#',
+    ];
+
+    // Lowercase letter to look for; `None` stands for `#`
+    fn initial(&self) -> Option<char> {
+        Some(Self::LABELS[self.selected].to_ascii_lowercase()).filter(char::is_ascii_lowercase)
+    }
+
+    fn step(&mut self, code: KeyCode) {
+        let last = Self::LABELS.len() - 1;
+        self.selected = match code {
+            KeyCode::Left => self.selected.saturating_sub(1),
+            KeyCode::Right => (self.selected + 1).min(last),
+            KeyCode::Up => self.selected.saturating_sub(Self::COLUMNS),
+            KeyCode::Down => (self.selected + Self::COLUMNS).min(last),
+            _ => self.selected,
+        };
+    }
+}
+
+// Shift+G popup: tags read from the file names of a folder, previewed before writing
+struct TagGuess {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    // Files that already have a title; they start rejected so tags aren't overwritten by accident
+    tagged: Vec<bool>,
+    pattern: String,
+    // Built-in pattern that Up/Down step from
+    builtin: usize,
+    split: Split,
+    // Parsed tags (None if the name doesn't fit) and whether to write them, per file
+    rows: Vec<(Option<TagEdit>, bool)>,
+    error: Option<String>,
+    editing_pattern: bool,
+    selected: usize,
+    // What was written, shown until a key is pressed
+    summary: Option<Vec<String>>,
+}
+
+impl TagGuess {
+    fn new(dir: PathBuf, files: Vec<PathBuf>, tagged: Vec<bool>, pattern: String) -> Self {
+        let builtin = tag_guess_service::BUILTIN_PATTERNS
+            .iter()
+            .position(|builtin| *builtin == pattern)
+            .unwrap_or(0);
+        let mut guess = Self {
+            dir,
+            files,
+            tagged,
+            pattern,
+            builtin,
+            split: Split::default(),
+            rows: Vec::new(),
+            error: None,
+            editing_pattern: true,
+            selected: 0,
+            summary: None,
+        };
+        guess.reparse();
+        guess
+    }
+
+    // Re-reads every file name with the current pattern; accept/reject choices start over
+    fn reparse(&mut self) {
+        let pattern = FilenamePattern::parse(&self.pattern);
+        self.error = pattern.as_ref().err().cloned();
+        self.rows = self
+            .files
+            .iter()
+            .zip(&self.tagged)
+            .map(|(file, &tagged)| {
+                let edit = pattern
+                    .as_ref()
+                    .ok()
+                    .and_then(|pattern| pattern.apply(file, self.split));
+                let accepted = edit.is_some() && !tagged;
+                (edit, accepted)
+            })
+            .collect();
+    }
+
+    fn step_builtin(&mut self, down: bool) {
+        let count = tag_guess_service::BUILTIN_PATTERNS.len();
+        self.builtin = if down {
+            (self.builtin + 1) % count
+        } else {
+            (self.builtin + count - 1) % count
+        };
+        self.pattern = tag_guess_service::BUILTIN_PATTERNS[self.builtin].to_string();
+        self.reparse();
+    }
+}
+
+// Ctrl+F criteria editor; the text fields are only parsed when applied
+struct FilterEditor {
+    fields: [String; 6],
+    strict: bool,
+    selected: usize,
+}
+
+impl FilterEditor {
+    const LABELS: [&'static str; 7] = [
+        "Year from",
+        "Year to",
+        "Min length",
+        "Max length",
+        "Genres",
+        "Exclude genres",
+        "Strict",
+    ];
+    const STRICT_ROW: usize = 6;
+
+    fn new(filter: &TrackFilter) -> Self {
+        let year = |year: Option<u32>| year.map(|y| y.to_string()).unwrap_or_default();
+        let length = |seconds: Option<u64>| {
+            seconds
+                .map(|s| format_duration_clock(Duration::from_secs(s)))
+                .unwrap_or_default()
+        };
+        Self {
+            fields: [
+                year(filter.year_from),
+                year(filter.year_to),
+                length(filter.min_seconds),
+                length(filter.max_seconds),
+                filter.genres.join(", "),
+                filter.exclude_genres.join(", "),
+            ],
+            strict: filter.strict,
+            selected: 0,
+        }
+    }
+
+    fn to_filter(&self) -> Result<TrackFilter, String> {
+        let year = |i: usize| -> Result<Option<u32>, String> {
+            let text = self.fields[i].trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            text.parse()
+                .map(Some)
+                .map_err(|_| format!("{}: not a year: {}", Self::LABELS[i], text))
+        };
+        let length = |i: usize| -> Result<Option<u64>, String> {
+            let text = self.fields[i].trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            parse_duration(text)
+                .map(|d| Some(d.as_secs()))
+                .ok_or_else(|| format!("{}: not a length: {}", Self::LABELS[i], text))
+        };
+        let genres = |i: usize| -> Vec<String> {
+            self.fields[i]
+                .split(',')
+                .map(str::trim)
+                .filter(|genre| !genre.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Ok(TrackFilter {
+            year_from: year(0)?,
+            year_to: year(1)?,
+            min_seconds: length(2)?,
+            max_seconds: length(3)?,
+            genres: genres(4),
+            exclude_genres: genres(5),
+            strict: self.strict,
+        })
+    }
+}
+
 struct InputState {
     last_key_press: Instant,
 }
@@ -87,6 +968,17 @@ enum ActiveTab {
     Stats,
 }
 
+impl ActiveTab {
+    fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(ActiveTab::FolderNavigation),
+            1 => Some(ActiveTab::PlaylistNavigation),
+            2 => Some(ActiveTab::Stats),
+            _ => None,
+        }
+    }
+}
+
 impl From<ActiveTab> for usize {
     fn from(tab: ActiveTab) -> Self {
         match tab {
@@ -97,13 +989,60 @@ impl From<ActiveTab> for usize {
     }
 }
 
+enum UndoAction {
+    DeletedPlaylist(Playlist),
+    RemovedTrack {
+        playlist_name: String,
+        index: usize,
+        track: TrackRef,
+    },
+    ReorderedTracks {
+        playlist_name: String,
+        old_order: Vec<TrackRef>,
+    },
+    // Track lists as they were before missing entries were cleaned out
+    CleanedPlaylists(Vec<(String, Vec<TrackRef>)>),
+}
+
+// Slow work left for an idle tick so the input handler returns right away
+#[derive(Debug, PartialEq, Eq)]
+enum AppAction {
+    // Re-read the tags under a folder of the library
+    RescanDirectory(PathBuf),
+    // Regroup the library by genre and artist without re-reading tags
+    RebuildIndex,
+    SaveConfig,
+    // Write a playlist's file, by name, after one or more edits
+    PersistPlaylist(String),
+}
+
 struct App {
+    config: Config,
+    theme: Theme,
+    symbols: Symbols,
     music_path: PathBuf,
     current_dir: PathBuf,
+    dir_history: Vec<PathBuf>,
+    // Deepest folder left with Alt+Left, so Alt+Right can walk back down
+    breadcrumb_trail: Option<PathBuf>,
+    dir_scanner: DirScanner,
+    // Merged multi-disc albums whose discs are listed under them, by merged path
+    open_disc_albums: HashSet<PathBuf>,
+    // Scanned entries behind `items`, in the same order; two names that aren't
+    // valid UTF-8 can share a label, so rows are resolved by index, never by label
+    entries: Vec<DirEntry>,
     items: Vec<String>,
     filtered_items: Vec<String>,
+    // Index into `items` and `entries` of each row of filtered_items
+    filtered_rows: Vec<usize>,
+    // Matched field shown next to each search result, parallel to filtered_items
+    filtered_context: Vec<Option<String>>,
     selected: usize,
     marked_tracks: HashSet<PathBuf>,
+    // Tracks already in some playlist, rebuilt when marking starts
+    indexed_playlist_tracks: HashSet<PathBuf>,
+    // Tracks of the playlists held only as summaries, read in the background
+    playlist_tracks_scan: Option<Receiver<HashSet<PathBuf>>>,
     playlist_name_input: String,
     is_creating_playlist: bool,
     playlist_creation_selected: usize,
@@ -111,111 +1050,1009 @@ struct App {
     playlist_to_delete: Option<usize>,
     playlists: Vec<Playlist>,
     playlist_service: PlaylistService,
+    // Computed once after the scan: it looks for cover images on disk
+    library_health: LibraryHealthReport,
+    incomplete_albums: Vec<IncompleteAlbum>,
     input_state: InputState,
+    key_repeat: KeyRepeat,
+    key_held: Duration,
     player: PlayerService,
     current_folder: Option<String>,
     is_playing: bool,
     is_paused: bool,
-    is_shuffle_mode: bool,
+    shuffle_mode: ShuffleMode,
     active_tab: ActiveTab,
     playlist_selected: usize,
     playlist_track_selected: usize,
     viewing_playlist: Option<usize>,
+    // Level shown to the user (1.0 is 100%); the player's amplitude goes through volume_service
     volume: f32,
+    volume_limiter: VolumeLimiter,
+    // Typed percentage while the Shift+V popup is open
+    volume_input: Option<String>,
     is_searching: bool,
     search_query: String,
+    current_track_path: Option<PathBuf>,
+    // Resolved once per track, so rows reached through another link still match
+    current_track_real_path: Option<PathBuf>,
+    track_position: Duration,
+    // Track and output sample rates in Hz, as reported by the player
+    audio_format: Option<(u32, u32)>,
+    hooks: HookRunner,
+    notification: Option<(String, Instant)>,
+    undo_stack: Vec<UndoAction>,
+    stashed_marks: Option<HashSet<PathBuf>>,
+    track_info_path: Option<PathBuf>,
+    viewing_incomplete_albums: bool,
+    // Result of --import-from, shown until any key is pressed
+    import_summary: Option<ImportSummary>,
+    incomplete_album_selected: usize,
+    // Stats tab: focused section, whether it fills the tab, and its selected row
+    stats_focus: usize,
+    stats_expanded: bool,
+    stats_row: usize,
+    stats_scroll: usize,
+    stats_first_section: Cell<usize>,
+    // Track moved with Shift+arrows, highlighted for one frame
+    moved_track: Cell<Option<usize>>,
+    shuffle_history: ShuffleHistory,
+    // Names of the fully loaded playlists, least recently used first
+    loaded_playlists: VecDeque<String>,
+    // Album folder that starts playing once the grace period passes untouched
+    pending_auto_play: Option<(PathBuf, Instant)>,
+    trims: TrimStore,
+    // Embedded covers already extracted; None if the cache folder can't be created
+    cover_cache: Option<CoverCache>,
+    chords: ChordDetector,
+    palette: Option<Palette>,
+    filter_editor: Option<FilterEditor>,
+    folder_reorder: Option<FolderReorder>,
+    letter_picker: Option<LetterPicker>,
+    tag_guess: Option<TagGuess>,
+    folder_orders: FolderOrderStore,
+    // The current folder lists its tracks in a saved custom order
+    custom_order: bool,
+    context_menu: Option<ContextMenu>,
+    // Where the folder list and the whole screen were last drawn, for mouse clicks
+    folder_list_area: Cell<Rect>,
+    screen_area: Cell<Rect>,
+    // Last state change, shown on the bottom line in screen reader mode
+    announcement: Option<String>,
+    // Session left by a crash, offered for restore at startup
+    restore_prompt: Option<PartialAppState>,
+    // Genre export waiting for the missing playlist folder to be created
+    create_dir_prompt: Option<(PathBuf, String)>,
+    // Last session written and when, so it's only rewritten after changes
+    saved_session: Option<PartialAppState>,
+    session_saved_at: Instant,
+    // Tracks the player couldn't decode this session, flagged with ⚠ in listings
+    broken_tracks: HashMap<PathBuf, TrackProblem>,
+    // Desktop notification sent when a track starts
+    notifier: Box<dyn Notifier>,
+    // Player's queue as of its last snapshot: current index and paths
+    last_queue_snapshot: Option<(usize, Vec<PathBuf>)>,
+    // Deferred work, run one action per tick while no input is waiting
+    pending_actions: VecDeque<AppAction>,
+    // Listening log from session_log_path, one JSON line per track
+    session_log: Option<SessionLog>,
+    // Time listened per track, read from the log when ranking stats by listening time
+    listening_time: HashMap<PathBuf, Duration>,
+    // Built on first use; cleared when the library, ranking or listening data change
+    stats_cache: RefCell<Option<StatsSections>>,
+    // Looked up once per queue snapshot instead of on every redraw
+    queue_spans: RefCell<Option<QueueSpans>>,
+    // Scroll offsets survive between frames so lists don't jump around
+    folder_scroll: Cell<usize>,
+    playlist_scroll: Cell<usize>,
+    playlist_track_scroll: Cell<usize>,
+    playlist_creation_scroll: Cell<usize>,
+    incomplete_albums_scroll: Cell<usize>,
+    // Read-only view of an .m3u found while browsing
+    viewing_m3u: Option<Playlist>,
+    m3u_track_selected: usize,
+    m3u_track_scroll: Cell<usize>,
+    // "Recently Added" listing opened from the Stats tab
+    recent_tracks: Option<Playlist>,
+    recent_track_selected: usize,
+    // Volume roots found unavailable the last time a listing was opened
+    offline_roots: HashSet<PathBuf>,
+    // Missing-track check running in the background; true when the user asked for it
+    dangling_check: Option<(Receiver<Vec<DanglingPlaylist>>, bool)>,
+    shuffle_collection: Option<ShuffleCollection>,
+    // Session shuffle filter (starts from the config) and how many tracks passed it
+    shuffle_filter: TrackFilter,
+    shuffle_pool: usize,
+    dangling_prompt: Option<Vec<DanglingPlaylist>>,
+    // "Ignore" silences the startup prompt for the rest of the session
+    dangling_ignored: bool,
+    recent_track_scroll: Cell<usize>,
+}
+
+// What App::new reads from disk and the audio device; tests start from the default
+struct Stores {
+    playlists: Vec<Playlist>,
+    favorites_error: Option<PlaylistError>,
+    player: PlayerService,
+    shuffle_history: ShuffleHistory,
+    trims: TrimStore,
+    folder_orders: FolderOrderStore,
+}
+
+// Nothing saved and no audio device, with the favorites list in its place
+impl Default for Stores {
+    fn default() -> Self {
+        Stores {
+            playlists: vec![Playlist::favorites()],
+            favorites_error: None,
+            player: PlayerService::default(),
+            shuffle_history: ShuffleHistory::default(),
+            trims: TrimStore::default(),
+            folder_orders: FolderOrderStore::default(),
+        }
+    }
+}
+
+impl Stores {
+    fn load(config: &Config) -> Self {
+        let (playlists, favorites_error) = load_all_playlists();
+        Stores {
+            playlists,
+            favorites_error,
+            player: PlayerService::with_audio(),
+            shuffle_history: ShuffleHistory::load(),
+            trims: TrimStore::load(),
+            folder_orders: FolderOrderStore::load(config.portable_folder_order),
+        }
+    }
 }
 
 impl App {
-    fn new(music_path: &std::path::Path) -> Self {
-        let playlist_service = PlaylistService::new();
+    fn new(config: &Config) -> Self {
+        Self::with_stores(config, Stores::load(config))
+    }
+
+    fn with_stores(config: &Config, stores: Stores) -> Self {
+        let mut playlist_service = PlaylistService::new();
+        playlist_service.set_artist_folding(config.fold_artist_names);
+        playlist_service.set_disc_merging(config.merge_disc_albums);
+        playlist_service.set_respect_nomedia(config.respect_nomedia);
+        playlist_service.set_show_hidden(config.show_hidden_files);
+        let tag_priority: Vec<TagSource> = config
+            .tag_priority
+            .iter()
+            .filter_map(|name| TagSource::from_name(name))
+            .collect();
+        playlist_service.set_tag_priority(if tag_priority.is_empty() {
+            DEFAULT_TAG_PRIORITY.to_vec()
+        } else {
+            tag_priority
+        });
 
         let mut app = App {
-            music_path: music_path.to_path_buf(),
-            current_dir: music_path.to_path_buf(),
+            config: config.clone(),
+            music_path: config.music_path.clone(),
+            current_dir: config.music_path.clone(),
+            dir_history: vec![],
+            breadcrumb_trail: None,
+            dir_scanner: DirScanner {
+                show_hidden: config.show_hidden_files,
+                merge_discs: config.merge_disc_albums,
+                sort_mode: if config.sort_folders_by_date {
+                    SortMode::Modified
+                } else {
+                    SortMode::Name
+                },
+                ..DirScanner::new()
+            },
+            open_disc_albums: HashSet::new(),
+            entries: vec![],
             items: vec![],
             filtered_items: vec![],
+            filtered_rows: vec![],
+            filtered_context: vec![],
             selected: 0,
             marked_tracks: HashSet::new(),
+            indexed_playlist_tracks: HashSet::new(),
+            playlist_tracks_scan: None,
             playlist_name_input: String::new(),
             is_creating_playlist: false,
             playlist_creation_selected: 0,
             is_deleting_playlist: false,
             playlist_to_delete: None,
-            playlists: playlist_storage_service::load_playlists().unwrap_or_default(),
+            playlists: stores.playlists,
             playlist_service,
+            library_health: LibraryHealthReport::default(),
+            incomplete_albums: Vec::new(),
             input_state: InputState::new(),
-            player: PlayerService::new(),
+            key_repeat: KeyRepeat::new(),
+            key_held: Duration::ZERO,
+            theme: theme_for_preset(config.theme_preset),
+            symbols: Symbols::new(config.symbol_set),
+            player: stores.player,
             current_folder: None,
             is_playing: false,
             is_paused: false,
-            is_shuffle_mode: false,
+            shuffle_mode: ShuffleMode::Off,
             active_tab: ActiveTab::FolderNavigation,
             playlist_selected: 0,
             playlist_track_selected: 0,
             viewing_playlist: None,
             volume: 1.0,
+            volume_limiter: VolumeLimiter::default(),
+            volume_input: None,
             is_searching: false,
             search_query: String::new(),
+            current_track_path: None,
+            current_track_real_path: None,
+            track_position: Duration::ZERO,
+            audio_format: None,
+            hooks: HookRunner::new(),
+            notification: None,
+            undo_stack: Vec::new(),
+            stashed_marks: None,
+            track_info_path: None,
+            viewing_incomplete_albums: false,
+            import_summary: None,
+            incomplete_album_selected: 0,
+            stats_focus: 0,
+            stats_expanded: false,
+            stats_row: 0,
+            stats_scroll: 0,
+            stats_first_section: Cell::new(0),
+            moved_track: Cell::new(None),
+            shuffle_history: stores.shuffle_history,
+            loaded_playlists: VecDeque::new(),
+            pending_auto_play: None,
+            trims: stores.trims,
+            cover_cache: (config.track_notifications != NotificationBackend::Off)
+                .then(CoverCache::open)
+                .and_then(Result::ok),
+            session_log: None,
+            listening_time: HashMap::new(),
+            stats_cache: RefCell::new(None),
+            queue_spans: RefCell::new(None),
+            notifier: notification_service::notifier_for(config.track_notifications),
+            last_queue_snapshot: None,
+            pending_actions: VecDeque::new(),
+            broken_tracks: HashMap::new(),
+            chords: ChordDetector::default(),
+            palette: None,
+            filter_editor: None,
+            folder_reorder: None,
+            letter_picker: None,
+            tag_guess: None,
+            folder_orders: stores.folder_orders,
+            custom_order: false,
+            context_menu: None,
+            folder_list_area: Cell::new(Rect::default()),
+            screen_area: Cell::new(Rect::default()),
+            announcement: None,
+            restore_prompt: None,
+            create_dir_prompt: None,
+            saved_session: None,
+            session_saved_at: Instant::now(),
+            folder_scroll: Cell::new(0),
+            playlist_scroll: Cell::new(0),
+            playlist_track_scroll: Cell::new(0),
+            playlist_creation_scroll: Cell::new(0),
+            incomplete_albums_scroll: Cell::new(0),
+            viewing_m3u: None,
+            m3u_track_selected: 0,
+            m3u_track_scroll: Cell::new(0),
+            recent_tracks: None,
+            recent_track_selected: 0,
+            offline_roots: HashSet::new(),
+            dangling_check: None,
+            shuffle_collection: None,
+            shuffle_filter: config.shuffle_filter.clone(),
+            shuffle_pool: 0,
+            dangling_prompt: None,
+            dangling_ignored: false,
+            recent_track_scroll: Cell::new(0),
         };
+        let _ = app.player.send(PlayerCommand::SetPreferNativeRate(
+            config.prefer_native_sample_rate,
+        ));
+        let _ = app.player.send(PlayerCommand::SetResumeAfterSleep(
+            config.resume_after_sleep,
+        ));
+        let _ = app.player.send(PlayerCommand::SetTrims(app.trims.all()));
+        if let Some(path) = &config.session_log_path {
+            match SessionLog::open(path) {
+                Ok(log) => app.session_log = Some(log),
+                Err(e) => app.notify(format!("Can't open session log: {}", e)),
+            }
+        }
+        match stores.favorites_error {
+            Some(PlaylistError::Serialization(_)) => app.notify(
+                "Favorites file was damaged; kept it as a .bak copy and started empty".to_string(),
+            ),
+            Some(e) => app.notify(format!("Can't read favorites: {}", e)),
+            None => {}
+        }
+        if config.stats_ranking == StatsRanking::Listening {
+            app.load_listening_time();
+        }
+        app.refresh_offline_roots();
         app.update_items();
         app
     }
 
-    fn scan_directory(&mut self) {
-        self.playlist_service
-            .scan_directory(self.music_path.as_path())
-            .unwrap();
+    // Checks each volume root once; tracks on unavailable volumes are collapsed in listings
+    fn refresh_offline_roots(&mut self) {
+        let tracks = self
+            .playlists
+            .iter()
+            .chain(self.viewing_m3u.as_ref())
+            .chain(self.recent_tracks.as_ref())
+            .flat_map(|playlist| playlist.tracks.iter().map(|track| track.path.as_path()));
+        self.offline_roots = offline_service::find_offline_roots(
+            tracks,
+            std::slice::from_ref(&self.music_path),
+            Path::exists,
+        );
     }
 
-    fn update_items(&mut self) {
-        self.items = std::fs::read_dir(&self.current_dir)
-            .unwrap_or_else(|_| std::fs::read_dir(".").unwrap())
-            .filter_map(|res| res.ok())
-            .filter(|entry| {
-                let path = entry.path();
-                if path.is_dir() {
-                    return true;
-                }
-                if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                    return matches!(ext.to_lowercase().as_str(), "mp3" | "flac" | "ogg" | "wav");
-                }
-                false
-            })
-            .map(|entry| {
-                let file_name = entry.file_name().into_string().unwrap_or_default();
-                if entry.path().is_dir() {
-                    format!("[DIR] {}", file_name)
-                } else {
-                    file_name
-                }
-            })
-            .collect();
+    fn is_on_offline_volume(&self, track: &Path) -> bool {
+        offline_service::is_on_offline_volume(
+            track,
+            &self.offline_roots,
+            std::slice::from_ref(&self.music_path),
+        )
+    }
 
-        if self.current_dir.parent().is_some() {
-            self.items.insert(0, "[DIR] ..".to_string());
-        }
+    fn is_playable(&self, track: impl AsRef<Path>) -> bool {
+        let track = track.as_ref();
+        !self.is_on_offline_volume(track) && track.exists() && is_audio_file(track)
+    }
+
+    // Imported playlists only play what is inside the library unless allowed
+    fn is_playable_in(&self, playlist: &Playlist, track: impl AsRef<Path>) -> bool {
+        let track = track.as_ref();
+        self.is_playable(track) && !self.is_blocked_external(playlist, track)
+    }
 
-        self.items.sort();
+    fn is_blocked_external(&self, playlist: &Playlist, track: &Path) -> bool {
+        playlist.is_blocked_external(track, std::slice::from_ref(&self.music_path))
+    }
 
-        if self.selected >= self.items.len() && !self.items.is_empty() {
-            self.selected = 0;
+    fn track_rows(&self, tracks: &[TrackRef]) -> Vec<TrackRow> {
+        offline_service::group_track_rows(
+            tracks,
+            &self.offline_roots,
+            std::slice::from_ref(&self.music_path),
+        )
+    }
+
+    // Moves the selection one row, stepping over collapsed offline entries
+    fn step_track_row(
+        &self,
+        tracks: &[TrackRef],
+        selected: usize,
+        down: bool,
+        wrap: bool,
+    ) -> usize {
+        let rows = self.track_rows(tracks);
+        if rows.is_empty() {
+            return 0;
         }
-        self.update_filtered_items();
+        let position = row_position(&rows, selected);
+        let next = if down {
+            if position + 1 < rows.len() {
+                position + 1
+            } else if wrap {
+                0
+            } else {
+                position
+            }
+        } else if position > 0 {
+            position - 1
+        } else if wrap {
+            rows.len() - 1
+        } else {
+            0
+        };
+        rows[next].track_index()
+    }
+
+    fn scan_directory(&mut self) {
+        if let Err(e) = self
+            .playlist_service
+            .scan_directory(self.music_path.as_path())
+        {
+            self.notify(format!("Can't scan {}: {}", self.music_path.display(), e));
+        }
+        self.refresh_library_reports();
+    }
+
+    // Reports derived from the whole library, recomputed only when it changes
+    fn refresh_library_reports(&mut self) {
+        self.library_health = self.playlist_service.analyze_library_health();
+        self.incomplete_albums = self.playlist_service.find_incomplete_albums();
+        self.stats_cache.take();
+        self.queue_spans.take();
+    }
+
+    fn update_items(&mut self) {
+        let mut entries = self.dir_scanner.scan(&self.current_dir);
+        let order = self.folder_orders.get(&self.current_dir);
+        self.custom_order = order.is_some();
+        if let Some(order) = order {
+            // Tracks come last, after folders and playlists
+            let first_track = entries
+                .iter()
+                .position(|entry| entry.kind == EntryKind::Track)
+                .unwrap_or(entries.len());
+            folder_order_service::sort_by_order(&order, &mut entries[first_track..], |entry| {
+                Some(entry.file_name.as_os_str())
+            });
+        }
+        let open: HashSet<OsString> = self
+            .open_disc_albums
+            .iter()
+            .filter(|album| album.parent() == Some(self.current_dir.as_path()))
+            .filter_map(|album| album.file_name().map(OsString::from))
+            .collect();
+        self.entries = dir_scanner_service::expand_disc_groups(entries, &open);
+        self.items = self.entries.iter().map(DirEntry::label).collect();
+
+        if self.selected >= self.items.len() && !self.items.is_empty() {
+            self.selected = 0;
+        }
+        self.update_filtered_items();
+    }
+
+    // The disc folders of the album `dir` belongs to, in disc order; just `dir` otherwise
+    fn album_dirs(&self, dir: &Path) -> Vec<PathBuf> {
+        let (Some(parent), Some(name)) = (dir.parent(), dir.file_name().and_then(|n| n.to_str()))
+        else {
+            return vec![dir.to_path_buf()];
+        };
+        if !self.dir_scanner.merge_discs || split_disc_suffix(name).is_none() {
+            return vec![dir.to_path_buf()];
+        }
+        self.dir_scanner
+            .scan(parent)
+            .into_iter()
+            .find(|entry| entry.discs.iter().any(|disc| disc == name))
+            .map(|entry| entry.discs.iter().map(|disc| parent.join(disc)).collect())
+            .unwrap_or_else(|| vec![dir.to_path_buf()])
+    }
+
+    // Moves into `dir`, remembering where we came from so Esc can go back
+    fn navigate_to(&mut self, dir: PathBuf) {
+        let previous = std::mem::replace(&mut self.current_dir, dir);
+        self.dir_history.push(previous);
+        self.show_current_dir();
+
+        // The listing was just scanned; disc rows only show up next to folders
+        match dir_scanner_service::leaf_album_action(
+            &self.current_dir,
+            &self.music_path,
+            &self.entries,
+            self.config.auto_play_album,
+        ) {
+            LeafAlbumAction::Browse => {}
+            LeafAlbumAction::Prompt => {
+                self.notify("Press p to play, Enter to browse".to_string());
+            }
+            LeafAlbumAction::AutoPlay => {
+                self.pending_auto_play = Some((self.current_dir.clone(), Instant::now()));
+                self.notify("Playing album... press any key to cancel".to_string());
+            }
+        }
+    }
+
+    fn open_start_dir(&mut self, dir: &Path) {
+        match library_subdir(&self.music_path, dir) {
+            Some(dir) if dir != self.music_path => self.navigate_to(dir),
+            Some(_) => {}
+            None => self.notify(format!(
+                "'{}' is not a folder in the library, showing the root",
+                dir.display()
+            )),
+        }
+    }
+
+    fn play_start_path(&mut self, path: PathBuf) {
+        let source = PlaybackSource::Path(path.clone());
+        match headless_service::playable_tracks(&self.config, &source) {
+            Ok(tracks) if !tracks.is_empty() => {
+                // --play wins over the auto-play of the start folder
+                self.pending_auto_play = None;
+                self.current_folder = path.file_name().map(|n| n.to_string_lossy().into_owned());
+                self.play_shuffled(tracks, ShuffleMode::Off);
+            }
+            Ok(_) => self.notify(format!("Nothing to play in '{}'", path.display())),
+            Err(e) => self.notify(format!("Can't play '{}': {}", path.display(), e)),
+        }
+    }
+
+    fn edit_trim(&mut self, path: &Path, edit: impl FnOnce(&mut TrimPoints)) {
+        self.trims.update(path, edit);
+        self.queue_spans.take();
+        if let Err(e) = self.trims.save() {
+            self.notify(format!("Couldn't save trim points: {}", e));
+        }
+        let _ = self.player.send(PlayerCommand::SetTrims(self.trims.all()));
+    }
+
+    // A panic in the audio thread leaves sends failing silently; start a new one
+    fn poll_player_crash(&mut self) {
+        let Some(recovery) = self.player.restart_if_crashed() else {
+            return;
+        };
+        // The new thread starts with an empty queue
+        self.handle_player_status(PlayerStatus::Stopped);
+        self.is_playing = false;
+        self.is_paused = false;
+        self.notify(match recovery {
+            CrashRecovery::Restarted(reason) => format!(
+                "Audio engine restarted ({}) {} playback stopped, start it again",
+                reason,
+                self.symbols.get("—")
+            ),
+            CrashRecovery::GaveUp(reason) => format!(
+                "Audio engine keeps crashing ({}) {} restart rusted-player",
+                reason,
+                self.symbols.get("—")
+            ),
+        });
+    }
+
+    fn poll_auto_play(&mut self) {
+        let Some((dir, started)) = &self.pending_auto_play else {
+            return;
+        };
+        if started.elapsed() < AUTO_PLAY_GRACE {
+            return;
+        }
+        // Navigating away without a key press (e.g. a paste) also drops it
+        if *dir == self.current_dir {
+            self.play_current_album();
+        }
+        self.pending_auto_play = None;
+    }
+
+    // Plays the current folder, or every disc when it is part of a multi-disc album
+    fn play_current_album(&mut self) {
+        let album_dirs = self.album_dirs(&self.current_dir);
+        let tracks: Vec<PathBuf> = album_dirs
+            .iter()
+            .flat_map(|dir| self.folder_tracks(dir))
+            .collect();
+
+        if !tracks.is_empty() {
+            let dir_name = self
+                .current_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned());
+            self.current_folder = if album_dirs.len() > 1 {
+                dir_name
+                    .and_then(|name| split_disc_suffix(&name))
+                    .map(|(album, _)| format!("{} ({} discs)", album, album_dirs.len()))
+            } else {
+                dir_name
+            };
+            self.is_playing = true;
+            self.is_paused = false;
+            self.shuffle_mode = ShuffleMode::Off;
+            let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+        }
+    }
+
+    // Audio files directly in `dir`, in its custom order if it has one
+    fn folder_tracks(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut tracks =
+            file_walker_service::walk_audio_files_shallow(dir, self.config.show_hidden_files);
+        if let Some(order) = self.folder_orders.get(dir) {
+            folder_order_service::sort_by_order(&order, &mut tracks, |track| track.file_name());
+        }
+        tracks
+    }
+
+    // Plays the listed tracks from the selected one down, in the order shown
+    fn play_folder_from_selected(&mut self) {
+        let tracks: Vec<PathBuf> = self
+            .filtered_items
+            .iter()
+            .enumerate()
+            .skip(self.selected)
+            .filter(|(_, item)| !item.starts_with("[DIR]") && !item.starts_with("[PL]"))
+            .map(|(row, _)| self.row_path(row))
+            .filter(|path| is_audio_file(path))
+            .collect();
+        if tracks.is_empty() {
+            return;
+        }
+        self.current_folder = self
+            .current_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+        self.is_playing = true;
+        self.is_paused = false;
+        self.shuffle_mode = ShuffleMode::Off;
+        let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+    }
+
+    // Selects the first track of the open playlist whose title starts with the letter;
+    // `None` is `#`, titles starting with a digit, a symbol or another alphabet
+    fn jump_to_initial(&mut self, initial: Option<char>) {
+        let Some(playlist_index) = self.viewing_playlist else {
+            return;
+        };
+        let titled: HashSet<&Path> = initial
+            .map(|letter| {
+                self.playlist_service
+                    .get_tracks_by_title_prefix(&letter.to_string())
+                    .into_iter()
+                    .map(|track| track.path.as_path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let tracks = &self.playlists[playlist_index].tracks;
+        let found = self.track_rows(tracks).into_iter().find_map(|row| {
+            let TrackRow::Track(index) = row else {
+                return None;
+            };
+            let track = &tracks[index];
+            let library = self
+                .playlist_service
+                .get_track_by_path(&track.path)
+                .filter(|metadata| metadata.title.is_some());
+            let matches = match (library, initial) {
+                (Some(metadata), Some(_)) => titled.contains(metadata.path.as_path()),
+                (Some(metadata), None) => {
+                    title_initial(metadata.title.as_deref().unwrap_or_default()).is_none()
+                }
+                // Outside the library (or untitled) the listed name decides
+                (None, _) => {
+                    let name = track
+                        .cached_title
+                        .clone()
+                        .unwrap_or_else(|| Self::track_name(&track.path));
+                    title_initial(&name) == initial
+                }
+            };
+            matches.then_some(index)
+        });
+        match found {
+            Some(index) => self.playlist_track_selected = index,
+            None => {
+                let label = initial.map_or('#', |letter| letter.to_ascii_uppercase());
+                self.notify(format!("No track titles under {}", label));
+            }
+        }
+    }
+
+    fn open_folder_reorder(&mut self) {
+        let dir = self.current_dir.clone();
+        let tracks = self.folder_tracks(&dir);
+        if tracks.is_empty() {
+            self.notify("No tracks to reorder here".to_string());
+            return;
+        }
+        if let Some(order) = self.folder_orders.get(&dir) {
+            let present: Vec<&std::ffi::OsStr> = tracks
+                .iter()
+                .filter_map(|track| track.file_name())
+                .collect();
+            let gone = folder_order_service::stale_count(&order, &present);
+            let new = tracks.len().saturating_sub(order.len() - gone);
+            if gone > 0 || new > 0 {
+                self.notify(format!(
+                    "Saved order: {} file(s) gone, {} new at the end",
+                    gone, new
+                ));
+            }
+        }
+        self.folder_reorder = Some(FolderReorder {
+            dir,
+            tracks,
+            selected: 0,
+        });
+    }
+
+    fn open_tag_guess(&mut self) {
+        let dir = self.current_dir.clone();
+        let files =
+            file_walker_service::walk_audio_files_shallow(&dir, self.config.show_hidden_files);
+        if files.is_empty() {
+            self.notify("No tracks to tag here".to_string());
+            return;
+        }
+        let tagged = files
+            .iter()
+            .map(|file| {
+                self.playlist_service
+                    .get_track_by_path(file)
+                    .is_some_and(|track| track.title.is_some())
+            })
+            .collect();
+        let pattern = self
+            .config
+            .last_tag_pattern
+            .clone()
+            .unwrap_or_else(|| tag_guess_service::BUILTIN_PATTERNS[0].to_string());
+        self.tag_guess = Some(TagGuess::new(dir, files, tagged, pattern));
+    }
+
+    // Writes the accepted rows and leaves a summary in the popup
+    fn write_guessed_tags(&mut self, guess: &mut TagGuess) {
+        if guess.error.is_some() {
+            return;
+        }
+        let mut written = 0;
+        let mut skipped = 0;
+        let mut unparsed = Vec::new();
+        let mut failed = Vec::new();
+        for (file, (edit, accepted)) in guess.files.iter().zip(&guess.rows) {
+            match edit {
+                None => unparsed.push(Self::track_name(file)),
+                Some(edit) if *accepted && !edit.is_empty() => {
+                    match metadata_service::write_tags(file, edit) {
+                        Ok(()) => written += 1,
+                        Err(e) => failed.push(format!("{}: {}", Self::track_name(file), e)),
+                    }
+                }
+                Some(_) => skipped += 1,
+            }
+        }
+
+        let mut summary = vec![format!(
+            "Tags written to {} file(s), {} skipped",
+            written, skipped
+        )];
+        if !unparsed.is_empty() {
+            summary.push(String::new());
+            summary.push(format!(
+                "{} name(s) didn't fit the pattern:",
+                unparsed.len()
+            ));
+            summary.extend(unparsed.into_iter().map(|name| format!("  {}", name)));
+        }
+        if !failed.is_empty() {
+            summary.push(String::new());
+            summary.push(format!("{} file(s) could not be written:", failed.len()));
+            summary.extend(failed.into_iter().map(|line| format!("  {}", line)));
+        }
+        guess.summary = Some(summary);
+
+        if written > 0 && guess.dir.starts_with(&self.music_path) {
+            self.defer(AppAction::RescanDirectory(guess.dir.clone()));
+        }
+        if self.config.last_tag_pattern.as_deref() != Some(guess.pattern.as_str()) {
+            self.config.last_tag_pattern = Some(guess.pattern.clone());
+            self.defer(AppAction::SaveConfig);
+        }
+    }
+
+    // Plays a mix that flows from the highlighted track through similar ones
+    fn play_mix(&mut self) {
+        let Some(seed) = self.highlighted_track_path() else {
+            return;
+        };
+        let tracks: Vec<PathBuf> = self
+            .playlist_service
+            .generate_mix(&seed, MIX_LENGTH)
+            .into_iter()
+            .filter(|track| self.is_playable(track))
+            .collect();
+        if tracks.is_empty() {
+            self.notify("Track is not in the library, can't build a mix".to_string());
+            return;
+        }
+        self.current_folder = Some(format!("Mix: {}", Self::track_name(&seed)));
+        self.notify(format!("Mix of {} tracks", tracks.len()));
+        self.play_shuffled(tracks, ShuffleMode::Off);
+    }
+
+    // Shuffles the current folder subtree, or the viewed/selected playlist
+    fn shuffle_scope(&mut self, mode: ShuffleMode) {
+        match self.active_tab {
+            ActiveTab::FolderNavigation => {
+                // Huge trees take seconds to walk, so it happens off the UI thread
+                self.shuffle_collection = Some(ShuffleCollection {
+                    collector: TrackCollector::spawn(
+                        self.current_dir.clone(),
+                        self.config.respect_nomedia,
+                        self.config.show_hidden_files,
+                    ),
+                    source: self
+                        .current_dir
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned()),
+                    mode,
+                    found: 0,
+                    pending: Vec::new(),
+                    generation: self.player.generation(),
+                    started: false,
+                });
+            }
+            ActiveTab::PlaylistNavigation => {
+                let playlist_to_shuffle = if let Some(playlist_index) = self.viewing_playlist {
+                    Some(&self.playlists[playlist_index])
+                } else if !self.playlists.is_empty()
+                    && self.ensure_playlist_loaded(self.playlist_selected)
+                {
+                    Some(&self.playlists[self.playlist_selected])
+                } else {
+                    None
+                };
+
+                if let Some(playlist) = playlist_to_shuffle {
+                    let valid_tracks: Vec<PathBuf> = playlist
+                        .tracks
+                        .iter()
+                        .filter(|track| self.is_playable_in(playlist, track))
+                        .map(|track| track.path.clone())
+                        .collect();
+
+                    if !valid_tracks.is_empty() {
+                        self.current_folder = Some(format!(
+                            "Playlist: {}{}",
+                            playlist.name,
+                            shuffle_label(mode)
+                        ));
+                        self.play_shuffled(valid_tracks, mode);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Goes back to the previous directory, or to the parent when there is no history
+    fn navigate_up(&mut self) {
+        let target = self
+            .dir_history
+            .pop()
+            .or_else(|| self.current_dir.parent().map(Path::to_path_buf));
+        if let Some(dir) = target {
+            self.current_dir = dir;
+            self.show_current_dir();
+        }
+    }
+
+    // Jumps straight to a breadcrumb segment, keeping the way back down for Alt+Right
+    fn jump_to_breadcrumb(&mut self, dir: PathBuf) {
+        if dir == self.current_dir {
+            return;
+        }
+        if !self
+            .breadcrumb_trail
+            .as_ref()
+            .is_some_and(|trail| trail.starts_with(&self.current_dir))
+        {
+            self.breadcrumb_trail = Some(self.current_dir.clone());
+        }
+        let previous = std::mem::replace(&mut self.current_dir, dir);
+        self.dir_history.push(previous);
+        self.show_current_dir();
+
+        // Highlight the folder that leads back along the trail
+        let child = self.breadcrumb_trail.as_ref().and_then(|trail| {
+            trail
+                .strip_prefix(&self.current_dir)
+                .ok()?
+                .components()
+                .next()
+                .map(|c| self.current_dir.join(c))
+        });
+        if let Some(position) = child.and_then(|child| {
+            (0..self.filtered_items.len()).position(|row| self.row_path(row) == child)
+        }) {
+            self.selected = position;
+        }
+    }
+
+    // Alt+Left goes up a segment, Alt+Right back down the trail, Alt+digit to segment n
+    fn breadcrumb_key(&mut self, code: KeyCode) {
+        let segments = breadcrumb_service::segments(&self.music_path, &self.current_dir);
+        let target = match code {
+            KeyCode::Left => segments
+                .len()
+                .checked_sub(2)
+                .map(|i| segments[i].path.clone()),
+            KeyCode::Right => self.breadcrumb_trail.as_ref().and_then(|trail| {
+                let next = trail
+                    .strip_prefix(&self.current_dir)
+                    .ok()?
+                    .components()
+                    .next()?;
+                Some(self.current_dir.join(next))
+            }),
+            KeyCode::Char(c) => c
+                .to_digit(10)
+                .and_then(|n| segments.get((n as usize).checked_sub(1)?))
+                .map(|segment| segment.path.clone()),
+            _ => None,
+        };
+        if let Some(dir) = target.filter(|dir| dir.is_dir()) {
+            self.jump_to_breadcrumb(dir);
+        }
+    }
+
+    fn show_current_dir(&mut self) {
+        self.selected = 0;
+        self.search_query.clear();
+        self.is_searching = false;
+        self.update_items();
+    }
+
+    // Scanned entry behind a row of filtered_items
+    fn row_entry(&self, row: usize) -> Option<&DirEntry> {
+        self.entries.get(*self.filtered_rows.get(row)?)
+    }
+
+    // Exact path behind a row of filtered_items, never rebuilt from the displayed label
+    fn row_path(&self, row: usize) -> PathBuf {
+        self.row_entry(row).map_or_else(
+            || self.current_dir.clone(),
+            |entry| entry.path(&self.current_dir),
+        )
     }
 
     fn update_filtered_items(&mut self) {
         if self.search_query.is_empty() {
             self.filtered_items = self.items.clone();
+            self.filtered_rows = (0..self.items.len()).collect();
+            self.filtered_context = vec![None; self.filtered_items.len()];
         } else {
-            self.filtered_items = self
+            let mut ranked: Vec<(usize, SearchMatch, Option<SystemTime>)> = self
                 .items
                 .iter()
-                .filter(|item| {
-                    item.to_lowercase()
-                        .contains(&self.search_query.to_lowercase())
+                .zip(&self.entries)
+                .enumerate()
+                .filter_map(|(index, (item, entry))| {
+                    let name = item
+                        .strip_prefix("[DIR] ")
+                        .or_else(|| item.strip_prefix("[PL] "))
+                        .unwrap_or(item);
+                    let path = entry.path(&self.current_dir);
+                    let track = self.playlist_service.get_track_by_path(&path);
+                    // Folders between the library root and the entry; ".." would match them all
+                    let folders = path
+                        .strip_prefix(&self.music_path)
+                        .ok()
+                        .filter(|_| entry.file_name != "..")
+                        .and_then(Path::parent)
+                        .into_iter()
+                        .flat_map(Path::iter)
+                        .filter_map(|segment| segment.to_str());
+                    let fields = SearchFields {
+                        title: track.and_then(|t| t.title.as_deref()),
+                        artist: track.and_then(|t| t.artist.as_deref()),
+                        album: track.and_then(|t| t.album.as_deref()),
+                        file_name: name,
+                        path_segments: folders.collect(),
+                    };
+                    let found = search_service::score_match(&self.search_query, &fields)?;
+                    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                    Some((index, found, modified))
+                })
+                .collect();
+            // No play counts are tracked yet, so ties fall back to the newest file
+            ranked.sort_by(|a, b| b.1.score.cmp(&a.1.score).then_with(|| b.2.cmp(&a.2)));
+
+            self.filtered_context = ranked
+                .iter()
+                .map(|(_, found, _)| {
+                    (found.field != MatchField::FileName).then(|| {
+                        format!(
+                            " {} in {}: {}",
+                            self.symbols.get("…"),
+                            found.field.name(),
+                            found.text
+                        )
+                    })
                 })
-                .cloned()
+                .collect();
+            self.filtered_rows = ranked.into_iter().map(|(index, _, _)| index).collect();
+            self.filtered_items = self
+                .filtered_rows
+                .iter()
+                .map(|&index| self.items[index].clone())
                 .collect();
         }
         if self.selected >= self.filtered_items.len() && !self.filtered_items.is_empty() {
@@ -228,32 +2065,2085 @@ impl App {
             terminal.draw(|f| self.ui(f))?;
 
             while let Ok(status) = self.player.receiver.try_recv() {
-                match status {
-                    PlayerStatus::Volume(vol) => self.volume = vol,
-                }
+                self.handle_player_status(status);
             }
+            self.poll_dangling_check();
+            self.poll_playlist_tracks();
+            self.poll_shuffle_collection();
+            self.poll_auto_play();
+            self.poll_player_crash();
+            self.poll_hooks();
+            self.autosave_session();
 
             if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    if self.input_state.can_process_key() {
-                        if self.handle_input(key)? {
-                            return Ok(());
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Release => {
+                        self.key_repeat.release();
+                    }
+                    Event::Key(key)
+                        if self.input_state.can_process_key() && self.handle_input(key)? =>
+                    {
+                        self.flush_pending_actions();
+                        return Ok(());
+                    }
+                    Event::Paste(text) => self.paste(&text),
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    _ => {}
+                }
+            } else {
+                self.run_pending_action();
+            }
+        }
+    }
+
+    // Queues slow work for the next idle tick; an action already waiting isn't queued twice
+    fn defer(&mut self, action: AppAction) {
+        if !self.pending_actions.contains(&action) {
+            self.pending_actions.push_back(action);
+        }
+    }
+
+    fn run_pending_action(&mut self) {
+        let Some(action) = self.pending_actions.pop_front() else {
+            return;
+        };
+        match action {
+            AppAction::RescanDirectory(path) => {
+                // Kept to tell new files apart from the ones only indexed again
+                let before = self.playlist_service.deep_clone();
+                let result = if path == self.music_path {
+                    self.playlist_service.scan_directory(&path)
+                } else {
+                    self.playlist_service.rescan_path(&path)
+                };
+                match result {
+                    Err(e) => self.notify(format!("Can't rescan {}: {}", path.display(), e)),
+                    Ok(()) => {
+                        let new = self.playlist_service.get_new_tracks_since(&before).len();
+                        if new > 0 {
+                            self.notify(format!(
+                                "{} new {} found",
+                                format_count(new),
+                                if new == 1 { "track" } else { "tracks" }
+                            ));
                         }
                     }
                 }
+                self.refresh_library_reports();
+            }
+            AppAction::RebuildIndex => {
+                self.playlist_service.rebuild_indices();
+                self.refresh_library_reports();
+            }
+            AppAction::SaveConfig => {
+                if let Err(e) = config_service::save_config(&self.config) {
+                    self.notify(format!("Could not save config: {}", e));
+                }
+            }
+            AppAction::PersistPlaylist(name) => {
+                let Some(playlist) = self.playlists.iter_mut().find(|p| p.name == name) else {
+                    return;
+                };
+                if let Err(e) = playlist_storage_service::save_playlist(playlist) {
+                    self.notify(format!("Could not save playlist '{}': {}", name, e));
+                }
             }
         }
     }
 
-    fn handle_input(&mut self, key: event::KeyEvent) -> io::Result<bool> {
+    // On quit, writes still waiting are done now; rescans are dropped
+    fn flush_pending_actions(&mut self) {
+        self.pending_actions.retain(|action| {
+            matches!(
+                action,
+                AppAction::SaveConfig | AppAction::PersistPlaylist(_)
+            )
+        });
+        while !self.pending_actions.is_empty() {
+            self.run_pending_action();
+        }
+    }
+
+    fn session_state(&self) -> PartialAppState {
+        let mut marked_tracks: Vec<PathBuf> = self.marked_tracks.iter().cloned().collect();
+        marked_tracks.sort();
+        PartialAppState {
+            music_path: self.config.music_path.clone(),
+            current_dir: self.current_dir.clone(),
+            selected: self.selected,
+            active_tab: self.active_tab.into(),
+            // The amplitude, not the level: with another curve next time it still sounds the same
+            volume: volume_service::level_to_amplitude(self.config.volume_curve, self.volume),
+            search_query: self.search_query.clone(),
+            viewing_playlist: self
+                .viewing_playlist
+                .map(|index| self.playlists[index].name.clone()),
+            marked_tracks,
+        }
+    }
+
+    // Writes the session every few seconds while it keeps changing
+    fn autosave_session(&mut self) {
+        if self.restore_prompt.is_some() || self.session_saved_at.elapsed() < SESSION_SAVE_INTERVAL
+        {
+            return;
+        }
+        self.session_saved_at = Instant::now();
+        let state = self.session_state();
+        if self.saved_session.as_ref() != Some(&state)
+            && session_service::save_session(&state).is_ok()
+        {
+            self.saved_session = Some(state);
+        }
+    }
+
+    fn restore_session(&mut self, state: PartialAppState) {
+        if state.current_dir.is_dir() {
+            self.current_dir = state.current_dir;
+            self.update_items();
+        }
+        if !state.search_query.is_empty() {
+            self.is_searching = true;
+            self.search_query = state.search_query;
+            self.update_filtered_items();
+        }
+        self.selected = state
+            .selected
+            .min(self.filtered_items.len().saturating_sub(1));
+        if let Some(tab) = ActiveTab::from_index(state.active_tab) {
+            self.active_tab = tab;
+        }
+        if let Some(name) = state.viewing_playlist {
+            self.open_playlist_named(&name);
+        }
+        self.marked_tracks = state.marked_tracks.into_iter().collect();
+        self.index_playlist_tracks();
+        let _ = self.player.send(PlayerCommand::SetVolume(state.volume));
+        self.notify("Previous session restored".to_string());
+    }
+
+    fn handle_player_status(&mut self, status: PlayerStatus) {
+        match status {
+            PlayerStatus::Volume(amplitude) => {
+                let vol = volume_service::amplitude_to_level(self.config.volume_curve, amplitude);
+                let percent = |volume: f32| (volume * 100.0).round() as u32;
+                if percent(vol) != percent(self.volume) {
+                    self.announce(format!("Volume {}%", percent(vol)));
+                }
+                self.volume = vol;
+                if let Some(target) = self.volume_limiter.confirm(Instant::now()) {
+                    self.send_volume(target);
+                }
+            }
+            PlayerStatus::TrackChanged(path) => {
+                if let Some(previous) = self.current_track_path.take() {
+                    self.run_hook(self.config.on_track_finished.clone(), Some(&previous));
+                }
+                self.run_hook(self.config.on_track_change.clone(), Some(&path));
+                if !self.is_playing {
+                    self.is_playing = true;
+                    self.is_paused = false;
+                    self.current_folder
+                        .get_or_insert_with(|| "Queue".to_string());
+                }
+                self.shuffle_history
+                    .record(&path, playlist_storage_service::unix_now());
+                self.log_track_started(&path);
+                self.notify_track_started(&path);
+                // Moving on to the next track keeps the cached queue; anything else
+                // (a skip back, a jump, a new queue) asks the player for it again
+                if !self.advance_queue_snapshot(&path) {
+                    let _ = self.player.send(PlayerCommand::QueueStatus);
+                }
+                self.current_track_real_path = path.canonicalize().ok();
+                self.current_track_path = Some(path);
+                self.track_position = Duration::ZERO;
+                self.announce(self.status_line(usize::MAX));
+            }
+            PlayerStatus::Position(position) => {
+                self.track_position = position;
+                if let Some(log) = &mut self.session_log {
+                    log.position(position);
+                }
+            }
+            PlayerStatus::Stopped => {
+                if let Some(log) = &mut self.session_log
+                    && let Err(e) = log.finish_track()
+                {
+                    self.notify(format!("Can't write session log: {}", e));
+                }
+                if let Some(previous) = self.current_track_path.take() {
+                    self.run_hook(self.config.on_track_finished.clone(), Some(&previous));
+                }
+                self.run_hook(self.config.on_playback_stopped.clone(), None);
+                self.track_position = Duration::ZERO;
+                self.audio_format = None;
+                self.last_queue_snapshot = None;
+                self.announce("Stopped".to_string());
+            }
+            PlayerStatus::Error(message) => {
+                self.announce(format!("Error: {}", message));
+                self.notify(message);
+            }
+            PlayerStatus::TrackError(path, problem) => {
+                let message = format!("Skipped {}: {}", Self::track_name(&path), problem);
+                self.announce(format!("Error: {}", message));
+                self.notify(message);
+                self.broken_tracks.insert(path, problem);
+            }
+            PlayerStatus::AudioFormat {
+                track_rate,
+                output_rate,
+            } => self.audio_format = Some((track_rate, output_rate)),
+            PlayerStatus::QueueSnapshot {
+                current_index,
+                paths,
+            } => {
+                self.last_queue_snapshot = Some((current_index, paths));
+                self.queue_spans.take();
+            }
+            PlayerStatus::ResumedAfterSleep { paused } => {
+                self.is_paused = paused;
+                let message = if paused {
+                    "Audio reopened after sleep, paused"
+                } else {
+                    "Resumed after sleep"
+                };
+                self.announce(message.to_string());
+                self.notify(message.to_string());
+            }
+        }
+    }
+
+    // Desktop notification with "Artist - Title" and the cover as icon
+    fn notify_track_started(&self, path: &Path) {
+        if self.config.track_notifications == NotificationBackend::Off {
+            return;
+        }
+        let info = self.playlist_service.get_track_by_path(path);
+        let body = match info.and_then(|t| t.title.as_deref().map(|title| (t, title))) {
+            Some((track, title)) => match &track.artist {
+                Some(artist) => format!("{} - {}", artist, title),
+                None => title.to_string(),
+            },
+            None => Self::track_name(path),
+        };
+        let icon = get_album_art_path(path)
+            .and_then(|cover| std::fs::read(cover).ok())
+            .or_else(|| {
+                let track = info.filter(|t| t.has_embedded_art)?;
+                // Every track of an album shares one cached cover
+                let album = track
+                    .album
+                    .as_ref()
+                    .map(|_| self.playlist_service.album_position(path).album);
+                match &self.cover_cache {
+                    Some(cache) => {
+                        cover_cache_service::get_or_extract_cover(path, album.as_deref(), cache)
+                    }
+                    None => read_embedded_art(path),
+                }
+            });
+        self.notifier.notify("Now playing", &body, icon.as_deref());
+    }
+
+    fn log_track_started(&mut self, path: &Path) {
+        let Some(log) = &mut self.session_log else {
+            return;
+        };
+        let info = self.playlist_service.get_track_by_path(path);
+        if let Err(e) = log.track_started(
+            path,
+            info.and_then(|t| t.title.as_deref()),
+            info.and_then(|t| t.artist.as_deref()),
+            playlist_storage_service::unix_now(),
+        ) {
+            self.notify(format!("Can't write session log: {}", e));
+        }
+    }
+
+    fn run_hook(&mut self, template: Option<String>, path: Option<&Path>) {
+        let Some(template) = template else {
+            return;
+        };
+
+        let info = path.and_then(|p| self.playlist_service.get_track_by_path(p));
+        let context = HookContext {
+            artist: info.and_then(|t| t.artist.as_deref()),
+            title: info.and_then(|t| t.title.as_deref()),
+            album: info.and_then(|t| t.album.as_deref()),
+            path,
+        };
+
+        if let Err(e) = self.hooks.run(&template, &context)
+            && self.hooks.should_notify_failure()
+        {
+            self.notify(format!("Hook failed: {}", e));
+        }
+    }
+
+    // Reports a hook that finished with an error, at most once per notice interval
+    fn poll_hooks(&mut self) {
+        if let Some(e) = self.hooks.reap()
+            && self.hooks.should_notify_failure()
+        {
+            self.notify(format!("Hook failed: {}", e));
+        }
+    }
+
+    // Looks for playlist entries whose files are gone without blocking the UI
+    fn start_dangling_check(&mut self, on_demand: bool) {
+        // Playlists held only as summaries are read from disk by the worker
+        let (loaded, summaries): (Vec<&Playlist>, Vec<&Playlist>) = self
+            .playlists
+            .iter()
+            .partition(|playlist| playlist.is_loaded());
+        let loaded: Vec<(String, Vec<PathBuf>)> = loaded
+            .into_iter()
+            .map(|playlist| {
+                let tracks = playlist.tracks.iter().map(|t| t.path.clone()).collect();
+                (playlist.name.clone(), tracks)
+            })
+            .collect();
+        let summaries: Vec<String> = summaries.into_iter().map(|p| p.name.clone()).collect();
+        let library_roots = vec![self.music_path.clone()];
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut playlists = loaded;
+            playlists.extend(summaries.into_iter().filter_map(|name| {
+                let playlist = playlist_storage_service::load_playlist(&name).ok()??;
+                let tracks = playlist.tracks.into_iter().map(|t| t.path).collect();
+                Some((name, tracks))
+            }));
+            let _ = sender.send(offline_service::find_dangling_tracks(
+                &playlists,
+                &library_roots,
+            ));
+        });
+        self.dangling_check = Some((receiver, on_demand));
+    }
+
+    fn poll_shuffle_collection(&mut self) {
+        let Some(mut collection) = self.shuffle_collection.take() else {
+            return;
+        };
+        // Other playback (or Stop) since the collection began: dropping it stops the walk
+        if collection.generation != self.player.generation() {
+            return;
+        }
+        let mut done = false;
+        let mut received = false;
+        for message in collection.collector.drain() {
+            let tracks = match message {
+                CollectorMessage::Batch(tracks) => tracks,
+                CollectorMessage::Done => {
+                    done = true;
+                    continue;
+                }
+            };
+            received = true;
+            let tracks: Vec<PathBuf> = tracks
+                .into_iter()
+                // The collector already checked existence and format on its own thread
+                .filter(|track| {
+                    !self.is_on_offline_volume(track) && self.passes_shuffle_filter(track)
+                })
+                .collect();
+            collection.found += tracks.len();
+            if tracks.is_empty() {
+                continue;
+            }
+            match collection.mode {
+                ShuffleMode::Tracks if !collection.started => {
+                    // Until playback really starts, later batches wait for their own turn here
+                    self.current_folder = collection.source.clone();
+                    if self.play_shuffled(tracks, ShuffleMode::Tracks) {
+                        collection.generation = self.player.generation();
+                        collection.started = true;
+                    }
+                }
+                ShuffleMode::Tracks => {
+                    self.shuffle_pool += tracks.len();
+                    let window = self.config.shuffle_exclusion_window();
+                    let now = playlist_storage_service::unix_now();
+                    let fresh: Vec<PathBuf> = tracks
+                        .into_iter()
+                        .filter(|track| {
+                            window.is_zero()
+                                || !self.shuffle_history.played_within(track, window, now)
+                        })
+                        .collect();
+                    if !fresh.is_empty() {
+                        let _ = self.player.send(PlayerCommand::EnqueueShuffled(fresh));
+                    }
+                }
+                _ => collection.pending.extend(tracks),
+            }
+        }
+
+        if !done {
+            if received {
+                self.notify(format!(
+                    "Collecting{} {} tracks",
+                    self.symbols.get("…"),
+                    format_count(collection.found)
+                ));
+            }
+            self.shuffle_collection = Some(collection);
+            return;
+        }
+        if !collection.pending.is_empty() {
+            self.current_folder = collection.source.clone();
+            let pending = std::mem::take(&mut collection.pending);
+            self.play_shuffled(pending, collection.mode);
+        }
+        // Batches were enqueued without asking for the queue each time
+        let _ = self.player.send(PlayerCommand::QueueStatus);
+        if collection.found == 0 {
+            self.notify("No tracks to shuffle here".to_string());
+        } else {
+            self.notify(format!(
+                "Shuffling {} tracks",
+                format_count(collection.found)
+            ));
+        }
+    }
+
+    fn poll_dangling_check(&mut self) {
+        let Some((receiver, on_demand)) = &self.dangling_check else {
+            return;
+        };
+        let on_demand = *on_demand;
+        let Ok(dangling) = receiver.try_recv() else {
+            return;
+        };
+        self.dangling_check = None;
+        if dangling.is_empty() {
+            if on_demand {
+                self.notify("No missing tracks in playlists".to_string());
+            }
+        } else if on_demand || !self.dangling_ignored {
+            self.dangling_prompt = Some(dangling);
+        }
+    }
+
+    fn clean_dangling_tracks(&mut self, dangling: Vec<DanglingPlaylist>) {
+        let mut previous = Vec::new();
+        let mut removed = 0;
+        for entry in dangling {
+            let Some(index) = self.load_playlist_named(&entry.name) else {
+                continue;
+            };
+            let playlist = &mut self.playlists[index];
+            let missing: HashSet<PathBuf> = entry.missing.into_iter().collect();
+            let old_tracks = playlist.tracks.clone();
+            // Files may have come back since the check ran
+            playlist
+                .tracks
+                .retain(|track| !missing.contains(&track.path) || track.path.exists());
+            let count = old_tracks.len() - playlist.tracks.len();
+            if count > 0 {
+                removed += count;
+                previous.push((playlist.name.clone(), old_tracks));
+            }
+        }
+        for (name, _) in &previous {
+            self.defer(AppAction::PersistPlaylist(name.clone()));
+        }
+        if !previous.is_empty() {
+            self.push_undo(UndoAction::CleanedPlaylists(previous));
+        }
+        self.notify(format!(
+            "Removed {} missing tracks (Ctrl+Z to undo)",
+            removed
+        ));
+    }
+
+    // Reads a playlist's tracks if only its summary is in memory, evicting the
+    // least recently used one when too many are loaded
+    fn ensure_playlist_loaded(&mut self, index: usize) -> bool {
+        let Some(playlist) = self.playlists.get_mut(index) else {
+            return false;
+        };
+        if let Err(e) = playlist.load_tracks() {
+            let message = format!("Couldn't load '{}': {}", playlist.name, e);
+            self.notify(message);
+            return false;
+        }
+        if playlist.is_favorites {
+            return true;
+        }
+
+        let name = playlist.name.clone();
+        self.loaded_playlists.retain(|loaded| *loaded != name);
+        self.loaded_playlists.push_back(name);
+        if self.loaded_playlists.len() > LOADED_PLAYLISTS_LIMIT {
+            let viewing = self
+                .viewing_playlist
+                .map(|i| self.playlists[i].name.clone());
+            if let Some(position) = self
+                .loaded_playlists
+                .iter()
+                .position(|loaded| Some(loaded) != viewing.as_ref())
+                && let Some(evicted) = self.loaded_playlists.remove(position)
+                && let Some(playlist) = self.playlists.iter_mut().find(|p| p.name == evicted)
+            {
+                // A deferred write can't wait: unloading drops the edited tracks
+                let pending = AppAction::PersistPlaylist(evicted);
+                if self.pending_actions.contains(&pending) {
+                    self.pending_actions.retain(|action| *action != pending);
+                    let _ = playlist_storage_service::save_playlist(playlist);
+                }
+                playlist.unload();
+            }
+        }
+        true
+    }
+
+    // Opens the playlist picker with a name suggested from the marked tracks
+    fn start_playlist_creation(&mut self) {
+        let tracks: Vec<PathBuf> = self.marked_tracks.iter().cloned().collect();
+        self.playlist_name_input = self.playlist_service.suggest_playlist_name(&tracks);
+        self.is_creating_playlist = true;
+    }
+
+    fn open_playlist_named(&mut self, name: &str) -> bool {
+        let Some(index) = self.load_playlist_named(name) else {
+            return false;
+        };
+        self.playlist_selected = index;
+        self.viewing_playlist = Some(index);
+        self.playlist_track_selected = 0;
+        self.refresh_offline_roots();
+        true
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return;
+        };
+        if let Some(menu) = self.context_menu.take() {
+            let area = menu.area(self.screen_area.get());
+            let inside = mouse.column >= area.x
+                && mouse.column < area.x + area.width
+                && mouse.row > area.y
+                && mouse.row + 1 < area.y + area.height;
+            if inside && button == MouseButton::Left {
+                let index = (mouse.row - area.y - 1) as usize;
+                if let Some(&action) = ContextAction::ALL.get(index) {
+                    self.run_context_action(menu.track, action);
+                }
+                return;
+            }
+            // A click elsewhere closes it; a right click may open another
+        }
+        if button != MouseButton::Right {
+            return;
+        }
+        if let Some(track) = self.track_at(mouse.column, mouse.row) {
+            self.context_menu = Some(ContextMenu {
+                track,
+                column: mouse.column,
+                row: mouse.row,
+                selected: 0,
+            });
+        }
+    }
+
+    // The folder view track under the mouse; selects its row
+    fn track_at(&mut self, column: u16, row: u16) -> Option<PathBuf> {
+        let busy = self.palette.is_some()
+            || self.is_creating_playlist
+            || self.is_deleting_playlist
+            || self.track_info_path.is_some()
+            || self.volume_input.is_some();
+        if busy
+            || !matches!(self.active_tab, ActiveTab::FolderNavigation)
+            || self.viewing_m3u.is_some()
+        {
+            return None;
+        }
+        let area = self.folder_list_area.get();
+        if column < area.x
+            || column >= area.x + area.width
+            || row <= area.y
+            || row + 1 >= area.y + area.height
+        {
+            return None;
+        }
+        let index = self.folder_scroll.get() + (row - area.y - 1) as usize;
+        self.filtered_items.get(index)?;
+        let track = self.row_path(index);
+        if !is_audio_file(&track) {
+            return None;
+        }
+        self.selected = index;
+        Some(track)
+    }
+
+    fn run_context_action(&mut self, track: PathBuf, action: ContextAction) {
+        match action {
+            ContextAction::Play => self.play_track(track),
+            ContextAction::PlayNext => self.enqueue(track, true),
+            ContextAction::AddToQueue => self.enqueue(track, false),
+            ContextAction::Mark => self.toggle_mark(track),
+            ContextAction::AddToPlaylist => {
+                // Same flow as a, keeping the user's marks aside
+                let marks = std::mem::replace(&mut self.marked_tracks, HashSet::from([track]));
+                self.stashed_marks = Some(marks);
+                self.start_playlist_creation();
+            }
+            ContextAction::ShowInStats => self.show_genre_in_stats(&track),
+        }
+    }
+
+    // Opens the genre ranking on the track's genre
+    fn show_genre_in_stats(&mut self, track: &Path) {
+        let Some(genre) = self
+            .playlist_service
+            .get_track_by_path(track)
+            .and_then(|t| t.genre.as_deref())
+            .map(normalize_genre)
+        else {
+            self.notify("The track has no genre".to_string());
+            return;
+        };
+        let found = self
+            .stats_sections()
+            .iter()
+            .enumerate()
+            .find_map(|(section, s)| {
+                let row = s.rows().iter().position(
+                    |action| matches!(action, StatsAction::PlayGenre(g) if normalize_genre(g) == genre),
+                )?;
+                Some((section, row))
+            });
+        self.active_tab = ActiveTab::Stats;
+        if let Some((section, row)) = found {
+            self.stats_focus = section;
+            self.stats_expanded = true;
+            self.stats_row = row;
+        }
+    }
+
+    fn open_palette(&mut self) {
+        let mut targets: Vec<PaletteTarget> = COMMANDS
+            .iter()
+            .map(|command| PaletteTarget::Command {
+                name: command.name,
+                keys: command.keys,
+                description: command.description,
+            })
+            .collect();
+        // Most recent folders first, skipping the one we're in
+        let mut seen = HashSet::from([&self.current_dir]);
+        targets.extend(
+            self.dir_history
+                .iter()
+                .rev()
+                .filter(|dir| seen.insert(*dir))
+                .map(|dir| PaletteTarget::Folder(dir.clone())),
+        );
+        targets.extend(
+            self.playlists
+                .iter()
+                .map(|playlist| PaletteTarget::Playlist(playlist.name.clone())),
+        );
+        targets.extend(
+            self.playlist_service
+                .get_top_artists()
+                .into_iter()
+                .map(|(artist, _)| PaletteTarget::Artist(artist)),
+        );
+        targets.extend(
+            self.playlist_service
+                .get_album_list()
+                .into_iter()
+                .map(PaletteTarget::Album),
+        );
+        self.palette = Some(Palette::new(targets));
+    }
+
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::Browse(dir) => {
+                self.active_tab = ActiveTab::FolderNavigation;
+                self.viewing_m3u = None;
+                if dir != self.current_dir {
+                    self.navigate_to(dir);
+                }
+            }
+            PaletteAction::PlayFolder(dir) => {
+                let tracks = file_walker_service::walk_audio_files(
+                    &dir,
+                    self.config.respect_nomedia,
+                    self.config.show_hidden_files,
+                );
+                self.current_folder = Some(Self::track_name(&dir));
+                self.play_shuffled(tracks, ShuffleMode::Off);
+            }
+            PaletteAction::OpenPlaylist(name) => {
+                if self.open_playlist_named(&name) {
+                    self.active_tab = ActiveTab::PlaylistNavigation;
+                }
+            }
+            PaletteAction::PlayPlaylist(name) => {
+                if let Some(index) = self.load_playlist_named(&name) {
+                    let playlist = &self.playlists[index];
+                    let tracks: Vec<PathBuf> = playlist
+                        .tracks
+                        .iter()
+                        .filter(|track| self.is_playable_in(playlist, track))
+                        .map(|track| track.path.clone())
+                        .collect();
+                    self.current_folder = Some(name);
+                    self.play_shuffled(tracks, ShuffleMode::Off);
+                }
+            }
+            PaletteAction::PlayTracks { source, tracks } => {
+                self.current_folder = Some(source);
+                self.play_shuffled(tracks, ShuffleMode::Off);
+            }
+            PaletteAction::RunCommand(name) => {
+                let Some(command) = COMMANDS.iter().find(|command| command.name == name) else {
+                    return;
+                };
+                if let Err(e) = (command.action)(self) {
+                    self.notify(format!("{} failed: {}", command.name, e));
+                }
+            }
+            PaletteAction::Nothing => self.notify("Nothing left in the library there".to_string()),
+        }
+    }
+
+    fn cycle_theme(&mut self) {
+        self.config.theme_preset = self.config.theme_preset.next();
+        self.theme = theme_for_preset(self.config.theme_preset);
+        self.notify(format!("Theme: {}", self.config.theme_preset.name()));
+    }
+
+    // Loaded playlists count right away; summaries are read from disk by a worker,
+    // without keeping their tracks loaded, and join the set when it finishes
+    fn index_playlist_tracks(&mut self) {
+        let (loaded, summaries): (Vec<&Playlist>, Vec<&Playlist>) = self
+            .playlists
+            .iter()
+            .partition(|playlist| playlist.is_loaded());
+        self.indexed_playlist_tracks = loaded
+            .into_iter()
+            .flat_map(|playlist| playlist.tracks.iter().map(|t| t.path.clone()))
+            .collect();
+        self.playlist_tracks_scan = None;
+        if summaries.is_empty() {
+            return;
+        }
+        let names: Vec<String> = summaries.into_iter().map(|p| p.name.clone()).collect();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let tracks: HashSet<PathBuf> = names
+                .iter()
+                .filter_map(|name| playlist_storage_service::load_playlist(name).ok()?)
+                .flat_map(|playlist| playlist.tracks.into_iter().map(|t| t.path))
+                .collect();
+            let _ = sender.send(tracks);
+        });
+        self.playlist_tracks_scan = Some(receiver);
+    }
+
+    fn poll_playlist_tracks(&mut self) {
+        let Some(receiver) = &self.playlist_tracks_scan else {
+            return;
+        };
+        let Ok(tracks) = receiver.try_recv() else {
+            return;
+        };
+        self.playlist_tracks_scan = None;
+        self.indexed_playlist_tracks.extend(tracks);
+    }
+
+    fn load_playlist_named(&mut self, name: &str) -> Option<usize> {
+        let index = self.playlists.iter().position(|p| p.name == name)?;
+        self.ensure_playlist_loaded(index).then_some(index)
+    }
+
+    fn delete_playlist(&mut self, index: usize) {
+        // The undo entry needs the full track list to restore the file
+        if !self.ensure_playlist_loaded(index) {
+            return;
+        }
+        let playlist = &self.playlists[index];
+        if playlist_storage_service::delete_playlist(&playlist.name).is_ok() {
+            // A write still waiting would bring the file back
+            let pending = AppAction::PersistPlaylist(playlist.name.clone());
+            self.pending_actions.retain(|action| *action != pending);
+            let playlist = self.playlists.remove(index);
+            self.push_undo(UndoAction::DeletedPlaylist(playlist));
+            self.playlist_selected = self
+                .playlist_selected
+                .min(self.playlists.len().saturating_sub(1));
+        }
+    }
+
+    fn confirm_style(&self, action: ChordAction) -> ConfirmStyle {
+        self.config.confirm_style_for(action.config_name())
+    }
+
+    // In chord mode the action only goes ahead on the second press; popup
+    // mode leaves confirmation to the caller, or none for quit and clear queue
+    fn confirmed(&mut self, key: event::KeyEvent, action: ChordAction) -> bool {
+        match self.confirm_style(action) {
+            ConfirmStyle::Popup => true,
+            ConfirmStyle::Chord => self.chords.press(key, action, Instant::now()),
+        }
+    }
+
+    fn chord_hint(&self) -> Option<String> {
+        let (action, left) = self.chords.waiting(Instant::now())?;
+        let hint = match action {
+            ChordAction::DeletePlaylist(index) => format!(
+                "press d again to delete '{}'",
+                self.playlists.get(index)?.name
+            ),
+            ChordAction::Quit => "press Ctrl+Q again to quit".to_string(),
+            ChordAction::ClearQueue => {
+                "press Backspace again to stop and clear the queue".to_string()
+            }
+        };
+        Some(format!("{} ({:.1}s)", hint, left.as_secs_f32()))
+    }
+
+    fn push_undo(&mut self, action: UndoAction) {
+        if self.undo_stack.len() >= UNDO_HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(action);
+    }
+
+    fn remove_playlist_track(&mut self, playlist_index: usize, track_index: usize) {
+        let playlist = &mut self.playlists[playlist_index];
+        if track_index >= playlist.tracks.len() {
+            return;
+        }
+
+        let track = playlist.tracks.remove(track_index);
+        let playlist_name = playlist.name.clone();
+        self.playlist_track_selected = track_index.min(playlist.tracks.len().saturating_sub(1));
+        self.defer(AppAction::PersistPlaylist(playlist_name.clone()));
+        self.push_undo(UndoAction::RemovedTrack {
+            playlist_name,
+            index: track_index,
+            track,
+        });
+    }
+
+    fn move_playlist_track(&mut self, playlist_index: usize, down: bool) {
+        let index = self.playlist_track_selected;
+        let playlist = &mut self.playlists[playlist_index];
+        let target = if down {
+            index + 1
+        } else {
+            index.wrapping_sub(1)
+        };
+        if index >= playlist.tracks.len() || target >= playlist.tracks.len() {
+            return;
+        }
+
+        let old_order = playlist.tracks.clone();
+        playlist.tracks.swap(index, target);
+        let playlist_name = playlist.name.clone();
+        self.defer(AppAction::PersistPlaylist(playlist_name.clone()));
+        self.playlist_track_selected = target;
+        self.moved_track.set(Some(target));
+        self.push_undo(UndoAction::ReorderedTracks {
+            playlist_name,
+            old_order,
+        });
+    }
+
+    fn undo(&mut self) {
+        let Some(action) = self.undo_stack.pop() else {
+            self.notify("Nothing to undo".to_string());
+            return;
+        };
+
+        match action {
+            UndoAction::DeletedPlaylist(mut playlist) => {
+                if playlist_storage_service::save_playlist(&mut playlist).is_ok() {
+                    self.notify(format!("Restored playlist '{}'", playlist.name));
+                    self.playlists.push(playlist);
+                }
+            }
+            UndoAction::RemovedTrack {
+                playlist_name,
+                index,
+                track,
+            } => {
+                if let Some(playlist_index) = self.load_playlist_named(&playlist_name) {
+                    let playlist = &mut self.playlists[playlist_index];
+                    let index = index.min(playlist.tracks.len());
+                    playlist.tracks.insert(index, track);
+                    self.defer(AppAction::PersistPlaylist(playlist_name.clone()));
+                    self.notify(format!("Restored track in '{}'", playlist_name));
+                }
+            }
+            UndoAction::CleanedPlaylists(previous) => {
+                for (playlist_name, tracks) in previous {
+                    if let Some(index) = self.load_playlist_named(&playlist_name) {
+                        let playlist = &mut self.playlists[index];
+                        playlist.tracks = tracks;
+                        self.defer(AppAction::PersistPlaylist(playlist_name));
+                    }
+                }
+                self.notify("Restored missing tracks".to_string());
+            }
+            UndoAction::ReorderedTracks {
+                playlist_name,
+                old_order,
+            } => {
+                if let Some(index) = self.load_playlist_named(&playlist_name) {
+                    let playlist = &mut self.playlists[index];
+                    playlist.tracks = old_order;
+                    self.defer(AppAction::PersistPlaylist(playlist_name.clone()));
+                    self.notify(format!("Restored order of '{}'", playlist_name));
+                }
+            }
+        }
+    }
+
+    // Small steps on a fresh press, larger ones once the key has been held a while
+    fn volume_step(&self) -> f32 {
+        if !self.config.key_acceleration {
+            VOLUME_STEP
+        } else if self.key_held >= Duration::from_millis(self.config.key_acceleration_delay_ms) {
+            VOLUME_STEP_LARGE
+        } else {
+            VOLUME_STEP_SMALL
+        }
+    }
+
+    // `volume` is the level the user sees; the player gets it through the volume curve
+    fn set_volume(&mut self, volume: f32) {
+        let target = volume.clamp(0.0, volume_service::max_level(self.config.volume_curve));
+        if let Some(target) = self.volume_limiter.request(target, Instant::now()) {
+            self.send_volume(target);
+        }
+    }
+
+    fn send_volume(&self, level: f32) {
+        let amplitude = volume_service::level_to_amplitude(self.config.volume_curve, level);
+        let _ = self.player.send(PlayerCommand::SetVolume(amplitude));
+    }
+
+    fn step_volume(&mut self, delta: f32) {
+        self.set_volume(self.volume_limiter.current(self.volume) + delta);
+    }
+
+    fn apply_volume_input(&mut self, input: &str) {
+        match input.parse::<u32>() {
+            Ok(percent) if percent <= self.max_volume_percent() => {
+                self.set_volume(percent as f32 / 100.0);
+            }
+            _ => self.notify(format!(
+                "Volume must be a number from 0 to {}",
+                self.max_volume_percent()
+            )),
+        }
+    }
+
+    // 200% with the linear curve, 110% with the logarithmic one (+6 dB either way)
+    fn max_volume_percent(&self) -> u32 {
+        (volume_service::max_level(self.config.volume_curve) * 100.0).floor() as u32
+    }
+
+    fn notify(&mut self, message: String) {
+        self.notification = Some((message, Instant::now()));
+    }
+
+    fn highlighted_track_path(&self) -> Option<PathBuf> {
+        match self.active_tab {
+            ActiveTab::FolderNavigation => {
+                if let Some(playlist) = &self.viewing_m3u {
+                    return playlist
+                        .tracks
+                        .get(self.m3u_track_selected)
+                        .map(|track| track.path.clone());
+                }
+                let item = self.filtered_items.get(self.selected)?;
+                if item.starts_with("[DIR]") || item.starts_with("[PL]") {
+                    None
+                } else {
+                    Some(self.row_path(self.selected))
+                }
+            }
+            ActiveTab::PlaylistNavigation => {
+                let playlist = &self.playlists[self.viewing_playlist?];
+                playlist
+                    .tracks
+                    .get(self.playlist_track_selected)
+                    .map(|track| track.path.clone())
+            }
+            ActiveTab::Stats => {
+                let recent = self.recent_tracks.as_ref()?;
+                recent
+                    .tracks
+                    .get(self.recent_track_selected)
+                    .map(|track| track.path.clone())
+            }
+        }
+    }
+
+    fn play_track(&mut self, track_path: PathBuf) {
+        self.current_folder = self
+            .current_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+        self.is_playing = true;
+        self.is_paused = false;
+        self.shuffle_mode = ShuffleMode::Off;
+        let _ = self.player.send(PlayerCommand::PlaySong(track_path));
+    }
+
+    fn enqueue(&mut self, track_path: PathBuf, next: bool) {
+        if next {
+            self.notify(format!("Playing next: {}", Self::track_name(&track_path)));
+            let _ = self.player.send(PlayerCommand::InsertNext(track_path));
+        } else {
+            self.notify(format!("Added to queue: {}", Self::track_name(&track_path)));
+            let _ = self.player.send(PlayerCommand::Enqueue(track_path));
+        }
+        let _ = self.player.send(PlayerCommand::QueueStatus);
+    }
+
+    fn toggle_mark(&mut self, track_path: PathBuf) {
+        if self.marked_tracks.contains(&track_path) {
+            self.marked_tracks.remove(&track_path);
+        } else {
+            if self.marked_tracks.is_empty() {
+                self.index_playlist_tracks();
+            }
+            self.marked_tracks.insert(track_path);
+        }
+    }
+
+    fn queueable_track_path(&self) -> Option<PathBuf> {
+        self.highlighted_track_path()
+            .filter(|path| path.exists() && is_audio_file(path))
+    }
+
+    fn highlighted_m3u_path(&self) -> Option<PathBuf> {
+        let item = self.filtered_items.get(self.selected)?;
+        item.starts_with("[PL] ")
+            .then(|| self.row_path(self.selected))
+    }
+
+    // The m3u being viewed, or the one highlighted in the folder list
+    fn active_m3u(&mut self) -> Option<Playlist> {
+        if !matches!(self.active_tab, ActiveTab::FolderNavigation) {
+            return None;
+        }
+        if self.viewing_m3u.is_some() {
+            return self.viewing_m3u.clone();
+        }
+        let path = self.highlighted_m3u_path()?;
+        match playlist_storage_service::parse_m3u(&path) {
+            Ok(playlist) => Some(playlist),
+            Err(e) => {
+                self.notify(format!("Could not read {}: {}", path.display(), e));
+                None
+            }
+        }
+    }
+
+    // Shuffled queue without the tracks heard within the exclusion window
+    fn fresh_shuffle(&mut self, tracks: Vec<PathBuf>) -> Vec<PathBuf> {
+        let window = self.config.shuffle_exclusion_window();
+        let queue = shuffle_service::shuffle_fresh(
+            tracks,
+            &self.shuffle_history,
+            window,
+            playlist_storage_service::unix_now(),
+            &mut rand::rng(),
+        );
+        if queue.fell_back {
+            self.notify("Few unheard tracks left, shuffling all of them".to_string());
+        }
+        queue.tracks
+    }
+
+    // Shuffles for the given mode and starts playing; album shuffle keeps each album in order.
+    // Returns false when the shuffle filter left nothing to play
+    fn play_shuffled(&mut self, mut tracks: Vec<PathBuf>, mode: ShuffleMode) -> bool {
+        if mode != ShuffleMode::Off {
+            tracks.retain(|track| self.passes_shuffle_filter(track));
+            if tracks.is_empty() {
+                self.notify(format!(
+                    "No tracks match the shuffle filter ({})",
+                    self.shuffle_filter.summary(&self.symbols)
+                ));
+                return false;
+            }
+            self.shuffle_pool = tracks.len();
+        }
+        self.is_playing = true;
+        self.is_paused = false;
+        self.shuffle_mode = mode;
+        match mode {
+            ShuffleMode::Off => {
+                let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+            }
+            ShuffleMode::Tracks => {
+                let tracks = self.fresh_shuffle(tracks);
+                let _ = self.player.send(PlayerCommand::PlayShuffle(tracks));
+            }
+            ShuffleMode::Albums => {
+                let queue = shuffle_service::shuffle_fresh_albums(
+                    tracks,
+                    |path| self.playlist_service.album_position(path),
+                    &self.shuffle_history,
+                    self.config.shuffle_exclusion_window(),
+                    playlist_storage_service::unix_now(),
+                    &mut rand::rng(),
+                );
+                if queue.fell_back {
+                    self.notify("Few unheard albums left, shuffling all of them".to_string());
+                }
+                let _ = self.player.send(PlayerCommand::PlayAlbum(queue.tracks));
+            }
+        }
+        true
+    }
+
+    fn passes_shuffle_filter(&self, track: &Path) -> bool {
+        self.shuffle_filter.is_empty()
+            || self
+                .playlist_service
+                .passes_filter(track, &self.shuffle_filter)
+    }
+
+    // Shuffle the whole library through a new filter, which stays for the session
+    fn apply_shuffle_filter(&mut self, filter: TrackFilter) {
+        self.shuffle_filter = filter;
+        let tracks: Vec<PathBuf> = self
+            .playlist_service
+            .filter_tracks(&self.shuffle_filter)
+            .into_iter()
+            .filter(|track| self.is_playable(track))
+            .collect();
+        if tracks.is_empty() {
+            self.notify(format!(
+                "No tracks match the shuffle filter ({})",
+                self.shuffle_filter.summary(&self.symbols)
+            ));
+            return;
+        }
+        self.current_folder = Some(format!("Library{}", shuffle_label(ShuffleMode::Tracks)));
+        self.play_shuffled(tracks, ShuffleMode::Tracks);
+        self.notify(format!(
+            "Shuffling {} tracks",
+            format_count(self.shuffle_pool)
+        ));
+    }
+
+    // Library entry of the playing track, also when it was reached through another link
+    fn current_track_info(&self) -> Option<&TrackMetadata> {
+        let path = self.current_track_path.as_deref()?;
+        self.playlist_service.get_track_by_path(path).or_else(|| {
+            let real = self.current_track_real_path.as_deref()?;
+            self.playlist_service.get_track_by_path(real)
+        })
+    }
+
+    // Lexical match first; resolving links touches the disk, so only rows with the
+    // playing file's name pay for it
+    fn is_now_playing(&self, path: &Path) -> bool {
+        let Some(playing) = &self.current_track_path else {
+            return false;
+        };
+        if path == playing || normalize_path(path) == normalize_path(playing) {
+            return true;
+        }
+        path.file_name() == playing.file_name()
+            && self.current_track_real_path.is_some()
+            && path.canonicalize().ok() == self.current_track_real_path
+    }
+
+    // Style of a track row from its emphasis; `plain` is the row's own look otherwise
+    fn emphasis_style(&self, emphasis: RowEmphasis, plain: Style) -> Style {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        match emphasis {
+            RowEmphasis::Marked => Style::default().fg(self.theme.marked),
+            RowEmphasis::Playing => bold.fg(self.theme.playing),
+            RowEmphasis::Selected => bold.fg(self.theme.accent),
+            RowEmphasis::Plain => plain,
+        }
+    }
+
+    // Text colour on the selection bar, which only the selected row's state can change
+    fn highlight_fg(&self, emphasis: RowEmphasis) -> Color {
+        match emphasis {
+            RowEmphasis::Marked => self.theme.marked,
+            RowEmphasis::Playing => self.theme.playing,
+            RowEmphasis::Selected | RowEmphasis::Plain => self.theme.text,
+        }
+    }
+
+    fn play_m3u(&mut self, mode: ShuffleMode) -> bool {
+        let Some(playlist) = self.active_m3u() else {
+            return false;
+        };
+        let tracks: Vec<PathBuf> = playlist
+            .tracks
+            .iter()
+            .filter(|track| self.is_playable_in(&playlist, track))
+            .map(|track| track.path.clone())
+            .collect();
+        if !tracks.is_empty() {
+            self.current_folder = Some(format!(
+                "Playlist: {}{}",
+                playlist.name,
+                shuffle_label(mode)
+            ));
+            self.play_shuffled(tracks, mode);
+        }
+        true
+    }
+
+    fn open_recently_added(&mut self) {
+        let tracks = self
+            .playlist_service
+            .get_tracks_added_recently(RECENTLY_ADDED_DAYS)
+            .into_iter()
+            .map(|t| t.path.clone())
+            .collect();
+        self.recent_tracks = Some(Playlist::new(
+            format!("Recently Added ({} days)", RECENTLY_ADDED_DAYS),
+            tracks,
+        ));
+        self.recent_track_selected = 0;
+        self.refresh_offline_roots();
+    }
+
+    // Saves every imported playlist that matched at least one track, never overwriting
+    fn import_library(&mut self, path: &Path) {
+        // A relative path missing from the working directory is looked up in the playlist folder
+        let in_playlist_dir = self
+            .config
+            .playlist_dir()
+            .map(|dir| dir.join(path))
+            .filter(|candidate| path.is_relative() && !path.exists() && candidate.exists());
+        let path = in_playlist_dir.as_deref().unwrap_or(path);
+        let remap = PathRemap {
+            old_prefix: self.config.import_path_prefix.as_deref(),
+            music_path: &self.music_path,
+        };
+        let summary = match import_service::import_library(path, &remap) {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.notify(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        for imported in summary.playlists.iter().filter(|p| !p.tracks.is_empty()) {
+            let name = self.free_playlist_name(&imported.name);
+            let mut playlist = Playlist::new(name, imported.tracks.clone());
+            playlist.imported = true;
+            if playlist_storage_service::save_playlist(&mut playlist).is_ok() {
+                self.playlists.push(playlist);
+            }
+        }
+        self.import_summary = Some(summary);
+    }
+
+    fn free_playlist_name(&self, base: &str) -> String {
+        playlist_storage_service::unique_playlist_name(base, &self.playlists)
+    }
+
+    // o on an imported playlist: play (or stop playing) its entries outside the library
+    fn toggle_allow_external(&mut self) {
+        let is_m3u = self.viewing_m3u.is_some();
+        let playlist = match self.viewing_playlist {
+            Some(index) if !is_m3u => &mut self.playlists[index],
+            _ => match &mut self.viewing_m3u {
+                Some(playlist) => playlist,
+                None => return,
+            },
+        };
+        if !playlist.imported {
+            self.notify("Only imported playlists restrict outside paths".to_string());
+            return;
+        }
+        playlist.allow_external = !playlist.allow_external;
+        let allowed = playlist.allow_external;
+        // The .m3u listing keeps it in memory until imported with c
+        let saved = if is_m3u {
+            Ok(())
+        } else {
+            playlist_storage_service::save_playlist(playlist)
+        };
+        match saved {
+            Ok(()) if allowed => self.notify("Tracks outside the library will play".to_string()),
+            Ok(()) => self.notify("Tracks outside the library are skipped".to_string()),
+            Err(e) => self.notify(format!("Could not save playlist: {}", e)),
+        }
+    }
+
+    fn import_m3u(&mut self) -> bool {
+        let Some(mut playlist) = self.active_m3u() else {
+            return false;
+        };
+        // Never replace a saved playlist that happens to share the file's name
+        playlist.name = self.free_playlist_name(&playlist.name);
+        match playlist_storage_service::save_playlist(&mut playlist) {
+            Ok(()) => {
+                self.notify(format!("Imported playlist: {}", playlist.name));
+                self.playlists.push(playlist);
+            }
+            Err(e) => self.notify(format!("Import failed: {}", e)),
+        }
+        true
+    }
+
+    fn track_name(path: &Path) -> String {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    fn top_genres(&self) -> Vec<(String, usize)> {
+        let mut genres: Vec<(String, usize)> = self
+            .playlist_service
+            .get_genres()
+            .iter()
+            .map(|genre| {
+                (
+                    genre.clone(),
+                    self.playlist_service.get_playlist_by_genre(genre).len(),
+                )
+            })
+            .collect();
+        genres.sort_by_key(|g| std::cmp::Reverse(g.1));
+        genres
+    }
+
+    // t in Stats: rank the top lists by track count, total duration or listening time
+    fn cycle_stats_ranking(&mut self) {
+        self.config.stats_ranking = self.config.stats_ranking.next();
+        if self.config.stats_ranking == StatsRanking::Listening {
+            self.load_listening_time();
+        }
+        self.stats_cache.take();
+        self.defer(AppAction::SaveConfig);
+        self.notify(format!("Ranking by {}", self.config.stats_ranking.name()));
+    }
+
+    // Re-read on every switch so the tracks finished this session count too
+    fn load_listening_time(&mut self) {
+        let Some(path) = &self.config.session_log_path else {
+            self.listening_time.clear();
+            self.notify("Set session_log_path to rank by listening time".to_string());
+            return;
+        };
+        match session_log_service::read_listening_time(path) {
+            Ok(listened) => {
+                self.listening_time = listened;
+                self.stats_cache.take();
+            }
+            Err(e) => self.notify(format!("Can't read the session log: {}", e)),
+        }
+    }
+
+    // Top genres and artists with their value, formatted for the current ranking
+    fn ranked_stats(&self) -> (TopList, TopList) {
+        if self.config.stats_ranking == StatsRanking::Tracks {
+            let counted = |ranked: Vec<(String, usize)>| {
+                ranked
+                    .into_iter()
+                    .map(|(name, count)| (name, count.to_string()))
+                    .collect()
+            };
+            return (
+                counted(self.top_genres()),
+                counted(self.playlist_service.get_top_artists()),
+            );
+        }
+        let weight = |track: &TrackMetadata| match self.config.stats_ranking {
+            StatsRanking::Listening => self
+                .listening_time
+                .get(&track.path)
+                .copied()
+                .unwrap_or_default(),
+            _ => track.duration.unwrap_or_default(),
+        };
+        let timed = |ranked: Vec<(String, Duration)>| {
+            ranked
+                .into_iter()
+                .map(|(name, total)| (name, format_duration_hms(total)))
+                .collect()
+        };
+        (
+            timed(self.playlist_service.get_top_genres_by(weight)),
+            timed(self.playlist_service.get_top_artists_by(weight)),
+        )
+    }
+
+    fn stats_sections(&self) -> StatsSections {
+        if let Some(sections) = &*self.stats_cache.borrow() {
+            return Rc::clone(sections);
+        }
+        let sections = Rc::new(self.build_stats_sections());
+        *self.stats_cache.borrow_mut() = Some(Rc::clone(&sections));
+        sections
+    }
+
+    fn build_stats_sections(&self) -> Vec<Box<dyn StatsSection>> {
+        let stats = self.playlist_service.get_stats();
+        let loudness = self.playlist_service.get_loudness_stats();
+        let loudness = if loudness.tracks_without_replaygain == stats.total_tracks {
+            "No ReplayGain tags".to_string()
+        } else {
+            format!(
+                "{:.1} dB LUFS | Range: {:.1} dB | {} tracks without gain",
+                loudness.average_loudness_lufs(),
+                loudness.range_db,
+                format_count(loudness.tracks_without_replaygain)
+            )
+        };
+        let summary = SummarySection {
+            rows: vec![
+                (
+                    "Total Tracks".to_string(),
+                    stats.total_tracks.to_string(),
+                    None,
+                ),
+                (
+                    "Total Genres".to_string(),
+                    stats.total_genres.to_string(),
+                    None,
+                ),
+                (
+                    "Total Albums".to_string(),
+                    stats.total_albums.to_string(),
+                    None,
+                ),
+                (
+                    "Total Duration".to_string(),
+                    format_duration_hms(stats.total_duration),
+                    None,
+                ),
+                ("Average Loudness".to_string(), loudness, None),
+                (
+                    "Tag Conflicts".to_string(),
+                    self.playlist_service.get_tag_conflicts().len().to_string(),
+                    None,
+                ),
+                (
+                    "Incomplete Albums (Enter)".to_string(),
+                    self.incomplete_albums.len().to_string(),
+                    Some(StatsAction::IncompleteAlbums),
+                ),
+            ],
+        };
+
+        let ranking = self.config.stats_ranking.name();
+        let (top_genres, top_artists) = self.ranked_stats();
+        let genres = RankingSection {
+            title: format!(
+                "Top-Genres by {} (Enter play, e export m3u, t rank by)",
+                ranking
+            ),
+            entries: top_genres
+                .into_iter()
+                .map(|(genre, count)| {
+                    (
+                        format!("{} ({})", genre, count),
+                        Some(StatsAction::PlayGenre(genre)),
+                    )
+                })
+                .collect(),
+        };
+
+        let artists = RankingSection {
+            title: format!("Top-Artists by {}", ranking),
+            entries: top_artists
+                .into_iter()
+                .map(|(artist, count)| {
+                    let albums = self.playlist_service.get_albums_for_artist(&artist).len();
+                    let featured = self.playlist_service.count_featured_credits(&artist);
+                    let label = if featured > 0 {
+                        format!(
+                            "{} ({}, {} albums, featured on {})",
+                            artist, count, albums, featured
+                        )
+                    } else {
+                        format!("{} ({}, {} albums)", artist, count, albums)
+                    };
+                    (label, None)
+                })
+                .collect(),
+        };
+
+        let mut decades: Vec<(String, u64)> = self
+            .playlist_service
+            .get_tracks_by_decade()
+            .into_iter()
+            .collect();
+        decades.sort();
+
+        let health = HealthSection {
+            score: self.library_health.score(),
+            issues: self
+                .library_health
+                .issues()
+                .into_iter()
+                .map(|(label, count)| (label.to_string(), count))
+                .collect(),
+        };
+
+        vec![
+            Box::new(summary),
+            Box::new(health),
+            Box::new(genres),
+            Box::new(artists),
+            Box::new(DecadesSection { decades }),
+        ]
+    }
+
+    fn selected_stats_action(&self) -> Option<StatsAction> {
+        let sections = self.stats_sections();
+        sections
+            .get(self.stats_focus)?
+            .rows()
+            .into_iter()
+            .nth(self.stats_row)
+    }
+
+    // Up/Down: between sections when collapsed, between rows (or lines) when expanded
+    fn step_stats(&mut self, down: bool) {
+        let sections = self.stats_sections();
+        if self.stats_expanded {
+            let section = &sections[self.stats_focus];
+            let rows = section.rows().len();
+            if rows > 0 {
+                self.stats_row = if down {
+                    (self.stats_row + 1).min(rows - 1)
+                } else {
+                    self.stats_row.saturating_sub(1)
+                };
+            } else {
+                self.scroll_stats(down, 1);
+            }
+        } else {
+            let focus = if down {
+                (self.stats_focus + 1).min(sections.len() - 1)
+            } else {
+                self.stats_focus.saturating_sub(1)
+            };
+            if focus != self.stats_focus {
+                self.stats_focus = focus;
+                self.stats_row = 0;
+            }
+        }
+    }
+
+    // PageUp/PageDown inside an expanded section
+    fn page_stats(&mut self, down: bool) {
+        let sections = self.stats_sections();
+        let rows = sections[self.stats_focus].rows().len();
+        if rows > 0 {
+            self.stats_row = if down {
+                (self.stats_row + STATS_PAGE).min(rows - 1)
+            } else {
+                self.stats_row.saturating_sub(STATS_PAGE)
+            };
+        } else {
+            self.scroll_stats(down, STATS_PAGE);
+        }
+    }
+
+    fn scroll_stats(&mut self, down: bool, amount: usize) {
+        let len = self.stats_sections()[self.stats_focus].content_len();
+        self.stats_scroll = if down {
+            (self.stats_scroll + amount).min(len.saturating_sub(1))
+        } else {
+            self.stats_scroll.saturating_sub(amount)
+        };
+    }
+
+    fn activate_stats_row(&mut self) {
+        match self.selected_stats_action() {
+            Some(StatsAction::IncompleteAlbums) => self.viewing_incomplete_albums = true,
+            Some(StatsAction::PlayGenre(genre)) => {
+                let tracks: Vec<PathBuf> = self
+                    .playlist_service
+                    .get_playlist_by_genre(&genre)
+                    .into_iter()
+                    .filter(|path| self.is_playable(path))
+                    .collect();
+                if !tracks.is_empty() {
+                    self.current_folder = Some(format!("Genre: {}", genre));
+                    self.is_playing = true;
+                    self.is_paused = false;
+                    self.shuffle_mode = ShuffleMode::Off;
+                    let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn export_selected_genre(&mut self) {
+        let Some(StatsAction::PlayGenre(genre)) = self.selected_stats_action() else {
+            return;
+        };
+        match self.config.playlist_dir() {
+            Some(dir) if !dir.is_dir() => self.create_dir_prompt = Some((dir, genre)),
+            dir => self.export_genre_to(&genre, dir.as_deref()),
+        }
+    }
+
+    // Into the playlist folder when there is one, otherwise into the config folder
+    fn export_genre_to(&mut self, genre: &str, dir: Option<&Path>) {
+        let file_name = format!("{}.m3u", genre);
+        let result = match dir {
+            Some(dir) => Ok(dir.join(file_name)),
+            None => config_service::get_export_path(&file_name),
+        }
+        .and_then(|dest| {
+            self.playlist_service
+                .export_m3u_by_genre(genre, &dest)
+                .map(|()| dest)
+        });
+        match result {
+            Ok(dest) => self.notify(format!("Exported to {}", dest.display())),
+            Err(e) => self.notify(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn export_incomplete_albums(&mut self) {
+        let report = self
+            .incomplete_albums
+            .iter()
+            .map(|album| {
+                format!(
+                    "{} - {}: missing {}",
+                    album.artist.as_deref().unwrap_or("Unknown"),
+                    album.album,
+                    album.format_missing()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        match config_service::save_report("incomplete_albums.txt", &report) {
+            Ok(path) => self.notify(format!("Exported to {}", path.display())),
+            Err(e) => self.notify(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn list_state(
+        &self,
+        scroll: &Cell<usize>,
+        selected: Option<usize>,
+        len: usize,
+        height: u16,
+    ) -> ListState {
+        let Some(selected) = selected else {
+            return ListState::default();
+        };
+        let offset = scroll_offset(
+            scroll.get(),
+            selected,
+            len,
+            height as usize,
+            self.config.scrolloff,
+            self.config.centered_cursor,
+        );
+        scroll.set(offset);
+        ListState::default()
+            .with_selected(Some(selected))
+            .with_offset(offset)
+    }
+
+    fn is_favorite(&self, path: &Path) -> bool {
+        self.playlists[FAVORITES_INDEX]
+            .tracks
+            .iter()
+            .any(|track| track.path == path)
+    }
+
+    // Hearts the highlighted track, or the one playing when nothing is highlighted
+    fn toggle_favorite(&mut self) {
+        let Some(path) = self
+            .highlighted_track_path()
+            .or_else(|| self.current_track_path.clone())
+        else {
+            return;
+        };
+        let favorites = &mut self.playlists[FAVORITES_INDEX];
+        let added = favorites.toggle_track(&path);
+        if let Err(e) = playlist_storage_service::save_playlist(favorites) {
+            self.notify(format!("Could not save favorites: {}", e));
+            return;
+        }
+        if self.viewing_playlist == Some(FAVORITES_INDEX) {
+            self.playlist_track_selected = self.playlist_track_selected.min(
+                self.playlists[FAVORITES_INDEX]
+                    .tracks
+                    .len()
+                    .saturating_sub(1),
+            );
+        }
+        let verb = if added { "Loved" } else { "Unloved" };
+        self.notify(format!("{}: {}", verb, Self::track_name(&path)));
+    }
+
+    fn paste(&mut self, text: &str) {
+        if self.is_searching {
+            insert_str(&mut self.search_query, text);
+            self.update_filtered_items();
+        } else if self.is_creating_playlist
+            && self.playlist_creation_selected == self.playlists.len()
+        {
+            insert_str(&mut self.playlist_name_input, text);
+        }
+    }
+
+    // Keys that matter right now, in the same order handle_input checks the modes
+    fn hints(&self) -> &'static str {
+        if self.palette.is_some() {
+            return "Type to filter | > commands only | ↑↓ select | Enter go/run | Ctrl+Enter play | Esc close";
+        }
+        if self.folder_reorder.is_some() {
+            return "↑↓ select | Shift+↑↓ move | Enter save | D normal order | Esc cancel";
+        }
+        if self.letter_picker.is_some() {
+            return "Type a letter or # | ←→↑↓ select | Enter jump | Esc close";
+        }
+        if let Some(guess) = &self.tag_guess {
+            return if guess.summary.is_some() {
+                "any key close"
+            } else if guess.editing_pattern {
+                "Type the pattern | ↑↓ built-in patterns | Tab/Enter preview | Esc close"
+            } else {
+                "↑↓ select | Space accept/reject | G lazy/greedy split | Tab pattern | Enter write | Esc close"
+            };
+        }
+        if self.track_info_path.is_some() {
+            return "S trim start | E trim end | C clear trims | any key close";
+        }
+        if self.create_dir_prompt.is_some() {
+            return "Y create folder | any key export to the config folder";
+        }
+        if self.restore_prompt.is_some() {
+            return "Y restore session | any key skip";
+        }
+        if self.volume_input.is_some() {
+            return "Type 0-200 | Enter set | Esc cancel";
+        }
+        if self.is_deleting_playlist {
+            return "Y delete | N cancel";
+        }
+        if self.is_creating_playlist {
+            return "↑↓ choose | Type name | Enter save | Esc cancel";
+        }
+        if self.is_searching {
+            return "Type to filter | Enter confirm | Esc cancel";
+        }
+        match self.active_tab {
+            ActiveTab::FolderNavigation if self.viewing_m3u.is_some() => {
+                "↑↓ nav | Enter play | P play | C import | O allow outside | Esc back"
+            }
+            ActiveTab::FolderNavigation => {
+                "↑↓ nav | Enter play | P album | F from here | S shuffle | L mark | C create | B search | Shift+O order | Right-click menu"
+            }
+            ActiveTab::PlaylistNavigation if self.viewing_playlist.is_some() => {
+                "↑↓ nav | Enter play | F play from here | # jump to letter | Shift+↑↓ move | D remove | Esc back"
+            }
+            ActiveTab::PlaylistNavigation => "↑↓ nav | Enter open | P play | S shuffle | D delete",
+            ActiveTab::Stats => {
+                "↑↓ section | Enter expand | E export genre | R recent | t rank by | Esc collapse"
+            }
+        }
+    }
+
+    fn help_view(&self) -> View {
+        match self.active_tab {
+            ActiveTab::FolderNavigation => View::Folders,
+            ActiveTab::PlaylistNavigation if self.viewing_playlist.is_some() => View::Playlist,
+            ActiveTab::PlaylistNavigation => View::Playlists,
+            ActiveTab::Stats => View::Stats,
+        }
+    }
+
+    fn handle_input(&mut self, key: event::KeyEvent) -> io::Result<bool> {
+        self.key_held = self.key_repeat.press(key.code, Instant::now());
+
+        if self.pending_auto_play.take().is_some() {
+            self.notify("Auto-play cancelled".to_string());
+        }
+        self.chords.interrupt(key);
+
+        if let Some(path) = self.track_info_path.clone() {
+            // Trim points can be set from the info popup of the playing track
+            let now_playing = self.current_track_path.as_ref() == Some(&path);
+            let position = self.track_position.as_millis() as u64;
+            match key.code {
+                KeyCode::Char('s') | KeyCode::Char('S') if now_playing => {
+                    self.edit_trim(&path, |trim| trim.start_ms = Some(position));
+                }
+                KeyCode::Char('e') | KeyCode::Char('E') if now_playing => {
+                    self.edit_trim(&path, |trim| trim.end_ms = Some(position));
+                }
+                KeyCode::Char('c') | KeyCode::Char('C') => {
+                    self.edit_trim(&path, |trim| *trim = TrimPoints::default());
+                }
+                _ => self.track_info_path = None,
+            }
+            return Ok(false);
+        }
+
+        if self.import_summary.is_some() {
+            self.import_summary = None;
+            return Ok(false);
+        }
+
+        if key.code == KeyCode::Char('v') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Some(text) = clipboard_service::read_clipboard() {
+                self.paste(&text);
+            }
+            return Ok(false);
+        }
+
+        if let Some((dir, genre)) = self.create_dir_prompt.take() {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                match std::fs::create_dir_all(&dir) {
+                    Ok(()) => self.export_genre_to(&genre, Some(&dir)),
+                    Err(e) => self.notify(format!("Can't create {}: {}", dir.display(), e)),
+                }
+            } else {
+                self.export_genre_to(&genre, None);
+            }
+            return Ok(false);
+        }
+
+        if let Some(mut menu) = self.context_menu.take() {
+            match key.code {
+                KeyCode::Up => menu.selected = menu.selected.saturating_sub(1),
+                KeyCode::Down => {
+                    menu.selected = (menu.selected + 1).min(ContextAction::ALL.len() - 1)
+                }
+                KeyCode::Enter => {
+                    self.run_context_action(menu.track, ContextAction::ALL[menu.selected]);
+                    return Ok(false);
+                }
+                _ => return Ok(false),
+            }
+            self.context_menu = Some(menu);
+            return Ok(false);
+        }
+
+        if let Some(mut reorder) = self.folder_reorder.take() {
+            match key.code {
+                KeyCode::Esc => return Ok(false),
+                KeyCode::Up | KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                    reorder.move_selected(key.code == KeyCode::Down);
+                }
+                KeyCode::Up => reorder.selected = reorder.selected.saturating_sub(1),
+                KeyCode::Down if reorder.selected + 1 < reorder.tracks.len() => {
+                    reorder.selected += 1;
+                }
+                KeyCode::Enter => {
+                    match self.folder_orders.save(&reorder.dir, &reorder.tracks) {
+                        Ok(()) => self.notify("Custom order saved".to_string()),
+                        Err(e) => self.notify(format!("Could not save the order: {}", e)),
+                    }
+                    self.update_items();
+                    return Ok(false);
+                }
+                KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete => {
+                    match self.folder_orders.remove(&reorder.dir) {
+                        Ok(()) => self.notify("Back to the normal order".to_string()),
+                        Err(e) => self.notify(format!("Could not remove the order: {}", e)),
+                    }
+                    self.update_items();
+                    return Ok(false);
+                }
+                _ => {}
+            }
+            self.folder_reorder = Some(reorder);
+            return Ok(false);
+        }
+
+        if let Some(mut guess) = self.tag_guess.take() {
+            if guess.summary.is_some() || key.code == KeyCode::Esc {
+                return Ok(false);
+            }
+            match key.code {
+                KeyCode::Tab | KeyCode::BackTab => guess.editing_pattern = !guess.editing_pattern,
+                KeyCode::Up | KeyCode::Down if guess.editing_pattern => {
+                    guess.step_builtin(key.code == KeyCode::Down);
+                }
+                KeyCode::Enter if guess.editing_pattern => guess.editing_pattern = false,
+                KeyCode::Backspace if guess.editing_pattern => {
+                    guess.pattern.pop();
+                    guess.reparse();
+                }
+                KeyCode::Char(c)
+                    if guess.editing_pattern && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    guess.pattern.push(c);
+                    guess.reparse();
+                }
+                KeyCode::Up => guess.selected = guess.selected.saturating_sub(1),
+                KeyCode::Down if guess.selected + 1 < guess.rows.len() => guess.selected += 1,
+                KeyCode::Char(' ') => {
+                    if let Some((Some(_), accepted)) = guess.rows.get_mut(guess.selected) {
+                        *accepted = !*accepted;
+                    }
+                }
+                KeyCode::Char('g') | KeyCode::Char('G') => {
+                    guess.split = match guess.split {
+                        Split::Lazy => Split::Greedy,
+                        Split::Greedy => Split::Lazy,
+                    };
+                    guess.reparse();
+                }
+                KeyCode::Enter => self.write_guessed_tags(&mut guess),
+                _ => {}
+            }
+            self.tag_guess = Some(guess);
+            return Ok(false);
+        }
+
+        if let Some(mut picker) = self.letter_picker.take() {
+            match key.code {
+                KeyCode::Esc => return Ok(false),
+                KeyCode::Enter => {
+                    self.jump_to_initial(picker.initial());
+                    return Ok(false);
+                }
+                KeyCode::Char('#') => {
+                    self.jump_to_initial(None);
+                    return Ok(false);
+                }
+                KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                    self.jump_to_initial(Some(c.to_ascii_lowercase()));
+                    return Ok(false);
+                }
+                code => picker.step(code),
+            }
+            self.letter_picker = Some(picker);
+            return Ok(false);
+        }
+
+        if let Some(mut editor) = self.filter_editor.take() {
+            match key.code {
+                KeyCode::Esc => return Ok(false),
+                KeyCode::Enter => match editor.to_filter() {
+                    Ok(filter) => {
+                        self.apply_shuffle_filter(filter);
+                        return Ok(false);
+                    }
+                    Err(e) => self.notify(e),
+                },
+                KeyCode::Up | KeyCode::BackTab => {
+                    editor.selected = editor.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Tab if editor.selected < FilterEditor::STRICT_ROW => {
+                    editor.selected += 1;
+                }
+                KeyCode::Char(' ') if editor.selected == FilterEditor::STRICT_ROW => {
+                    editor.strict = !editor.strict;
+                }
+                KeyCode::Backspace if editor.selected < FilterEditor::STRICT_ROW => {
+                    editor.fields[editor.selected].pop();
+                }
+                KeyCode::Char(c)
+                    if editor.selected < FilterEditor::STRICT_ROW
+                        && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    editor.fields[editor.selected].push(c);
+                }
+                _ => {}
+            }
+            self.filter_editor = Some(editor);
+            return Ok(false);
+        }
+
+        if let Some(mut palette) = self.palette.take() {
+            match key.code {
+                KeyCode::Esc => return Ok(false),
+                KeyCode::Enter => {
+                    let play = key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                    if let Some(target) = palette.current() {
+                        let action = palette_service::resolve(target, play, &self.playlist_service);
+                        self.run_palette_action(action);
+                    }
+                    return Ok(false);
+                }
+                KeyCode::Up => palette.selected = palette.selected.saturating_sub(1),
+                KeyCode::Down if palette.selected + 1 < palette.matches.len() => {
+                    palette.selected += 1;
+                }
+                KeyCode::Backspace => {
+                    palette.query.pop();
+                    palette.refilter(&self.playlist_service);
+                }
+                KeyCode::Tab => {
+                    palette.track_sort = palette.track_sort.next();
+                    palette.refilter(&self.playlist_service);
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    palette.query.push(c);
+                    palette.refilter(&self.playlist_service);
+                }
+                _ => {}
+            }
+            self.palette = Some(palette);
+            return Ok(false);
+        }
+
+        if let Some(state) = self.restore_prompt.take() {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.restore_session(state);
+            }
+            return Ok(false);
+        }
+
+        if let Some(dangling) = self.dangling_prompt.take() {
+            match key.code {
+                KeyCode::Char('c') | KeyCode::Char('C') => self.clean_dangling_tracks(dangling),
+                KeyCode::Char('i') | KeyCode::Char('I') | KeyCode::Esc => {
+                    self.dangling_ignored = true;
+                }
+                _ => self.dangling_prompt = Some(dangling),
+            }
+            return Ok(false);
+        }
+
+        if let Some(input) = &mut self.volume_input {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 3 => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let input = self.volume_input.take().unwrap_or_default();
+                    self.apply_volume_input(&input);
+                }
+                KeyCode::Esc => self.volume_input = None,
+                _ => {}
+            }
+            return Ok(false);
+        }
+
         if self.is_deleting_playlist {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
                     if let Some(index) = self.playlist_to_delete {
-                        let playlist = &self.playlists[index];
-                        if playlist_storage_service::delete_playlist(&playlist.name).is_ok() {
-                            self.playlists.remove(index);
-                        }
+                        self.delete_playlist(index);
                     }
                     self.is_deleting_playlist = false;
                     self.playlist_to_delete = None;
@@ -267,6 +4157,122 @@ impl App {
             return Ok(false);
         }
 
+        if self.viewing_incomplete_albums {
+            let albums_len = self.incomplete_albums.len();
+            match key.code {
+                KeyCode::Up => {
+                    self.incomplete_album_selected =
+                        self.incomplete_album_selected.saturating_sub(1);
+                }
+                KeyCode::Down if self.incomplete_album_selected + 1 < albums_len => {
+                    self.incomplete_album_selected += 1;
+                }
+                KeyCode::Char('e') | KeyCode::Char('E') => self.export_incomplete_albums(),
+                KeyCode::Esc => {
+                    self.viewing_incomplete_albums = false;
+                    self.incomplete_album_selected = 0;
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        if let Some(playlist) = &self.viewing_m3u
+            && matches!(self.active_tab, ActiveTab::FolderNavigation)
+        {
+            match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    self.m3u_track_selected = self.step_track_row(
+                        &playlist.tracks,
+                        self.m3u_track_selected,
+                        key.code == KeyCode::Down,
+                        false,
+                    );
+                    return Ok(false);
+                }
+                KeyCode::Esc => {
+                    self.viewing_m3u = None;
+                    self.m3u_track_selected = 0;
+                    return Ok(false);
+                }
+                // Enter plays the listing from the highlighted entry onwards
+                KeyCode::Enter => {
+                    let tracks: Vec<PathBuf> = playlist
+                        .tracks
+                        .iter()
+                        .skip(self.m3u_track_selected)
+                        .filter(|track| self.is_playable_in(playlist, track))
+                        .map(|track| track.path.clone())
+                        .collect();
+                    if !tracks.is_empty() {
+                        self.current_folder = Some(format!("Playlist: {}", playlist.name));
+                        self.is_playing = true;
+                        self.is_paused = false;
+                        self.shuffle_mode = ShuffleMode::Off;
+                        let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+                    }
+                    return Ok(false);
+                }
+                KeyCode::Char('o') | KeyCode::Char('O') => {
+                    self.toggle_allow_external();
+                    return Ok(false);
+                }
+                // Searching and marking only apply to the folder listing underneath
+                KeyCode::Char('b')
+                | KeyCode::Char('B')
+                | KeyCode::Char('l')
+                | KeyCode::Char('L') => {
+                    return Ok(false);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(recent) = &self.recent_tracks
+            && matches!(self.active_tab, ActiveTab::Stats)
+        {
+            match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    self.recent_track_selected = self.step_track_row(
+                        &recent.tracks,
+                        self.recent_track_selected,
+                        key.code == KeyCode::Down,
+                        false,
+                    );
+                    return Ok(false);
+                }
+                KeyCode::Esc => {
+                    self.recent_tracks = None;
+                    return Ok(false);
+                }
+                KeyCode::Enter => {
+                    let tracks: Vec<PathBuf> = recent
+                        .tracks
+                        .iter()
+                        .skip(self.recent_track_selected)
+                        .filter(|track| self.is_playable(track))
+                        .map(|track| track.path.clone())
+                        .collect();
+                    if !tracks.is_empty() {
+                        self.current_folder = Some(recent.name.clone());
+                        self.is_playing = true;
+                        self.is_paused = false;
+                        self.shuffle_mode = ShuffleMode::Off;
+                        let _ = self.player.send(PlayerCommand::PlayAlbum(tracks));
+                    }
+                    return Ok(false);
+                }
+                // Stats shortcuts don't apply while the listing covers the tab
+                KeyCode::Char('e')
+                | KeyCode::Char('E')
+                | KeyCode::Char('r')
+                | KeyCode::Char('R') => {
+                    return Ok(false);
+                }
+                _ => {}
+            }
+        }
+
         if self.is_searching {
             match key.code {
                 KeyCode::Char(c) => {
@@ -292,60 +4298,105 @@ impl App {
 
         if self.is_creating_playlist {
             match key.code {
-                KeyCode::Up => {
-                    if self.playlist_creation_selected > 0 {
-                        self.playlist_creation_selected -= 1;
-                    }
+                KeyCode::Up if self.playlist_creation_selected > 0 => {
+                    self.playlist_creation_selected -= 1;
                 }
-                KeyCode::Down => {
-                    if self.playlist_creation_selected < self.playlists.len() {
-                        self.playlist_creation_selected += 1;
-                    }
+                KeyCode::Down if self.playlist_creation_selected < self.playlists.len() => {
+                    self.playlist_creation_selected += 1;
                 }
                 KeyCode::Enter => {
                     if self.playlist_creation_selected == self.playlists.len() {
                         // Create new playlist
-                        let playlist = Playlist {
-                            name: self.playlist_name_input.clone(),
-                            tracks: self.marked_tracks.iter().cloned().collect(),
-                        };
-                        if playlist_storage_service::save_playlist(&playlist).is_ok() {
+                        let mut playlist = Playlist::new(
+                            self.playlist_name_input.clone(),
+                            self.marked_tracks.iter().cloned().collect(),
+                        );
+                        if playlist_storage_service::save_playlist(&mut playlist).is_ok() {
                             self.playlists.push(playlist);
                         }
-                    } else {
+                    } else if self.ensure_playlist_loaded(self.playlist_creation_selected) {
                         // Add to existing playlist
                         let playlist = &mut self.playlists[self.playlist_creation_selected];
-                        playlist.tracks.extend(self.marked_tracks.iter().cloned());
-                        let _ = playlist_storage_service::save_playlist(playlist);
+                        playlist
+                            .tracks
+                            .extend(self.marked_tracks.iter().cloned().map(TrackRef::from));
+                        let name = playlist.name.clone();
+                        self.defer(AppAction::PersistPlaylist(name));
                     }
                     self.is_creating_playlist = false;
                     self.playlist_name_input.clear();
-                    self.marked_tracks.clear();
+                    self.marked_tracks = self.stashed_marks.take().unwrap_or_default();
                     self.playlist_creation_selected = 0;
                 }
-                KeyCode::Char(c) => {
-                    if self.playlist_creation_selected == self.playlists.len() {
-                        self.playlist_name_input.push(c);
-                    }
+                KeyCode::Char(c) if self.playlist_creation_selected == self.playlists.len() => {
+                    self.playlist_name_input.push(c);
                 }
-                KeyCode::Backspace => {
-                    if self.playlist_creation_selected == self.playlists.len() {
-                        self.playlist_name_input.pop();
-                    }
+                KeyCode::Backspace if self.playlist_creation_selected == self.playlists.len() => {
+                    self.playlist_name_input.pop();
                 }
                 KeyCode::Esc => {
                     self.is_creating_playlist = false;
                     self.playlist_name_input.clear();
                     self.playlist_creation_selected = 0;
+                    if let Some(marks) = self.stashed_marks.take() {
+                        self.marked_tracks = marks;
+                    }
                 }
                 _ => {}
             }
         } else {
+            if let KeyCode::Char(c) = key.code
+                && (key.modifiers - KeyModifiers::SHIFT).is_empty()
+                && let Some(hint) = help_service::unavailable_hint(
+                    c,
+                    key.modifiers.contains(KeyModifiers::SHIFT),
+                    self.help_view(),
+                )
+            {
+                self.notify(hint);
+            }
             match key.code {
+                KeyCode::Left | KeyCode::Right | KeyCode::Char('1'..='9')
+                    if key.modifiers.contains(KeyModifiers::ALT)
+                        && matches!(self.active_tab, ActiveTab::FolderNavigation)
+                        && self.viewing_m3u.is_none() =>
+                {
+                    self.breadcrumb_key(key.code);
+                }
                 KeyCode::Char('1') => self.active_tab = ActiveTab::FolderNavigation,
                 KeyCode::Char('2') => self.active_tab = ActiveTab::PlaylistNavigation,
                 KeyCode::Char('3') => self.active_tab = ActiveTab::Stats,
-                KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(true),
+                KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(self.confirmed(key, ChordAction::Quit));
+                }
+                KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.open_palette();
+                }
+                KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.filter_editor = Some(FilterEditor::new(&self.shuffle_filter));
+                }
+                KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    match help_service::export_keybindings() {
+                        Ok(path) => {
+                            self.notify(format!("Shortcuts exported to {}", path.display()))
+                        }
+                        Err(e) => self.notify(format!("Export failed: {}", e)),
+                    }
+                }
+                KeyCode::Char('q') | KeyCode::Char('Q') => {
+                    if let Some(track_path) = self.queueable_track_path() {
+                        self.enqueue(track_path, false);
+                    }
+                }
+                KeyCode::Up | KeyCode::Down
+                    if key.modifiers.contains(KeyModifiers::SHIFT)
+                        && matches!(self.active_tab, ActiveTab::PlaylistNavigation)
+                        && self.viewing_playlist.is_some() =>
+                {
+                    if let Some(playlist_index) = self.viewing_playlist {
+                        self.move_playlist_track(playlist_index, key.code == KeyCode::Down);
+                    }
+                }
                 KeyCode::Up => match self.active_tab {
                     ActiveTab::FolderNavigation => {
                         let items_len = self.filtered_items.len();
@@ -357,12 +4408,12 @@ impl App {
                     }
                     ActiveTab::PlaylistNavigation => {
                         if let Some(playlist_index) = self.viewing_playlist {
-                            let playlist = &self.playlists[playlist_index];
-                            if self.playlist_track_selected > 0 {
-                                self.playlist_track_selected -= 1;
-                            } else if !playlist.tracks.is_empty() {
-                                self.playlist_track_selected = playlist.tracks.len() - 1;
-                            }
+                            self.playlist_track_selected = self.step_track_row(
+                                &self.playlists[playlist_index].tracks,
+                                self.playlist_track_selected,
+                                false,
+                                true,
+                            );
                         } else {
                             if self.playlist_selected > 0 {
                                 self.playlist_selected -= 1;
@@ -371,7 +4422,7 @@ impl App {
                             }
                         }
                     }
-                    _ => {}
+                    ActiveTab::Stats => self.step_stats(false),
                 },
                 KeyCode::Down => match self.active_tab {
                     ActiveTab::FolderNavigation => {
@@ -384,14 +4435,12 @@ impl App {
                     }
                     ActiveTab::PlaylistNavigation => {
                         if let Some(playlist_index) = self.viewing_playlist {
-                            let playlist = &self.playlists[playlist_index];
-                            if self.playlist_track_selected
-                                < playlist.tracks.len().saturating_sub(1)
-                            {
-                                self.playlist_track_selected += 1;
-                            } else {
-                                self.playlist_track_selected = 0;
-                            }
+                            self.playlist_track_selected = self.step_track_row(
+                                &self.playlists[playlist_index].tracks,
+                                self.playlist_track_selected,
+                                true,
+                                true,
+                            );
                         } else {
                             if self.playlist_selected < self.playlists.len().saturating_sub(1) {
                                 self.playlist_selected += 1;
@@ -400,7 +4449,7 @@ impl App {
                             }
                         }
                     }
-                    _ => {}
+                    ActiveTab::Stats => self.step_stats(true),
                 },
                 KeyCode::Char('b') | KeyCode::Char('B') => {
                     if let ActiveTab::FolderNavigation = self.active_tab {
@@ -408,85 +4457,156 @@ impl App {
                         self.search_query.clear();
                     }
                 }
-                KeyCode::Char('c') | KeyCode::Char('C') => {
-                    if !self.is_creating_playlist {
-                        self.is_creating_playlist = true;
+                KeyCode::Char('c') | KeyCode::Char('C') if self.import_m3u() => {}
+                KeyCode::Char('c') | KeyCode::Char('C') if !self.is_creating_playlist => {
+                    self.start_playlist_creation();
+                }
+                KeyCode::Char('i') | KeyCode::Char('I') => {
+                    self.track_info_path = self
+                        .highlighted_track_path()
+                        .or_else(|| self.current_track_path.clone());
+                }
+                KeyCode::Char('a') | KeyCode::Char('A') => {
+                    if let Some(track_path) = self.current_track_path.clone() {
+                        // Reuse the marked-tracks flow, keeping the user's marks aside
+                        let marks =
+                            std::mem::replace(&mut self.marked_tracks, HashSet::from([track_path]));
+                        self.stashed_marks = Some(marks);
+                        self.start_playlist_creation();
                     }
                 }
                 KeyCode::Char('d') | KeyCode::Char('D') => {
                     if let ActiveTab::PlaylistNavigation = self.active_tab {
-                        if self.viewing_playlist.is_none() && !self.playlists.is_empty() {
+                        if let Some(playlist_index) = self.viewing_playlist {
+                            // A collapsed offline row stands for several tracks; leave them be
+                            let on_offline_volume = self.playlists[playlist_index]
+                                .tracks
+                                .get(self.playlist_track_selected)
+                                .is_some_and(|track| self.is_on_offline_volume(&track.path));
+                            if on_offline_volume {
+                                return Ok(false);
+                            }
+                            self.remove_playlist_track(
+                                playlist_index,
+                                self.playlist_track_selected,
+                            );
+                        } else if self.playlists[self.playlist_selected].is_favorites {
+                            self.notify("Favorites can't be deleted".to_string());
+                        } else if self.confirm_style(ChordAction::DeletePlaylist(0))
+                            == ConfirmStyle::Popup
+                        {
                             self.is_deleting_playlist = true;
                             self.playlist_to_delete = Some(self.playlist_selected);
+                        } else if self
+                            .confirmed(key, ChordAction::DeletePlaylist(self.playlist_selected))
+                        {
+                            self.delete_playlist(self.playlist_selected);
                         }
                     }
                 }
-                KeyCode::Enter => {
-                    if !self.filtered_items.is_empty() {
-                        match self.active_tab {
-                            ActiveTab::FolderNavigation => {
-                                let selected_item = self.filtered_items[self.selected].clone();
-
-                                if selected_item == "[DIR] .." {
-                                    if self.current_dir.parent().is_some() {
-                                        self.current_dir.pop();
-                                        self.selected = 0;
-                                        self.search_query.clear();
-                                        self.is_searching = false;
-                                        self.update_items();
-                                    }
-                                } else if selected_item.starts_with("[DIR] ") {
-                                    let dir_name = &selected_item[6..];
-                                    let new_path = self.current_dir.join(dir_name);
-                                    if new_path.is_dir() {
-                                        self.current_dir = new_path;
-                                        self.selected = 0;
-                                        self.search_query.clear();
-                                        self.is_searching = false;
-                                        self.update_items();
-                                    }
-                                } else {
-                                    let track_path = self.current_dir.join(selected_item);
-                                    if Self::is_audio_file(&track_path) {
-                                        self.current_folder = self
-                                            .current_dir
-                                            .file_name()
-                                            .and_then(|n| n.to_str())
-                                            .map(|s| s.to_string());
-                                        self.is_playing = true;
-                                        self.is_paused = false;
-                                        self.is_shuffle_mode = false;
-                                        self.player
-                                            .send(PlayerCommand::PlayAlbum(vec![track_path]));
-                                    }
+                KeyCode::Char('.') => self.toggle_favorite(),
+                KeyCode::Char('V') => self.volume_input = Some(String::new()),
+                KeyCode::Char('M') => self.play_mix(),
+                KeyCode::Char('m') if matches!(self.active_tab, ActiveTab::PlaylistNavigation) => {
+                    self.start_dangling_check(true);
+                }
+                KeyCode::Char('T') => self.cycle_theme(),
+                KeyCode::Char('e') | KeyCode::Char('E')
+                    if matches!(self.active_tab, ActiveTab::Stats) =>
+                {
+                    self.export_selected_genre();
+                }
+                KeyCode::Char('r') | KeyCode::Char('R')
+                    if matches!(self.active_tab, ActiveTab::Stats) =>
+                {
+                    self.open_recently_added();
+                }
+                KeyCode::Char('t') if matches!(self.active_tab, ActiveTab::Stats) => {
+                    self.cycle_stats_ranking();
+                }
+                KeyCode::Enter if matches!(self.active_tab, ActiveTab::Stats) => {
+                    if self.stats_expanded {
+                        self.activate_stats_row();
+                    } else {
+                        self.stats_expanded = true;
+                        self.stats_scroll = 0;
+                    }
+                }
+                KeyCode::PageUp | KeyCode::PageDown
+                    if matches!(self.active_tab, ActiveTab::Stats) && self.stats_expanded =>
+                {
+                    self.page_stats(key.code == KeyCode::PageDown);
+                }
+                KeyCode::Enter if !self.filtered_items.is_empty() => match self.active_tab {
+                    ActiveTab::FolderNavigation => {
+                        let selected_item = self.filtered_items[self.selected].clone();
+
+                        if selected_item == "[DIR] .." {
+                            if let Some(parent) = self.current_dir.parent() {
+                                self.navigate_to(parent.to_path_buf());
+                            }
+                        } else if selected_item.starts_with("[PL] ") {
+                            let m3u_path = self.row_path(self.selected);
+                            match playlist_storage_service::parse_m3u(&m3u_path) {
+                                Ok(playlist) => {
+                                    self.viewing_m3u = Some(playlist);
+                                    self.refresh_offline_roots();
+                                    self.m3u_track_selected = 0;
                                 }
+                                Err(e) => self.notify(format!(
+                                    "Could not read {}: {}",
+                                    m3u_path.display(),
+                                    e
+                                )),
+                            }
+                        } else if self
+                            .row_entry(self.selected)
+                            .is_some_and(|entry| !entry.discs.is_empty())
+                        {
+                            // A merged multi-disc album lists its discs under it
+                            let album = self.row_path(self.selected);
+                            if !self.open_disc_albums.remove(&album) {
+                                self.open_disc_albums.insert(album);
+                            }
+                            self.update_items();
+                        } else if selected_item.starts_with("[DIR] ") {
+                            let new_path = self.row_path(self.selected);
+                            if new_path.is_dir() {
+                                self.navigate_to(new_path);
+                            }
+                        } else {
+                            let track_path = self.row_path(self.selected);
+                            if is_audio_file(&track_path) {
+                                self.play_track(track_path);
                             }
-                            ActiveTab::PlaylistNavigation => {
-                                if let Some(playlist_index) = self.viewing_playlist {
-                                    let playlist = &self.playlists[playlist_index];
-                                    if self.playlist_track_selected < playlist.tracks.len() {
-                                        let track_path =
-                                            &playlist.tracks[self.playlist_track_selected];
-                                        if track_path.exists() && Self::is_audio_file(track_path) {
-                                            self.current_folder =
-                                                Some(format!("Playlist: {}", playlist.name));
-                                            self.is_playing = true;
-                                            self.is_paused = false;
-                                            self.is_shuffle_mode = false;
-                                            self.player.send(PlayerCommand::PlayAlbum(vec![
-                                                track_path.clone(),
-                                            ]));
-                                        }
-                                    }
-                                } else if !self.playlists.is_empty() {
-                                    self.viewing_playlist = Some(self.playlist_selected);
-                                    self.playlist_track_selected = 0;
+                        }
+                    }
+                    ActiveTab::PlaylistNavigation => {
+                        if let Some(playlist_index) = self.viewing_playlist {
+                            let playlist = &self.playlists[playlist_index];
+                            if self.playlist_track_selected < playlist.tracks.len() {
+                                let track_path = &playlist.tracks[self.playlist_track_selected];
+                                if self.is_playable_in(playlist, track_path) {
+                                    self.current_folder =
+                                        Some(format!("Playlist: {}", playlist.name));
+                                    self.is_playing = true;
+                                    self.is_paused = false;
+                                    self.shuffle_mode = ShuffleMode::Off;
+                                    let _ = self.player.send(PlayerCommand::PlayAlbum(vec![
+                                        track_path.path.clone(),
+                                    ]));
                                 }
                             }
-                            _ => {}
+                        } else if !self.playlists.is_empty()
+                            && self.ensure_playlist_loaded(self.playlist_selected)
+                        {
+                            self.viewing_playlist = Some(self.playlist_selected);
+                            self.refresh_offline_roots();
+                            self.playlist_track_selected = 0;
                         }
                     }
-                }
+                    _ => {}
+                },
                 KeyCode::Esc => {
                     if self.is_searching {
                         self.is_searching = false;
@@ -494,173 +4614,151 @@ impl App {
                         self.update_filtered_items();
                     } else {
                         match self.active_tab {
-                            ActiveTab::FolderNavigation => {
-                                if self.current_dir.parent().is_some() {
-                                    self.current_dir.pop();
-                                    self.selected = 0;
-                                    self.update_items();
-                                }
+                            ActiveTab::FolderNavigation => self.navigate_up(),
+                            ActiveTab::PlaylistNavigation if self.viewing_playlist.is_some() => {
+                                self.viewing_playlist = None;
+                                self.playlist_track_selected = 0;
                             }
-                            ActiveTab::PlaylistNavigation => {
-                                if self.viewing_playlist.is_some() {
-                                    self.viewing_playlist = None;
-                                    self.playlist_track_selected = 0;
-                                }
+                            ActiveTab::Stats => {
+                                self.stats_expanded = false;
+                                self.stats_scroll = 0;
                             }
                             _ => {}
                         }
                     }
                 }
                 KeyCode::Char('l') | KeyCode::Char('L') => {
-                    if let ActiveTab::FolderNavigation = self.active_tab {
-                        if !self.items.is_empty() {
-                            let selected_item = &self.items[self.selected];
-                            if !selected_item.starts_with("[DIR]") {
-                                let track_path = self.current_dir.join(selected_item);
-                                if self.marked_tracks.contains(&track_path) {
-                                    self.marked_tracks.remove(&track_path);
-                                } else {
-                                    self.marked_tracks.insert(track_path);
-                                }
-                            }
-                        }
+                    if let ActiveTab::FolderNavigation = self.active_tab
+                        && let Some(selected_item) = self.filtered_items.get(self.selected)
+                        && !selected_item.starts_with("[DIR]")
+                        && !selected_item.starts_with("[PL]")
+                    {
+                        let track_path = self.row_path(self.selected);
+                        self.toggle_mark(track_path);
                     }
                 }
+                KeyCode::Char('p') | KeyCode::Char('P') if self.play_m3u(ShuffleMode::Off) => {}
                 KeyCode::Char('p') | KeyCode::Char('P') => match self.active_tab {
-                    ActiveTab::FolderNavigation => {
-                        let tracks: Vec<PathBuf> = std::fs::read_dir(&self.current_dir)
-                            .unwrap_or_else(|_| std::fs::read_dir(".").unwrap())
-                            .filter_map(|res| res.ok())
-                            .map(|entry| entry.path())
-                            .filter(|p| p.is_file() && Self::is_audio_file(p))
-                            .collect();
-
-                        if !tracks.is_empty() {
-                            self.current_folder = self
-                                .current_dir
-                                .file_name()
-                                .and_then(|n| n.to_str())
-                                .map(|s| s.to_string());
-                            self.is_playing = true;
-                            self.is_paused = false;
-                            self.is_shuffle_mode = false;
-                            self.player.send(PlayerCommand::PlayAlbum(tracks));
-                        }
-                    }
+                    ActiveTab::FolderNavigation => self.play_current_album(),
                     ActiveTab::PlaylistNavigation => {
                         if let Some(playlist_index) = self.viewing_playlist {
                             let playlist = &self.playlists[playlist_index];
                             let valid_tracks: Vec<PathBuf> = playlist
                                 .tracks
                                 .iter()
-                                .filter(|track| track.exists() && Self::is_audio_file(track))
-                                .cloned()
+                                .filter(|track| self.is_playable_in(playlist, track))
+                                .map(|track| track.path.clone())
                                 .collect();
 
                             if !valid_tracks.is_empty() {
                                 self.current_folder = Some(format!("Playlist: {}", playlist.name));
                                 self.is_playing = true;
                                 self.is_paused = false;
-                                self.is_shuffle_mode = false;
-                                self.player.send(PlayerCommand::PlayAlbum(valid_tracks));
+                                self.shuffle_mode = ShuffleMode::Off;
+                                let _ = self.player.send(PlayerCommand::PlayAlbum(valid_tracks));
                             }
-                        } else if !self.playlists.is_empty() {
+                        } else if !self.playlists.is_empty()
+                            && self.ensure_playlist_loaded(self.playlist_selected)
+                        {
                             let playlist = &self.playlists[self.playlist_selected];
                             let valid_tracks: Vec<PathBuf> = playlist
                                 .tracks
                                 .iter()
-                                .filter(|track| track.exists() && Self::is_audio_file(track))
-                                .cloned()
+                                .filter(|track| self.is_playable_in(playlist, track))
+                                .map(|track| track.path.clone())
                                 .collect();
 
                             if !valid_tracks.is_empty() {
                                 self.current_folder = Some(format!("Playlist: {}", playlist.name));
                                 self.is_playing = true;
                                 self.is_paused = false;
-                                self.is_shuffle_mode = false;
-                                self.player.send(PlayerCommand::PlayAlbum(valid_tracks));
+                                self.shuffle_mode = ShuffleMode::Off;
+                                let _ = self.player.send(PlayerCommand::PlayAlbum(valid_tracks));
                             }
                         }
                     }
                     _ => {}
                 },
-                KeyCode::Char('s') | KeyCode::Char('S') => match self.active_tab {
-                    ActiveTab::FolderNavigation => {
-                        let mut tracks: Vec<PathBuf> = WalkDir::new(&self.current_dir)
-                            .into_iter()
-                            .filter_map(|e| e.ok())
-                            .map(|e| e.into_path())
-                            .filter(|p| p.is_file() && Self::is_audio_file(p))
+                KeyCode::Char('o') | KeyCode::Char('O')
+                    if matches!(self.active_tab, ActiveTab::PlaylistNavigation)
+                        && self.viewing_playlist.is_some() =>
+                {
+                    self.toggle_allow_external();
+                }
+                KeyCode::Char('#')
+                    if matches!(self.active_tab, ActiveTab::PlaylistNavigation)
+                        && self.viewing_playlist.is_some() =>
+                {
+                    self.letter_picker = Some(LetterPicker::default());
+                }
+                KeyCode::Char('G') if matches!(self.active_tab, ActiveTab::FolderNavigation) => {
+                    self.open_tag_guess();
+                }
+                KeyCode::Char('O') if matches!(self.active_tab, ActiveTab::FolderNavigation) => {
+                    self.open_folder_reorder();
+                }
+                KeyCode::Char('f') | KeyCode::Char('F')
+                    if matches!(self.active_tab, ActiveTab::FolderNavigation) =>
+                {
+                    self.play_folder_from_selected();
+                }
+                KeyCode::Char('f') | KeyCode::Char('F') => {
+                    // Play the open playlist from the selected track to the end
+                    if let ActiveTab::PlaylistNavigation = self.active_tab
+                        && let Some(playlist_index) = self.viewing_playlist
+                    {
+                        let playlist = &self.playlists[playlist_index];
+                        let start = self.playlist_track_selected.min(playlist.tracks.len());
+                        let valid_tracks: Vec<PathBuf> = playlist.tracks[start..]
+                            .iter()
+                            .filter(|track| self.is_playable_in(playlist, track))
+                            .map(|track| track.path.clone())
                             .collect();
 
-                        if !tracks.is_empty() {
-                            let mut rng = rand::rng();
-                            tracks.shuffle(&mut rng);
-                            self.current_folder = self
-                                .current_dir
-                                .file_name()
-                                .and_then(|n| n.to_str())
-                                .map(|s| s.to_string());
+                        if !valid_tracks.is_empty() {
+                            self.current_folder = Some(format!("Playlist: {}", playlist.name));
                             self.is_playing = true;
                             self.is_paused = false;
-                            self.is_shuffle_mode = true;
-                            self.player.send(PlayerCommand::PlayShuffle(tracks));
+                            self.shuffle_mode = ShuffleMode::Off;
+                            let _ = self.player.send(PlayerCommand::PlayAlbum(valid_tracks));
                         }
                     }
-                    ActiveTab::PlaylistNavigation => {
-                        let playlist_to_shuffle =
-                            if let Some(playlist_index) = self.viewing_playlist {
-                                Some(&self.playlists[playlist_index])
-                            } else if !self.playlists.is_empty() {
-                                Some(&self.playlists[self.playlist_selected])
-                            } else {
-                                None
-                            };
-
-                        if let Some(playlist) = playlist_to_shuffle {
-                            let mut valid_tracks: Vec<PathBuf> = playlist
-                                .tracks
-                                .iter()
-                                .filter(|track| track.exists() && Self::is_audio_file(track))
-                                .cloned()
-                                .collect();
-
-                            if !valid_tracks.is_empty() {
-                                let mut rng = rand::rng();
-                                valid_tracks.shuffle(&mut rng);
-
-                                self.current_folder =
-                                    Some(format!("Playlist: {} (shuffle)", playlist.name));
-                                self.is_playing = true;
-                                self.is_paused = false;
-                                self.is_shuffle_mode = true;
-                                self.player.send(PlayerCommand::PlayShuffle(valid_tracks));
-                            }
-                        }
+                }
+                KeyCode::Char(c @ ('s' | 'S')) => {
+                    // Shift+S shuffles whole albums instead of tracks
+                    let mode = if c == 'S' {
+                        ShuffleMode::Albums
+                    } else {
+                        ShuffleMode::Tracks
+                    };
+                    if !self.play_m3u(mode) {
+                        self.shuffle_scope(mode);
                     }
-                    _ => {}
-                },
-                KeyCode::Char('n') | KeyCode::Char('N') => {
-                    self.player.send(PlayerCommand::SkipNext);
                 }
-                KeyCode::Char(' ') => {
-                    if self.is_playing {
-                        self.is_paused = !self.is_paused;
-                        self.player.send(PlayerCommand::TogglePause);
+                KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let _ = self.player.send(PlayerCommand::SkipNext);
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') => {
+                    if let Some(track_path) = self.queueable_track_path() {
+                        self.enqueue(track_path, true);
                     }
                 }
-                KeyCode::Char('z') | KeyCode::Char('Z') => {
-                    self.player.send(PlayerCommand::VolumeDown);
+                KeyCode::Char(' ') if self.is_playing => {
+                    self.is_paused = !self.is_paused;
+                    let _ = self.player.send(PlayerCommand::TogglePause);
+                    self.announce(if self.is_paused { "Paused" } else { "Resumed" }.to_string());
                 }
-                KeyCode::Char('x') | KeyCode::Char('X') => {
-                    self.player.send(PlayerCommand::VolumeUp);
+                KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.undo();
                 }
-                KeyCode::Backspace => {
+                KeyCode::Char('z') | KeyCode::Char('Z') => self.step_volume(-self.volume_step()),
+                KeyCode::Char('x') | KeyCode::Char('X') => self.step_volume(self.volume_step()),
+                KeyCode::Backspace if self.confirmed(key, ChordAction::ClearQueue) => {
                     self.current_folder = None;
                     self.is_playing = false;
                     self.is_paused = false;
-                    self.is_shuffle_mode = false;
-                    self.player.send(PlayerCommand::Stop);
+                    self.shuffle_mode = ShuffleMode::Off;
+                    let _ = self.player.send(PlayerCommand::Stop);
                 }
                 _ => {}
             }
@@ -669,18 +4767,24 @@ impl App {
     }
 
     fn ui(&self, f: &mut Frame) {
+        self.screen_area.set(f.size());
         let main_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3),
                 Constraint::Min(0),
+                Constraint::Length(1),
                 Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Length(self.config.screen_reader_mode.into()),
             ])
             .split(f.size());
 
         self.draw_header(f, main_chunks[0]);
         self.draw_main_content(f, main_chunks[1]);
-        self.draw_status(f, main_chunks[2]);
+        self.draw_mini_player(f, main_chunks[2]);
+        self.draw_status(f, main_chunks[3]);
+        self.draw_hints(f, main_chunks[4]);
 
         if self.is_creating_playlist {
             self.draw_playlist_creation_popup(f);
@@ -689,10 +4793,67 @@ impl App {
         if self.is_deleting_playlist {
             self.draw_delete_confirmation_popup(f);
         }
+
+        if let Some(track_path) = &self.track_info_path {
+            self.draw_track_info_popup(f, track_path);
+        }
+
+        if self.viewing_incomplete_albums {
+            self.draw_incomplete_albums_popup(f);
+        }
+
+        if let Some(summary) = &self.import_summary {
+            self.draw_import_summary_popup(f, summary);
+        }
+
+        if let Some(input) = &self.volume_input {
+            self.draw_volume_popup(f, input);
+        }
+
+        if let Some(dangling) = &self.dangling_prompt {
+            self.draw_dangling_popup(f, dangling);
+        }
+
+        if let Some(state) = &self.restore_prompt {
+            self.draw_restore_popup(f, state);
+        }
+
+        if let Some((dir, _)) = &self.create_dir_prompt {
+            self.draw_create_dir_popup(f, dir);
+        }
+
+        if let Some(palette) = &self.palette {
+            self.draw_palette(f, palette);
+        }
+
+        if let Some(menu) = &self.context_menu {
+            self.draw_context_menu(f, menu);
+        }
+
+        if let Some(editor) = &self.filter_editor {
+            self.draw_filter_editor(f, editor);
+        }
+
+        if let Some(reorder) = &self.folder_reorder {
+            self.draw_folder_reorder(f, reorder);
+        }
+
+        if let Some(picker) = &self.letter_picker {
+            self.draw_letter_picker(f, picker);
+        }
+
+        if let Some(guess) = &self.tag_guess {
+            self.draw_tag_guess(f, guess);
+        }
+
+        // Last, so screen readers pick it up after everything else
+        if self.config.screen_reader_mode {
+            self.draw_announcement(f, main_chunks[5]);
+        }
     }
 
     fn draw_header(&self, f: &mut Frame, area: Rect) {
-        let header_text = "  ↑/↓ nav | Enter sel | Space pause | P play album | S shuffle | B search | z/x vol | Esc back | Q quit ";
+        let header_text = "  Space pause | z/x vol | N play next | Q queue | Ctrl+N skip | 1/2/3 tabs | Ctrl+P jump | Esc back | Ctrl+Q quit ";
         let header = Block::default()
             .title("rusted-player")
             .title_style(Style::default().add_modifier(Modifier::BOLD))
@@ -703,6 +4864,12 @@ impl App {
         f.render_widget(header_paragraph, area);
     }
 
+    fn draw_hints(&self, f: &mut Frame, area: Rect) {
+        let hints = Paragraph::new(format!(" {}", self.symbols.text(self.hints())))
+            .style(Style::default().fg(self.theme.dim));
+        f.render_widget(hints, area);
+    }
+
     fn draw_main_content(&self, f: &mut Frame, area: Rect) {
         let content_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -723,22 +4890,37 @@ impl App {
         let tabs = ratatui::widgets::Tabs::new(titles)
             .block(Block::default().borders(Borders::ALL).title("tabs"))
             .select(self.active_tab as usize)
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(self.theme.text))
             .highlight_style(
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(self.theme.accent)
                     .add_modifier(Modifier::BOLD),
             );
         f.render_widget(tabs, area);
     }
 
     fn draw_folder_navigation(&self, f: &mut Frame, area: Rect) {
+        if let Some(playlist) = &self.viewing_m3u {
+            self.draw_track_list(
+                f,
+                area,
+                playlist,
+                self.m3u_track_selected,
+                &self.m3u_track_scroll,
+            );
+            return;
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(if self.is_searching {
-                vec![Constraint::Length(3), Constraint::Min(0)]
+                vec![
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ]
             } else {
-                vec![Constraint::Min(0)]
+                vec![Constraint::Length(1), Constraint::Min(0)]
             })
             .split(area);
 
@@ -751,33 +4933,89 @@ impl App {
             f.render_widget(search_input, chunks[0]);
         }
 
-        let content_area = if self.is_searching { chunks[1] } else { area };
+        let (breadcrumb_area, content_area) = if self.is_searching {
+            (chunks[1], chunks[2])
+        } else {
+            (chunks[0], chunks[1])
+        };
+        self.draw_breadcrumbs(f, breadcrumb_area);
 
-        let folder_title = format!("[{}]", self.current_dir.to_string_lossy());
+        let folder_title = if self.custom_order {
+            "Folders (custom order)"
+        } else {
+            "Folders"
+        };
 
         let list_items: Vec<ListItem> = self
             .filtered_items
             .iter()
             .enumerate()
             .map(|(i, item)| {
-                let track_path = self
-                    .current_dir
-                    .join(item.strip_prefix("[DIR] ").unwrap_or(item));
-                let style = if self.marked_tracks.contains(&track_path) {
-                    Style::default().fg(Color::Green)
-                } else if i == self.selected {
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::Yellow)
+                let track_path = self.row_path(i);
+                let playing = self.is_now_playing(&track_path);
+                let emphasis = row_emphasis(
+                    self.marked_tracks.contains(&track_path),
+                    playing,
+                    i == self.selected,
+                );
+                let style = if !self.marked_tracks.is_empty()
+                    && emphasis != RowEmphasis::Marked
+                    && self.indexed_playlist_tracks.contains(&track_path)
+                {
+                    // While marking, tell apart tracks some playlist already has
+                    let style = Style::default().fg(self.theme.in_playlist);
+                    if emphasis == RowEmphasis::Plain {
+                        style
+                    } else {
+                        style.add_modifier(Modifier::BOLD)
+                    }
                 } else if item.starts_with("[DIR]") {
-                    Style::default().fg(Color::Blue)
+                    self.emphasis_style(emphasis, Style::default().fg(self.theme.directory))
                 } else {
-                    Style::default()
+                    self.emphasis_style(emphasis, Style::default())
                 };
-                ListItem::new(item.as_str()).style(style)
+                let mut spans = Vec::new();
+                if playing {
+                    spans.push(Span::raw(format!("{} ", self.symbols.now_playing)));
+                }
+                if self.broken_tracks.contains_key(&track_path) {
+                    spans.push(Span::styled(
+                        format!("{} ", self.symbols.warning),
+                        Style::default().fg(self.theme.alert),
+                    ));
+                }
+                if self.is_favorite(&track_path) {
+                    spans.push(Span::styled(
+                        format!("{} ", self.symbols.favorite),
+                        Style::default().fg(self.theme.alert),
+                    ));
+                }
+                spans.push(Span::raw(item.as_str()));
+                if self.row_entry(i).is_some_and(|entry| entry.symlink) {
+                    spans.push(Span::styled("@", Style::default().fg(self.theme.dim)));
+                }
+                if let Some(Some(context)) = self.filtered_context.get(i) {
+                    spans.push(Span::styled(
+                        context.as_str(),
+                        Style::default().fg(self.theme.dim),
+                    ));
+                }
+                ListItem::new(Line::from(spans)).style(style)
             })
             .collect();
 
+        let highlight_fg = match self.filtered_items.get(self.selected) {
+            Some(_) => {
+                let path = self.row_path(self.selected);
+                self.highlight_fg(row_emphasis(
+                    self.marked_tracks.contains(&path),
+                    self.is_now_playing(&path),
+                    true,
+                ))
+            }
+            None => self.theme.text,
+        };
+
         let list = List::new(list_items)
             .block(
                 Block::default()
@@ -788,79 +5026,192 @@ impl App {
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .bg(Color::DarkGray)
-                    .fg(Color::White),
+                    .bg(self.theme.highlight_bg)
+                    .fg(highlight_fg),
             )
             .highlight_symbol("> ");
 
-        let mut list_state = ListState::default();
-        if !self.filtered_items.is_empty() {
-            list_state.select(Some(self.selected));
-        }
+        let mut list_state = self.list_state(
+            &self.folder_scroll,
+            (!self.filtered_items.is_empty()).then_some(self.selected),
+            self.filtered_items.len(),
+            content_area.height.saturating_sub(2),
+        );
 
         f.render_stateful_widget(list, content_area, &mut list_state);
+        self.folder_list_area.set(content_area);
     }
 
-    fn draw_playlist_navigation(&self, f: &mut Frame, area: Rect) {
-        if let Some(playlist_index) = self.viewing_playlist {
-            let playlist = &self.playlists[playlist_index];
-            let title = format!(
+    fn draw_breadcrumbs(&self, f: &mut Frame, area: Rect) {
+        let segments = breadcrumb_service::segments(&self.music_path, &self.current_dir);
+        let labels: Vec<&str> = segments.iter().map(|s| s.label.as_str()).collect();
+        let dim = Style::default().fg(self.theme.dim);
+        let mut spans = Vec::new();
+        for crumb in breadcrumb_service::collapse(&labels, area.width as usize) {
+            if !spans.is_empty() {
+                spans.push(Span::styled(
+                    self.symbols.text(breadcrumb_service::SEPARATOR),
+                    dim,
+                ));
+            }
+            match crumb {
+                Crumb::Segment { index, label } if index + 1 == segments.len() => {
+                    spans.push(Span::styled(
+                        label,
+                        Style::default()
+                            .fg(self.theme.directory)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                Crumb::Segment { label, .. } => {
+                    spans.push(Span::styled(label, Style::default().fg(self.theme.text)));
+                }
+                Crumb::Collapsed => spans.push(Span::styled(
+                    self.symbols.text(breadcrumb_service::COLLAPSED),
+                    dim,
+                )),
+            }
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    // Track listing shared by saved playlists and .m3u files; missing files are flagged
+    // and tracks on unavailable volumes collapse into one row per volume
+    fn draw_track_list(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        playlist: &Playlist,
+        selected: usize,
+        scroll: &Cell<usize>,
+    ) {
+        let outside = playlist
+            .tracks
+            .iter()
+            .filter(|track| self.is_blocked_external(playlist, &track.path))
+            .count();
+        let title = if outside > 0 {
+            format!(
+                "Playlist: {} ({} tracks, {} outside the library, o allows them)",
+                self.favorites_label(&playlist.name),
+                playlist.tracks.len(),
+                outside
+            )
+        } else {
+            format!(
                 "Playlist: {} ({} tracks)",
-                playlist.name,
+                self.favorites_label(&playlist.name),
                 playlist.tracks.len()
-            );
+            )
+        };
 
-            let list_items: Vec<ListItem> = playlist
+        let rows = self.track_rows(&playlist.tracks);
+        let selected_row = row_position(&rows, selected);
+        let highlight_fg = if self.moved_track.take() == Some(selected) {
+            self.theme.moved
+        } else {
+            let playing = playlist
                 .tracks
-                .iter()
-                .enumerate()
-                .map(|(i, track)| {
-                    let track_name = track
+                .get(selected)
+                .is_some_and(|track| self.is_now_playing(&track.path));
+            self.highlight_fg(row_emphasis(false, playing, true))
+        };
+
+        let list_items: Vec<ListItem> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let track = match row {
+                    TrackRow::Track(index) => &playlist.tracks[*index],
+                    TrackRow::Offline { root, count, .. } => {
+                        let style = if i == selected_row {
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(self.theme.accent)
+                        } else {
+                            Style::default().fg(self.theme.dim)
+                        };
+                        return ListItem::new(TrackRow::offline_label(root, *count)).style(style);
+                    }
+                };
+                let track_name = track.cached_title.as_deref().unwrap_or_else(|| {
+                    track
+                        .path
                         .file_name()
                         .and_then(|n| n.to_str())
-                        .unwrap_or("Unknown");
+                        .unwrap_or("Unknown")
+                });
+                let track = &track.path;
 
-                    let style = if i == self.playlist_track_selected {
-                        Style::default()
-                            .add_modifier(Modifier::BOLD)
-                            .fg(Color::Yellow)
-                    } else if track.exists() {
-                        Style::default().fg(Color::White)
-                    } else {
-                        Style::default().fg(Color::Red)
-                    };
+                let blocked = self.is_blocked_external(playlist, track);
+                let playing = self.is_now_playing(track);
+                let plain = if blocked {
+                    Style::default().fg(self.theme.dim)
+                } else if track.exists() {
+                    Style::default().fg(self.theme.text)
+                } else {
+                    Style::default().fg(self.theme.alert)
+                };
+                let style =
+                    self.emphasis_style(row_emphasis(false, playing, i == selected_row), plain);
 
-                    let display_text = if track.exists() {
-                        format!("{}", track_name)
+                let mark = |shown: bool, symbol: &str| {
+                    if shown {
+                        format!("{} ", symbol)
                     } else {
-                        format!("{} [MISSING]", track_name)
-                    };
+                        String::new()
+                    }
+                };
+                let prefix = mark(playing, self.symbols.now_playing);
+                let heart = mark(self.is_favorite(track), self.symbols.favorite);
+                let warning = mark(self.broken_tracks.contains_key(track), self.symbols.warning);
+                let display_text = if blocked {
+                    format!("{}{}{} [OUTSIDE LIBRARY]", prefix, heart, track_name)
+                } else if track.exists() {
+                    format!("{}{}{}{}", prefix, warning, heart, track_name)
+                } else {
+                    format!("{}{}{} [MISSING]", prefix, heart, track_name)
+                };
 
-                    ListItem::new(display_text).style(style)
-                })
-                .collect();
+                ListItem::new(display_text).style(style)
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .title(title)
+                    .title_style(Style::default().add_modifier(Modifier::BOLD))
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg)
+                    .fg(highlight_fg),
+            )
+            .highlight_symbol("> ");
 
-            let list = List::new(list_items)
-                .block(
-                    Block::default()
-                        .title(title)
-                        .title_style(Style::default().add_modifier(Modifier::BOLD))
-                        .borders(Borders::ALL),
-                )
-                .highlight_style(
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .bg(Color::DarkGray)
-                        .fg(Color::White),
-                )
-                .highlight_symbol("> ");
+        let mut list_state = self.list_state(
+            scroll,
+            (!rows.is_empty()).then_some(selected_row),
+            rows.len(),
+            area.height.saturating_sub(2),
+        );
 
-            let mut list_state = ListState::default();
-            if !playlist.tracks.is_empty() {
-                list_state.select(Some(self.playlist_track_selected));
-            }
+        f.render_stateful_widget(list, area, &mut list_state);
+    }
 
-            f.render_stateful_widget(list, area, &mut list_state);
+    fn draw_playlist_navigation(&self, f: &mut Frame, area: Rect) {
+        if let Some(playlist_index) = self.viewing_playlist {
+            let playlist = &self.playlists[playlist_index];
+            self.draw_track_list(
+                f,
+                area,
+                playlist,
+                self.playlist_track_selected,
+                &self.playlist_track_scroll,
+            );
         } else {
             let title = format!("Playlists ({} total)", self.playlists.len());
 
@@ -870,33 +5221,49 @@ impl App {
                     .wrap(Wrap { trim: true });
                 f.render_widget(placeholder, area);
             } else {
+                let now = playlist_storage_service::unix_now();
                 let list_items: Vec<ListItem> = self
                     .playlists
                     .iter()
                     .enumerate()
                     .map(|(i, playlist)| {
-                        let valid_tracks = playlist
-                            .tracks
-                            .iter()
-                            .filter(|track| track.exists())
-                            .count();
-
-                        let display_text = format!(
-                            "{} ({}/{})",
-                            playlist.name,
-                            valid_tracks,
-                            playlist.tracks.len()
-                        );
+                        // Missing tracks are only counted for playlists already in memory
+                        let display_text = if playlist.is_loaded() {
+                            let valid_tracks = playlist
+                                .tracks
+                                .iter()
+                                .filter(|track| track.path.exists())
+                                .count();
+                            format!(
+                                "{} ({}/{})",
+                                self.favorites_label(&playlist.name),
+                                valid_tracks,
+                                playlist.tracks.len()
+                            )
+                        } else {
+                            format!(
+                                "{} ({})",
+                                self.favorites_label(&playlist.name),
+                                playlist.track_count()
+                            )
+                        };
 
                         let style = if i == self.playlist_selected {
                             Style::default()
                                 .add_modifier(Modifier::BOLD)
-                                .fg(Color::Yellow)
+                                .fg(self.theme.accent)
                         } else {
-                            Style::default().fg(Color::White)
+                            Style::default().fg(self.theme.text)
                         };
 
-                        ListItem::new(display_text).style(style)
+                        // Second line: dates and track-count trend
+                        ListItem::new(Text::from(vec![
+                            Line::styled(display_text, style),
+                            Line::styled(
+                                format!("  {}", playlist_history_line(playlist, now)),
+                                Style::default().fg(self.theme.dim),
+                            ),
+                        ]))
                     })
                     .collect();
 
@@ -910,13 +5277,18 @@ impl App {
                     .highlight_style(
                         Style::default()
                             .add_modifier(Modifier::BOLD)
-                            .bg(Color::DarkGray)
-                            .fg(Color::White),
+                            .bg(self.theme.highlight_bg)
+                            .fg(self.theme.text),
                     )
                     .highlight_symbol("> ");
 
-                let mut list_state = ListState::default();
-                list_state.select(Some(self.playlist_selected));
+                // Each playlist takes two rows
+                let mut list_state = self.list_state(
+                    &self.playlist_scroll,
+                    Some(self.playlist_selected),
+                    self.playlists.len(),
+                    area.height.saturating_sub(2) / 2,
+                );
 
                 f.render_stateful_widget(list, area, &mut list_state);
             }
@@ -924,152 +5296,679 @@ impl App {
     }
 
     fn draw_stats(&self, f: &mut Frame, area: Rect) {
-        let stats = self.playlist_service.get_stats();
+        if let Some(recent) = &self.recent_tracks {
+            self.draw_track_list(
+                f,
+                area,
+                recent,
+                self.recent_track_selected,
+                &self.recent_track_scroll,
+            );
+            return;
+        }
+
+        let sections = self.stats_sections();
+        let selected_row = |i: usize| {
+            (i == self.stats_focus && !sections[i].rows().is_empty()).then_some(self.stats_row)
+        };
+
+        if self.stats_expanded {
+            let view = SectionView {
+                theme: self.theme,
+                focused: true,
+                expanded: true,
+                selected_row: selected_row(self.stats_focus),
+                scroll: self.stats_scroll,
+            };
+            sections[self.stats_focus].render(f, area, &view);
+            return;
+        }
+
+        // Scroll the column just enough to keep the focused section in view
+        let mut first = self.stats_first_section.get().min(self.stats_focus);
+        while first < self.stats_focus
+            && sections[first..=self.stats_focus]
+                .iter()
+                .map(|section| section.height_hint())
+                .sum::<u16>()
+                > area.height
+        {
+            first += 1;
+        }
+        self.stats_first_section.set(first);
 
+        let mut constraints: Vec<Constraint> = sections[first..]
+            .iter()
+            .map(|section| Constraint::Length(section.height_hint()))
+            .collect();
+        constraints.push(Constraint::Min(0));
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .constraints(constraints)
             .split(area);
 
-        let table_data: Vec<Row> = vec![
-            Row::new(vec![
-                "Total Tracks".to_string(),
-                stats.total_tracks.to_string(),
-            ]),
-            Row::new(vec![
-                "Total Genres".to_string(),
-                stats.total_genres.to_string(),
-            ]),
-            Row::new(vec![
-                "Total Albums".to_string(),
-                stats.total_albums.to_string(),
-            ]),
-            Row::new(vec!["Total Duration".to_string(), stats.format_duration()]),
-        ];
-
-        let table = Table::new(
-            table_data,
-            &[Constraint::Percentage(50), Constraint::Percentage(50)],
-        )
-        .block(Block::default().title("Stats").borders(Borders::ALL));
-
-        f.render_widget(table, chunks[0]);
+        for (i, (section, chunk)) in sections[first..].iter().zip(chunks.iter()).enumerate() {
+            let index = first + i;
+            let view = SectionView {
+                theme: self.theme,
+                focused: index == self.stats_focus,
+                expanded: false,
+                selected_row: selected_row(index),
+                scroll: 0,
+            };
+            section.render(f, *chunk, &view);
+        }
+    }
 
-        let bottom_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(chunks[1]);
+    fn draw_playlist_creation_popup(&self, f: &mut Frame) {
+        let popup_area = Self::centered_rect(60, 40, f.size());
+        f.render_widget(Clear, popup_area);
 
-        let left_chunks = Layout::default()
+        let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(bottom_chunks[0]);
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(popup_area);
 
-        let mut top_genres_data: Vec<(String, usize)> = self
-            .playlist_service
-            .get_genres()
+        let title = Block::default()
+            .title("Add to Playlist")
+            .borders(Borders::ALL);
+        f.render_widget(title, popup_area);
+
+        let mut items = self
+            .playlists
             .iter()
-            .map(|genre| {
-                (
-                    genre.clone(),
-                    self.playlist_service.get_playlist_by_genre(genre).len(),
-                )
-            })
-            .collect();
-        top_genres_data.sort_by(|a, b| b.1.cmp(&a.1));
-        top_genres_data.truncate(5);
+            .map(|p| ListItem::new(self.favorites_label(&p.name)))
+            .collect::<Vec<_>>();
+        items.push(ListItem::new("Create new playlist..."));
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::NONE))
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg),
+            );
+
+        let mut list_state = self.list_state(
+            &self.playlist_creation_scroll,
+            Some(self.playlist_creation_selected),
+            self.playlists.len() + 1,
+            chunks[1].height,
+        );
+
+        f.render_stateful_widget(list, chunks[1], &mut list_state);
+
+        if self.playlist_creation_selected == self.playlists.len() {
+            let input = Paragraph::new(self.playlist_name_input.as_str()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("New Playlist Name"),
+            );
+            f.render_widget(input, chunks[2]);
+        }
+    }
+
+    fn draw_track_info_popup(&self, f: &mut Frame, track_path: &Path) {
+        let popup_area = Self::centered_rect(70, 50, f.size());
+        f.render_widget(Clear, popup_area);
+
+        let mut lines = vec![format!("Path: {}", track_path.display()), String::new()];
+        match self.playlist_service.get_track_by_path(track_path) {
+            Some(info) => {
+                let field = |name: &str, value: Option<String>, source: Option<TagSource>| {
+                    let source = source
+                        .map(|s| format!(" ({})", s.name()))
+                        .unwrap_or_default();
+                    format!(
+                        "{}: {}{}",
+                        name,
+                        value.unwrap_or_else(|| "-".to_string()),
+                        source
+                    )
+                };
+                let sources = &info.tag_sources;
+                lines.push(field("Title", info.title.clone(), sources.title));
+                lines.push(field("Artist", info.artist.clone(), sources.artist));
+                if info.album_artist.is_some() && info.album_artist != info.artist {
+                    lines.push(field("Album artist", info.album_artist.clone(), None));
+                }
+                lines.push(field("Album", info.album.clone(), sources.album));
+                lines.push(field("Genre", info.genre.clone(), sources.genre));
+                lines.push(field(
+                    "Year",
+                    info.year.map(|y| y.to_string()),
+                    sources.year,
+                ));
+                lines.push(format!(
+                    "Duration: {}",
+                    info.duration
+                        .map(format_duration_clock)
+                        .unwrap_or_else(|| "-".to_string())
+                ));
+                let trim = self.trims.get(track_path);
+                if !trim.is_empty() {
+                    lines.push(format!("Trim: {}", trim_label(&trim)));
+                }
+                if let Some(cover) = get_album_art_path(track_path) {
+                    lines.push(format!("Cover: {}", cover.display()));
+                }
+                // The rest of the artist's albums, oldest first
+                if let Some(artist) = info.album_artist.as_deref().or(info.artist.as_deref()) {
+                    let albums = self.playlist_service.get_artist_discography(artist);
+                    if albums.len() > 1 {
+                        lines.push(String::new());
+                        lines.push(format!("Albums by {}:", artist));
+                        lines.extend(albums.iter().map(|album| {
+                            format!(
+                                "  {}  {} ({} tracks)",
+                                album.year.map_or("----".to_string(), |y| y.to_string()),
+                                album.name,
+                                album.track_count
+                            )
+                        }));
+                    }
+                }
+                if !info.tag_conflicts.is_empty() {
+                    lines.push(String::new());
+                    lines.push(format!(
+                        "Conflicting tags: {}",
+                        info.tag_conflicts.join(", ")
+                    ));
+                }
+            }
+            None => lines.push("Not found in the scanned library".to_string()),
+        }
 
-        let top_genres_list = List::new(
-            top_genres_data
+        let title = if self.current_track_path.as_deref() == Some(track_path) {
+            "Track Info (s/e set trim start/end here, c clears, any other key closes)"
+        } else {
+            "Track Info (c clears trim, any other key closes)"
+        };
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_incomplete_albums_popup(&self, f: &mut Frame) {
+        let popup_area = Self::centered_rect(70, 60, f.size());
+        f.render_widget(Clear, popup_area);
+
+        let albums = &self.incomplete_albums;
+        let items: Vec<ListItem> = if albums.is_empty() {
+            vec![ListItem::new("No gaps found in numbered albums")]
+        } else {
+            albums
                 .iter()
-                .enumerate()
-                .map(|(i, (genre, count))| {
-                    ListItem::new(format!("{}. {} ({})", i + 1, genre, count))
+                .map(|album| {
+                    ListItem::new(format!(
+                        "{} - {} (missing {})",
+                        album.artist.as_deref().unwrap_or("Unknown"),
+                        album.album,
+                        album.format_missing()
+                    ))
                 })
-                .collect::<Vec<ListItem>>(),
-        )
-        .block(Block::default().title("Top-Genres").borders(Borders::ALL));
+                .collect()
+        };
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Incomplete Albums (e export, Esc close)"),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg),
+            );
+
+        let mut list_state = self.list_state(
+            &self.incomplete_albums_scroll,
+            (!albums.is_empty()).then_some(self.incomplete_album_selected),
+            albums.len(),
+            popup_area.height.saturating_sub(2),
+        );
+
+        f.render_stateful_widget(list, popup_area, &mut list_state);
+    }
+
+    fn draw_import_summary_popup(&self, f: &mut Frame, summary: &ImportSummary) {
+        let popup_area = Self::centered_rect(70, 60, f.size());
+        f.render_widget(Clear, popup_area);
+
+        let mut lines = vec![
+            format!("Playlists found: {}", summary.playlists.len()),
+            format!("Tracks matched: {}", summary.matched_count()),
+            format!("Tracks unresolved: {}", summary.unresolved_count()),
+        ];
+        let outside = summary
+            .playlists
+            .iter()
+            .flat_map(|p| &p.tracks)
+            .filter(|track| !is_within_roots(track, std::slice::from_ref(&self.music_path)))
+            .count();
+        if outside > 0 {
+            lines.push(format!(
+                "Outside the library: {} (not played until allowed with o in the playlist)",
+                outside
+            ));
+        }
+        lines.push(String::new());
+        for playlist in &summary.playlists {
+            lines.push(format!(
+                "{}: {} matched, {} unresolved",
+                playlist.name,
+                playlist.tracks.len(),
+                playlist.unresolved.len()
+            ));
+        }
+
+        let unresolved: Vec<_> = summary
+            .playlists
+            .iter()
+            .flat_map(|p| p.unresolved.iter())
+            .collect();
+        if !unresolved.is_empty() {
+            lines.push(String::new());
+            lines.push("Unresolved:".to_string());
+            for track in unresolved.iter().take(IMPORT_UNRESOLVED_SHOWN) {
+                lines.push(format!("  {} ({})", track.location, track.reason));
+            }
+            if unresolved.len() > IMPORT_UNRESOLVED_SHOWN {
+                lines.push(format!(
+                    "  ... and {} more",
+                    unresolved.len() - IMPORT_UNRESOLVED_SHOWN
+                ));
+            }
+        }
+
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Import Summary (any key to close)"),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(popup, popup_area);
+    }
 
-        f.render_widget(top_genres_list, left_chunks[0]);
+    fn draw_dangling_popup(&self, f: &mut Frame, dangling: &[DanglingPlaylist]) {
+        let popup_area = Self::centered_rect(60, 50, f.size());
+        f.render_widget(Clear, popup_area);
 
-        let top_artists_data = self.playlist_service.get_top_artists();
-        let top_artists_list = List::new(
-            top_artists_data
+        let total: usize = dangling.iter().map(|p| p.missing.len()).sum();
+        let mut lines = vec![
+            format!(
+                "{} playlists contain {} missing tracks",
+                dangling.len(),
+                total
+            ),
+            String::new(),
+        ];
+        lines.extend(
+            dangling
                 .iter()
-                .enumerate()
-                .map(|(i, (artist, count))| {
-                    ListItem::new(format!("{}. {} ({})", i + 1, artist, count))
-                })
-                .collect::<Vec<ListItem>>(),
-        )
-        .block(Block::default().title("Top-Artists").borders(Borders::ALL));
+                .map(|p| format!("{}: {} missing", p.name, p.missing.len())),
+        );
+        lines.push(String::new());
+        lines.push("Tracks on disconnected drives are left alone.".to_string());
+
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Missing Tracks (c clean now, i ignore)"),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_restore_popup(&self, f: &mut Frame, state: &PartialAppState) {
+        let popup_area = Self::centered_rect(60, 30, f.size());
+        f.render_widget(Clear, popup_area);
+
+        let mut lines = vec![
+            "rusted-player didn't close normally last time.".to_string(),
+            String::new(),
+            format!("Folder: {}", state.current_dir.display()),
+        ];
+        if let Some(name) = &state.viewing_playlist {
+            lines.push(format!("Playlist: {}", name));
+        }
+        if !state.marked_tracks.is_empty() {
+            lines.push(format!("{} marked tracks", state.marked_tracks.len()));
+        }
+
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Restore Session? (y restore, any other key skips)"),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_context_menu(&self, f: &mut Frame, menu: &ContextMenu) {
+        let area = menu.area(f.size());
+        f.render_widget(Clear, area);
+        let items: Vec<ListItem> = ContextAction::ALL
+            .iter()
+            .map(|action| ListItem::new(action.label()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.text),
+            );
+        let mut state = ListState::default().with_selected(Some(menu.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
 
-        f.render_widget(top_artists_list, left_chunks[1]);
+    fn draw_filter_editor(&self, f: &mut Frame, editor: &FilterEditor) {
+        let popup_area = Self::centered_rect(50, 40, f.size());
+        f.render_widget(Clear, popup_area);
+        let items: Vec<ListItem> = FilterEditor::LABELS
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let value = match editor.fields.get(i) {
+                    Some(text) => text.clone(),
+                    None => if editor.strict { "[x]" } else { "[ ]" }.to_string(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<15}", label),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                    Span::raw(value),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Shuffle Filter (Enter shuffle library, Space strict, Esc close)"),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.text),
+            );
+        let mut state = ListState::default().with_selected(Some(editor.selected));
+        f.render_stateful_widget(list, popup_area, &mut state);
+    }
 
-        let decades = self.playlist_service.get_tracks_by_decade();
-        let mut decade_data: Vec<(&str, u64)> = decades
+    fn draw_folder_reorder(&self, f: &mut Frame, reorder: &FolderReorder) {
+        let popup_area = Self::centered_rect(60, 60, f.size());
+        f.render_widget(Clear, popup_area);
+        let items: Vec<ListItem> = reorder
+            .tracks
             .iter()
-            .map(|(decade, count)| (decade.as_str(), *count))
+            .enumerate()
+            .map(|(i, track)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>3}. ", i + 1),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                    Span::raw(Self::track_name(track)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default().borders(Borders::ALL).title(
+                    self.symbols
+                        .text("Reorder (Shift+↑↓ move, Enter save, d normal order, Esc cancel)")
+                        .into_owned(),
+                ),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.text),
+            );
+        let mut state = ListState::default().with_selected(Some(reorder.selected));
+        f.render_stateful_widget(list, popup_area, &mut state);
+    }
+
+    fn draw_letter_picker(&self, f: &mut Frame, picker: &LetterPicker) {
+        let popup_area = Self::centered_rect(40, 20, f.size());
+        f.render_widget(Clear, popup_area);
+        let lines: Vec<Line> = LetterPicker::LABELS
+            .chunks(LetterPicker::COLUMNS)
+            .enumerate()
+            .map(|(row, labels)| {
+                let spans: Vec<Span> = labels
+                    .iter()
+                    .enumerate()
+                    .map(|(column, label)| {
+                        let style = if row * LetterPicker::COLUMNS + column == picker.selected {
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .bg(self.theme.highlight_bg)
+                                .fg(self.theme.text)
+                        } else {
+                            Style::default().fg(self.theme.text)
+                        };
+                        Span::styled(format!(" {} ", label), style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
             .collect();
+        let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Jump to letter (Enter jump, Esc close)"),
+        );
+        f.render_widget(paragraph, popup_area);
+    }
+
+    fn draw_tag_guess(&self, f: &mut Frame, guess: &TagGuess) {
+        let popup_area = Self::centered_rect(80, 70, f.size());
+        f.render_widget(Clear, popup_area);
+
+        if let Some(summary) = &guess.summary {
+            let paragraph = Paragraph::new(summary.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Tags from file names"),
+                );
+            f.render_widget(paragraph, popup_area);
+            return;
+        }
 
-        decade_data.sort_by_key(|k| k.0);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)])
+            .split(popup_area);
 
-        let decade_barchart = BarChart::default()
-            .block(Block::default().title("Decades").borders(Borders::ALL))
-            .data(decade_data.as_slice())
-            .bar_width(9)
-            .bar_style(Style::default().fg(Color::Green))
-            .value_style(Style::default().fg(Color::Black).bg(Color::Green));
+        let split = match guess.split {
+            Split::Lazy => "extra separators go to the last field",
+            Split::Greedy => "extra separators go to the first field",
+        };
+        let border = if guess.editing_pattern {
+            self.theme.accent
+        } else {
+            self.theme.dim
+        };
+        let pattern = Paragraph::new(guess.pattern.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border))
+                .title(format!("Pattern ({})", split)),
+        );
+        f.render_widget(pattern, chunks[0]);
 
-        f.render_widget(decade_barchart, bottom_chunks[1]);
+        let items: Vec<ListItem> = guess
+            .files
+            .iter()
+            .zip(&guess.rows)
+            .zip(&guess.tagged)
+            .map(|((file, (edit, accepted)), &tagged)| {
+                let name = Self::track_name(file);
+                let Some(edit) = edit else {
+                    return ListItem::new(format!("    {}  (doesn't fit)", name))
+                        .style(Style::default().fg(self.theme.alert));
+                };
+                let mut fields = Vec::new();
+                if let Some(disc) = edit.disc_number {
+                    fields.push(format!("disc {}", disc));
+                }
+                if let Some(number) = edit.track_number {
+                    fields.push(format!("#{}", number));
+                }
+                fields.extend(edit.artist.clone());
+                fields.extend(edit.album.clone());
+                fields.extend(edit.title.clone());
+                if let Some(year) = edit.year {
+                    fields.push(year.to_string());
+                }
+                let mark = if *accepted { "[x]" } else { "[ ]" };
+                let style = if *accepted {
+                    Style::default().fg(self.theme.text)
+                } else {
+                    Style::default().fg(self.theme.dim)
+                };
+                let note = if tagged { "  (has tags)" } else { "" };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} {}", mark, fields.join(" | ")), style),
+                    Span::styled(
+                        format!("  {} {}{}", self.symbols.get("←"), name, note),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                ]))
+            })
+            .collect();
+        let title = match &guess.error {
+            Some(error) => error.clone(),
+            None => format!(
+                "{} ({} of {} to write)",
+                guess.dir.display(),
+                guess.rows.iter().filter(|(_, accepted)| *accepted).count(),
+                guess.files.len()
+            ),
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .bg(self.theme.highlight_bg),
+            );
+        let selected = (!guess.editing_pattern && !guess.rows.is_empty()).then_some(guess.selected);
+        let mut state = ListState::default().with_selected(selected);
+        f.render_stateful_widget(list, chunks[1], &mut state);
     }
 
-    fn draw_playlist_creation_popup(&self, f: &mut Frame) {
-        let popup_area = Self::centered_rect(60, 40, f.size());
+    fn draw_palette(&self, f: &mut Frame, palette: &Palette) {
+        let popup_area = Self::centered_rect(70, 60, f.size());
         f.render_widget(Clear, popup_area);
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Min(0),
-                Constraint::Length(3),
-            ])
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
             .split(popup_area);
 
-        let title = Block::default()
-            .title("Add to Playlist")
-            .borders(Borders::ALL);
-        f.render_widget(title, popup_area);
+        let input = Paragraph::new(palette.query.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Jump to or run (> commands only, Enter go, Ctrl+Enter play, Esc close)"),
+        );
+        f.render_widget(input, chunks[0]);
 
-        let mut items = self
-            .playlists
+        let items: Vec<ListItem> = palette
+            .matches
             .iter()
-            .map(|p| ListItem::new(p.name.as_str()))
-            .collect::<Vec<_>>();
-        items.push(ListItem::new("Create new playlist..."));
-
+            .map(|&i| {
+                let target = &palette.targets[i];
+                let mut spans = vec![
+                    Span::styled(
+                        format!("[{}] ", target.tag()),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                    Span::raw(target.label()),
+                ];
+                if let PaletteTarget::Command {
+                    keys, description, ..
+                } = target
+                {
+                    let keys = if keys.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", keys)
+                    };
+                    spans.push(Span::styled(
+                        self.symbols
+                            .text(&format!("{} — {}", keys, description))
+                            .into_owned(),
+                        Style::default().fg(self.theme.dim),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
         let list = List::new(items)
-            .block(Block::default().borders(Borders::NONE))
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "{} matches, tracks by {} (Tab)",
+                palette.matches.len(),
+                palette.track_sort.name()
+            )))
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .bg(Color::DarkGray),
-            );
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.text),
+            )
+            .highlight_symbol("> ");
+        let mut state = ListState::default()
+            .with_selected((!palette.matches.is_empty()).then_some(palette.selected));
+        f.render_stateful_widget(list, chunks[1], &mut state);
+    }
 
-        let mut list_state = ListState::default();
-        list_state.select(Some(self.playlist_creation_selected));
+    fn draw_create_dir_popup(&self, f: &mut Frame, dir: &Path) {
+        let popup_area = Self::centered_rect(60, 20, f.size());
+        f.render_widget(Clear, popup_area);
 
-        f.render_stateful_widget(list, chunks[1], &mut list_state);
+        let popup = Paragraph::new(format!(
+            "The playlist folder doesn't exist:\n{}\n\nOther keys export to the config folder instead.",
+            dir.display()
+        ))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Create Folder? (y create)"),
+        )
+        .wrap(Wrap { trim: false });
+        f.render_widget(popup, popup_area);
+    }
 
-        if self.playlist_creation_selected == self.playlists.len() {
-            let input = Paragraph::new(self.playlist_name_input.as_str()).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("New Playlist Name"),
-            );
-            f.render_widget(input, chunks[2]);
-        }
+    fn draw_volume_popup(&self, f: &mut Frame, input: &str) {
+        let popup_area = Self::centered_rect(30, 20, f.size());
+        f.render_widget(Clear, popup_area);
+
+        let popup = Paragraph::new(format!("{}%", input)).block(
+            Block::default().borders(Borders::ALL).title(format!(
+                "Volume 0-{} (Enter set, Esc cancel)",
+                self.max_volume_percent()
+            )),
+        );
+        f.render_widget(popup, popup_area);
     }
 
     fn draw_delete_confirmation_popup(&self, f: &mut Frame) {
@@ -1093,37 +5992,258 @@ impl App {
         f.render_widget(popup, popup_area);
     }
 
+    fn draw_mini_player(&self, f: &mut Frame, area: Rect) {
+        let Some(track_path) = &self.current_track_path else {
+            return;
+        };
+
+        let info = self.current_track_info();
+        let title = info
+            .and_then(|t| t.title.clone())
+            .or_else(|| {
+                track_path
+                    .file_stem()
+                    .and_then(|n| n.to_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| "Unknown".to_string());
+        let label = match info.and_then(|t| t.artist.as_deref()) {
+            Some(artist) => match normalize_featured_artist(artist) {
+                (primary, Some(featured)) => {
+                    format!("{} - {} (feat. {})", primary, title, featured)
+                }
+                _ => format!("{} - {}", artist, title),
+            },
+            None => title,
+        };
+        let duration = info.and_then(|t| t.duration);
+        let icon = if self.is_paused {
+            self.symbols.paused
+        } else {
+            self.symbols.playing
+        };
+        let label = if self.is_favorite(track_path) {
+            format!("{} {}", self.symbols.favorite, label)
+        } else {
+            label
+        };
+
+        let mut text = match duration {
+            // The ticking clock and bar would be read out on every redraw
+            _ if self.config.screen_reader_mode => label,
+            Some(total) => format!(
+                "{} {} | {} / {}",
+                icon,
+                label,
+                format_duration_clock(self.track_position),
+                format_duration_clock(total)
+            ),
+            None => format!(
+                "{} {} | {}",
+                icon,
+                label,
+                format_duration_clock(self.track_position)
+            ),
+        };
+        if let Some((current_index, paths)) = &self.last_queue_snapshot
+            && paths.len() > 1
+        {
+            text.push_str(&format!(" | {}/{}", current_index + 1, paths.len()));
+        }
+        let trim = self.trims.get(track_path).clamped(duration);
+        if !trim.is_empty() {
+            text.push_str(&format!(
+                " [{} {}]",
+                self.symbols.trim,
+                self.symbols.text(&trim_label(&trim))
+            ));
+        }
+
+        if let Some((track_rate, output_rate)) = self.audio_format {
+            let khz = |rate: u32| format!("{:.1} kHz", rate as f64 / 1000.0);
+            if track_rate == output_rate || output_rate == 0 {
+                text.push_str(&format!(" | {}", khz(track_rate)));
+            } else {
+                text.push_str(&format!(
+                    " | {} {} {}",
+                    khz(track_rate),
+                    self.symbols.arrow,
+                    khz(output_rate)
+                ));
+            }
+        }
+
+        if let Some(total) = duration.filter(|_| !self.config.screen_reader_mode) {
+            // Room left for " [" + bar + "]"
+            let bar_width = (area.width as usize).saturating_sub(text.chars().count() + 4);
+            if bar_width >= 4 {
+                let ratio = if total.is_zero() {
+                    0.0
+                } else {
+                    (self.track_position.as_secs_f64() / total.as_secs_f64()).min(1.0)
+                };
+                let filled = (ratio * bar_width as f64) as usize;
+                let mut bar = self.symbols.bar_done.repeat(filled);
+                if filled < bar_width {
+                    bar.push_str(self.symbols.bar_head);
+                    bar.push_str(&self.symbols.bar_todo.repeat(bar_width - filled - 1));
+                }
+                text = format!("{} [{}]", text, bar);
+            }
+        }
+
+        let mini_player =
+            Paragraph::new(format!(" {}", text)).style(Style::default().fg(self.theme.player));
+        f.render_widget(mini_player, area);
+    }
+
+    // Moves the cached queue on to `path` when it is simply the next track
+    fn advance_queue_snapshot(&mut self, path: &Path) -> bool {
+        let Some((current_index, paths)) = &mut self.last_queue_snapshot else {
+            return false;
+        };
+        if paths
+            .get(*current_index + 1)
+            .is_some_and(|next| next == path)
+        {
+            *current_index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Time left in the player's queue, counting trims and the current track's elapsed part
+    fn queue_time(&self) -> Option<QueueTime> {
+        let (current_index, paths) = self.last_queue_snapshot.as_ref()?;
+        if paths.len() < 2 {
+            return None;
+        }
+        let mut cache = self.queue_spans.borrow_mut();
+        let (starts, durations) = cache.get_or_insert_with(|| {
+            paths
+                .iter()
+                .map(|path| {
+                    let duration = self
+                        .playlist_service
+                        .get_track_by_path(path)
+                        .and_then(|t| t.duration);
+                    let trim = self.trims.get(path).clamped(duration);
+                    let start = trim.start().unwrap_or_default();
+                    (
+                        start,
+                        trim.end().or(duration).map(|end| end.saturating_sub(start)),
+                    )
+                })
+                .unzip()
+        });
+        let current_start = starts.get(*current_index).copied().unwrap_or_default();
+        queue_time_service::queue_time(
+            durations,
+            *current_index,
+            self.track_position.saturating_sub(current_start),
+            self.is_paused,
+            SystemTime::now(),
+            Duration::from_secs(self.config.unknown_duration_estimate_secs),
+        )
+    }
+
+    // The configured status line; in screen reader mode without icons or a ticking clock
+    fn status_line(&self, width: usize) -> String {
+        let plain = self.config.screen_reader_mode;
+        let info = self.current_track_info();
+        let shuffle_filter = (!self.shuffle_filter.is_empty()).then(|| {
+            format!(
+                "{}, {} tracks",
+                self.shuffle_filter.summary(&self.symbols),
+                format_count(self.shuffle_pool)
+            )
+        });
+        let state = match (self.is_paused, plain) {
+            (true, true) => "Paused:".to_string(),
+            (true, false) => format!("{} Paused:", self.symbols.paused),
+            (false, true) => "Playing:".to_string(),
+            (false, false) => format!("{} Playing:", self.symbols.playing),
+        };
+        let queue = self.queue_time().map(|queue| queue.summary(&self.symbols));
+        let source = self.favorites_label(self.current_folder.as_deref().unwrap_or(""));
+        let context = StatusContext {
+            state: &state,
+            source: &source,
+            artist: info.and_then(|t| t.artist.as_deref()),
+            title: info.and_then(|t| t.title.as_deref()),
+            elapsed: self
+                .current_track_path
+                .as_ref()
+                .filter(|_| !plain)
+                .map(|_| self.track_position),
+            duration: info.and_then(|t| t.duration),
+            shuffle: self.shuffle_mode,
+            shuffle_filter: shuffle_filter.as_deref(),
+            volume: (self.volume_limiter.current(self.volume) * 100.0).round() as u32,
+            queue: queue.as_deref(),
+            symbols: self.symbols,
+        };
+        // Only the interface's own pieces follow the symbol set: titles are shown as tagged
+        status_format_service::render_status(&self.config.status_format, &context, width)
+    }
+
+    // The favorites playlist's name with its heart in the current symbol set
+    fn favorites_label<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.symbols.text(FAVORITES_NAME) {
+            Cow::Owned(label) if text.contains(FAVORITES_NAME) => {
+                Cow::Owned(text.replacen(FAVORITES_NAME, &label, 1))
+            }
+            _ => Cow::Borrowed(text),
+        }
+    }
+
+    // Screen reader mode: one plain line per state change, optionally copied to stderr
+    fn announce(&mut self, line: String) {
+        if !self.config.screen_reader_mode {
+            return;
+        }
+        if self.config.announce_to_stderr {
+            eprintln!("{}", line);
+        }
+        self.announcement = Some(line);
+    }
+
+    fn draw_announcement(&self, f: &mut Frame, area: Rect) {
+        let line = self.announcement.as_deref().unwrap_or("");
+        f.render_widget(Paragraph::new(line), area);
+    }
+
     fn draw_status(&self, f: &mut Frame, area: Rect) {
         let status_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(90), Constraint::Percentage(10)])
             .split(area);
 
-        let status_text = if self.is_paused {
-            if self.is_shuffle_mode {
-                format!(
-                    "⏸ Paused: {} in shuffle mode",
-                    self.current_folder.as_deref().unwrap_or("")
-                )
-            } else {
-                format!("⏸ Paused: {}", self.current_folder.as_deref().unwrap_or(""))
-            }
-        } else if self.is_playing {
-            if self.is_shuffle_mode {
-                format!(
-                    "♪ Playing: {} in shuffle mode",
-                    self.current_folder.as_deref().unwrap_or("")
-                )
-            } else {
-                format!(
-                    "♪ Playing: {}",
-                    self.current_folder.as_deref().unwrap_or("")
-                )
+        // A pending chord takes the notification's place until it completes or expires
+        let notification = match (self.chord_hint(), &self.notification) {
+            (Some(hint), _) => Some(format!(" | {}", self.symbols.text(&hint))),
+            (None, Some((message, shown_at))) if shown_at.elapsed() < NOTIFICATION_DURATION => {
+                Some(format!(" | {}", message))
             }
+            _ => None,
+        };
+
+        let status_text = if self.is_playing || self.is_paused {
+            // Inside the borders, leaving room for the notification
+            let width = (status_chunks[0].width as usize)
+                .saturating_sub(2)
+                .saturating_sub(notification.as_deref().map_or(0, |n| n.chars().count()));
+            self.status_line(width)
         } else {
             "No album selected".to_string()
         };
 
+        let status_text = match notification {
+            Some(notification) => status_text + &notification,
+            None => status_text,
+        };
+
         let status_paragraph = Paragraph::new(status_text).block(
             Block::default()
                 .title("status")
@@ -1132,20 +6252,15 @@ impl App {
         );
         f.render_widget(status_paragraph, status_chunks[0]);
 
-        let volume_text = format!("Vol: {:.0}/20", self.volume * 10.0);
+        let volume_text = format!(
+            "Vol: {:.0}/20",
+            self.volume_limiter.current(self.volume) * 10.0
+        );
         let volume_paragraph =
             Paragraph::new(volume_text).block(Block::default().borders(Borders::ALL));
         f.render_widget(volume_paragraph, status_chunks[1]);
     }
 
-    fn is_audio_file(path: &PathBuf) -> bool {
-        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-            matches!(ext.to_lowercase().as_str(), "mp3" | "flac" | "ogg" | "wav")
-        } else {
-            false
-        }
-    }
-
     fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         let popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -1166,3 +6281,608 @@ impl App {
             .split(popup_layout[1])[1]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> event::KeyEvent {
+        event::KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    // An app over a temporary library with one track, nothing saved and no audio device
+    struct TestApp {
+        app: App,
+        library: PathBuf,
+    }
+
+    impl TestApp {
+        fn new(name: &str, config: Config) -> Self {
+            let library = std::env::temp_dir().join(format!(
+                "rusted-player-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&library);
+            std::fs::create_dir_all(&library).unwrap();
+            std::fs::write(library.join("Song.mp3"), b"audio").unwrap();
+            let config = Config {
+                music_path: library.clone(),
+                ..config
+            };
+            let app = App::with_stores(&config, Stores::default());
+            TestApp { app, library }
+        }
+
+        fn song(&self) -> PathBuf {
+            self.library.join("Song.mp3")
+        }
+
+        // The screen as text, one trimmed line per row
+        fn screen(&self, width: u16, height: u16) -> Vec<String> {
+            let mut terminal =
+                ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| self.app.ui(f)).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..height)
+                .map(|y| {
+                    let row: String = (0..width).map(|x| buffer.get(x, y).symbol()).collect();
+                    row.trim_end().to_string()
+                })
+                .collect()
+        }
+    }
+
+    impl Drop for TestApp {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.library);
+        }
+    }
+
+    fn screen_reader() -> Config {
+        Config {
+            screen_reader_mode: true,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn screen_reader_layout_drops_the_clock_and_ends_with_the_announcement() {
+        let mut reader = TestApp::new("a11y-layout", screen_reader());
+        let mut regular = TestApp::new("a11y-layout-regular", Config::default());
+        for test in [&mut reader, &mut regular] {
+            let song = test.song();
+            test.app
+                .handle_player_status(PlayerStatus::TrackChanged(song));
+            test.app
+                .handle_player_status(PlayerStatus::Position(Duration::from_secs(65)));
+        }
+
+        // Same height: the announcement row takes one line from the folder list
+        let screen = reader.screen(60, 14);
+        assert_eq!(screen[8], " Song");
+        assert!(screen[10].starts_with("│Playing: Queue "));
+        assert!(screen[12].starts_with(" ↑↓ nav |"));
+        assert_eq!(screen[13], "Playing: Queue");
+
+        let screen = regular.screen(60, 14);
+        assert_eq!(screen[9], " ♪ Song | 1:05");
+        assert!(screen[11].starts_with("│♪ Playing: Queue "));
+        assert!(screen[13].starts_with(" ↑↓ nav |"));
+    }
+
+    #[test]
+    fn each_state_change_is_announced_once() {
+        let mut test = TestApp::new("a11y-once", screen_reader());
+        let song = test.song();
+        let app = &mut test.app;
+        let step = |app: &mut App, status: Option<PlayerStatus>, expected: Option<&str>| {
+            match status {
+                Some(status) => app.handle_player_status(status),
+                None => {
+                    app.handle_input(key(KeyCode::Char(' '))).unwrap();
+                }
+            }
+            assert_eq!(app.announcement.take().as_deref(), expected);
+        };
+        step(
+            app,
+            Some(PlayerStatus::TrackChanged(song.clone())),
+            Some("Playing: Queue"),
+        );
+        step(
+            app,
+            Some(PlayerStatus::Position(Duration::from_secs(1))),
+            None,
+        );
+        step(
+            app,
+            Some(PlayerStatus::QueueSnapshot {
+                current_index: 0,
+                paths: vec![song],
+            }),
+            None,
+        );
+        step(app, Some(PlayerStatus::Volume(0.5)), Some("Volume 50%"));
+        step(app, Some(PlayerStatus::Volume(0.5)), None);
+        step(app, None, Some("Paused"));
+        step(app, None, Some("Resumed"));
+        step(
+            app,
+            Some(PlayerStatus::Error("boom".to_string())),
+            Some("Error: boom"),
+        );
+        step(app, Some(PlayerStatus::Stopped), Some("Stopped"));
+    }
+
+    #[test]
+    fn regular_mode_announces_nothing() {
+        let mut test = TestApp::new("a11y-off", Config::default());
+        let song = test.song();
+        test.app
+            .handle_player_status(PlayerStatus::TrackChanged(song));
+        test.app.handle_player_status(PlayerStatus::Volume(0.5));
+        test.app.handle_player_status(PlayerStatus::Stopped);
+        assert_eq!(test.app.announcement, None);
+    }
+
+    #[test]
+    fn chords_complete_on_a_second_press_within_the_timeout() {
+        let start = Instant::now();
+        let q = key(KeyCode::Char('q'));
+        // (description, second press after, completes)
+        let cases = [
+            ("quick second press", Duration::from_millis(300), true),
+            ("exactly at the timeout", CHORD_TIMEOUT, true),
+            (
+                "just past the timeout",
+                CHORD_TIMEOUT + Duration::from_millis(1),
+                false,
+            ),
+        ];
+        for (description, after, completes) in cases {
+            let mut chords = ChordDetector::default();
+            assert!(
+                !chords.press(q, ChordAction::Quit, start),
+                "{}",
+                description
+            );
+            assert_eq!(
+                chords.press(q, ChordAction::Quit, start + after),
+                completes,
+                "{}",
+                description
+            );
+        }
+    }
+
+    #[test]
+    fn a_late_press_starts_a_new_chord() {
+        let start = Instant::now();
+        let q = key(KeyCode::Char('q'));
+        let mut chords = ChordDetector::default();
+        chords.press(q, ChordAction::Quit, start);
+        let late = start + CHORD_TIMEOUT * 2;
+        assert!(!chords.press(q, ChordAction::Quit, late));
+        assert!(chords.press(q, ChordAction::Quit, late + Duration::from_millis(100)));
+        assert_eq!(chords.waiting(late + Duration::from_millis(100)), None);
+    }
+
+    #[test]
+    fn any_other_key_cancels_the_chord() {
+        let start = Instant::now();
+        let soon = start + Duration::from_millis(100);
+        let d = key(KeyCode::Char('d'));
+        let mut chords = ChordDetector::default();
+        chords.press(d, ChordAction::DeletePlaylist(2), start);
+        chords.interrupt(key(KeyCode::Down));
+        assert!(!chords.press(d, ChordAction::DeletePlaylist(2), soon));
+
+        // Same key with another modifier, or for another target, doesn't complete it
+        let mut chords = ChordDetector::default();
+        chords.press(d, ChordAction::DeletePlaylist(2), start);
+        let shifted = event::KeyEvent::new(KeyCode::Char('d'), KeyModifiers::SHIFT);
+        assert!(!chords.press(shifted, ChordAction::DeletePlaylist(2), soon));
+        let mut chords = ChordDetector::default();
+        chords.press(d, ChordAction::DeletePlaylist(2), start);
+        assert!(!chords.press(d, ChordAction::DeletePlaylist(3), soon));
+
+        // The same key doesn't interrupt its own chord
+        let mut chords = ChordDetector::default();
+        chords.press(d, ChordAction::DeletePlaylist(2), start);
+        chords.interrupt(d);
+        assert!(chords.press(d, ChordAction::DeletePlaylist(2), soon));
+    }
+
+    #[test]
+    fn waiting_counts_down_to_the_timeout() {
+        let start = Instant::now();
+        let mut chords = ChordDetector::default();
+        assert_eq!(chords.waiting(start), None);
+        chords.press(key(KeyCode::Char('c')), ChordAction::ClearQueue, start);
+        assert_eq!(
+            chords.waiting(start + Duration::from_millis(400)),
+            Some((ChordAction::ClearQueue, Duration::from_millis(600)))
+        );
+        assert_eq!(
+            chords.waiting(start + CHORD_TIMEOUT),
+            Some((ChordAction::ClearQueue, Duration::ZERO))
+        );
+        assert_eq!(chords.waiting(start + CHORD_TIMEOUT * 2), None);
+    }
+
+    #[test]
+    fn marked_wins_over_playing_and_playing_over_selected() {
+        use RowEmphasis::*;
+        let table = [
+            ((false, false, false), Plain),
+            ((false, false, true), Selected),
+            ((false, true, false), Playing),
+            ((false, true, true), Playing),
+            ((true, false, false), Marked),
+            ((true, false, true), Marked),
+            ((true, true, false), Marked),
+            ((true, true, true), Marked),
+        ];
+        for ((marked, playing, selected), expected) in table {
+            assert_eq!(
+                row_emphasis(marked, playing, selected),
+                expected,
+                "marked={marked} playing={playing} selected={selected}"
+            );
+        }
+    }
+
+    #[test]
+    fn row_style_and_selection_bar_follow_the_emphasis() {
+        let test = TestApp::new("emphasis", Config::default());
+        let app = &test.app;
+        let plain = Style::default().fg(Color::DarkGray);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        assert_eq!(app.emphasis_style(RowEmphasis::Plain, plain), plain);
+        assert_eq!(
+            app.emphasis_style(RowEmphasis::Marked, plain),
+            Style::default().fg(app.theme.marked)
+        );
+        assert_eq!(
+            app.emphasis_style(RowEmphasis::Playing, plain),
+            bold.fg(app.theme.playing)
+        );
+        assert_eq!(
+            app.emphasis_style(RowEmphasis::Selected, plain),
+            bold.fg(app.theme.accent)
+        );
+
+        // A marked track under the bar keeps its colour; a plain selection uses the text colour
+        assert_eq!(app.highlight_fg(RowEmphasis::Marked), app.theme.marked);
+        assert_eq!(app.highlight_fg(RowEmphasis::Playing), app.theme.playing);
+        assert_eq!(app.highlight_fg(RowEmphasis::Selected), app.theme.text);
+    }
+
+    #[test]
+    fn regrouping_waits_for_an_idle_tick() {
+        let mut test = TestApp::new("regroup", Config::default());
+        let app = &mut test.app;
+        app.run_palette_action(PaletteAction::RunCommand("Regroup library"));
+        app.run_palette_action(PaletteAction::RunCommand("Regroup library"));
+        assert_eq!(
+            app.pending_actions.iter().collect::<Vec<_>>(),
+            [&AppAction::RebuildIndex]
+        );
+        app.run_pending_action();
+        assert!(app.pending_actions.is_empty());
+    }
+
+    #[test]
+    fn marking_indexes_loaded_playlists_without_touching_the_disk() {
+        let mut test = TestApp::new("mark-index", Config::default());
+        let song = test.song();
+        let other = test.library.join("Other.mp3");
+        let app = &mut test.app;
+        app.playlists
+            .push(Playlist::new("Mix".to_string(), vec![song.clone()]));
+
+        app.toggle_mark(other.clone());
+        assert!(app.indexed_playlist_tracks.contains(&song));
+        assert!(!app.indexed_playlist_tracks.contains(&other));
+        // Every playlist was already loaded: there is nothing for a worker to read
+        assert!(app.playlist_tracks_scan.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_rows_are_marked_and_dangling_links_dont_stop_the_scan() {
+        let mut test = TestApp::new("links", Config::default());
+        let song = test.song();
+        let library = test.library.clone();
+        let linked = std::os::unix::fs::symlink(&song, library.join("Linked.mp3")).is_ok()
+            && std::os::unix::fs::symlink(library.join("gone.mp3"), library.join("Gone.mp3"))
+                .is_ok();
+        if !linked {
+            return;
+        }
+
+        test.app.scan_directory();
+        assert_eq!(test.app.notification, None);
+        assert!(test.app.playlist_service.get_track_by_path(&song).is_some());
+        test.app.show_current_dir();
+        let screen = test.screen(60, 30);
+        assert!(
+            screen.iter().any(|row| row.contains("Linked.mp3@")),
+            "{screen:#?}"
+        );
+        assert!(
+            !screen.iter().any(|row| row.contains("Song.mp3@")),
+            "{screen:#?}"
+        );
+    }
+
+    #[test]
+    fn search_matches_the_folders_above_an_entry() {
+        let mut test = TestApp::new("search-folders", Config::default());
+        let album = test.library.join("Pink Floyd/Meddle");
+        std::fs::create_dir_all(&album).unwrap();
+        std::fs::write(album.join("Echoes.mp3"), b"audio").unwrap();
+        let app = &mut test.app;
+        app.navigate_to(album);
+
+        app.search_query = "floyd".to_string();
+        app.update_filtered_items();
+        assert_eq!(app.filtered_items, ["Echoes.mp3"]);
+        let context = app.filtered_context[0].as_deref().unwrap();
+        assert!(context.ends_with("in folder: Pink Floyd"), "{context}");
+    }
+
+    #[test]
+    fn entering_a_leaf_album_offers_to_play_it() {
+        let mut test = TestApp::new("leaf-album", Config::default());
+        let album = test.library.join("Artist/Album");
+        std::fs::create_dir_all(&album).unwrap();
+        std::fs::write(album.join("01.mp3"), b"audio").unwrap();
+        let app = &mut test.app;
+
+        app.navigate_to(test.library.join("Artist"));
+        assert_eq!(app.notification, None);
+        app.navigate_to(album);
+        assert_eq!(
+            app.notification.as_ref().map(|(text, _)| text.as_str()),
+            Some("Press p to play, Enter to browse")
+        );
+        assert_eq!(app.filtered_items, ["[DIR] ..", "01.mp3"]);
+    }
+
+    #[test]
+    fn palette_selection_runs_the_matching_command() {
+        // Commands are found again by name, so each name must be unique
+        let names: HashSet<&str> = COMMANDS.iter().map(|command| command.name).collect();
+        assert_eq!(names.len(), COMMANDS.len());
+
+        let mut test = TestApp::new("palette-command", Config::default());
+        let app = &mut test.app;
+        app.open_palette();
+        for c in ">regroup".chars() {
+            app.handle_input(key(KeyCode::Char(c))).unwrap();
+        }
+        assert!(matches!(
+            app.palette.as_ref().and_then(Palette::current),
+            Some(PaletteTarget::Command {
+                name: "Regroup library",
+                ..
+            })
+        ));
+        app.handle_input(key(KeyCode::Enter)).unwrap();
+        assert!(app.palette.is_none());
+        assert_eq!(
+            app.pending_actions.iter().collect::<Vec<_>>(),
+            [&AppAction::RebuildIndex]
+        );
+    }
+
+    #[test]
+    fn scroll_offset_table() {
+        // (previous, selected, len, height, scrolloff, centered, expected)
+        let table = [
+            // A list that fits never scrolls, whatever the selection
+            (5, 3, 5, 10, 2, false, 0),
+            (5, 9, 10, 10, 2, true, 0),
+            (0, 0, 50, 0, 2, false, 0),
+            // First row
+            (10, 0, 50, 10, 2, false, 0),
+            (0, 0, 50, 10, 2, true, 0),
+            // Last row stops at the end of the list
+            (0, 49, 50, 10, 2, false, 40),
+            (40, 49, 50, 10, 2, true, 40),
+            // Inside the margins the offset stays put
+            (10, 15, 50, 10, 2, false, 10),
+            (10, 12, 50, 10, 2, false, 10),
+            // Past them it moves just enough
+            (10, 18, 50, 10, 2, false, 11),
+            (10, 11, 50, 10, 2, false, 9),
+            // scrolloff is capped at half the height
+            (10, 15, 50, 10, 100, false, 10),
+            (10, 16, 50, 10, 100, false, 11),
+            (3, 7, 10, 1, 2, false, 7),
+            (0, 25, 50, 10, 2, true, 20),
+        ];
+        for (i, (previous, selected, len, height, scrolloff, centered, expected)) in
+            table.into_iter().enumerate()
+        {
+            assert_eq!(
+                scroll_offset(previous, selected, len, height, scrolloff, centered),
+                expected,
+                "row {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn volume_requests_coalesce_until_the_player_confirms() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut limiter = VolumeLimiter::default();
+
+        assert_eq!(limiter.request(0.5, at(0)), Some(0.5));
+        // Presses before the report only move the target
+        assert_eq!(limiter.request(0.6, at(100)), None);
+        assert_eq!(limiter.request(0.7, at(200)), None);
+        assert_eq!(limiter.current(0.4), 0.7);
+        // The report of 0.5 sends the latest target once
+        assert_eq!(limiter.confirm(at(300)), Some(0.7));
+        assert_eq!(limiter.current(0.5), 0.7);
+        // Its own report settles it
+        assert_eq!(limiter.confirm(at(350)), None);
+        assert_eq!(limiter.current(0.7), 0.7);
+        assert_eq!(limiter.request(0.8, at(360)), Some(0.8));
+        // With no answer the request is sent again after the timeout
+        assert_eq!(
+            limiter.request(0.9, at(360) + VOLUME_CONFIRM_TIMEOUT),
+            Some(0.9)
+        );
+        assert_eq!(limiter.confirm(at(1000)), None);
+        assert_eq!(limiter.current(0.3), 0.3);
+    }
+
+    #[test]
+    fn volume_shown_follows_the_target_until_reported() {
+        let mut test = TestApp::new("volume-limiter", Config::default());
+        let app = &mut test.app;
+        let curve = app.config.volume_curve;
+        app.volume = 0.5;
+        app.set_volume(0.6);
+        app.set_volume(0.7);
+        assert_eq!(app.volume_limiter.current(app.volume), 0.7);
+
+        let report = |level| PlayerStatus::Volume(volume_service::level_to_amplitude(curve, level));
+        app.handle_player_status(report(0.6));
+        assert_eq!(app.volume_limiter.current(app.volume), 0.7);
+        app.handle_player_status(report(0.7));
+        assert_eq!(app.volume_limiter.target, None);
+        assert!((app.volume - 0.7).abs() < 1e-4, "{}", app.volume);
+    }
+
+    #[test]
+    fn key_repeat_tracks_how_long_a_key_is_held() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let ms = Duration::from_millis;
+        let mut repeat = KeyRepeat::new();
+
+        assert_eq!(repeat.press(KeyCode::Up, at(0)), Duration::ZERO);
+        assert_eq!(repeat.press(KeyCode::Up, at(100)), ms(100));
+        // A gap of exactly KEY_REPEAT_GAP still counts as held
+        assert_eq!(repeat.press(KeyCode::Up, at(700)), ms(700));
+        // A longer gap starts over
+        assert_eq!(repeat.press(KeyCode::Up, at(1301)), Duration::ZERO);
+        assert_eq!(repeat.press(KeyCode::Up, at(1400)), ms(99));
+        // So does another key, and the first one again after it
+        assert_eq!(repeat.press(KeyCode::Down, at(1450)), Duration::ZERO);
+        assert_eq!(repeat.press(KeyCode::Up, at(1500)), Duration::ZERO);
+        // A release ends the hold even right away
+        repeat.release();
+        assert_eq!(repeat.press(KeyCode::Up, at(1510)), Duration::ZERO);
+    }
+
+    #[test]
+    fn volume_steps_grow_once_the_key_is_held() {
+        let mut test = TestApp::new("key-accel", Config::default());
+        let app = &mut test.app;
+        let delay = Duration::from_millis(app.config.key_acceleration_delay_ms);
+
+        app.key_held = Duration::ZERO;
+        assert_eq!(app.volume_step(), VOLUME_STEP_SMALL);
+        app.key_held = delay;
+        assert_eq!(app.volume_step(), VOLUME_STEP_LARGE);
+        app.config.key_acceleration = false;
+        assert_eq!(app.volume_step(), VOLUME_STEP);
+    }
+
+    #[test]
+    fn pastes_stop_at_the_limit_without_splitting_graphemes() {
+        let mut input = String::new();
+        insert_str(&mut input, &"a".repeat(MAX_INPUT_LEN + 50));
+        assert_eq!(input, "a".repeat(MAX_INPUT_LEN));
+        insert_str(&mut input, "more");
+        assert_eq!(input.len(), MAX_INPUT_LEN);
+
+        let mut input = "x".repeat(MAX_INPUT_LEN - 1);
+        insert_str(&mut input, "\u{e9}e\u{301}");
+        assert!(input.ends_with('\u{e9}'));
+
+        // A letter with a combining accent is one grapheme of two chars
+        let mut input = String::new();
+        insert_str(&mut input, &"e\u{301}".repeat(MAX_INPUT_LEN + 1));
+        assert_eq!(input.graphemes(true).count(), MAX_INPUT_LEN);
+        assert_eq!(input.chars().count(), 2 * MAX_INPUT_LEN);
+        assert!(input.ends_with("e\u{301}"));
+    }
+
+    #[test]
+    fn pasted_lines_join_and_control_chars_drop() {
+        let mut input = "Pink ".to_string();
+        insert_str(&mut input, "Floyd\n\n  Meddle \r\nEch\x07oes\t");
+        assert_eq!(input, "Pink Floyd Meddle Echoes");
+    }
+
+    #[test]
+    fn favorites_are_always_first_shown_and_never_deleted() {
+        let mut test = TestApp::new("favorites", Config::default());
+        let song = test.song();
+        let app = &mut test.app;
+        app.playlists
+            .push(Playlist::new("Mix".to_string(), vec![song.clone()]));
+        app.playlists[FAVORITES_INDEX].toggle_track(&song);
+        assert!(app.is_favorite(&song));
+
+        // Folder rows of loved tracks carry the heart
+        app.show_current_dir();
+        let screen = test.screen(60, 30);
+        assert!(
+            screen.iter().any(|row| row.contains("♥ Song.mp3")),
+            "{screen:#?}"
+        );
+
+        let app = &mut test.app;
+        app.active_tab = ActiveTab::PlaylistNavigation;
+        app.playlist_selected = FAVORITES_INDEX;
+        app.handle_input(key(KeyCode::Char('d'))).unwrap();
+        app.handle_input(key(KeyCode::Char('d'))).unwrap();
+        assert_eq!(app.playlists.len(), 2);
+        assert!(app.playlists[FAVORITES_INDEX].is_favorites);
+        assert_eq!(
+            app.notification.as_ref().map(|(text, _)| text.as_str()),
+            Some("Favorites can't be deleted")
+        );
+        // It plays like any saved playlist
+        assert_eq!(
+            app.load_playlist_named(FAVORITES_NAME),
+            Some(FAVORITES_INDEX)
+        );
+    }
+
+    #[test]
+    fn the_next_track_advances_the_cached_queue() {
+        let mut test = TestApp::new("queue-advance", Config::default());
+        let tracks: Vec<PathBuf> = ["a", "b", "c"]
+            .iter()
+            .map(|name| test.library.join(format!("{name}.mp3")))
+            .collect();
+        let app = &mut test.app;
+        app.handle_player_status(PlayerStatus::QueueSnapshot {
+            current_index: 0,
+            paths: tracks.clone(),
+        });
+
+        assert!(app.advance_queue_snapshot(&tracks[1]));
+        assert_eq!(app.last_queue_snapshot, Some((1, tracks.clone())));
+        // Anything but the next track needs a fresh snapshot
+        assert!(!app.advance_queue_snapshot(&tracks[0]));
+        assert!(!app.advance_queue_snapshot(&tracks[1]));
+        app.handle_player_status(PlayerStatus::TrackChanged(tracks[2].clone()));
+        assert_eq!(app.last_queue_snapshot, Some((2, tracks.clone())));
+        assert!(!app.advance_queue_snapshot(&tracks[0]));
+
+        app.handle_player_status(PlayerStatus::Stopped);
+        assert!(!app.advance_queue_snapshot(&tracks[0]));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::models::config::VolumeCurve;
+
+/// Nivel máximo de la curva lineal (200%)
+const MAX_LEVEL: f32 = 2.0;
+/// Amplitud máxima que acepta el reproductor (el doble, unos +6 dB)
+const MAX_AMPLITUDE: f32 = 2.0;
+/// dB que recorre la curva logarítmica por cada 100%: el 1% queda unos 60 dB abajo
+const LOG_RANGE_DB: f32 = 60.0;
+
+/// Nivel más alto que se puede pedir con la curva
+///
+/// En `Linear` es el 200%. En `Logarithmic` cada 1% vale 0.6 dB también por
+/// encima del 100%, así que los +6 dB de `MAX_AMPLITUDE` se alcanzan en el 110%.
+pub fn max_level(curve: VolumeCurve) -> f32 {
+    match curve {
+        VolumeCurve::Linear => MAX_LEVEL,
+        VolumeCurve::Logarithmic => 1.0 + 20.0 * MAX_AMPLITUDE.log10() / LOG_RANGE_DB,
+    }
+}
+
+/// Amplitud que se envía al reproductor para un nivel de la interfaz
+///
+/// El nivel va de 0.0 (0%) a `max_level` y 1.0 es el volumen original en las
+/// dos curvas. En `Linear` nivel y amplitud son iguales. En `Logarithmic` cada
+/// paso vale lo mismo en dB en todo el rango: hasta el 100% se recorren 60 dB
+/// (el 0% sigue siendo silencio) y hasta el 110% se sube a +6 dB.
+///
+/// Es la única conversión entre ambos: la usan los pasos de `z`/`x`, `Shift+V`
+/// y, con `amplitude_to_level`, el porcentaje que se muestra. Cualquier otra
+/// ganancia (silencio, atenuación, fundidos, ReplayGain) tiene que multiplicar
+/// esta amplitud y no el nivel, para que la curva no la deforme.
+pub fn level_to_amplitude(curve: VolumeCurve, level: f32) -> f32 {
+    let level = level.clamp(0.0, max_level(curve));
+    match curve {
+        VolumeCurve::Linear => level,
+        VolumeCurve::Logarithmic if level <= 0.0 => 0.0,
+        VolumeCurve::Logarithmic => 10f32
+            .powf((level - 1.0) * LOG_RANGE_DB / 20.0)
+            .min(MAX_AMPLITUDE),
+    }
+}
+
+/// Nivel de la interfaz para la amplitud que informa el reproductor
+///
+/// Inversa de `level_to_amplitude`; una amplitud por debajo del rango
+/// logarítmico se muestra como 0%.
+pub fn amplitude_to_level(curve: VolumeCurve, amplitude: f32) -> f32 {
+    let amplitude = amplitude.clamp(0.0, MAX_AMPLITUDE);
+    match curve {
+        VolumeCurve::Linear => amplitude,
+        VolumeCurve::Logarithmic if amplitude <= 0.0 => 0.0,
+        VolumeCurve::Logarithmic => (1.0 + 20.0 * amplitude.log10() / LOG_RANGE_DB).max(0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [VolumeCurve; 2] = [VolumeCurve::Linear, VolumeCurve::Logarithmic];
+
+    fn db(amplitude: f32) -> f32 {
+        20.0 * amplitude.log10()
+    }
+
+    #[test]
+    fn level_and_amplitude_round_trip() {
+        for curve in CURVES {
+            let top = (max_level(curve) * 100.0).floor() as u32;
+            for percent in 0..=top {
+                let level = percent as f32 / 100.0;
+                let back = amplitude_to_level(curve, level_to_amplitude(curve, level));
+                assert!((back - level).abs() < 1e-4, "{curve:?} {percent}%: {back}");
+            }
+        }
+    }
+
+    #[test]
+    fn logarithmic_steps_are_equal_on_both_sides_of_100() {
+        let curve = VolumeCurve::Logarithmic;
+        let step = |from: f32| {
+            db(level_to_amplitude(curve, from + 0.01)) - db(level_to_amplitude(curve, from))
+        };
+        for from in [0.10, 0.50, 0.95, 0.99, 1.00, 1.05] {
+            assert!(
+                (step(from) - 0.6).abs() < 1e-3,
+                "step from {from}: {}",
+                step(from)
+            );
+        }
+    }
+
+    #[test]
+    fn both_curves_share_the_fixed_points() {
+        for curve in CURVES {
+            assert_eq!(level_to_amplitude(curve, 0.0), 0.0);
+            assert!((level_to_amplitude(curve, 1.0) - 1.0).abs() < 1e-6);
+            assert!((level_to_amplitude(curve, max_level(curve)) - MAX_AMPLITUDE).abs() < 1e-4);
+            // Pedir más que el máximo no pasa de la amplitud máxima
+            assert!(level_to_amplitude(curve, 5.0) <= MAX_AMPLITUDE);
+        }
+        assert_eq!((max_level(VolumeCurve::Logarithmic) * 100.0).floor(), 110.0);
+    }
+
+    #[test]
+    fn small_steps_add_up_to_a_large_one() {
+        for curve in CURVES {
+            let mut level = 0.95;
+            for _ in 0..10 {
+                level += 0.01;
+            }
+            let direct = level_to_amplitude(curve, 1.05);
+            assert!((level_to_amplitude(curve, level) - direct).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn steps_compose_with_other_gains_through_the_amplitude() {
+        // Bajar a la mitad del nivel y multiplicar la amplitud no dan lo mismo en la curva
+        // logarítmica: la atenuación se aplica a la amplitud, no al nivel
+        let curve = VolumeCurve::Logarithmic;
+        let amplitude = level_to_amplitude(curve, 0.8);
+        let attenuated = amplitude * 0.5;
+        let shown = amplitude_to_level(curve, attenuated);
+        assert!((db(attenuated) - db(amplitude) + 6.02).abs() < 0.01);
+        assert!((shown - (0.8 - 6.02 / LOG_RANGE_DB)).abs() < 1e-3);
+    }
+}
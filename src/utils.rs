@@ -0,0 +1,390 @@
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Formatos de audio soportados: los que decodifica rodio con sus features por defecto
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav"];
+
+/// Verifica si un archivo es de audio soportado según su extensión
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// Resuelve una carpeta dentro de la biblioteca
+///
+/// `target` puede ser relativa a `root` o absoluta. Retorna la carpeta expresada
+/// bajo `root` (sin resolver enlaces), o `None` si no existe, no es un directorio
+/// o queda fuera de la biblioteca.
+pub fn library_subdir(root: &Path, target: &Path) -> Option<PathBuf> {
+    let canonical_root = root.canonicalize().ok()?;
+    let canonical = root.join(target).canonicalize().ok()?;
+    if !canonical.is_dir() {
+        return None;
+    }
+    let relative = canonical.strip_prefix(&canonical_root).ok()?;
+    Some(root.join(relative))
+}
+
+/// Quita los `.` y resuelve los `..` de una ruta sin consultar el disco
+///
+/// Un `..` en la raíz se descarta; en una ruta relativa que ya no tiene de dónde
+/// subir se conserva.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let can_pop = matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                );
+                if can_pop {
+                    normalized.pop();
+                } else if !normalized.has_root() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Verifica si una ruta queda dentro de alguna de las raíces
+///
+/// Si el archivo existe manda su ruta canónica: un enlace dentro de la biblioteca
+/// que apunta afuera no cuenta como propio, y la biblioteca escrita por otro camino
+/// (un enlace, `/home` montado en otro lado) sí. Si no existe se compara la ruta
+/// normalizada, así `../../etc/passwd` no pasa por estar escrita desde la biblioteca.
+pub fn is_within_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    match path.canonicalize() {
+        Ok(canonical) => roots.iter().any(|root| {
+            let root = root.canonicalize().unwrap_or_else(|_| normalize_path(root));
+            canonical.starts_with(root)
+        }),
+        Err(_) => {
+            let normalized = normalize_path(path);
+            roots
+                .iter()
+                .any(|root| normalized.starts_with(normalize_path(root)))
+        }
+    }
+}
+
+/// Hash FNV-1a de 64 bits: estable entre versiones de Rust, a diferencia de
+/// `DefaultHasher`, así los nombres de archivo que se derivan de él siguen
+/// sirviendo después de recompilar
+pub fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Formatea una cantidad con separador de miles: `12,345`
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Formatea una duración como `1h 05m`, `3m 20s` o `45s`
+///
+/// Con horas se omiten los segundos; las fracciones de segundo se descartan.
+pub fn format_duration_hms(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Formatea una duración como `m:ss` o, desde una hora, `h:mm:ss`
+///
+/// Las fracciones de segundo se descartan, como en un reloj.
+pub fn format_duration_clock(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Interpreta una duración escrita como `90` (segundos), `1:30`, `1:02:03` o `1h30m`
+///
+/// En la forma de reloj los minutos y segundos después del primer campo no
+/// pueden llegar a 60. Retorna `None` si el texto no es una duración válida.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    if input.contains(':') {
+        let fields: Vec<&str> = input.split(':').collect();
+        if fields.len() > 3 {
+            return None;
+        }
+        let mut total = 0u64;
+        for (i, field) in fields.iter().enumerate() {
+            let value: u64 = parse_digits(field)?;
+            if i > 0 && value >= 60 {
+                return None;
+            }
+            total = total.checked_mul(60)?.checked_add(value)?;
+        }
+        return Some(Duration::from_secs(total));
+    }
+
+    if input.bytes().all(|b| b.is_ascii_digit()) {
+        return parse_digits(&input).map(Duration::from_secs);
+    }
+
+    // Unidades en orden y sin repetir: 1h30m, 1h 30m, 2m15s, 45s
+    let compact: String = input.split_whitespace().collect();
+    let mut total = 0u64;
+    let mut rest = compact.as_str();
+    let mut next_unit = 0;
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let value = parse_digits(&rest[..digits])?;
+        let (order, multiplier) = match rest[digits..].chars().next()? {
+            'h' => (0, 3600),
+            'm' => (1, 60),
+            's' => (2, 1),
+            _ => return None,
+        };
+        if order < next_unit {
+            return None;
+        }
+        next_unit = order + 1;
+        total = total.checked_add(value.checked_mul(multiplier)?)?;
+        rest = &rest[digits + 1..];
+    }
+    Some(Duration::from_secs(total))
+}
+
+/// Número sin signo de al menos una cifra
+fn parse_digits(text: &str) -> Option<u64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rusted-player-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn durations_format_at_the_edges() {
+        let cases = [
+            (Duration::ZERO, "0s", "0:00"),
+            (Duration::from_millis(999), "0s", "0:00"),
+            (Duration::from_millis(59_999), "59s", "0:59"),
+            (Duration::from_secs(60), "1m 00s", "1:00"),
+            (Duration::from_secs(3_599), "59m 59s", "59:59"),
+            (Duration::from_secs(3_600), "1h 00m", "1:00:00"),
+            (Duration::from_secs(3_600 + 5 * 60 + 9), "1h 05m", "1:05:09"),
+            (Duration::from_secs(25 * 3_600 + 1), "25h 00m", "25:00:01"),
+            (
+                Duration::from_secs(1_000 * 3_600),
+                "1000h 00m",
+                "1000:00:00",
+            ),
+        ];
+        for (duration, hms, clock) in cases {
+            assert_eq!(format_duration_hms(duration), hms, "{:?}", duration);
+            assert_eq!(format_duration_clock(duration), clock, "{:?}", duration);
+        }
+    }
+
+    #[test]
+    fn durations_parse_in_every_form() {
+        let secs = |s| Some(Duration::from_secs(s));
+        let cases = [
+            ("0", secs(0)),
+            ("90", secs(90)),
+            (" 90 ", secs(90)),
+            ("1:30", secs(90)),
+            ("0:00", secs(0)),
+            ("90:00", secs(5_400)),
+            ("1:02:03", secs(3_723)),
+            ("25:00:00", secs(90_000)),
+            ("1h30m", secs(5_400)),
+            ("1H 30M", secs(5_400)),
+            ("2m15s", secs(135)),
+            ("45s", secs(45)),
+            ("48h", secs(172_800)),
+            ("", None),
+            ("   ", None),
+            ("1:60", None),
+            ("1:2:3:4", None),
+            ("1:", None),
+            (":30", None),
+            ("-5", None),
+            ("1.5", None),
+            ("30m1h", None),
+            ("1h1h", None),
+            ("h", None),
+            ("10x", None),
+            ("99999999999999999999", None),
+            ("9999999999999999h", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_duration(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn formatted_durations_parse_back() {
+        for secs in [0, 59, 60, 3_599, 3_600, 86_399, 90_061] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(
+                parse_duration(&format_duration_clock(duration)),
+                Some(duration)
+            );
+        }
+    }
+
+    #[test]
+    fn only_decodable_extensions_are_audio() {
+        assert_eq!(AUDIO_EXTENSIONS, ["mp3", "flac", "ogg", "wav"]);
+        for name in ["a.mp3", "b.FLAC", "c.Ogg", "d.wav"] {
+            assert!(is_audio_file(Path::new(name)), "{}", name);
+        }
+        for name in ["a.m4a", "b.aac", "c.wma", "d.mp3.txt", "mp3", "e"] {
+            assert!(!is_audio_file(Path::new(name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn normalize_resolves_dots_lexically() {
+        assert_eq!(
+            normalize_path(Path::new("/m/a/../b/./c")),
+            PathBuf::from("/m/b/c")
+        );
+        assert_eq!(normalize_path(Path::new("/../etc")), PathBuf::from("/etc"));
+        assert_eq!(normalize_path(Path::new("../x")), PathBuf::from("../x"));
+    }
+
+    #[test]
+    fn missing_paths_are_checked_lexically() {
+        let roots = [PathBuf::from("/nonexistent-library")];
+        assert!(is_within_roots(
+            Path::new("/nonexistent-library/a/b.mp3"),
+            &roots
+        ));
+        assert!(!is_within_roots(
+            Path::new("/nonexistent-library/../../etc/passwd"),
+            &roots
+        ));
+        assert!(!is_within_roots(Path::new("/elsewhere/b.mp3"), &roots));
+    }
+
+    #[test]
+    fn existing_files_are_checked_by_their_real_path() {
+        let base = temp_dir("within-roots");
+        let library = base.join("library");
+        let outside = base.join("outside");
+        fs::create_dir_all(&library).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(library.join("in.mp3"), b"x").unwrap();
+        fs::write(outside.join("out.mp3"), b"x").unwrap();
+        let roots = [library.clone()];
+
+        assert!(is_within_roots(&library.join("in.mp3"), &roots));
+        assert!(!is_within_roots(&outside.join("out.mp3"), &roots));
+        // Escrita desde la biblioteca pero resuelta afuera
+        assert!(!is_within_roots(
+            &library.join("../outside/out.mp3"),
+            &roots
+        ));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, library.join("escape")).unwrap();
+            assert!(!is_within_roots(&library.join("escape/out.mp3"), &roots));
+            // La biblioteca escrita a través de un enlace sigue siendo la biblioteca
+            std::os::unix::fs::symlink(&library, base.join("alias")).unwrap();
+            assert!(is_within_roots(&base.join("alias/in.mp3"), &roots));
+            assert!(is_within_roots(
+                &library.join("in.mp3"),
+                &[base.join("alias")]
+            ));
+        }
+        let _ = fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn library_subdir_accepts_folders_inside_the_library_only() {
+        let base = temp_dir("library-subdir");
+        let library = base.join("library");
+        fs::create_dir_all(library.join("Rock/Meddle")).unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+        fs::write(library.join("Rock/song.mp3"), b"x").unwrap();
+
+        let table: [(PathBuf, Option<PathBuf>); 9] = [
+            ("Rock/Meddle".into(), Some(library.join("Rock/Meddle"))),
+            ("./Rock/../Rock".into(), Some(library.join("Rock"))),
+            (library.join("Rock"), Some(library.join("Rock"))),
+            (".".into(), Some(library.clone())),
+            // Fuera de la biblioteca, por relativa o por absoluta
+            ("../outside".into(), None),
+            (base.join("outside"), None),
+            (base.clone(), None),
+            // Un archivo o una carpeta que no existe no sirven
+            ("Rock/song.mp3".into(), None),
+            ("Jazz".into(), None),
+        ];
+        for (target, expected) in table {
+            assert_eq!(library_subdir(&library, &target), expected, "{target:?}");
+        }
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("outside"), library.join("escape")).unwrap();
+            assert_eq!(library_subdir(&library, Path::new("escape")), None);
+            // Una biblioteca abierta por un enlace se sigue nombrando por el enlace
+            std::os::unix::fs::symlink(&library, base.join("alias")).unwrap();
+            assert_eq!(
+                library_subdir(&base.join("alias"), Path::new("Rock")),
+                Some(base.join("alias/Rock"))
+            );
+        }
+        let _ = fs::remove_dir_all(base);
+    }
+}